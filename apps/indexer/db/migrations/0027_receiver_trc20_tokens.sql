@@ -0,0 +1,616 @@
+-- =========================
+-- RECEIVER TRC-20 TOKENS BEYOND USDT
+-- =========================
+/*
+The receiver transfer indexer used to follow only the controller's USDT (resolved from
+`UsdtSet`). Receivers can hold any TRC-20 and `pullFromReceivers` sweeps any token with a
+configured LP exchange rate, so the indexer now also follows an operator-configured token set
+(`TRC20_TOKENS`).
+
+- Transfers for every tracked token land in `ctl.receiver_usdt_transfers` (the `token` column
+  already distinguishes them), so `ctl.receiver_token_balances` is maintained unchanged.
+- USDT keeps its single `ctl.receiver_usdt_tail_cursor`. Each configured token gets its own
+  tail cursor here so a token added later catches up from the controller deployment block
+  without rewinding USDT.
+- Every view that reads `ctl.receiver_usdt_transfers` as USDT deposits is narrowed to tokens
+  that have been the controller's USDT: `api.receiver_usdt_transfer_actionability`,
+  `api.unaccounted_receiver_usdt_transfers`, `api.claim_usdt_deposit_attribution` (pre-entitle
+  origins match on tx_hash alone otherwise) and `api.usdt_deposit_txs`, which the USDT deposit
+  rollups (`api.usdt_deposits_daily`, `api.usdt_deposits_daily_by_action`,
+  `api.usdt_deposits_cumulative`, `api.usdt_deposit_funnel_daily`,
+  `api.realtor_deposits_daily`) read from. Other tokens are covered by
+  `api.receiver_token_transfer_actionability`.
+*/
+
+create table if not exists ctl.receiver_token_tail_cursor (
+    token tron_address primary key,
+    next_block bigint not null,
+    updated_at timestamptz not null default now(),
+
+    constraint receiver_token_tail_cursor_nonnegative
+    check (next_block >= 0)
+);
+
+comment on table ctl.receiver_token_tail_cursor is
+$$Per-token tail cursors for configured (non-USDT) TRC-20 receiver transfer indexing
+
+`next_block` is the next Tron block number the worker will query for `token` across the
+whole receiver watchlist. A cursor never runs ahead of `ctl.receiver_usdt_tail_cursor`, so
+per-receiver backfills (bounded by the USDT tail cursor) cover every tracked token.$$;
+
+-- Controller reorgs rewind the per-token cursors the same way
+-- chain.on_event_appended_canonical_update rewinds the USDT tail cursor.
+create or replace function ctl.on_event_appended_rewind_receiver_token_cursors()
+returns trigger language plpgsql as $$
+declare
+  ctl_block_rollback bigint;
+begin
+  select min(o.block_number)
+    into ctl_block_rollback
+    from old_rows o join new_rows n using (id)
+   where o.stream='controller' and o.canonical is true and n.canonical is false;
+
+  if ctl_block_rollback is not null then
+    update ctl.receiver_token_tail_cursor
+       set next_block = least(next_block, ctl_block_rollback),
+           updated_at = now()
+     where next_block > ctl_block_rollback;
+  end if;
+
+  return null;
+end $$;
+
+drop trigger if exists trg_event_appended_rewind_receiver_token_cursors
+on chain.event_appended;
+create trigger trg_event_appended_rewind_receiver_token_cursors
+after update on chain.event_appended
+referencing old table as old_rows new table as new_rows
+for each statement execute function ctl.on_event_appended_rewind_receiver_token_cursors();
+
+comment on column ctl.receiver_usdt_transfers.token is
+$$TRC-20 token address (Tron base58check): the controller's USDT at the time, or
+one of the indexer's configured extra tokens$$;
+
+-- =========================
+-- USDT ACTIONABILITY: USDT TOKENS ONLY
+-- =========================
+
+create or replace view api.receiver_usdt_transfer_actionability as
+with last_pull as (
+    select
+        l.receiver_salt,
+        l.token,
+        max(e.block_timestamp) as last_pull_timestamp
+    from ctl.pulled_from_receiver_ledger l
+    join chain.event_appended e
+        on
+            e.stream = 'controller'
+            and e.canonical
+            and e.event_seq = l.event_seq
+    group by l.receiver_salt, l.token
+)
+
+select
+    t.chain_id,
+    t.token,
+    t.receiver_salt,
+    t.sender,
+    t.recipient,
+    t.amount,
+    t.block_number,
+    t.block_timestamp,
+    to_timestamp(t.block_timestamp) as block_time,
+    t.block_hash,
+    t.tx_hash,
+    t.log_index,
+
+    c.origin as claim_origin,
+    c.lease_id as claim_lease_id,
+    c.claim_id,
+    c.status as claim_status,
+    c.amount_usdt as claim_amount_usdt,
+
+    (
+        select lv.lease_id
+        from hub.lease_versions lv
+        where
+            lv.receiver_salt = t.receiver_salt
+            and lv.start_time <= t.block_timestamp
+            and lv.nukeable_after > t.block_timestamp
+        order by lv.start_time desc
+        limit 1
+    ) as expected_lease_id,
+
+    lp.last_pull_timestamp,
+    (
+        lp.last_pull_timestamp is null
+        or t.block_timestamp > lp.last_pull_timestamp
+    ) as preentitle_time_ok,
+
+    case
+        -- Objective proof confirmed onchain (via cached depositProcessed), regardless of claim origin.
+        when dp.processed then 'already_accounted'
+
+        -- Claim origin PRE_ENTITLE exists (objective proven via event chain).
+        when c.lease_id is not null and c.origin = 1 then 'already_accounted'
+
+        -- Subjective claim exists; objective preEntitle still needed (typically for reimbursement).
+        when c.lease_id is not null and c.origin = 0 then 'pre_entitle'
+
+        when
+            (
+                lp.last_pull_timestamp is null
+                or t.block_timestamp > lp.last_pull_timestamp
+            )
+            then 'subjective_pre_entitle'
+        else 'pull'
+    end as recommended_action
+
+from ctl.receiver_usdt_transfers t
+join ctl.receiver_watchlist w
+    on w.receiver_salt = t.receiver_salt
+left join hub.claim_versions c
+    on
+        c.valid_to_seq is null
+        and c.origin in (0, 1)
+        and c.origin_id = t.tx_hash
+left join hub.deposit_processed_cache dp
+    on dp.tx_hash::text = t.tx_hash::text
+left join last_pull lp
+    on
+        lp.receiver_salt = t.receiver_salt
+        and lp.token = t.token
+where
+    t.canonical
+    and w.backfill_next_block is null
+    and t.token in (select u.usdt from ctl.usdt_versions u);
+
+comment on view api.receiver_usdt_transfer_actionability is
+$$Receiver USDT deposits + actionability hints (subjective_pre_entitle vs pre_entitle vs pull)
+
+For each canonical TRC-20 USDT transfer into a deterministic receiver, this view shows:
+- whether the hub has already created a claim for it (subjective or objective),
+- whether the hub has objectively processed the deposit (`depositProcessed[txId]`, best-effort cached),
+- the latest observed receiver pull timestamp for (receiver_salt, token), and
+- whether `preEntitle` is still time-eligible (`transfer_ts > last_pull_ts`).
+
+`recommended_action` is a best-effort operator hint:
+- 'already_accounted'        => objective preEntitle is already processed (via claim origin=PRE_ENTITLE or cached depositProcessed)
+- 'pre_entitle'              => SUBJECTIVE_PRE_ENTITLE claim exists; objective proof may be needed (e.g. reimbursement)
+- 'subjective_pre_entitle'   => no claim yet and pre-entitle timing is still allowed
+- 'pull'                     => no claim yet and a later pull timestamp suggests pre-entitle would revert
+
+Only tokens that have been the controller's USDT are included; other indexed TRC-20s are in
+`api.receiver_token_transfer_actionability`.
+
+Receivers that are still backfilling (`ctl.receiver_watchlist.backfill_next_block is not null`)
+are intentionally excluded so relayers can continue operating for already-synced receivers.$$;
+
+-- =========================
+-- OTHER USDT DEPOSIT READERS: USDT TOKENS ONLY
+-- =========================
+
+create or replace view api.unaccounted_receiver_usdt_transfers as
+select
+    t.chain_id,
+    t.token,
+    t.receiver_salt,
+    t.sender,
+    t.recipient,
+    t.amount,
+    t.block_number,
+    t.block_timestamp,
+    to_timestamp(t.block_timestamp) as block_time,
+    t.block_hash,
+    t.tx_hash,
+    t.log_index,
+    -- best-effort attribution: expected active lease at this Tron timestamp
+    (
+        select lv.lease_id
+        from hub.lease_versions lv
+        where
+            lv.receiver_salt = t.receiver_salt
+            and lv.start_time <= t.block_timestamp
+            and lv.nukeable_after > t.block_timestamp
+        order by lv.start_time desc
+        limit 1
+    ) as expected_lease_id
+from ctl.receiver_usdt_transfers t
+join ctl.receiver_watchlist w
+    on w.receiver_salt = t.receiver_salt
+left join hub.claim_versions c
+    on
+        c.valid_to_seq is null
+        and c.origin in (0, 1)
+        and c.origin_id = t.tx_hash
+where
+    t.canonical
+    and c.lease_id is null
+    and w.backfill_next_block is null
+    and t.token in (select u.usdt from ctl.usdt_versions u);
+
+create or replace view api.claim_usdt_deposit_attribution as
+select
+    c.lease_id,
+    c.claim_id,
+    coalesce(a.usdt_deposit_attribution, '[]'::jsonb) as usdt_deposit_attribution
+from hub.claim_versions c
+join hub.lease_versions lv
+    on lv.lease_id = c.lease_id and lv.valid_to_seq is null
+left join lateral (
+    select
+        case
+            -- SUBJECTIVE_PRE_ENTITLE / PRE_ENTITLE: match by txId (origin_id) and receiver_salt.
+            when c.origin in (0, 1) then (
+                select
+                    coalesce(
+                        jsonb_agg(
+                            jsonb_build_object(
+                                'tx_hash', t.tx_hash,
+                                'sender', t.sender,
+                                'amount', t.amount,
+                                'block_timestamp', t.block_timestamp,
+                                'log_index', t.log_index
+                            )
+                            order by t.log_index
+                        ),
+                        '[]'::jsonb
+                    )
+                from ctl.receiver_usdt_transfers t
+                where
+                    t.canonical
+                    and t.receiver_salt = lv.receiver_salt
+                    and t.tx_hash = c.origin_id
+                    and t.token in (select u.usdt from ctl.usdt_versions u)
+            )
+
+            -- RECEIVER_PULL: best-effort FIFO attribution against USDT transfers only (origin_token is Tron address).
+            when c.origin = 2 and c.origin_token like 'T%' then (
+                with prev_pull as (
+                    select
+                        max(e.block_timestamp) as prev_pull_timestamp
+                    from ctl.pulled_from_receiver_ledger l
+                    join chain.event_appended e
+                        on
+                            e.stream = 'controller'
+                            and e.canonical
+                            and e.event_seq = l.event_seq
+                    where
+                        l.receiver_salt = lv.receiver_salt
+                        and l.token = c.origin_token::public.tron_address
+                        and e.block_timestamp < c.origin_timestamp
+                ),
+                transfers as (
+                    select
+                        t.id,
+                        t.tx_hash,
+                        t.sender,
+                        t.amount,
+                        t.block_timestamp,
+                        t.log_index,
+                        sum(t.amount) over (
+                            order by t.block_timestamp asc, t.log_index asc, t.id asc
+                        ) as cum_amount
+                    from ctl.receiver_usdt_transfers t
+                    cross join prev_pull p
+                    where
+                        t.canonical
+                        and t.receiver_salt = lv.receiver_salt
+                        and t.token = c.origin_token::public.tron_address
+                        and t.block_timestamp <= c.origin_timestamp
+                        and t.block_timestamp > coalesce(p.prev_pull_timestamp, 0)
+                ),
+                allocated as (
+                    select
+                        tx_hash,
+                        sender,
+                        block_timestamp,
+                        log_index,
+                        greatest(
+                            0::numeric,
+                            least(
+                                amount,
+                                c.origin_raw_amount - (cum_amount - amount)
+                            )
+                        )::public.u256 as allocated_amount
+                    from transfers
+                )
+                select
+                    coalesce(
+                        jsonb_agg(
+                            jsonb_build_object(
+                                'tx_hash', tx_hash,
+                                'sender', sender,
+                                'amount', allocated_amount,
+                                'block_timestamp', block_timestamp,
+                                'log_index', log_index
+                            )
+                            order by block_timestamp asc, log_index asc
+                        ),
+                        '[]'::jsonb
+                    )
+                from allocated
+                where allocated_amount > 0
+            )
+
+            else '[]'::jsonb
+        end as usdt_deposit_attribution
+) a on true
+where c.valid_to_seq is null;
+
+create or replace view api.usdt_deposit_txs as
+with deposits as (
+    select
+        t.chain_id,
+        t.token,
+        t.receiver_salt,
+        t.sender,
+        t.recipient,
+        t.amount,
+        t.block_number,
+        t.block_timestamp,
+        to_timestamp(t.block_timestamp) as block_time,
+        t.block_hash,
+        t.tx_hash,
+        t.log_index,
+        t.inserted_at
+    from ctl.receiver_usdt_transfers t
+    where
+        t.canonical
+        and t.token in (select u.usdt from ctl.usdt_versions u)
+),
+claim_links_raw as (
+    select
+        a.lease_id,
+        a.claim_id,
+        (e->>'tx_hash')::public.txhash_hex as tx_hash,
+        (e->>'log_index')::int as log_index,
+        (e->>'amount')::public.u256 as attributed_amount
+    from api.claim_usdt_deposit_attribution a
+    cross join lateral jsonb_array_elements(a.usdt_deposit_attribution) e
+),
+claim_links_enriched as (
+    select
+        l.tx_hash,
+        l.log_index,
+        l.lease_id,
+        l.claim_id,
+        l.attributed_amount,
+        c.origin as claim_origin,
+        c.status as claim_status,
+        c.amount_usdt as claim_amount_usdt,
+        lv.lease_number,
+        lv.realtor,
+        lv.lessee
+    from claim_links_raw l
+    left join hub.claim_versions c
+        on
+            c.lease_id = l.lease_id
+            and c.claim_id = l.claim_id
+            and c.valid_to_seq is null
+    left join hub.lease_versions lv
+        on lv.lease_id = l.lease_id and lv.valid_to_seq is null
+),
+links_by_deposit as (
+    select
+        tx_hash,
+        log_index,
+        jsonb_agg(
+            jsonb_build_object(
+                'lease_id', lease_id,
+                'lease_number', lease_number,
+                'realtor', realtor,
+                'lessee', lessee,
+                'claim_id', claim_id,
+                'claim_origin', claim_origin,
+                'claim_status', claim_status,
+                'claim_amount_usdt', claim_amount_usdt::text,
+                'attributed_amount', attributed_amount::text
+            )
+            order by lease_id, claim_id
+        ) as linked_claims,
+        count(*) as linked_claims_total,
+        coalesce(sum(attributed_amount), 0) as linked_claims_amount
+    from claim_links_enriched
+    group by tx_hash, log_index
+)
+select
+    d.chain_id,
+    d.token,
+    d.receiver_salt,
+    d.sender,
+    d.recipient,
+    d.amount,
+    d.block_number,
+    d.block_timestamp,
+    d.block_time,
+    d.block_hash,
+    d.tx_hash,
+    d.log_index,
+    d.inserted_at,
+
+    a.recommended_action,
+    a.expected_lease_id,
+    elv.lease_number as expected_lease_number,
+    elv.realtor as expected_realtor,
+    elv.lessee as expected_lessee,
+
+    -- Direct (pre-entitle) claim match by origin_id=tx_hash (if any).
+    a.claim_lease_id,
+    a.claim_id,
+    a.claim_origin,
+    a.claim_status,
+    a.claim_amount_usdt,
+    clv.lease_number as claim_lease_number,
+    clv.realtor as claim_realtor,
+    clv.lessee as claim_lessee,
+
+    dp.processed as deposit_processed,
+    dp.last_checked_at as deposit_processed_last_checked_at,
+
+    coalesce(l.linked_claims, '[]'::jsonb) as linked_claims,
+    coalesce(l.linked_claims_total, 0) as linked_claims_total,
+    coalesce(l.linked_claims_amount, 0)::text as linked_claims_amount
+from deposits d
+left join api.receiver_usdt_transfer_actionability a
+    on
+        a.chain_id = d.chain_id
+        and a.token = d.token
+        and a.receiver_salt = d.receiver_salt
+        and a.tx_hash = d.tx_hash
+        and a.log_index = d.log_index
+left join hub.deposit_processed_cache dp
+    on dp.tx_hash::text = d.tx_hash::text
+left join links_by_deposit l
+    on l.tx_hash = d.tx_hash and l.log_index = d.log_index
+left join hub.lease_versions elv
+    on elv.lease_id = a.expected_lease_id and elv.valid_to_seq is null
+left join hub.lease_versions clv
+    on clv.lease_id = a.claim_lease_id and clv.valid_to_seq is null;
+
+-- =========================
+-- GENERIC TOKEN VIEWS
+-- =========================
+
+create or replace view api.receiver_token_indexer_status as
+select
+    u.usdt as token,
+    true as is_usdt,
+    t.next_block as tail_next_block,
+    t.updated_at as tail_updated_at
+from ctl.usdt_versions u
+cross join ctl.receiver_usdt_tail_cursor t
+where u.valid_to_seq is null
+union all
+select
+    c.token,
+    false as is_usdt,
+    c.next_block as tail_next_block,
+    c.updated_at as tail_updated_at
+from ctl.receiver_token_tail_cursor c
+where not exists (
+    select 1
+    from ctl.usdt_versions u
+    where u.valid_to_seq is null and u.usdt = c.token
+);
+
+comment on view api.receiver_token_indexer_status is
+$$Receiver transfer indexer tail cursor per tracked TRC-20 token.
+
+The current USDT reports the shared `ctl.receiver_usdt_tail_cursor`; configured extra tokens
+report their own cursor from `ctl.receiver_token_tail_cursor`. A token whose
+`tail_next_block` is far behind the Tron head is still catching up and its balances are
+incomplete. Per-receiver backfill state is in `api.receiver_usdt_indexer_status`.$$;
+
+create or replace view api.receiver_token_balances as
+select
+    w.receiver_salt,
+    w.receiver,
+    w.receiver_evm,
+    b.token,
+    (cu.usdt is not null) as is_usdt,
+    r.exchange_rate as lp_exchange_rate,
+    (cu.usdt is not null or r.exchange_rate is not null) as pullable,
+    (w.backfill_next_block is not null) as backfill_pending,
+    b.incoming_amount,
+    b.pulled_amount,
+    b.balance_amount
+from ctl.receiver_token_balances b
+join ctl.receiver_watchlist w
+    on w.receiver_salt = b.receiver_salt
+left join ctl.usdt_versions cu
+    on cu.valid_to_seq is null and cu.usdt = b.token
+left join ctl.lp_exchange_rate_versions r
+    on r.valid_to_seq is null and r.token = b.token;
+
+comment on view api.receiver_token_balances is
+$$Net receiver balances for every indexed TRC-20 token (USDT and configured extras).
+
+Same derivation as `api.receiver_usdt_balances` (incoming transfers - controller pulls),
+one row per (receiver, token) that has ever been seen.
+
+- `pullable` is true when `pullFromReceivers` accepts the token: it is the current USDT, or
+  the controller has a non-null LP exchange rate configured for it.
+- `backfill_pending` receivers may be missing historical transfers.$$;
+
+create or replace view api.receiver_token_transfer_actionability as
+with last_pull as (
+    select
+        l.receiver_salt,
+        l.token,
+        max(e.block_timestamp) as last_pull_timestamp
+    from ctl.pulled_from_receiver_ledger l
+    join chain.event_appended e
+        on
+            e.stream = 'controller'
+            and e.canonical
+            and e.event_seq = l.event_seq
+    group by l.receiver_salt, l.token
+)
+
+select
+    t.chain_id,
+    t.token,
+    t.receiver_salt,
+    t.sender,
+    t.recipient,
+    t.amount,
+    t.block_number,
+    t.block_timestamp,
+    to_timestamp(t.block_timestamp) as block_time,
+    t.block_hash,
+    t.tx_hash,
+    t.log_index,
+
+    r.exchange_rate as lp_exchange_rate,
+    lp.last_pull_timestamp,
+
+    case
+        when
+            lp.last_pull_timestamp is not null
+            and lp.last_pull_timestamp >= t.block_timestamp
+            then 'already_pulled'
+        when r.exchange_rate is null then 'no_exchange_rate'
+        else 'pull'
+    end as recommended_action
+
+from ctl.receiver_usdt_transfers t
+join ctl.receiver_watchlist w
+    on w.receiver_salt = t.receiver_salt
+left join ctl.lp_exchange_rate_versions r
+    on r.valid_to_seq is null and r.token = t.token
+left join last_pull lp
+    on
+        lp.receiver_salt = t.receiver_salt
+        and lp.token = t.token
+where
+    t.canonical
+    and w.backfill_next_block is null
+    and t.token not in (select u.usdt from ctl.usdt_versions u);
+
+comment on view api.receiver_token_transfer_actionability is
+$$Receiver deposits of non-USDT TRC-20 tokens + pull hints
+
+These deposits cannot be pre-entitled; they only reach the hub through `pullFromReceivers`,
+which converts them to USDT at the controller's LP exchange rate.
+
+`recommended_action` is a best-effort operator hint:
+- 'already_pulled'    => a pull of this (receiver_salt, token) landed at or after the transfer
+- 'no_exchange_rate'  => the controller has no LP exchange rate for the token; a pull would revert
+- 'pull'              => the deposit is still sitting in the receiver and can be swept
+
+Receivers that are still backfilling are excluded, as in
+`api.receiver_usdt_transfer_actionability`.$$;
+
+-- Ensure PostgREST anon role can read the new api views (no-op if role missing).
+do $$
+begin
+  if exists (select 1 from pg_roles where rolname = 'pgrst_anon') then
+    grant usage on schema api to pgrst_anon;
+    grant select on api.receiver_token_indexer_status to pgrst_anon;
+    grant select on api.receiver_token_balances to pgrst_anon;
+    grant select on api.receiver_token_transfer_actionability to pgrst_anon;
+  end if;
+end $$;
+
+notify pgrst, 'reload schema';
@@ -0,0 +1,161 @@
+-- =========================
+-- RECEIVER TRX / TRC-10 DEPOSITS
+-- =========================
+/*
+Receivers only sweep TRC-20 balances, so TRX and TRC-10 tokens sent to a receiver by mistake
+are stranded. They never emit event logs, so the TRC-20 receiver indexer cannot see them; a
+separate worker (`TRON_NATIVE_ENABLED`) reads full Tron blocks from a node and records
+`TransferContract` / `TransferAssetContract` transactions whose recipient is in
+`ctl.receiver_watchlist`.
+
+The worker scans forward from its own cursor (by default the safe head at first start) and
+matches against the watchlist as of scan time; it does not backfill receivers discovered later.
+*/
+
+create table if not exists ctl.receiver_native_transfers (
+    id bigint generated by default as identity primary key,
+
+    chain_id bigint not null,
+
+    -- 'trx' (TransferContract) or 'trc10' (TransferAssetContract).
+    kind text not null,
+
+    -- TRC-10 token id; null for TRX.
+    asset_id text null,
+
+    receiver_salt bytes32_hex not null,
+
+    sender tron_address not null,
+    recipient tron_address not null,
+    -- Sun for TRX, token base units for TRC-10.
+    amount u256 not null,
+
+    block_number bigint not null,
+    block_timestamp bigint not null,
+    block_hash bytes32_hex not null,
+
+    tx_hash txhash_hex not null,
+
+    canonical boolean not null default true,
+    inserted_at timestamptz not null default now(),
+
+    constraint receiver_native_transfers_kind
+    check (
+        (kind = 'trx' and asset_id is null)
+        or (kind = 'trc10' and asset_id is not null)
+    ),
+
+    constraint receiver_native_transfers_timestamp_seconds_range
+    check (block_timestamp >= 946684800 and block_timestamp < 20000000000),
+
+    constraint receiver_native_transfers_nonnegative
+    check (block_number >= 0)
+);
+
+comment on table ctl.receiver_native_transfers is
+$$TRX and TRC-10 transfers into deterministic receivers (canonical + reorg-safe)
+
+Each row is a `TransferContract` (kind='trx') or `TransferAssetContract` (kind='trc10')
+transaction whose `to_address` matches a receiver in `ctl.receiver_watchlist`. The controller
+cannot sweep these, so they need manual recovery.
+
+Reorgs are handled by flipping `canonical=false` in sync with controller stream reorgs, as for
+`ctl.receiver_usdt_transfers`.$$;
+
+create unique index if not exists ctl_receiver_native_transfers_uid
+on ctl.receiver_native_transfers (chain_id, tx_hash);
+
+create index if not exists ctl_receiver_native_transfers_recipient_time
+on ctl.receiver_native_transfers (receiver_salt, block_timestamp desc, id desc)
+where canonical;
+
+create table if not exists ctl.receiver_native_cursor (
+    stream chain.stream primary key default 'controller',
+    next_block bigint not null,
+    updated_at timestamptz not null default now(),
+
+    constraint receiver_native_cursor_stream_controller
+    check (stream = 'controller'),
+
+    constraint receiver_native_cursor_nonnegative
+    check (next_block >= 0)
+);
+
+comment on table ctl.receiver_native_cursor is
+$$Block cursor for the receiver TRX / TRC-10 transfer scanner (controller chain)
+
+`next_block` is the next Tron block the worker will fetch and scan.$$;
+
+-- Controller reorgs invalidate native transfer rows and rewind the scanner, mirroring
+-- chain.on_event_appended_canonical_update for TRC-20 receiver transfers.
+create or replace function ctl.on_event_appended_invalidate_receiver_native_transfers()
+returns trigger language plpgsql as $$
+declare
+  ctl_block_rollback bigint;
+begin
+  select min(o.block_number)
+    into ctl_block_rollback
+    from old_rows o join new_rows n using (id)
+   where o.stream='controller' and o.canonical is true and n.canonical is false;
+
+  if ctl_block_rollback is not null then
+    update ctl.receiver_native_transfers
+       set canonical = false
+     where canonical and block_number >= ctl_block_rollback;
+
+    update ctl.receiver_native_cursor
+       set next_block = least(next_block, ctl_block_rollback),
+           updated_at = now()
+     where stream = 'controller';
+  end if;
+
+  return null;
+end $$;
+
+drop trigger if exists trg_event_appended_invalidate_receiver_native_transfers
+on chain.event_appended;
+create trigger trg_event_appended_invalidate_receiver_native_transfers
+after update on chain.event_appended
+referencing old table as old_rows new table as new_rows
+for each statement execute function ctl.on_event_appended_invalidate_receiver_native_transfers();
+
+create or replace view api.receiver_native_deposits as
+select
+    t.chain_id,
+    t.receiver_salt,
+    w.receiver,
+    w.receiver_evm,
+    t.kind,
+    coalesce(t.asset_id, 'TRX') as asset,
+    t.asset_id,
+    t.sender,
+    t.amount,
+    t.block_number,
+    t.block_timestamp,
+    to_timestamp(t.block_timestamp) as block_time,
+    t.block_hash,
+    t.tx_hash
+from ctl.receiver_native_transfers t
+join ctl.receiver_watchlist w
+    on w.receiver_salt = t.receiver_salt
+where t.canonical;
+
+comment on view api.receiver_native_deposits is
+$$TRX and TRC-10 deposits into receivers (stranded funds)
+
+Receivers cannot sweep native TRX or TRC-10 tokens, so every row here is a deposit that needs
+manual recovery. `asset` is 'TRX' or the TRC-10 token id; `amount` is in sun for TRX and
+token base units for TRC-10.
+
+Only blocks scanned since the scanner started (`ctl.receiver_native_cursor`) are covered.$$;
+
+-- Ensure PostgREST anon role can read the new api view (no-op if role missing).
+do $$
+begin
+  if exists (select 1 from pg_roles where rolname = 'pgrst_anon') then
+    grant usage on schema api to pgrst_anon;
+    grant select on api.receiver_native_deposits to pgrst_anon;
+  end if;
+end $$;
+
+notify pgrst, 'reload schema';
@@ -0,0 +1,95 @@
+-- =========================
+-- RECEIVER TRX / TRC-10 BACKFILL
+-- =========================
+/*
+The native scanner (0028) matched blocks against the watchlist as of scan time, so a receiver
+discovered after the cursor had passed its first deposits was never covered for those blocks.
+
+- `ctl.receiver_native_cursor.start_block` records where the scanner started. Deployments
+  upgraded from 0028 start at the cursor position at upgrade time.
+- Every watched receiver gets a row in `ctl.receiver_native_backfill` whose `next_block` starts
+  at `start_block` and walks up to the scanner cursor, mirroring
+  `ctl.receiver_watchlist.backfill_next_block` for TRC-20 transfers.
+- Controller reorgs rewind the per-receiver cursors as well as the scanner cursor.
+*/
+
+alter table ctl.receiver_native_cursor
+    add column if not exists start_block bigint null;
+
+update ctl.receiver_native_cursor
+   set start_block = next_block
+ where start_block is null;
+
+alter table ctl.receiver_native_cursor
+    alter column start_block set not null;
+
+comment on table ctl.receiver_native_cursor is
+$$Block cursor for the receiver TRX / TRC-10 transfer scanner (controller chain)
+
+`next_block` is the next Tron block the worker will fetch and scan. `start_block` is the first
+block it covers; receivers discovered later are backfilled from there
+(`ctl.receiver_native_backfill`).$$;
+
+create table if not exists ctl.receiver_native_backfill (
+    receiver_salt bytes32_hex primary key
+        references ctl.receiver_watchlist (receiver_salt) on delete cascade,
+
+    -- Next block to rescan for this receiver; null once it reached the scanner cursor.
+    next_block bigint null,
+
+    updated_at timestamptz not null default now(),
+
+    constraint receiver_native_backfill_nonnegative
+    check (next_block is null or next_block >= 0)
+);
+
+comment on table ctl.receiver_native_backfill is
+$$Per-receiver backfill cursors for the receiver TRX / TRC-10 transfer scanner
+
+A receiver joins with `next_block = ctl.receiver_native_cursor.start_block`; the worker rescans
+blocks below the scanner cursor for it and sets `next_block` to null once it catches up.$$;
+
+create index if not exists ctl_receiver_native_backfill_next_block
+on ctl.receiver_native_backfill (next_block)
+where next_block is not null;
+
+create or replace function ctl.on_event_appended_invalidate_receiver_native_transfers()
+returns trigger language plpgsql as $$
+declare
+  ctl_block_rollback bigint;
+begin
+  select min(o.block_number)
+    into ctl_block_rollback
+    from old_rows o join new_rows n using (id)
+   where o.stream='controller' and o.canonical is true and n.canonical is false;
+
+  if ctl_block_rollback is not null then
+    update ctl.receiver_native_transfers
+       set canonical = false
+     where canonical and block_number >= ctl_block_rollback;
+
+    update ctl.receiver_native_cursor
+       set next_block = least(next_block, ctl_block_rollback),
+           updated_at = now()
+     where stream = 'controller';
+
+    update ctl.receiver_native_backfill
+       set next_block = least(next_block, ctl_block_rollback),
+           updated_at = now()
+     where next_block is not null and next_block > ctl_block_rollback;
+  end if;
+
+  return null;
+end $$;
+
+comment on view api.receiver_native_deposits is
+$$TRX and TRC-10 deposits into receivers (stranded funds)
+
+Receivers cannot sweep native TRX or TRC-10 tokens, so every row here is a deposit that needs
+manual recovery. `asset` is 'TRX' or the TRC-10 token id; `amount` is in sun for TRX and
+token base units for TRC-10.
+
+Blocks from `ctl.receiver_native_cursor.start_block` on are covered; receivers discovered later
+appear once their backfill (`ctl.receiver_native_backfill`) reaches the block of the deposit.$$;
+
+notify pgrst, 'reload schema';
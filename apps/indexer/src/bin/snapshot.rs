@@ -0,0 +1,823 @@
+//! Export / import of the indexer's `chain.*`, `hub.*` and `ctl.*` tables, so a new indexer can
+//! start from a snapshot instead of replaying both event streams from `deployment_block`.
+//!
+//! `snapshot export <dir> [<stream>=<event_seq>...]` writes one COPY file per table plus
+//! `manifest.json`, all read in a single repeatable-read transaction. The manifest records each
+//! stream's projection cursor (`applied_through_seq` and its tip) and the block of that event.
+//! Without a target a stream is exported at its current cursor. With `hub=<seq>` /
+//! `controller=<seq>`, the export transaction first rolls that stream back to `<seq>` with the
+//! projector's own `rollback_from`, drops the raw events after it and points the ingest cursor
+//! at the block of `<seq>`; the transaction is never committed, so the live DB is untouched.
+//!
+//! `snapshot import <dir>` checks every stream's tip against the `EventAppended` log on chain
+//! (via `HUB_RPC_URLS` / `CONTROLLER_RPC_URLS`), then loads the tables into an empty, migrated
+//! database. The indexer resumes ingestion from the snapshot's `chain.ingest_cursor`.
+
+use alloy::primitives::{Address, B256};
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use alloy::rpc::types::Filter;
+use alloy::sol_types::SolEvent;
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use untron_v3_bindings::{
+    untron_controller_index::UntronControllerIndex, untron_v3_index::UntronV3Index,
+};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const FORMAT_VERSION: u32 = 1;
+const SCHEMAS: [&str; 3] = ["chain", "hub", "ctl"];
+const COPY_CHUNK_BYTES: usize = 1 << 20;
+
+#[derive(Debug, Clone)]
+enum Command {
+    Export {
+        dir: PathBuf,
+        targets: Vec<(String, i64)>,
+    },
+    Import {
+        dir: PathBuf,
+    },
+}
+
+fn parse_args() -> Result<Command> {
+    let mut it = env::args().skip(1);
+    let cmd = it.next().context(
+        "usage: snapshot export <dir> [<stream>=<event_seq>...] | snapshot import <dir>",
+    )?;
+    let dir = PathBuf::from(it.next().context("missing snapshot directory")?);
+    match cmd.as_str() {
+        "export" => Ok(Command::Export {
+            dir,
+            targets: parse_targets(it)?,
+        }),
+        "import" => {
+            if let Some(other) = it.next() {
+                anyhow::bail!("unknown arg: {other}");
+            }
+            Ok(Command::Import { dir })
+        }
+        other => anyhow::bail!("unknown command: {other} (expected export or import)"),
+    }
+}
+
+/// `hub=<event_seq>` / `controller=<event_seq>`, at most once per stream.
+fn parse_targets(args: impl IntoIterator<Item = String>) -> Result<Vec<(String, i64)>> {
+    let mut targets: Vec<(String, i64)> = Vec::new();
+    for arg in args {
+        let (stream, seq) = arg
+            .split_once('=')
+            .with_context(|| format!("expected <stream>=<event_seq>, got {arg}"))?;
+        anyhow::ensure!(
+            matches!(stream, "hub" | "controller"),
+            "unknown stream {stream} (expected hub or controller)"
+        );
+        anyhow::ensure!(
+            targets.iter().all(|(s, _)| s != stream),
+            "{stream} event_seq given twice"
+        );
+        let seq = seq
+            .parse::<i64>()
+            .ok()
+            .filter(|seq| *seq >= 0)
+            .with_context(|| format!("invalid event_seq for {stream}: {seq}"))?;
+        targets.push((stream.to_string(), seq));
+    }
+    Ok(targets)
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    /// `_sqlx_migrations` version of the exporting DB; COPY files are only valid for the same
+    /// table layout.
+    schema_version: i64,
+    created_at_unix: u64,
+    streams: Vec<StreamSnapshot>,
+    /// In load order (referenced tables first).
+    tables: Vec<TableSnapshot>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct StreamSnapshot {
+    stream: String,
+    chain_id: i64,
+    contract_address: String,
+    /// Highest event_seq applied to the projections; `tip` is that event's `newTip` (the
+    /// genesis tip when 0).
+    applied_through_seq: i64,
+    tip: B256,
+    /// Block and block hash of the `applied_through_seq` event, checked against chain on import.
+    tip_block_number: Option<i64>,
+    tip_block_hash: Option<B256>,
+    /// `chain.ingest_cursor.next_block`, where ingestion resumes after import.
+    next_block: i64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct TableSnapshot {
+    name: String,
+    file: String,
+    rows: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let cmd = parse_args()?;
+    let database_url = env::var("DATABASE_URL").context("DATABASE_URL must be set")?;
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .context("connect to database")?;
+
+    match cmd {
+        Command::Export { dir, targets } => export(&pool, &dir, &targets).await,
+        Command::Import { dir } => import(&pool, &dir).await,
+    }
+}
+
+async fn export(pool: &PgPool, dir: &Path, targets: &[(String, i64)]) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    anyhow::ensure!(
+        !dir.join(MANIFEST_FILE).exists(),
+        "{} already contains a snapshot",
+        dir.display()
+    );
+
+    let mut tx = pool.begin().await.context("begin export tx")?;
+    // Every read below sees the same MVCC snapshot, so projections, cursors and raw events agree.
+    // Targeted exports write (and then discard) their rollback in this transaction.
+    let isolation = if targets.is_empty() {
+        "set transaction isolation level repeatable read, read only"
+    } else {
+        "set transaction isolation level repeatable read"
+    };
+    sqlx::query(isolation)
+        .execute(&mut *tx)
+        .await
+        .context("set export isolation")?;
+
+    let schema_version = schema_version(&mut *tx).await?;
+    for (stream, event_seq) in targets {
+        rewind_stream(&mut tx, stream, *event_seq)
+            .await
+            .with_context(|| format!("roll {stream} back to event_seq {event_seq}"))?;
+    }
+
+    let cursors = sqlx::query_as::<Postgres, (String, i64, String, i64, String, i64)>(
+        "select i.stream::text, i.chain_id, i.contract_address::text, \
+                c.applied_through_seq, c.tip::text, coalesce(g.next_block, 0) \
+           from chain.instance i \
+           join chain.stream_cursor c using (stream) \
+           left join chain.ingest_cursor g using (stream) \
+          order by i.stream",
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .context("read stream cursors")?;
+    anyhow::ensure!(
+        !cursors.is_empty(),
+        "chain.instance is empty; nothing to export"
+    );
+
+    let mut streams = Vec::with_capacity(cursors.len());
+    for (stream, chain_id, contract_address, applied_through_seq, tip, next_block) in cursors {
+        let tip = parse_b256(&tip)?;
+        let (tip_block_number, tip_block_hash) = if applied_through_seq == 0 {
+            (None, None)
+        } else {
+            let (block_number, block_hash, new_tip) =
+                sqlx::query_as::<Postgres, (i64, String, String)>(
+                    "select block_number, block_hash::text, new_tip::text \
+                       from chain.event_appended \
+                      where stream = $1::chain.stream and event_seq = $2 and canonical",
+                )
+                .bind(&stream)
+                .bind(applied_through_seq)
+                .fetch_one(&mut *tx)
+                .await
+                .with_context(|| format!("read {stream} event {applied_through_seq}"))?;
+            anyhow::ensure!(
+                parse_b256(&new_tip)? == tip,
+                "{stream} cursor tip {tip} does not match event {applied_through_seq} newTip {new_tip}"
+            );
+            (Some(block_number), Some(parse_b256(&block_hash)?))
+        };
+        streams.push(StreamSnapshot {
+            stream,
+            chain_id,
+            contract_address,
+            applied_through_seq,
+            tip,
+            tip_block_number,
+            tip_block_hash,
+            next_block,
+        });
+    }
+
+    let names = sqlx::query_scalar::<Postgres, String>(
+        "select format('%I.%I', n.nspname, c.relname) \
+           from pg_class c join pg_namespace n on n.oid = c.relnamespace \
+          where c.relkind = 'r' and n.nspname = any($1)",
+    )
+    .bind(&SCHEMAS[..])
+    .fetch_all(&mut *tx)
+    .await
+    .context("list snapshot tables")?;
+    let fks = sqlx::query_as::<Postgres, (String, String)>(
+        "select format('%I.%I', cn.nspname, cc.relname), format('%I.%I', pn.nspname, pc.relname) \
+           from pg_constraint k \
+           join pg_class cc on cc.oid = k.conrelid \
+           join pg_namespace cn on cn.oid = cc.relnamespace \
+           join pg_class pc on pc.oid = k.confrelid \
+           join pg_namespace pn on pn.oid = pc.relnamespace \
+          where k.contype = 'f' and cn.nspname = any($1)",
+    )
+    .bind(&SCHEMAS[..])
+    .fetch_all(&mut *tx)
+    .await
+    .context("list foreign keys")?;
+
+    let mut tables = Vec::new();
+    for name in load_order(names, &fks)? {
+        let file = format!("{name}.copy");
+        let mut out = BufWriter::new(
+            File::create(dir.join(&file)).with_context(|| format!("create {file}"))?,
+        );
+        let mut rows = 0u64;
+        let mut copy = tx
+            .copy_out_raw(&format!("copy {name} to stdout"))
+            .await
+            .with_context(|| format!("copy {name} to stdout"))?;
+        while let Some(chunk) = copy.next().await {
+            let chunk = chunk.with_context(|| format!("read COPY data for {name}"))?;
+            // Text COPY escapes embedded newlines, so each one ends a row.
+            rows += chunk.iter().filter(|&&b| b == b'\n').count() as u64;
+            out.write_all(&chunk)
+                .with_context(|| format!("write {file}"))?;
+        }
+        drop(copy);
+        out.flush().with_context(|| format!("write {file}"))?;
+        tables.push(TableSnapshot { name, file, rows });
+    }
+    // Nothing written here (a targeted rollback) may reach the live database.
+    tx.rollback().await.context("end export tx")?;
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        schema_version,
+        created_at_unix: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        streams,
+        tables,
+    };
+    std::fs::write(
+        dir.join(MANIFEST_FILE),
+        serde_json::to_vec_pretty(&manifest).context("encode manifest")?,
+    )
+    .context("write manifest")?;
+
+    for s in &manifest.streams {
+        println!(
+            "exported {} through event_seq={} tip={} next_block={}",
+            s.stream, s.applied_through_seq, s.tip, s.next_block
+        );
+    }
+    println!(
+        "snapshot written to {} ({} tables, schema version={})",
+        dir.display(),
+        manifest.tables.len(),
+        manifest.schema_version
+    );
+    Ok(())
+}
+
+/// Rolls `stream` back to `event_seq` inside the export transaction, the way a reorg would
+/// (`hub.rollback_from` / `ctl.rollback_from`), so the tables read afterwards hold the
+/// projection as of that event. Raw events after it are dropped and ingestion resumes at its
+/// block, whose later events the importer re-ingests and applies.
+async fn rewind_stream(
+    tx: &mut Transaction<'_, Postgres>,
+    stream: &str,
+    event_seq: i64,
+) -> Result<()> {
+    let applied: i64 = sqlx::query_scalar(
+        "select applied_through_seq from chain.stream_cursor where stream = $1::chain.stream",
+    )
+    .bind(stream)
+    .fetch_optional(&mut **tx)
+    .await
+    .context("read stream cursor")?
+    .with_context(|| format!("{stream} stream is not configured"))?;
+    anyhow::ensure!(
+        event_seq <= applied,
+        "projections have only applied through event_seq {applied}"
+    );
+
+    let schema = if stream == "hub" { "hub" } else { "ctl" };
+    sqlx::query(&format!("select {schema}.rollback_from($1)"))
+        .bind(event_seq + 1)
+        .execute(&mut **tx)
+        .await
+        .with_context(|| format!("{schema}.rollback_from"))?;
+
+    // 0 falls back to the stream's deployment block once no events are left.
+    let next_block: i64 = if event_seq == 0 {
+        0
+    } else {
+        sqlx::query_scalar(
+            "select block_number from chain.event_appended \
+              where stream = $1::chain.stream and event_seq = $2 and canonical",
+        )
+        .bind(stream)
+        .bind(event_seq)
+        .fetch_one(&mut **tx)
+        .await
+        .with_context(|| format!("read block of event {event_seq}"))?
+    };
+    sqlx::query(
+        "delete from chain.event_appended where stream = $1::chain.stream and event_seq > $2",
+    )
+    .bind(stream)
+    .bind(event_seq)
+    .execute(&mut **tx)
+    .await
+    .context("drop later events")?;
+    sqlx::query(
+        "update chain.ingest_cursor set next_block = $2, updated_at = now() \
+          where stream = $1::chain.stream",
+    )
+    .bind(stream)
+    .bind(next_block)
+    .execute(&mut **tx)
+    .await
+    .context("rewind ingest cursor")?;
+    Ok(())
+}
+
+async fn import(pool: &PgPool, dir: &Path) -> Result<()> {
+    let manifest: Manifest =
+        serde_json::from_slice(&std::fs::read(dir.join(MANIFEST_FILE)).context("read manifest")?)
+            .context("parse manifest")?;
+    anyhow::ensure!(
+        manifest.format_version == FORMAT_VERSION,
+        "unsupported snapshot format version {}",
+        manifest.format_version
+    );
+
+    let mut conn = pool.acquire().await.context("acquire connection")?;
+    let schema_version = schema_version(&mut *conn).await?;
+    anyhow::ensure!(
+        schema_version == manifest.schema_version,
+        "snapshot was taken at schema version {} but the database is at {schema_version}",
+        manifest.schema_version
+    );
+    drop(conn);
+
+    for s in &manifest.streams {
+        verify_stream_tip(s)
+            .await
+            .with_context(|| format!("verify {} snapshot tip against chain", s.stream))?;
+    }
+
+    let mut tx = pool.begin().await.context("begin import tx")?;
+    let existing: i64 = sqlx::query_scalar("select count(*) from chain.event_appended")
+        .fetch_one(&mut *tx)
+        .await
+        .context("count chain.event_appended")?;
+    anyhow::ensure!(
+        existing == 0,
+        "target database already holds {existing} events; import requires a freshly migrated database"
+    );
+
+    let names: Vec<&str> = manifest.tables.iter().map(|t| t.name.as_str()).collect();
+    // Projections and derived balances come from the snapshot as-is; the insert triggers that
+    // would otherwise re-apply events or double-count balances stay off during the load.
+    for name in &names {
+        sqlx::query(&format!("alter table {name} disable trigger user"))
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("disable triggers on {name}"))?;
+    }
+    sqlx::query(&format!("truncate {}", names.join(", ")))
+        .execute(&mut *tx)
+        .await
+        .context("truncate snapshot tables")?;
+
+    for table in &manifest.tables {
+        let mut file =
+            File::open(dir.join(&table.file)).with_context(|| format!("open {}", table.file))?;
+        let mut copy = tx
+            .copy_in_raw(&format!("copy {} from stdin", table.name))
+            .await
+            .with_context(|| format!("copy {} from stdin", table.name))?;
+        let mut buf = vec![0u8; COPY_CHUNK_BYTES];
+        loop {
+            let n = file
+                .read(&mut buf)
+                .with_context(|| format!("read {}", table.file))?;
+            if n == 0 {
+                break;
+            }
+            copy.send(&buf[..n])
+                .await
+                .with_context(|| format!("send COPY data for {}", table.name))?;
+        }
+        let rows = copy
+            .finish()
+            .await
+            .with_context(|| format!("finish COPY into {}", table.name))?;
+        anyhow::ensure!(
+            rows == table.rows,
+            "{}: loaded {rows} rows, manifest says {}",
+            table.name,
+            table.rows
+        );
+    }
+
+    // Identity columns were loaded with explicit values; move their sequences past them.
+    let identities = sqlx::query_as::<Postgres, (String, String)>(
+        "select format('%I.%I', table_schema, table_name), column_name::text \
+           from information_schema.columns \
+          where is_identity = 'YES' and table_schema = any($1)",
+    )
+    .bind(&SCHEMAS[..])
+    .fetch_all(&mut *tx)
+    .await
+    .context("list identity columns")?;
+    for (table, column) in identities {
+        sqlx::query(&format!(
+            "select setval(pg_get_serial_sequence($1, $2), coalesce(max({column}), 0) + 1, false) from {table}"
+        ))
+        .bind(&table)
+        .bind(&column)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("reset identity {table}.{column}"))?;
+    }
+
+    for name in &names {
+        sqlx::query(&format!("alter table {name} enable trigger user"))
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("enable triggers on {name}"))?;
+    }
+    tx.commit().await.context("commit import tx")?;
+
+    for s in &manifest.streams {
+        println!(
+            "imported {} through event_seq={} tip={}; ingestion resumes at block {}",
+            s.stream, s.applied_through_seq, s.tip, s.next_block
+        );
+    }
+    Ok(())
+}
+
+/// Re-reads the `EventAppended` log for the snapshot's last applied event and checks it is
+/// still canonical and carries the snapshot's tip.
+async fn verify_stream_tip(s: &StreamSnapshot) -> Result<()> {
+    let Some(block_number) = s.tip_block_number else {
+        return check_tip(s, &[]);
+    };
+
+    let (urls_var, contract, topic0) = match s.stream.as_str() {
+        "hub" => (
+            "HUB_RPC_URLS",
+            Address::from_str(&s.contract_address).context("invalid hub contract address")?,
+            UntronV3Index::EventAppended::SIGNATURE_HASH,
+        ),
+        "controller" => (
+            "CONTROLLER_RPC_URLS",
+            tron_to_evm_address(&s.contract_address)?,
+            UntronControllerIndex::EventAppended::SIGNATURE_HASH,
+        ),
+        other => anyhow::bail!("unknown stream in snapshot: {other}"),
+    };
+    let urls = env::var(urls_var).with_context(|| format!("{urls_var} must be set"))?;
+    let client = untron_rpc_fallback::rpc_client_from_urls_csv(&urls, Duration::from_secs(10))
+        .with_context(|| format!("connect {urls_var}"))?;
+    let provider = DynProvider::new(ProviderBuilder::default().connect_client(client));
+
+    let chain_id = provider.get_chain_id().await.context("eth_chainId")?;
+    anyhow::ensure!(
+        i64::try_from(chain_id).ok() == Some(s.chain_id),
+        "rpc chain_id {chain_id} does not match snapshot chain_id {}",
+        s.chain_id
+    );
+
+    let block_number = u64::try_from(block_number).context("negative tip block number")?;
+    let filter = Filter::new()
+        .address(contract)
+        .from_block(block_number)
+        .to_block(block_number)
+        .event_signature(topic0);
+    let logs = provider
+        .get_logs(&filter)
+        .await
+        .with_context(|| format!("eth_getLogs at block {block_number}"))?;
+
+    let mut events = Vec::with_capacity(logs.len());
+    for log in logs {
+        let (event_seq, new_tip) = match s.stream.as_str() {
+            "hub" => {
+                let ev = log
+                    .log_decode::<UntronV3Index::EventAppended>()
+                    .map_err(|e| anyhow::anyhow!("EventAppended decode failed: {e}"))?
+                    .inner
+                    .data;
+                (ev.eventSeq, ev.newTip)
+            }
+            _ => {
+                let ev = log
+                    .log_decode::<UntronControllerIndex::EventAppended>()
+                    .map_err(|e| anyhow::anyhow!("EventAppended decode failed: {e}"))?
+                    .inner
+                    .data;
+                (ev.eventSeq, ev.newTip)
+            }
+        };
+        events.push(ChainEvent {
+            event_seq: i64::try_from(event_seq).unwrap_or(i64::MAX),
+            new_tip,
+            block_hash: log.block_hash,
+        });
+    }
+    check_tip(s, &events)
+}
+
+/// An `EventAppended` log as read back from chain.
+#[derive(Debug, Clone)]
+struct ChainEvent {
+    event_seq: i64,
+    new_tip: B256,
+    block_hash: Option<B256>,
+}
+
+/// Checks that `events` (the stream's logs in the snapshot tip block) contain the snapshot's last
+/// applied event, in the same block and with the snapshot's tip.
+fn check_tip(s: &StreamSnapshot, events: &[ChainEvent]) -> Result<()> {
+    let (Some(block_number), Some(block_hash)) = (s.tip_block_number, s.tip_block_hash) else {
+        anyhow::ensure!(
+            s.applied_through_seq == 0,
+            "missing tip block for event_seq {}",
+            s.applied_through_seq
+        );
+        return Ok(());
+    };
+    let ev = events
+        .iter()
+        .find(|ev| ev.event_seq == s.applied_through_seq)
+        .with_context(|| {
+            format!(
+                "event {} not found on chain at block {block_number}",
+                s.applied_through_seq
+            )
+        })?;
+    anyhow::ensure!(
+        ev.block_hash == Some(block_hash),
+        "event {} block {block_number} hash changed (reorged?): snapshot {block_hash}, chain {:?}",
+        s.applied_through_seq,
+        ev.block_hash
+    );
+    anyhow::ensure!(
+        ev.new_tip == s.tip,
+        "event {} newTip on chain is {}, snapshot says {}",
+        s.applied_through_seq,
+        ev.new_tip,
+        s.tip
+    );
+    Ok(())
+}
+
+async fn schema_version(conn: &mut sqlx::PgConnection) -> Result<i64> {
+    sqlx::query_scalar::<Postgres, i64>("select coalesce(max(version), 0) from _sqlx_migrations")
+        .fetch_one(conn)
+        .await
+        .context("read migration version")
+}
+
+fn parse_b256(value: &str) -> Result<B256> {
+    B256::from_str(value).with_context(|| format!("invalid bytes32 hex: {value}"))
+}
+
+fn tron_to_evm_address(value: &str) -> Result<Address> {
+    let raw = bs58::decode(value)
+        .with_check(None)
+        .into_vec()
+        .with_context(|| format!("invalid Tron address: {value}"))?;
+    anyhow::ensure!(
+        raw.len() == 21 && raw[0] == 0x41,
+        "invalid Tron address: {value}"
+    );
+    Ok(Address::from_slice(&raw[1..]))
+}
+
+/// Orders tables so each comes after the tables its foreign keys reference (ties by name).
+fn load_order(tables: Vec<String>, fks: &[(String, String)]) -> Result<Vec<String>> {
+    let mut deps: BTreeMap<String, BTreeSet<String>> =
+        tables.into_iter().map(|t| (t, BTreeSet::new())).collect();
+    for (child, parent) in fks {
+        if child != parent
+            && deps.contains_key(parent)
+            && let Some(d) = deps.get_mut(child)
+        {
+            d.insert(parent.clone());
+        }
+    }
+
+    let mut order = Vec::with_capacity(deps.len());
+    while !deps.is_empty() {
+        let ready: Vec<String> = deps
+            .iter()
+            .filter(|(_, d)| d.is_empty())
+            .map(|(t, _)| t.clone())
+            .collect();
+        anyhow::ensure!(
+            !ready.is_empty(),
+            "foreign key cycle among tables: {:?}",
+            deps.keys().collect::<Vec<_>>()
+        );
+        for t in &ready {
+            deps.remove(t);
+        }
+        for d in deps.values_mut() {
+            for t in &ready {
+                d.remove(t);
+            }
+        }
+        order.extend(ready);
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_order_puts_referenced_tables_first() {
+        let tables = [
+            "hub.lease_versions",
+            "chain.event_appended",
+            "chain.stream_cursor",
+            "chain.instance",
+        ]
+        .map(String::from)
+        .to_vec();
+        let fks = [
+            ("chain.event_appended", "chain.instance"),
+            ("chain.stream_cursor", "chain.instance"),
+            ("hub.lease_versions", "hub.lease_versions"),
+            ("hub.lease_versions", "realtor.write_action"),
+        ]
+        .map(|(a, b)| (a.to_string(), b.to_string()));
+
+        assert_eq!(
+            load_order(tables, &fks).unwrap(),
+            vec![
+                "chain.instance",
+                "chain.event_appended",
+                "chain.stream_cursor",
+                "hub.lease_versions",
+            ]
+        );
+
+        let cycle = [("a.x", "a.y"), ("a.y", "a.x")].map(|(a, b)| (a.to_string(), b.to_string()));
+        assert!(load_order(vec!["a.x".into(), "a.y".into()], &cycle).is_err());
+    }
+
+    #[test]
+    fn manifest_round_trips_through_json() {
+        let manifest = Manifest {
+            format_version: FORMAT_VERSION,
+            schema_version: 20_250_101_000_000,
+            created_at_unix: 1_700_000_000,
+            streams: vec![
+                StreamSnapshot {
+                    stream: "controller".into(),
+                    chain_id: 728_126_428,
+                    contract_address: "TNPeeaaFB7K9cmo4uQpcU32zGK8G1NYqeL".into(),
+                    applied_through_seq: 0,
+                    tip: B256::repeat_byte(0x11),
+                    tip_block_number: None,
+                    tip_block_hash: None,
+                    next_block: 0,
+                },
+                StreamSnapshot {
+                    stream: "hub".into(),
+                    chain_id: 42_161,
+                    contract_address: "0x00000000000000000000000000000000000000aa".into(),
+                    applied_through_seq: 42,
+                    tip: B256::repeat_byte(0x22),
+                    tip_block_number: Some(1_234),
+                    tip_block_hash: Some(B256::repeat_byte(0x33)),
+                    next_block: 1_240,
+                },
+            ],
+            tables: vec![TableSnapshot {
+                name: "chain.instance".into(),
+                file: "chain.instance.copy".into(),
+                rows: 2,
+            }],
+        };
+
+        let json = serde_json::to_vec_pretty(&manifest).unwrap();
+        let parsed: Manifest = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed, manifest);
+
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            value["streams"][0]["tip_block_number"],
+            serde_json::Value::Null
+        );
+        assert_eq!(value["streams"][1]["tip"], format!("0x{}", "22".repeat(32)));
+    }
+
+    #[test]
+    fn parse_targets_takes_one_seq_per_stream() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            parse_targets(args(&["hub=12", "controller=0"])).unwrap(),
+            vec![("hub".to_string(), 12), ("controller".to_string(), 0)]
+        );
+        assert!(parse_targets(args(&["hub=1", "hub=2"])).is_err());
+        assert!(parse_targets(args(&["realtor=1"])).is_err());
+        assert!(parse_targets(args(&["hub=-1"])).is_err());
+        assert!(parse_targets(args(&["hub"])).is_err());
+    }
+
+    #[test]
+    fn snapshot_at_earlier_seq_passes_tip_check_after_reimport() {
+        // Events 2 and 3 landed in the same block; the export was rolled back to 2.
+        let block_hash = B256::repeat_byte(0x33);
+        let chain = [
+            ChainEvent {
+                event_seq: 2,
+                new_tip: B256::repeat_byte(0x02),
+                block_hash: Some(block_hash),
+            },
+            ChainEvent {
+                event_seq: 3,
+                new_tip: B256::repeat_byte(0x03),
+                block_hash: Some(block_hash),
+            },
+        ];
+        let exported = StreamSnapshot {
+            stream: "hub".into(),
+            chain_id: 42_161,
+            contract_address: "0x00000000000000000000000000000000000000aa".into(),
+            applied_through_seq: 2,
+            tip: B256::repeat_byte(0x02),
+            tip_block_number: Some(1_234),
+            tip_block_hash: Some(block_hash),
+            next_block: 1_234,
+        };
+        let imported: StreamSnapshot =
+            serde_json::from_slice(&serde_json::to_vec(&exported).unwrap()).unwrap();
+        check_tip(&imported, &chain).unwrap();
+
+        // The tip must be the one of the requested event, not the stream head.
+        let head_tip = StreamSnapshot {
+            tip: B256::repeat_byte(0x03),
+            ..imported
+        };
+        assert!(check_tip(&head_tip, &chain).is_err());
+
+        let reorged = StreamSnapshot {
+            tip_block_hash: Some(B256::repeat_byte(0x44)),
+            tip: B256::repeat_byte(0x02),
+            ..head_tip
+        };
+        assert!(check_tip(&reorged, &chain).is_err());
+
+        let missing = StreamSnapshot {
+            applied_through_seq: 4,
+            tip_block_hash: Some(block_hash),
+            ..reorged
+        };
+        assert!(check_tip(&missing, &chain).is_err());
+
+        let genesis = StreamSnapshot {
+            applied_through_seq: 0,
+            tip_block_number: None,
+            tip_block_hash: None,
+            next_block: 0,
+            ..missing
+        };
+        check_tip(&genesis, &[]).unwrap();
+    }
+}
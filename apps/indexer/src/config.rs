@@ -55,6 +55,26 @@ pub struct ReceiverUsdtConfig {
     pub range_concurrency: usize,
     pub backfill_concurrency: usize,
     pub discovery_interval: Duration,
+    /// Extra TRC-20 tokens indexed into receivers alongside the controller's USDT (which is
+    /// always tracked via `UsdtSet`). Each token has its own tail cursor, so adding one later
+    /// catches it up from the controller deployment block.
+    pub tokens: Vec<crate::domain::TronAddress>,
+}
+
+/// Scans Tron blocks for TRX (`TransferContract`) and TRC-10 (`TransferAssetContract`) transfers
+/// into watched receivers. These never emit logs, so this needs a Tron node rather than the
+/// controller's JSON-RPC.
+#[derive(Debug, Clone)]
+pub struct ReceiverNativeConfig {
+    pub enabled: bool,
+    /// gRPC URL, or `rest+https://…` for the `/wallet/*` HTTP API.
+    pub tron_url: String,
+    pub tron_api_key: Option<String>,
+    pub tron_api_key_header: String,
+    pub poll_interval: Duration,
+    pub block_concurrency: usize,
+    /// First block scanned on a fresh DB; `None` starts at the current safe head.
+    pub start_block: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -80,6 +100,7 @@ pub struct AppConfig {
     pub database_url: String,
     pub streams: Vec<StreamConfig>,
     pub receiver_usdt: ReceiverUsdtConfig,
+    pub receiver_native: ReceiverNativeConfig,
     pub hub_deposit_processed: HubDepositProcessedConfig,
     pub gap_repair: GapRepairConfig,
     pub db_max_connections: u32,
@@ -187,6 +208,9 @@ struct ReceiverUsdtEnv {
 
     #[serde(rename = "trc20_discovery_interval_secs")]
     discovery_interval_secs: u64,
+
+    #[serde(rename = "trc20_tokens")]
+    tokens: String,
 }
 
 impl Default for ReceiverUsdtEnv {
@@ -202,6 +226,43 @@ impl Default for ReceiverUsdtEnv {
             range_concurrency: DEFAULT_TRC20_RANGE_CONCURRENCY,
             backfill_concurrency: DEFAULT_TRC20_BACKFILL_CONCURRENCY,
             discovery_interval_secs: DEFAULT_TRC20_DISCOVERY_INTERVAL_SECS,
+            tokens: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct ReceiverNativeEnv {
+    #[serde(rename = "tron_native_enabled")]
+    enabled: bool,
+
+    tron_grpc_url: String,
+
+    tron_api_key: Option<String>,
+
+    tron_api_key_header: String,
+
+    #[serde(rename = "tron_native_poll_interval_secs")]
+    poll_interval_secs: u64,
+
+    #[serde(rename = "tron_native_block_concurrency")]
+    block_concurrency: usize,
+
+    #[serde(rename = "tron_native_start_block")]
+    start_block: Option<u64>,
+}
+
+impl Default for ReceiverNativeEnv {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tron_grpc_url: String::new(),
+            tron_api_key: None,
+            tron_api_key_header: tron::DEFAULT_API_KEY_HEADER.to_string(),
+            poll_interval_secs: DEFAULT_TRON_NATIVE_POLL_INTERVAL_SECS,
+            block_concurrency: DEFAULT_TRON_NATIVE_BLOCK_CONCURRENCY,
+            start_block: None,
         }
     }
 }
@@ -282,6 +343,32 @@ struct StreamEnv {
     /// Optional hard cap for raw JSON-RPC requests emitted by this stream provider.
     /// With a prefix this maps to e.g. `CONTROLLER_RPC_MAX_REQUESTS_PER_SECOND`.
     rpc_max_requests_per_second: Option<u32>,
+
+    /// Optional list of JSON-RPC methods read with consensus across endpoints, e.g.
+    /// `HUB_RPC_QUORUM_METHODS=eth_getBlockByNumber,eth_getLogs`. Empty disables quorum reads.
+    rpc_quorum_methods: Option<String>,
+    rpc_quorum_fanout: Option<usize>,
+    rpc_quorum_min_agree: Option<usize>,
+    rpc_quorum_allow_degraded: Option<bool>,
+
+    /// Optional list of JSON-RPC methods hedged onto a second endpoint when slow, e.g.
+    /// `HUB_RPC_HEDGE_METHODS=eth_getLogs`. Empty disables hedging.
+    rpc_hedge_methods: Option<String>,
+    rpc_hedge_percentile: Option<f64>,
+    rpc_hedge_min_delay_ms: Option<u64>,
+
+    /// Optional WS/IPC endpoints (`HUB_WS_URLS=wss://…`) holding a `newHeads` subscription, so
+    /// the stream reacts to new blocks instead of waiting for the next poll.
+    ws_urls: Option<String>,
+
+    /// Optional compute-unit budget applied to each endpoint on its own, e.g.
+    /// `HUB_RPC_ENDPOINT_COMPUTE_UNITS_PER_SECOND=330` for a provider's CU/s plan.
+    rpc_endpoint_compute_units_per_second: Option<u32>,
+    /// `method=units` pairs, e.g. `eth_getLogs=75,eth_blockNumber=10`.
+    rpc_method_costs: Option<String>,
+    rpc_default_method_cost: Option<u32>,
+    /// `method=requests/sec` caps, e.g. `eth_getLogs=5`.
+    rpc_method_max_requests_per_second: Option<String>,
 }
 
 pub fn load_config() -> Result<AppConfig> {
@@ -292,6 +379,8 @@ pub fn load_config() -> Result<AppConfig> {
     let retry_env: RetryEnv = envy::from_env().context("load retry env config")?;
     let receiver_usdt_env: ReceiverUsdtEnv =
         envy::from_env().context("load receiver_usdt env config")?;
+    let receiver_native_env: ReceiverNativeEnv =
+        envy::from_env().context("load receiver_native env config")?;
     let hub_deposit_processed_env: HubDepositProcessedEnv =
         envy::from_env().context("load hub_deposit_processed env config")?;
     let gap_repair_env: GapRepairEnv = envy::from_env().context("load gap repair env config")?;
@@ -330,6 +419,25 @@ pub fn load_config() -> Result<AppConfig> {
     let preknown_receiver_salts = parse_list(&receiver_usdt_env.preknown_receiver_salts);
     let controller_create2_prefix = parse_bytes1(&receiver_usdt_env.controller_create2_prefix)
         .context("UNTRON_CONTROLLER_CREATE2_PREFIX")?;
+    let mut receiver_tokens = Vec::new();
+    for raw in parse_list(&receiver_usdt_env.tokens) {
+        let token = crate::domain::TronAddress::parse_text(&raw)
+            .with_context(|| format!("TRC20_TOKENS: invalid token address {raw}"))?;
+        if !receiver_tokens.contains(&token) {
+            receiver_tokens.push(token);
+        }
+    }
+    let tron_url = receiver_native_env.tron_grpc_url.trim().to_string();
+    if receiver_native_env.enabled && tron_url.is_empty() {
+        anyhow::bail!("TRON_NATIVE_ENABLED requires TRON_GRPC_URL");
+    }
+    // The native scanner matches against ctl.receiver_watchlist, which only the TRC-20 indexer's
+    // discovery loop fills.
+    if receiver_native_env.enabled && !receiver_usdt_env.enabled {
+        anyhow::bail!(
+            "TRON_NATIVE_ENABLED requires TRC20_ENABLED (receiver discovery runs in the TRC-20 indexer)"
+        );
+    }
 
     Ok(AppConfig {
         database_url: base.database_url,
@@ -347,6 +455,18 @@ pub fn load_config() -> Result<AppConfig> {
             discovery_interval: Duration::from_secs(
                 receiver_usdt_env.discovery_interval_secs.max(5),
             ),
+            tokens: receiver_tokens,
+        },
+        receiver_native: ReceiverNativeConfig {
+            enabled: receiver_native_env.enabled,
+            tron_url,
+            tron_api_key: receiver_native_env
+                .tron_api_key
+                .filter(|k| !k.trim().is_empty()),
+            tron_api_key_header: receiver_native_env.tron_api_key_header,
+            poll_interval: Duration::from_secs(receiver_native_env.poll_interval_secs.max(1)),
+            block_concurrency: receiver_native_env.block_concurrency.clamp(1, 64),
+            start_block: receiver_native_env.start_block,
         },
         hub_deposit_processed: HubDepositProcessedConfig {
             enabled: hub_deposit_processed_env.enabled,
@@ -433,6 +553,79 @@ fn load_stream_config(
         .or(defaults.rpc_max_requests_per_second)
         .filter(|v| *v > 0);
 
+    let quorum_methods = parse_list(env.rpc_quorum_methods.as_deref().unwrap_or_default());
+    let rpc_quorum = if quorum_methods.is_empty() {
+        None
+    } else {
+        let min_agree = env
+            .rpc_quorum_min_agree
+            .unwrap_or(DEFAULT_RPC_QUORUM_MIN_AGREE);
+        Some(untron_rpc_fallback::QuorumPolicy {
+            methods: quorum_methods,
+            fanout: env.rpc_quorum_fanout.unwrap_or(min_agree).max(min_agree),
+            min_agree,
+            allow_degraded: env.rpc_quorum_allow_degraded.unwrap_or(false),
+        })
+    };
+
+    let rpc_endpoint_limits = match env.rpc_endpoint_compute_units_per_second {
+        Some(cu) if cu > 0 => {
+            let mut limits = untron_rpc_fallback::RpcRateLimits::new(cu);
+            limits.method_costs = untron_rpc_fallback::RpcRateLimits::method_map_from_csv(
+                env.rpc_method_costs.as_deref().unwrap_or_default(),
+            )
+            .with_context(|| format!("parse {prefix}RPC_METHOD_COSTS"))?;
+            limits.default_cost = env.rpc_default_method_cost.unwrap_or(1);
+            limits.method_requests_per_second =
+                untron_rpc_fallback::RpcRateLimits::method_map_from_csv(
+                    env.rpc_method_max_requests_per_second
+                        .as_deref()
+                        .unwrap_or_default(),
+                )
+                .with_context(|| format!("parse {prefix}RPC_METHOD_MAX_REQUESTS_PER_SECOND"))?;
+            Some(limits)
+        }
+        _ => {
+            // Costs and caps only act through the per-endpoint budget.
+            let orphaned = [
+                (
+                    "RPC_METHOD_COSTS",
+                    env.rpc_method_costs
+                        .as_deref()
+                        .is_some_and(|v| !v.trim().is_empty()),
+                ),
+                (
+                    "RPC_DEFAULT_METHOD_COST",
+                    env.rpc_default_method_cost.is_some(),
+                ),
+                (
+                    "RPC_METHOD_MAX_REQUESTS_PER_SECOND",
+                    env.rpc_method_max_requests_per_second
+                        .as_deref()
+                        .is_some_and(|v| !v.trim().is_empty()),
+                ),
+            ];
+            if let Some((name, _)) = orphaned.iter().find(|(_, set)| *set) {
+                anyhow::bail!(
+                    "{prefix}{name} requires a positive {prefix}RPC_ENDPOINT_COMPUTE_UNITS_PER_SECOND"
+                );
+            }
+            None
+        }
+    };
+
+    let hedge_methods = parse_list(env.rpc_hedge_methods.as_deref().unwrap_or_default());
+    let rpc_hedge = (!hedge_methods.is_empty()).then(|| untron_rpc_fallback::HedgePolicy {
+        methods: hedge_methods,
+        percentile: env
+            .rpc_hedge_percentile
+            .unwrap_or(DEFAULT_RPC_HEDGE_PERCENTILE),
+        min_delay: Duration::from_millis(
+            env.rpc_hedge_min_delay_ms
+                .unwrap_or(DEFAULT_RPC_HEDGE_MIN_DELAY_MS),
+        ),
+    });
+
     Ok(Some(StreamConfig {
         stream,
         chain_id,
@@ -440,6 +633,11 @@ fn load_stream_config(
             urls: rpc_urls,
             retry: retry.clone(),
             max_requests_per_second: rpc_max_requests_per_second,
+            quorum: rpc_quorum,
+            hedge: rpc_hedge,
+            ws_urls: parse_list(env.ws_urls.as_deref().unwrap_or_default()),
+            head_poll_interval: poll_interval,
+            endpoint_limits: rpc_endpoint_limits,
         },
         contract_address,
         deployment_block,
@@ -486,6 +684,9 @@ const DEFAULT_PROGRESS_TAIL_LAG_BLOCKS: u64 = 0;
 const DEFAULT_RPC_MAX_RATE_LIMIT_RETRIES: u32 = 8;
 const DEFAULT_RPC_INITIAL_BACKOFF_MS: u64 = 250;
 const DEFAULT_RPC_COMPUTE_UNITS_PER_SECOND: u64 = 500;
+const DEFAULT_RPC_QUORUM_MIN_AGREE: usize = 2;
+const DEFAULT_RPC_HEDGE_PERCENTILE: f64 = 0.95;
+const DEFAULT_RPC_HEDGE_MIN_DELAY_MS: u64 = 100;
 
 const DEFAULT_GAP_REPAIR_INITIAL_PAD_BLOCKS: u64 = 16;
 const DEFAULT_GAP_REPAIR_MAX_WINDOW_BLOCKS: u64 = 50_000;
@@ -500,3 +701,6 @@ const DEFAULT_TRC20_TO_BATCH_SIZE: usize = 50;
 const DEFAULT_TRC20_RANGE_CONCURRENCY: usize = 16;
 const DEFAULT_TRC20_BACKFILL_CONCURRENCY: usize = 2;
 const DEFAULT_TRC20_DISCOVERY_INTERVAL_SECS: u64 = 30;
+
+const DEFAULT_TRON_NATIVE_POLL_INTERVAL_SECS: u64 = 3;
+const DEFAULT_TRON_NATIVE_BLOCK_CONCURRENCY: usize = 8;
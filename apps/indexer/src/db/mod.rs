@@ -9,6 +9,7 @@ use std::time::Duration;
 pub mod deposit_processed;
 pub mod event_chain;
 mod instance;
+pub mod receiver_native;
 pub mod receiver_usdt;
 mod receiver_usdt_subjective_pre_entitle;
 mod types;
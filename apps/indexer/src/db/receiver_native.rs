@@ -0,0 +1,269 @@
+use anyhow::{Context, Result};
+use sqlx::types::BigDecimal;
+use sqlx::{Postgres, QueryBuilder, Transaction, query_scalar};
+use std::collections::HashMap;
+
+use crate::db::Db;
+use crate::db::receiver_usdt::{WatchReceiverRow, build_receiver_map};
+
+#[derive(Debug, Clone)]
+pub struct NativeTransferRow {
+    pub chain_id: i64,
+    /// `trx` or `trc10`.
+    pub kind: &'static str,
+    pub asset_id: Option<String>,
+    pub receiver_salt: String,
+    pub sender: String,
+    pub recipient: String,
+    pub amount: BigDecimal,
+    pub block_number: i64,
+    pub block_timestamp: i64,
+    pub block_hash: String,
+    pub tx_hash: String,
+}
+
+pub async fn ensure_cursor(db: &Db, start_block: u64) -> Result<u64> {
+    let start_block: i64 =
+        i64::try_from(start_block).context("start_block out of range for bigint")?;
+
+    let mut tx = db.pool.begin().await.context("begin ensure_cursor tx")?;
+    sqlx::query(
+        "insert into ctl.receiver_native_cursor (stream, next_block, start_block) \
+         values ('controller', $1, $1) \
+         on conflict (stream) do nothing",
+    )
+    .bind(start_block)
+    .execute(&mut *tx)
+    .await
+    .context("insert receiver_native_cursor")?;
+
+    let next: i64 = query_scalar(
+        "select next_block from ctl.receiver_native_cursor where stream = 'controller'",
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .context("read receiver_native_cursor")?;
+
+    tx.commit().await.context("commit ensure_cursor tx")?;
+
+    u64::try_from(next).context("native cursor next_block out of range")
+}
+
+/// Records `rows` and moves the cursor to `next_block` atomically, so a crash never skips blocks.
+pub async fn insert_transfers_and_advance(
+    db: &Db,
+    rows: &[NativeTransferRow],
+    next_block: u64,
+) -> Result<()> {
+    let next_block: i64 =
+        i64::try_from(next_block).context("next_block out of range for bigint")?;
+
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .context("begin insert_native_transfers tx")?;
+
+    insert_transfers(&mut tx, rows).await?;
+
+    sqlx::query(
+        "update ctl.receiver_native_cursor set next_block = $1, updated_at = now() where stream = 'controller'",
+    )
+    .bind(next_block)
+    .execute(&mut *tx)
+    .await
+    .context("update receiver_native_cursor")?;
+
+    tx.commit()
+        .await
+        .context("commit insert_native_transfers tx")?;
+    Ok(())
+}
+
+/// Gives every watched receiver without one a backfill cursor at the scanner's start block.
+pub async fn register_backfill_receivers(db: &Db) -> Result<u64> {
+    let res = sqlx::query(
+        "insert into ctl.receiver_native_backfill (receiver_salt, next_block) \
+         select w.receiver_salt, c.start_block \
+         from ctl.receiver_watchlist w \
+         cross join ctl.receiver_native_cursor c \
+         where c.stream = 'controller' \
+         on conflict (receiver_salt) do nothing",
+    )
+    .execute(&db.pool)
+    .await
+    .context("register receiver_native_backfill receivers")?;
+    Ok(res.rows_affected())
+}
+
+pub struct NativeBackfillWork {
+    pub start_block: u64,
+    pub stop_at_or_above: u64,
+    pub receiver_salts: Vec<String>,
+    pub receiver_map: HashMap<alloy::primitives::Address, String>,
+}
+
+/// The receivers furthest behind (all sharing the lowest backfill cursor), bounded by the
+/// scanner cursor: blocks at or above it are covered by the forward scan.
+pub async fn next_backfill_work(db: &Db) -> Result<Option<NativeBackfillWork>> {
+    let stop_at_or_above: i64 = query_scalar(
+        "select next_block from ctl.receiver_native_cursor where stream = 'controller'",
+    )
+    .fetch_one(&db.pool)
+    .await
+    .context("read receiver_native_cursor")?;
+
+    let start: Option<i64> = query_scalar(
+        "select min(next_block) from ctl.receiver_native_backfill where next_block is not null",
+    )
+    .fetch_one(&db.pool)
+    .await
+    .context("read min receiver_native_backfill next_block")?;
+
+    let Some(start) = start else {
+        return Ok(None);
+    };
+
+    let rows = sqlx::query_as::<Postgres, (String, String)>(
+        "select b.receiver_salt::text, w.receiver_evm::text \
+         from ctl.receiver_native_backfill b \
+         join ctl.receiver_watchlist w on w.receiver_salt = b.receiver_salt \
+         where b.next_block = $1 \
+         order by b.receiver_salt asc",
+    )
+    .bind(start)
+    .fetch_all(&db.pool)
+    .await
+    .context("list receiver_native_backfill batch")?;
+
+    let batch = rows
+        .into_iter()
+        .map(|(receiver_salt, receiver_evm)| WatchReceiverRow {
+            receiver_salt,
+            receiver_evm,
+        })
+        .collect::<Vec<_>>();
+    if batch.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(NativeBackfillWork {
+        start_block: u64::try_from(start).context("backfill next_block out of range")?,
+        stop_at_or_above: u64::try_from(stop_at_or_above)
+            .context("native cursor next_block out of range")?,
+        receiver_salts: batch.iter().map(|r| r.receiver_salt.clone()).collect(),
+        receiver_map: build_receiver_map(&batch)?,
+    }))
+}
+
+/// Clears the backfill of receivers that need none (their account never held TRX or TRC-10).
+pub async fn clear_backfill_receivers(db: &Db, receiver_salts: &[String]) -> Result<()> {
+    if receiver_salts.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "update ctl.receiver_native_backfill \
+         set next_block = null, updated_at = now() \
+         where receiver_salt = any($1)",
+    )
+    .bind(receiver_salts)
+    .execute(&db.pool)
+    .await
+    .context("clear receiver_native_backfill")?;
+    Ok(())
+}
+
+/// Records backfilled `rows` and moves the batch's cursors from `start_block` to `next_block`
+/// (`None` once the backfill is done) atomically.
+pub async fn insert_backfill_transfers_and_advance(
+    db: &Db,
+    rows: &[NativeTransferRow],
+    receiver_salts: &[String],
+    start_block: u64,
+    next_block: Option<u64>,
+) -> Result<()> {
+    let start_block: i64 =
+        i64::try_from(start_block).context("start_block out of range for bigint")?;
+    let next_block: Option<i64> = next_block
+        .map(i64::try_from)
+        .transpose()
+        .context("next_block out of range for bigint")?;
+
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .context("begin insert_native_backfill tx")?;
+
+    insert_transfers(&mut tx, rows).await?;
+
+    // Matching on the old cursor leaves receivers a reorg rewound in the meantime alone.
+    sqlx::query(
+        "update ctl.receiver_native_backfill \
+         set next_block = $3, updated_at = now() \
+         where receiver_salt = any($1) and next_block = $2",
+    )
+    .bind(receiver_salts)
+    .bind(start_block)
+    .bind(next_block)
+    .execute(&mut *tx)
+    .await
+    .context("advance receiver_native_backfill")?;
+
+    tx.commit()
+        .await
+        .context("commit insert_native_backfill tx")?;
+    Ok(())
+}
+
+async fn insert_transfers(
+    tx: &mut Transaction<'_, Postgres>,
+    rows: &[NativeTransferRow],
+) -> Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "insert into ctl.receiver_native_transfers (\
+         chain_id, kind, asset_id, receiver_salt, \
+         sender, recipient, amount, \
+         block_number, block_timestamp, block_hash, \
+         tx_hash, canonical\
+         ) ",
+    );
+    qb.push_values(rows, |mut b, row| {
+        b.push_bind(row.chain_id);
+        b.push_bind(row.kind);
+        b.push_bind(&row.asset_id);
+        b.push_bind(&row.receiver_salt);
+        b.push_bind(&row.sender);
+        b.push_bind(&row.recipient);
+        b.push_bind(&row.amount);
+        b.push_bind(row.block_number);
+        b.push_bind(row.block_timestamp);
+        b.push_bind(&row.block_hash);
+        b.push_bind(&row.tx_hash);
+        b.push_bind(true);
+    });
+    // A rescanned block (after a reorg rewind or a backfill) re-canonicalizes or replaces its rows.
+    qb.push(
+        " on conflict (chain_id, tx_hash) do update set \
+          kind = excluded.kind, \
+          asset_id = excluded.asset_id, \
+          receiver_salt = excluded.receiver_salt, \
+          sender = excluded.sender, \
+          recipient = excluded.recipient, \
+          amount = excluded.amount, \
+          block_number = excluded.block_number, \
+          block_timestamp = excluded.block_timestamp, \
+          block_hash = excluded.block_hash, \
+          canonical = excluded.canonical",
+    );
+    qb.build()
+        .execute(&mut **tx)
+        .await
+        .context("insert ctl.receiver_native_transfers")?;
+    Ok(())
+}
@@ -253,6 +253,55 @@ pub async fn update_tail_cursor(db: &Db, next_block: u64) -> Result<()> {
     Ok(())
 }
 
+/// Tail cursor for a configured (non-USDT) receiver token; created at `deployment_block`.
+pub async fn ensure_token_tail_cursor(db: &Db, token: &str, deployment_block: u64) -> Result<u64> {
+    let deployment_block: i64 =
+        i64::try_from(deployment_block).context("deployment_block out of range for bigint")?;
+
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .context("begin ensure_token_tail_cursor tx")?;
+    sqlx::query(
+        "insert into ctl.receiver_token_tail_cursor (token, next_block) \
+         values ($1, $2) \
+         on conflict (token) do nothing",
+    )
+    .bind(token)
+    .bind(deployment_block)
+    .execute(&mut *tx)
+    .await
+    .context("insert receiver_token_tail_cursor")?;
+
+    let next: i64 =
+        query_scalar("select next_block from ctl.receiver_token_tail_cursor where token = $1")
+            .bind(token)
+            .fetch_one(&mut *tx)
+            .await
+            .context("read receiver_token_tail_cursor")?;
+
+    tx.commit()
+        .await
+        .context("commit ensure_token_tail_cursor tx")?;
+
+    u64::try_from(next).context("token tail cursor next_block out of range")
+}
+
+pub async fn update_token_tail_cursor(db: &Db, token: &str, next_block: u64) -> Result<()> {
+    let next_block: i64 =
+        i64::try_from(next_block).context("next_block out of range for bigint")?;
+    sqlx::query(
+        "update ctl.receiver_token_tail_cursor set next_block = $2, updated_at = now() where token = $1",
+    )
+    .bind(token)
+    .bind(next_block)
+    .execute(&db.pool)
+    .await
+    .context("update receiver_token_tail_cursor")?;
+    Ok(())
+}
+
 pub async fn list_watch_receivers(db: &Db) -> Result<Vec<WatchReceiverRow>> {
     let rows = sqlx::query_as::<Postgres, (String, String)>(
         "select receiver_salt::text, receiver_evm::text from ctl.receiver_watchlist",
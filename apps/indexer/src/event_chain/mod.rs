@@ -1,11 +1,11 @@
 mod decode;
-mod errors;
 mod range;
 mod reorg;
 mod repair;
 mod rows;
 mod runner;
 mod state;
+mod tip;
 
 pub use runner::RunStreamParams;
 pub use runner::run_stream;
@@ -10,7 +10,7 @@ use untron_v3_bindings::{
 };
 
 use super::{rows, state::PollState};
-use crate::shared::{r#async, logs};
+use crate::shared::{r#async, logs, rpc_error::RpcError};
 
 pub(super) async fn process_range(
     dbh: &db::Db,
@@ -63,8 +63,9 @@ pub(super) async fn process_range_with_provider(
         let Some((raw_logs, rpc_event_ms)) = r#async::timed_await_or_cancel(shutdown, async {
             provider.get_logs(&filter).await.map_err(|e| {
                 state.telemetry.rpc_error("eth_getLogs", "event_chain");
-                anyhow::Error::new(e)
-                    .context(format!("eth_getLogs event_chain [{from_block}..{to_block}]"))
+                anyhow::Error::new(RpcError::from(e)).context(format!(
+                    "eth_getLogs event_chain [{from_block}..{to_block}]"
+                ))
             })
         })
         .await?
@@ -1,7 +1,8 @@
-use crate::shared::rpc_telemetry::RpcTelemetry;
+use crate::shared::{rpc_error::RpcError, rpc_telemetry::RpcTelemetry};
 use crate::{config::Stream, db, domain};
 use alloy::{providers::Provider, rpc::types::BlockNumberOrTag};
 use anyhow::{Context, Result};
+use futures::future::join_all;
 use serde_json::Value;
 use std::time::Instant;
 use tracing::{debug, warn};
@@ -10,6 +11,7 @@ pub async fn detect_reorg_start(
     dbh: &db::Db,
     provider: &impl Provider,
     pinned_providers: &[alloy::providers::DynProvider],
+    reorg_confirmations: usize,
     stream: Stream,
     scan_depth: u64,
     rpc_telemetry: Option<&dyn RpcTelemetry>,
@@ -48,9 +50,16 @@ pub async fn detect_reorg_start(
         return Ok(None);
     }
 
-    // Avoid false positives from a single bad/lagging RPC endpoint by checking at least one
-    // additional pinned endpoint (when configured).
-    if let Some(reason) = mismatch_not_confirmed(&latest, pinned_providers, rpc_telemetry).await? {
+    // Avoid false positives from a single bad/lagging RPC endpoint by having other pinned
+    // endpoints confirm the mismatch (unless a quorum read already did).
+    if let Some(reason) = mismatch_not_confirmed(
+        &latest,
+        pinned_providers,
+        reorg_confirmations,
+        rpc_telemetry,
+    )
+    .await?
+    {
         warn!(
             stream = stream.as_str(),
             block_number = latest.block_number,
@@ -149,7 +158,59 @@ async fn get_block_hash_opt(
                     start.elapsed().as_millis() as u64,
                 );
             }
-            anyhow::Error::new(e)
+            anyhow::Error::new(RpcError::from(e))
+        })
+        .with_context(|| format!("get_block_by_number({block_number})"))?;
+
+    let Some(block) = block else {
+        return Ok(None);
+    };
+
+    if let Some(rpc) = rpc_telemetry {
+        rpc.rpc_call(
+            "eth_getBlockByNumber",
+            "reorg",
+            true,
+            start.elapsed().as_millis() as u64,
+        );
+    }
+
+    let hash = block
+        .get("hash")
+        .and_then(|v| v.as_str())
+        .context("missing block.hash")?;
+    let b256: alloy::primitives::B256 = hash
+        .parse()
+        .with_context(|| format!("invalid block.hash: {hash}"))?;
+    Ok(Some(domain::BlockHash::from(b256)))
+}
+
+async fn get_block_hash_opt(
+    provider: &impl Provider,
+    block_number: u64,
+    rpc_telemetry: Option<&dyn RpcTelemetry>,
+) -> Result<Option<domain::BlockHash>> {
+    // Tron JSON-RPC block responses are not Ethereum-typed (e.g. `stateRoot: "0x"`), which can
+    // break strict decoding. For reorg detection we only need the block hash, so fetch raw JSON.
+    let start = Instant::now();
+    let block: Option<Value> = provider
+        .client()
+        .request(
+            "eth_getBlockByNumber",
+            (BlockNumberOrTag::Number(block_number), false),
+        )
+        .await
+        .map_err(|e| {
+            if let Some(rpc) = rpc_telemetry {
+                rpc.rpc_error("eth_getBlockByNumber", "reorg");
+                rpc.rpc_call(
+                    "eth_getBlockByNumber",
+                    "reorg",
+                    false,
+                    start.elapsed().as_millis() as u64,
+                );
+            }
+            anyhow::Error::new(RpcError::from(e))
         })
         .with_context(|| format!("get_block_by_number({block_number})"))?;
 
@@ -215,3 +276,74 @@ async fn mismatch_not_confirmed(
 
     Ok(Some("unable to confirm mismatch with multiple pinned RPCs"))
 }
+
+// Returns `Some(reason)` when a mismatch is not confirmed (and we should avoid invalidation).
+async fn mismatch_not_confirmed(
+    latest: &db::event_chain::StoredBlockHash,
+    pinned_providers: &[alloy::providers::DynProvider],
+    required: usize,
+    rpc_telemetry: Option<&dyn RpcTelemetry>,
+) -> Result<Option<&'static str>> {
+    if required == 0 {
+        return Ok(None);
+    }
+
+    // Ask every pinned endpoint at once. If any still reports the stored hash, treat this as
+    // inconclusive; otherwise `required` successful mismatching responses confirm it.
+    let hashes = join_all(
+        pinned_providers
+            .iter()
+            .map(|p| get_block_hash_opt(p, latest.block_number, rpc_telemetry)),
+    )
+    .await;
+    let hashes = hashes
+        .into_iter()
+        .filter_map(|h| h.ok().flatten())
+        .collect::<Vec<_>>();
+    Ok(confirmation_verdict(&latest.block_hash, &hashes, required))
+}
+
+fn confirmation_verdict(
+    stored: &domain::BlockHash,
+    reported: &[domain::BlockHash],
+    required: usize,
+) -> Option<&'static str> {
+    if reported.contains(stored) {
+        return Some("another RPC still matches stored hash");
+    }
+    if reported.len() >= required {
+        return None;
+    }
+    if reported.is_empty() {
+        return Some("unable to confirm mismatch with pinned RPCs");
+    }
+    Some("unable to confirm mismatch with enough pinned RPCs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::B256;
+
+    fn hash(byte: u8) -> domain::BlockHash {
+        domain::BlockHash::from(B256::repeat_byte(byte))
+    }
+
+    #[test]
+    fn mismatch_needs_the_required_confirmations_and_no_match() {
+        let stored = hash(1);
+        assert_eq!(confirmation_verdict(&stored, &[hash(2), hash(2)], 2), None);
+        assert_eq!(
+            confirmation_verdict(&stored, &[hash(2), hash(2)], 3),
+            Some("unable to confirm mismatch with enough pinned RPCs")
+        );
+        assert_eq!(
+            confirmation_verdict(&stored, &[hash(2), stored, hash(2)], 2),
+            Some("another RPC still matches stored hash")
+        );
+        assert_eq!(
+            confirmation_verdict(&stored, &[], 1),
+            Some("unable to confirm mismatch with pinned RPCs")
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use crate::{config::GapRepairConfig, db};
+use crate::{config::GapRepairConfig, db, shared::rpc_error};
 use anyhow::Result;
 use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 use tracing::warn;
 
-use super::{errors, range, state::PollState};
+use super::{range, state::PollState};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) struct RepairWindow {
@@ -142,6 +142,20 @@ pub(super) async fn scan_window(
             }
             Ok(None) => return Ok(()),
             Err(e) => {
+                // The caller backs off and retries the whole window; shrinking or hopping to
+                // pinned providers would only add requests to a throttled or flaky upstream.
+                if let Some(kind) = rpc_error::kind(&e).filter(|k| k.is_retryable()) {
+                    warn!(
+                        stream = state.stream.as_str(),
+                        from_block = current,
+                        to_block,
+                        kind = kind.as_str(),
+                        err = %e,
+                        "gap repair scan failed transiently"
+                    );
+                    return Err(e);
+                }
+
                 if state.chunk_current > 1 {
                     state.chunk_current = (state.chunk_current / 2).max(1);
                     warn!(
@@ -191,16 +205,6 @@ pub(super) async fn scan_window(
                     continue;
                 }
 
-                if errors::looks_like_transient(&e) {
-                    warn!(
-                        stream = state.stream.as_str(),
-                        from_block = current,
-                        to_block,
-                        err = %e,
-                        "gap repair scan failed transiently"
-                    );
-                }
-
                 return Err(e);
             }
         }
@@ -9,7 +9,7 @@ use untron_v3_bindings::{
     untron_controller_index::UntronControllerIndex, untron_v3_index::UntronV3Index,
 };
 
-use super::{decode, state::PollState};
+use super::{decode, state::PollState, tip};
 use crate::shared::{logs::ValidatedLog, timestamps};
 
 pub(super) fn decode_event_appended(
@@ -37,6 +37,17 @@ fn decode_hub_event_appended(state: &mut PollState, log: ValidatedLog) -> Result
     let prev_tip: alloy::primitives::B256 = ev.prevTip;
     let new_tip: alloy::primitives::B256 = ev.newTip;
 
+    verify_new_tip(
+        state,
+        ev.eventSeq,
+        block_number,
+        block_timestamp,
+        semantic_sig,
+        &ev.abiEncodedEventData,
+        prev_tip,
+        new_tip,
+    )?;
+
     let semantic =
         decode::decode_semantic_event(Stream::Hub, semantic_sig, &ev.abiEncodedEventData)?;
     let (event_type, args_json) = semantic.into_db_parts();
@@ -80,6 +91,17 @@ fn decode_controller_event_appended(
     let prev_tip: alloy::primitives::B256 = ev.prevTip;
     let new_tip: alloy::primitives::B256 = ev.newTip;
 
+    verify_new_tip(
+        state,
+        ev.eventSeq,
+        block_number,
+        block_timestamp,
+        semantic_sig,
+        &ev.abiEncodedEventData,
+        prev_tip,
+        new_tip,
+    )?;
+
     let semantic =
         decode::decode_semantic_event(Stream::Controller, semantic_sig, &ev.abiEncodedEventData)?;
     let (event_type, args_json) = semantic.into_db_parts();
@@ -141,6 +163,47 @@ pub(super) fn decode_tip_proof(
     })
 }
 
+/// Refuses a log whose `newTip` doesn't follow from its own fields, so a buggy RPC or decoder
+/// can't feed the projections a chain the contract never produced.
+#[allow(clippy::too_many_arguments)]
+fn verify_new_tip(
+    state: &PollState,
+    event_seq: U256,
+    block_number: u64,
+    block_timestamp: u64,
+    event_signature: alloy::primitives::B256,
+    abi_encoded_event_data: &[u8],
+    prev_tip: alloy::primitives::B256,
+    new_tip: alloy::primitives::B256,
+) -> Result<()> {
+    let computed = tip::next_tip(
+        prev_tip,
+        event_seq,
+        block_number,
+        block_timestamp,
+        event_signature,
+        abi_encoded_event_data,
+    );
+    if computed == new_tip {
+        return Ok(());
+    }
+
+    state.telemetry.tip_mismatch();
+    tracing::error!(
+        stream = state.stream.as_str(),
+        event_seq = %event_seq,
+        block_number,
+        block_timestamp,
+        prev_tip = %prev_tip,
+        new_tip = %new_tip,
+        computed_tip = %computed,
+        "EventAppended newTip does not match recomputed hash"
+    );
+    anyhow::bail!(
+        "event chain tip mismatch at event_seq {event_seq} (block {block_number}): log newTip {new_tip}, recomputed {computed}"
+    )
+}
+
 fn u256_to_u64(value: U256) -> Result<u64> {
     u64::try_from(value).with_context(|| format!("U256 too large for u64: {value}"))
 }
@@ -3,7 +3,13 @@ use crate::{
     db::{self, ResolvedStream},
     metrics::StreamTelemetry,
     rpc::RpcProviders,
-    shared::{r#async, r#async::timed_await_or_cancel, head_cache::HeadCache},
+    shared::{
+        r#async,
+        r#async::timed_await_or_cancel,
+        head_cache::HeadCache,
+        rpc_error::{self, RpcErrorKind},
+        rpc_retry::RpcRetry,
+    },
 };
 use anyhow::{Context, Result};
 use std::sync::Arc;
@@ -11,14 +17,11 @@ use std::time::Duration;
 use tokio::time;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
+use untron_rpc_fallback::HeadSubscription;
 
-use super::{errors, range, reorg, repair, state::PollState};
+use super::{range, reorg, repair, state::PollState};
 use crate::shared::progress::ProgressReporter;
 
-const MAX_TRANSIENT_RETRIES: u32 = 3;
-const TRANSIENT_BACKOFF_INITIAL: Duration = Duration::from_millis(250);
-const TRANSIENT_BACKOFF_MAX: Duration = Duration::from_secs(2);
-
 fn grow_chunk(current: u64, target: u64) -> u64 {
     if current >= target {
         return current;
@@ -30,19 +33,16 @@ fn shrink_chunk(current: u64) -> u64 {
     (current / 2).max(1)
 }
 
-#[allow(clippy::too_many_arguments)]
 fn shrink_chunk_with_backoff_reset(
     progress: &mut ProgressReporter,
     state: &mut PollState,
-    transient_attempts: &mut u32,
-    transient_backoff: &mut Duration,
+    retry: &mut RpcRetry,
     from_block: u64,
     to_block: u64,
     err: &anyhow::Error,
     msg: &'static str,
 ) {
-    *transient_attempts = 0;
-    *transient_backoff = TRANSIENT_BACKOFF_INITIAL;
+    retry.reset();
     state.chunk_current = shrink_chunk(state.chunk_current);
     progress.on_chunk_shrink();
     progress.update_event_chain_chunk_blocks(state.chunk_current);
@@ -70,6 +70,13 @@ fn resolve_rpc_contract_address(
     }
 }
 
+async fn next_head(heads: &mut Option<HeadSubscription>) -> Option<u64> {
+    match heads {
+        Some(heads) => heads.changed().await,
+        None => std::future::pending().await,
+    }
+}
+
 pub struct RunStreamParams {
     pub dbh: db::Db,
     pub cfg: StreamConfig,
@@ -134,6 +141,7 @@ pub async fn run_stream(params: RunStreamParams) -> Result<()> {
         chunk_target: cfg.chunk_blocks.max(1),
         chunk_current: cfg.chunk_blocks.max(1),
         pinned_providers: providers.pinned,
+        reorg_confirmations: providers.reorg_confirmations,
         provider: providers.fallback,
         timestamps: crate::shared::timestamps::TimestampState::new(
             block_timestamp_cache_size,
@@ -144,11 +152,12 @@ pub async fn run_stream(params: RunStreamParams) -> Result<()> {
 
     let mut ticker = time::interval(cfg.poll_interval.max(Duration::from_secs(1)));
     ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    // With a head subscription, new blocks wake the loop early and restart the ticker, which
+    // then only bounds how long the loop waits when no head arrives.
+    let mut heads = providers.heads;
 
-    let mut transient_attempts: u32 = 0;
-    let mut transient_backoff = TRANSIENT_BACKOFF_INITIAL;
-    let mut head_attempts: u32 = 0;
-    let mut head_backoff = TRANSIENT_BACKOFF_INITIAL;
+    let mut range_retry = RpcRetry::new();
+    let mut head_retry = RpcRetry::new();
 
     let mut last_gap_check = std::time::Instant::now()
         .checked_sub(Duration::from_secs(3600))
@@ -162,6 +171,10 @@ pub async fn run_stream(params: RunStreamParams) -> Result<()> {
                 return Ok(());
             }
             _ = ticker.tick() => {}
+            Some(head) = next_head(&mut heads) => {
+                head_cache.observe(head).await;
+                ticker.reset();
+            }
         }
 
         let head_res = r#async::await_or_cancel(&shutdown, async {
@@ -171,27 +184,27 @@ pub async fn run_stream(params: RunStreamParams) -> Result<()> {
 
         let Some(head) = (match head_res {
             Ok(opt) => opt,
-            Err(e) if errors::looks_like_transient(&e) => {
-                head_attempts = head_attempts.saturating_add(1);
+            Err(e) => {
+                let Some(kind) = rpc_error::kind(&e).filter(|k| k.is_retryable()) else {
+                    return Err(e);
+                };
+                let backoff = head_retry.backoff(kind);
                 progress.on_transient_retry();
                 warn!(
                     stream = state.stream.as_str(),
-                    attempt = head_attempts,
-                    backoff_ms = head_backoff.as_millis() as u64,
+                    attempt = head_retry.attempts(),
+                    backoff_ms = backoff.as_millis() as u64,
                     err = %e,
                     "transient RPC error; retrying eth_blockNumber"
                 );
-                r#async::sleep_or_cancel(&shutdown, head_backoff).await?;
-                head_backoff = (head_backoff * 2).min(TRANSIENT_BACKOFF_MAX);
+                r#async::sleep_or_cancel(&shutdown, backoff).await?;
                 continue;
             }
-            Err(e) => return Err(e),
         }) else {
             return Ok(());
         };
 
-        head_attempts = 0;
-        head_backoff = TRANSIENT_BACKOFF_INITIAL;
+        head_retry.reset();
 
         let safe_head = head.saturating_sub(state.confirmations);
         state
@@ -241,6 +254,7 @@ pub async fn run_stream(params: RunStreamParams) -> Result<()> {
                 &dbh,
                 &state.provider,
                 &state.pinned_providers,
+                state.reorg_confirmations,
                 state.stream,
                 state.reorg_scan_depth,
                 Some(&state.telemetry),
@@ -252,7 +266,7 @@ pub async fn run_stream(params: RunStreamParams) -> Result<()> {
         let reorg_start = match reorg_res {
             Ok(None) => return Ok(()),
             Ok(Some(v)) => v,
-            Err(e) if errors::looks_like_transient(&e) => {
+            Err(e) if rpc_error::is_retryable(&e) => {
                 warn!(
                     stream = state.stream.as_str(),
                     err = %e,
@@ -417,29 +431,28 @@ pub async fn run_stream(params: RunStreamParams) -> Result<()> {
                 Ok(Some(metrics)) => {
                     progress.observe_range(metrics);
                     from_block = metrics.to_block.saturating_add(1);
-                    transient_attempts = 0;
-                    transient_backoff = TRANSIENT_BACKOFF_INITIAL;
+                    range_retry.reset();
                     state.chunk_current = grow_chunk(state.chunk_current, state.chunk_target);
                     progress.update_event_chain_chunk_blocks(state.chunk_current);
                     progress.maybe_report(head, safe_head, from_block);
                 }
                 Ok(None) => return Ok(()),
                 Err(e) => {
-                    if errors::looks_like_transient(&e)
-                        && transient_attempts < MAX_TRANSIENT_RETRIES
+                    let kind = rpc_error::kind(&e);
+
+                    if let Some(kind) = kind
+                        && let Some(backoff) = range_retry.next_backoff(kind)
                     {
-                        transient_attempts += 1;
                         progress.on_transient_retry();
                         warn!(
                             stream = state.stream.as_str(),
                             from_block,
                             to_block,
-                            attempt = transient_attempts,
+                            attempt = range_retry.attempts(),
+                            kind = kind.as_str(),
                             err = %e,
-                            "transient RPC error; retrying range"
+                            "retryable RPC error; retrying range"
                         );
-                        let backoff = transient_backoff;
-                        transient_backoff = (transient_backoff * 2).min(TRANSIENT_BACKOFF_MAX);
                         r#async::sleep_or_cancel(&shutdown, backoff).await?;
                         if shutdown.is_cancelled() {
                             return Ok(());
@@ -447,42 +460,41 @@ pub async fn run_stream(params: RunStreamParams) -> Result<()> {
                         continue;
                     }
 
-                    if state.chunk_current > 1 && errors::looks_like_range_too_large(&e) {
-                        shrink_chunk_with_backoff_reset(
-                            &mut progress,
-                            &mut state,
-                            &mut transient_attempts,
-                            &mut transient_backoff,
-                            from_block,
-                            to_block,
-                            &e,
-                            "eth_getLogs failed; shrinking chunk",
-                        );
-                        continue;
-                    }
-
-                    if state.chunk_current > 1 {
+                    // Range too large, or transient errors that outlasted their retries. A
+                    // permanent or non-RPC failure (bad params, undecodable data, tip mismatch)
+                    // would fail the same way on a smaller range, so it goes straight to the
+                    // pinned providers in case only the fallback provider is at fault.
+                    if state.chunk_current > 1
+                        && matches!(
+                            kind,
+                            Some(RpcErrorKind::RangeTooLarge | RpcErrorKind::Transient)
+                        )
+                    {
+                        let msg = if kind == Some(RpcErrorKind::RangeTooLarge) {
+                            "eth_getLogs range too large; shrinking chunk"
+                        } else {
+                            "range processing failed; shrinking chunk"
+                        };
                         shrink_chunk_with_backoff_reset(
                             &mut progress,
                             &mut state,
-                            &mut transient_attempts,
-                            &mut transient_backoff,
+                            &mut range_retry,
                             from_block,
                             to_block,
                             &e,
-                            "range processing failed; shrinking chunk",
+                            msg,
                         );
                         continue;
                     }
 
-                    // chunk_current == 1: try each pinned provider for this block
+                    // Try each pinned provider for this range.
                     if !state.pinned_providers.is_empty() {
-                        transient_attempts = 0;
-                        transient_backoff = TRANSIENT_BACKOFF_INITIAL;
+                        range_retry.reset();
                         progress.on_pinned_repair_attempt();
                         warn!(
                             stream = state.stream.as_str(),
-                            block = from_block,
+                            from_block,
+                            to_block,
                             err = %e,
                             "range processing failed; attempting pinned providers"
                         );
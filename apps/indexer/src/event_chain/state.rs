@@ -18,6 +18,7 @@ pub(super) struct PollState {
 
     pub(super) provider: alloy::providers::DynProvider,
     pub(super) pinned_providers: Vec<alloy::providers::DynProvider>,
+    pub(super) reorg_confirmations: usize,
 
     pub(super) timestamps: crate::shared::timestamps::TimestampState,
 
@@ -0,0 +1,70 @@
+use alloy::primitives::{B256, U256};
+use sha2::{Digest, Sha256};
+
+/// Recomputes `newTip` the way `UntronV3Index` / `UntronControllerIndex` `_appendEventChain` do:
+/// `sha256(abi.encodePacked(prevTip, eventSeq, block.number, block.timestamp, eventSignature,
+/// abiEncodedEventData))`.
+pub(super) fn next_tip(
+    prev_tip: B256,
+    event_seq: U256,
+    block_number: u64,
+    block_timestamp: u64,
+    event_signature: B256,
+    abi_encoded_event_data: &[u8],
+) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_tip);
+    hasher.update(event_seq.to_be_bytes::<32>());
+    hasher.update(U256::from(block_number).to_be_bytes::<32>());
+    hasher.update(U256::from(block_timestamp).to_be_bytes::<32>());
+    hasher.update(event_signature);
+    hasher.update(abi_encoded_event_data);
+    B256::from_slice(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::Bytes;
+    use alloy::sol_types::SolValue;
+
+    #[test]
+    fn next_tip_matches_solidity_encode_packed() {
+        let prev_tip = B256::repeat_byte(0x11);
+        let event_seq = U256::from(42u64);
+        let event_signature = B256::repeat_byte(0x22);
+        let data = Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]);
+
+        let packed = (
+            prev_tip,
+            event_seq,
+            U256::from(1_000u64),
+            U256::from(1_700_000_000u64),
+            event_signature,
+            data.clone(),
+        )
+            .abi_encode_packed();
+        let expected = B256::from_slice(&Sha256::digest(&packed));
+
+        let tip = next_tip(
+            prev_tip,
+            event_seq,
+            1_000,
+            1_700_000_000,
+            event_signature,
+            &data,
+        );
+        assert_eq!(tip, expected);
+        assert_ne!(
+            tip,
+            next_tip(
+                prev_tip,
+                event_seq,
+                1_000,
+                1_700_000_001,
+                event_signature,
+                &data
+            )
+        );
+    }
+}
@@ -4,6 +4,7 @@ mod domain;
 mod event_chain;
 mod hub_deposit_processed;
 mod metrics;
+mod receiver_native;
 mod receiver_usdt;
 mod rpc;
 mod shared;
@@ -36,6 +37,7 @@ async fn main() -> Result<()> {
         database_url,
         streams,
         receiver_usdt: receiver_usdt_cfg,
+        receiver_native: receiver_native_cfg,
         db_max_connections,
         block_header_concurrency,
         block_timestamp_cache_size,
@@ -46,7 +48,7 @@ async fn main() -> Result<()> {
     } = config::load_config()?;
     let dbh = db::Db::connect(&database_url, db_max_connections).await?;
     // Keep this in sync with the latest migration file number.
-    let _schema_version = db::ensure_schema_version(&dbh, 22).await?;
+    let _schema_version = db::ensure_schema_version(&dbh, 28).await?;
 
     let shutdown = CancellationToken::new();
 
@@ -169,6 +171,44 @@ async fn main() -> Result<()> {
         let dbh = dbh.clone();
         let shutdown = shutdown.clone();
 
+        // TRX / TRC-10 deposits into the same receivers need a Tron node; off unless configured.
+        if receiver_native_cfg.enabled {
+            let dbh = dbh.clone();
+            let shutdown = shutdown.clone();
+            let cfg = cfg.clone();
+            join_set.spawn(async move {
+                let mut backoff = Duration::from_millis(250);
+                loop {
+                    if shutdown.is_cancelled() {
+                        return Ok(());
+                    }
+
+                    let res = receiver_native::run_receiver_native_indexer(
+                        receiver_native::RunReceiverNativeParams {
+                            dbh: dbh.clone(),
+                            controller_cfg: cfg.clone(),
+                            cfg: receiver_native_cfg.clone(),
+                            shutdown: shutdown.clone(),
+                        },
+                    )
+                    .await;
+
+                    match res {
+                        Ok(()) => {
+                            if shutdown.is_cancelled() {
+                                return Ok(());
+                            }
+                            error!("receiver_native task exited unexpectedly; restarting")
+                        }
+                        Err(e) => error!(err = ?e, "receiver_native task failed; restarting"),
+                    }
+
+                    time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(Duration::from_secs(5));
+                }
+            });
+        }
+
         // Receiver USDT indexer has its own env-driven knobs; it only requires controller RPC access + DB.
         if receiver_usdt_cfg.enabled {
             // KPI loop: how long deposits sit in recommended_action=subjective_pre_entitle.
@@ -22,6 +22,7 @@ struct Inner {
     blocks_total: Counter<u64>,
     logs_total: Counter<u64>,
     reorgs_total: Counter<u64>,
+    tip_mismatches_total: Counter<u64>,
     rpc_calls_total: Counter<u64>,
     rpc_errors_total: Counter<u64>,
     db_errors_total: Counter<u64>,
@@ -76,6 +77,10 @@ impl StreamTelemetry {
             .u64_counter("indexer.reorgs_total")
             .with_description("Total reorgs detected")
             .build();
+        let tip_mismatches_total = meter
+            .u64_counter("indexer.tip_mismatches_total")
+            .with_description("EventAppended logs whose newTip did not match the recomputed hash")
+            .build();
         let rpc_calls_total = meter
             .u64_counter("indexer.rpc_calls_total")
             .with_description("Total RPC calls (best-effort classification via code paths)")
@@ -210,6 +215,7 @@ impl StreamTelemetry {
                 blocks_total,
                 logs_total,
                 reorgs_total,
+                tip_mismatches_total,
                 rpc_calls_total,
                 rpc_errors_total,
                 db_errors_total,
@@ -328,6 +334,10 @@ impl StreamTelemetry {
         self.inner.reorgs_total.add(1, &self.inner.attrs);
     }
 
+    pub fn tip_mismatch(&self) {
+        self.inner.tip_mismatches_total.add(1, &self.inner.attrs);
+    }
+
     pub fn rows_upserted(&self, table: &'static str, rows: u64) {
         self.inner.rows_upserted_total.add(
             rows,
@@ -0,0 +1,478 @@
+use crate::db::receiver_native::{self as nativedb, NativeTransferRow};
+use crate::db::receiver_usdt as receiverdb;
+use crate::{
+    config, db,
+    shared::{r#async, rpc_error::RpcErrorKind, rpc_retry::RpcRetry},
+};
+use alloy::primitives::Address;
+use anyhow::{Context, Result};
+use futures::{StreamExt, stream};
+use sqlx::types::BigDecimal;
+use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use tron::TronApi;
+use tron::protocol::{Account, BlockExtention};
+
+pub struct RunReceiverNativeParams {
+    pub dbh: db::Db,
+    pub controller_cfg: config::StreamConfig,
+    pub cfg: config::ReceiverNativeConfig,
+    pub shutdown: CancellationToken,
+}
+
+/// Scans every Tron block for TRX / TRC-10 transfers into watched receivers.
+///
+/// Unlike the TRC-20 indexer there are no logs to filter on, so each block is fetched in full.
+/// The receiver watchlist itself is maintained by the TRC-20 indexer's discovery loop; receivers
+/// it adds after the forward scan passed their blocks are backfilled from the start block, unless
+/// their account holds no TRX or TRC-10 at all.
+pub async fn run_receiver_native_indexer(params: RunReceiverNativeParams) -> Result<()> {
+    let RunReceiverNativeParams {
+        dbh,
+        controller_cfg,
+        cfg,
+        shutdown,
+    } = params;
+
+    let chain_id = i64::try_from(controller_cfg.chain_id).context("chain_id out of range")?;
+    let mut tron = tron::TronClient::connect(
+        &cfg.tron_url,
+        cfg.tron_api_key.as_deref(),
+        &cfg.tron_api_key_header,
+    )
+    .await
+    .context("connect tron node")?;
+
+    info!(
+        transport = tron.transport(),
+        block_concurrency = cfg.block_concurrency,
+        poll_interval_secs = cfg.poll_interval.as_secs(),
+        "receiver native transfer scanner started"
+    );
+
+    let mut watchlist_snapshot: Option<receiverdb::WatchlistSnapshot> = None;
+    let mut head_retry = RpcRetry::new();
+
+    loop {
+        if shutdown.is_cancelled() {
+            return Ok(());
+        }
+
+        let head = match tron.get_now_block2().await {
+            Ok(head) => {
+                head_retry.reset();
+                head
+            }
+            Err(e) => {
+                // The node is unreachable or lagging; keep the task (and its cursors) alive.
+                let backoff = head_retry.backoff(RpcErrorKind::Transient);
+                warn!(
+                    err = %e,
+                    attempts = head_retry.attempts(),
+                    backoff_ms = backoff.as_millis() as u64,
+                    "get_now_block2 failed; retrying"
+                );
+                r#async::sleep_or_cancel(&shutdown, backoff).await?;
+                continue;
+            }
+        };
+        let head = head
+            .block_header
+            .as_ref()
+            .and_then(|h| h.raw_data.as_ref())
+            .context("missing head block_header.raw_data")?
+            .number;
+        let head = u64::try_from(head).context("head block number out of range")?;
+        let safe_head = head.saturating_sub(controller_cfg.confirmations);
+
+        // Re-read every tick: controller reorgs rewind the cursor in the DB.
+        let from_block =
+            nativedb::ensure_cursor(&dbh, cfg.start_block.unwrap_or(safe_head)).await?;
+
+        let watchlist_epoch = receiverdb::watchlist_last_updated_at_epoch(&dbh)
+            .await?
+            .unwrap_or(0);
+        if watchlist_snapshot
+            .as_ref()
+            .is_none_or(|s| s.updated_at_epoch != watchlist_epoch)
+        {
+            watchlist_snapshot = Some(receiverdb::load_watchlist_snapshot(&dbh).await?);
+            let registered = nativedb::register_backfill_receivers(&dbh).await?;
+            if registered > 0 {
+                info!(
+                    receivers = registered,
+                    "queued receivers for native backfill"
+                );
+            }
+        }
+        let snapshot = watchlist_snapshot.as_ref().expect("set");
+
+        let behind = if from_block > safe_head {
+            false
+        } else if snapshot.receiver_map.is_empty() {
+            // Nothing can match an empty watchlist; skip ahead without fetching blocks.
+            // Receivers discovered later are backfilled from the start block.
+            nativedb::insert_transfers_and_advance(&dbh, &[], safe_head.saturating_add(1)).await?;
+            false
+        } else {
+            let to_block = scan_window_end(from_block, safe_head + 1, cfg.block_concurrency);
+            let rows = scan_blocks(
+                &tron,
+                chain_id,
+                from_block,
+                to_block,
+                &snapshot.receiver_map,
+                cfg.block_concurrency,
+            )
+            .await?;
+
+            if shutdown.is_cancelled() {
+                return Ok(());
+            }
+            nativedb::insert_transfers_and_advance(&dbh, &rows, to_block + 1).await?;
+            log_deposits(&rows, from_block, to_block);
+            to_block < safe_head
+        };
+
+        // If we are still behind, don't wait the full interval.
+        if behind {
+            continue;
+        }
+        // Once the forward scan is caught up, spend the idle time on receivers discovered after
+        // it passed their blocks.
+        if backfill_step(&dbh, &tron, chain_id, &cfg, &shutdown).await? {
+            continue;
+        }
+        r#async::sleep_or_cancel(&shutdown, cfg.poll_interval).await?;
+    }
+}
+
+/// Rescans one window of blocks for the receivers with the lowest backfill cursor, up to the
+/// forward scan's cursor. Returns whether there was backfill work.
+async fn backfill_step(
+    dbh: &db::Db,
+    tron: &tron::TronClient,
+    chain_id: i64,
+    cfg: &config::ReceiverNativeConfig,
+    shutdown: &CancellationToken,
+) -> Result<bool> {
+    let Some(mut work) = nativedb::next_backfill_work(dbh).await? else {
+        return Ok(false);
+    };
+
+    if work.start_block >= work.stop_at_or_above {
+        // Joined at or after the forward scan's position; nothing to rescan.
+        nativedb::insert_backfill_transfers_and_advance(
+            dbh,
+            &[],
+            &work.receiver_salts,
+            work.start_block,
+            None,
+        )
+        .await?;
+        return Ok(true);
+    }
+
+    // Receivers cannot move TRX or TRC-10 out, so an account holding neither has never been
+    // sent any and there is nothing to find in its history.
+    let empty_salts = empty_receivers(tron, &work.receiver_map, cfg.block_concurrency).await;
+    if !empty_salts.is_empty() {
+        nativedb::clear_backfill_receivers(dbh, &empty_salts).await?;
+        work.receiver_map
+            .retain(|_, salt| !empty_salts.contains(salt));
+        work.receiver_salts
+            .retain(|salt| !empty_salts.contains(salt));
+        if work.receiver_salts.is_empty() {
+            return Ok(true);
+        }
+    }
+
+    let to_block = scan_window_end(
+        work.start_block,
+        work.stop_at_or_above,
+        cfg.block_concurrency,
+    );
+    let rows = scan_blocks(
+        tron,
+        chain_id,
+        work.start_block,
+        to_block,
+        &work.receiver_map,
+        cfg.block_concurrency,
+    )
+    .await?;
+
+    if shutdown.is_cancelled() {
+        return Ok(true);
+    }
+    nativedb::insert_backfill_transfers_and_advance(
+        dbh,
+        &rows,
+        &work.receiver_salts,
+        work.start_block,
+        next_backfill_cursor(to_block + 1, work.stop_at_or_above),
+    )
+    .await?;
+    log_deposits(&rows, work.start_block, to_block);
+    Ok(true)
+}
+
+/// Last block of the window starting at `from_block`: at most `concurrency` blocks, all below
+/// `stop_before`. The caller ensures `from_block < stop_before`.
+fn scan_window_end(from_block: u64, stop_before: u64, concurrency: usize) -> u64 {
+    from_block
+        .saturating_add(concurrency.max(1) as u64 - 1)
+        .min(stop_before - 1)
+}
+
+/// Where a receiver's backfill resumes after scanning up to `next_block`; `None` once it reached
+/// the forward scan's cursor.
+fn next_backfill_cursor(next_block: u64, stop_at_or_above: u64) -> Option<u64> {
+    (next_block < stop_at_or_above).then_some(next_block)
+}
+
+fn holds_native_assets(account: &Account) -> bool {
+    account.balance != 0
+        || account.asset.values().any(|amount| *amount != 0)
+        || account.asset_v2.values().any(|amount| *amount != 0)
+}
+
+/// Salts of the receivers whose account holds no TRX or TRC-10. Receivers whose account can't be
+/// read are treated as funded and backfilled.
+async fn empty_receivers(
+    tron: &tron::TronClient,
+    receivers: &HashMap<Address, String>,
+    concurrency: usize,
+) -> Vec<String> {
+    let results = stream::iter(receivers.iter())
+        .map(|(addr, salt)| {
+            let mut tron = tron.clone();
+            async move {
+                let account = tron
+                    .get_account(tron::TronAddress::from_evm(*addr).prefixed_bytes().to_vec())
+                    .await;
+                (salt, account)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut empty = Vec::new();
+    for (salt, account) in results {
+        match account {
+            Ok(account) if !holds_native_assets(&account) => empty.push(salt.clone()),
+            Ok(_) => {}
+            Err(e) => warn!(
+                receiver_salt = %salt,
+                err = %e,
+                "receiver account precheck failed; backfilling it"
+            ),
+        }
+    }
+    empty
+}
+
+async fn scan_blocks(
+    tron: &tron::TronClient,
+    chain_id: i64,
+    from_block: u64,
+    to_block: u64,
+    receivers: &HashMap<Address, String>,
+    concurrency: usize,
+) -> Result<Vec<NativeTransferRow>> {
+    let blocks = stream::iter(from_block..=to_block)
+        .map(|n| {
+            let mut tron = tron.clone();
+            async move {
+                let num = i64::try_from(n).context("block number out of range")?;
+                tron.get_block_by_num2(num)
+                    .await
+                    .with_context(|| format!("get_block_by_num2({n})"))
+            }
+        })
+        .buffered(concurrency)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut rows = Vec::new();
+    for (block_number, block) in (from_block..=to_block).zip(blocks) {
+        rows.extend(native_transfer_rows(
+            chain_id,
+            block_number,
+            &block?,
+            receivers,
+        )?);
+    }
+    Ok(rows)
+}
+
+fn log_deposits(rows: &[NativeTransferRow], from_block: u64, to_block: u64) {
+    if !rows.is_empty() {
+        warn!(
+            deposits = rows.len(),
+            from_block,
+            to_block,
+            "TRX/TRC-10 deposits into receivers recorded; these need manual recovery"
+        );
+    }
+}
+
+fn native_transfer_rows(
+    chain_id: i64,
+    block_number: u64,
+    block: &BlockExtention,
+    receivers: &HashMap<Address, String>,
+) -> Result<Vec<NativeTransferRow>> {
+    let raw = block
+        .block_header
+        .as_ref()
+        .and_then(|h| h.raw_data.as_ref())
+        .context("missing block_header.raw_data")?;
+    if u64::try_from(raw.number).ok() != Some(block_number) {
+        anyhow::bail!("node returned block {} for {block_number}", raw.number);
+    }
+    // Tron header timestamps are in milliseconds.
+    let block_timestamp = raw.timestamp / 1000;
+    let block_hash = format!("0x{}", hex::encode(&block.blockid));
+
+    let mut rows = Vec::new();
+    for txe in &block.transactions {
+        let Some(tx) = txe.transaction.as_ref() else {
+            continue;
+        };
+        // contractRet DEFAULT (0) / SUCCESS (1); anything else moved nothing.
+        if tx.ret.first().is_some_and(|r| r.contract_ret > 1) {
+            continue;
+        }
+        let transfer = match tron::decode_native_transfer(tx) {
+            Ok(Some(transfer)) => transfer,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!(
+                    block_number,
+                    txid = %hex::encode(&txe.txid),
+                    err = %e,
+                    "skipping undecodable native transfer"
+                );
+                continue;
+            }
+        };
+        let Some(receiver_salt) = receivers.get(&transfer.to.evm()) else {
+            continue;
+        };
+
+        rows.push(NativeTransferRow {
+            chain_id,
+            kind: if transfer.asset_id.is_some() {
+                "trc10"
+            } else {
+                "trx"
+            },
+            asset_id: transfer.asset_id,
+            receiver_salt: receiver_salt.clone(),
+            sender: crate::domain::TronAddress::from_evm(transfer.from.evm()).to_string(),
+            recipient: crate::domain::TronAddress::from_evm(transfer.to.evm()).to_string(),
+            amount: BigDecimal::from(transfer.amount),
+            block_number: i64::try_from(block_number)
+                .context("block_number out of range for bigint")?,
+            block_timestamp,
+            block_hash: block_hash.clone(),
+            tx_hash: format!("0x{}", hex::encode(&txe.txid)),
+        });
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tron::protocol::{BlockHeader, Transaction, TransactionExtention, block_header};
+
+    #[test]
+    fn native_transfer_rows_keeps_watched_receivers_only() {
+        let sender = tron::TronAddress::from_evm(Address::repeat_byte(0x01));
+        let watched = tron::TronAddress::from_evm(Address::repeat_byte(0x02));
+        let other = tron::TronAddress::from_evm(Address::repeat_byte(0x03));
+
+        let transfer_to = |to, txid: u8| TransactionExtention {
+            transaction: Some(Transaction {
+                raw_data: Some(tron::protocol::transaction::Raw {
+                    contract: vec![
+                        tron::TronSystemContract::Transfer { to, amount_sun: 7 }
+                            .to_contract(sender),
+                    ],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            txid: vec![txid; 32],
+            ..Default::default()
+        };
+        let block = BlockExtention {
+            block_header: Some(BlockHeader {
+                raw_data: Some(block_header::Raw {
+                    number: 100,
+                    timestamp: 1_700_000_000_123,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            blockid: vec![0xbb; 32],
+            transactions: vec![transfer_to(other, 0xaa), transfer_to(watched, 0xcc)],
+            ..Default::default()
+        };
+        let receivers = HashMap::from([(watched.evm(), "0xsalt".to_string())]);
+
+        let rows = native_transfer_rows(1, 100, &block, &receivers).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].kind, "trx");
+        assert_eq!(rows[0].receiver_salt, "0xsalt");
+        assert_eq!(rows[0].amount, BigDecimal::from(7u64));
+        assert_eq!(rows[0].block_timestamp, 1_700_000_000);
+        assert_eq!(rows[0].tx_hash, format!("0x{}", "cc".repeat(32)));
+
+        assert!(native_transfer_rows(1, 101, &block, &receivers).is_err());
+    }
+
+    #[test]
+    fn backfill_windows_stop_below_the_forward_cursor() {
+        // Windows are `concurrency` blocks long and never reach the forward scan's cursor.
+        assert_eq!(scan_window_end(100, 1_000, 16), 115);
+        assert_eq!(scan_window_end(990, 1_000, 16), 999);
+        assert_eq!(scan_window_end(999, 1_000, 16), 999);
+        assert_eq!(scan_window_end(100, 1_000, 0), 100);
+
+        // Walking the windows covers [start, stop) exactly once and then clears the cursor.
+        let (start, stop) = (100, 140);
+        let mut cursor = Some(start);
+        let mut scanned = Vec::new();
+        while let Some(from) = cursor {
+            let to = scan_window_end(from, stop, 16);
+            scanned.push((from, to));
+            cursor = next_backfill_cursor(to + 1, stop);
+        }
+        assert_eq!(scanned, [(100, 115), (116, 131), (132, 139)]);
+
+        assert_eq!(next_backfill_cursor(140, 140), None);
+        assert_eq!(next_backfill_cursor(141, 140), None);
+    }
+
+    #[test]
+    fn only_accounts_without_trx_or_trc10_skip_the_backfill() {
+        assert!(!holds_native_assets(&Account::default()));
+        assert!(holds_native_assets(&Account {
+            balance: 1,
+            ..Default::default()
+        }));
+        assert!(holds_native_assets(&Account {
+            asset_v2: [("1002000".to_string(), 5)].into(),
+            ..Default::default()
+        }));
+        assert!(!holds_native_assets(&Account {
+            asset_v2: [("1002000".to_string(), 0)].into(),
+            ..Default::default()
+        }));
+    }
+}
@@ -7,7 +7,7 @@ use std::{collections::HashMap, time::Instant};
 use tokio_util::sync::CancellationToken;
 use tracing::{Instrument, debug};
 
-use crate::shared::rpc_telemetry::RpcTelemetry;
+use crate::shared::{rpc_error::RpcError, rpc_telemetry::RpcTelemetry};
 use crate::{
     db::receiver_usdt as db,
     receiver_usdt::telemetry::ReceiverUsdtTelemetry,
@@ -98,7 +98,7 @@ pub(crate) async fn fetch_token_range_logs(
         let Some((raw_logs, rpc_ms)) = r#async::timed_await_or_cancel(shutdown, async {
             provider.get_logs(&filter).await.map_err(|e| {
                 telemetry.error(mode, token_tron, "rpc");
-                anyhow::Error::new(e).context(format!(
+                anyhow::Error::new(RpcError::from(e)).context(format!(
                     "eth_getLogs receiver_usdt Transfer [{from_block}..{to_block}]"
                 ))
             })
@@ -177,7 +177,7 @@ pub(crate) async fn fetch_token_range_logs_unfiltered(
         let Some((raw_logs, rpc_ms)) = r#async::timed_await_or_cancel(shutdown, async {
             provider.get_logs(&filter).await.map_err(|e| {
                 telemetry.error(mode, token_tron, "rpc");
-                anyhow::Error::new(e).context(format!(
+                anyhow::Error::new(RpcError::from(e)).context(format!(
                     "eth_getLogs receiver_usdt Transfer (unfiltered) [{from_block}..{to_block}]"
                 ))
             })
@@ -321,7 +321,9 @@ pub(crate) async fn fetch_receiver_init_code_hash(
             .call(request)
             .block(BlockId::latest())
             .await
-            .map_err(|e| anyhow::Error::new(e).context("eth_call(receiverBytecode)"))
+            .map_err(|e| {
+                anyhow::Error::new(RpcError::from(e)).context("eth_call(receiverBytecode)")
+            })
     })
     .await?
     .unwrap_or_default();
@@ -6,7 +6,12 @@ use crate::{
     receiver_usdt::telemetry::ReceiverUsdtTelemetry,
     rpc::RpcProviders,
     shared::{
-        r#async, r#async::timed_await_or_cancel, head_cache::HeadCache, timestamps,
+        r#async,
+        r#async::timed_await_or_cancel,
+        head_cache::HeadCache,
+        rpc_error::{self, RpcError, RpcErrorKind},
+        rpc_retry::RpcRetry,
+        timestamps,
     },
 };
 use alloy::providers::Provider;
@@ -58,6 +63,29 @@ struct ProcessCtx<'a> {
     telemetry: &'a ReceiverUsdtTelemetry,
     mode: &'static str,
     chain_id_i64: i64,
+    /// Block span providers accepted last, after a range-too-large rejection; `None` uses the
+    /// configured chunk size. Doubles back towards it after every successful window.
+    chunk_cap: Option<u64>,
+}
+
+/// A configured TRC-20 tracked alongside the controller's USDT.
+#[derive(Debug, Clone)]
+struct ReceiverToken {
+    evm: alloy::primitives::Address,
+    tron: String,
+}
+
+/// Tokens a scan covers: the controller's USDT (split by `UsdtSet` history) and/or configured
+/// extra tokens.
+#[derive(Debug, Clone, Copy)]
+struct TokenSelection<'a> {
+    usdt: bool,
+    extra: &'a [ReceiverToken],
+}
+
+struct TokenCursor {
+    token: ReceiverToken,
+    next_block: u64,
 }
 
 fn report_receiver_usdt_progress(
@@ -82,16 +110,23 @@ async fn scan_chunks(
     max_chunks: usize,
     chunk_blocks: u64,
     unfiltered: bool,
+    tokens: TokenSelection<'_>,
 ) -> Result<Option<u64>> {
-    let chunk_blocks = chunk_blocks.max(1);
+    let chunk_target = chunk_blocks.max(1);
     let max_parallel_chunks = ctx.receiver_usdt_cfg.range_concurrency.max(1);
     let mut chunks_done = 0usize;
+    let mut retry = RpcRetry::new();
 
     while from_block <= safe_head && chunks_done < max_chunks {
         if ctx.shutdown.is_cancelled() {
             return Ok(None);
         }
 
+        let chunk_blocks = ctx
+            .chunk_cap
+            .map_or(chunk_target, |cap| cap.min(chunk_target));
+        let window_from_block = from_block;
+        let window_chunks_done = chunks_done;
         let remaining_chunks = max_chunks.saturating_sub(chunks_done);
         let window_chunks = max_parallel_chunks.min(remaining_chunks);
         let mut ranges = Vec::with_capacity(window_chunks);
@@ -110,12 +145,60 @@ async fn scan_chunks(
             chunks_done += 1;
         }
 
-        if process_block_ranges(ctx, receiver_map, to_addrs, &ranges, unfiltered)
-            .await?
-            .is_none()
-        {
-            return Ok(None);
+        let err =
+            match process_block_ranges(ctx, receiver_map, to_addrs, &ranges, unfiltered, tokens)
+                .await
+            {
+                Ok(Some(())) => {
+                    retry.reset();
+                    ctx.chunk_cap = ctx
+                        .chunk_cap
+                        .map(|cap| cap.saturating_mul(2))
+                        .filter(|cap| *cap < chunk_target);
+                    continue;
+                }
+                Ok(None) => return Ok(None),
+                Err(e) => e,
+            };
+
+        // Nothing from a failed window is persisted until every fetch succeeded, so it is
+        // retried from its first block.
+        let Some(kind) = rpc_error::kind(&err) else {
+            return Err(err);
+        };
+        from_block = window_from_block;
+        chunks_done = window_chunks_done;
+
+        if kind == RpcErrorKind::RangeTooLarge && chunk_blocks > 1 {
+            let shrunk = (chunk_blocks / 2).max(1);
+            ctx.chunk_cap = Some(shrunk);
+            retry.reset();
+            ctx.progress.on_chunk_shrink();
+            warn!(
+                mode = ctx.mode,
+                from_block,
+                chunk_blocks = shrunk,
+                err = %err,
+                "eth_getLogs range too large; shrinking chunk"
+            );
+            continue;
+        }
+
+        if let Some(backoff) = retry.next_backoff(kind) {
+            ctx.progress.on_transient_retry();
+            warn!(
+                mode = ctx.mode,
+                from_block,
+                attempt = retry.attempts(),
+                kind = kind.as_str(),
+                err = %err,
+                "retryable RPC error; retrying range"
+            );
+            r#async::sleep_or_cancel(ctx.shutdown, backoff).await?;
+            continue;
         }
+
+        return Err(err);
     }
 
     Ok(Some(from_block))
@@ -165,6 +248,7 @@ pub async fn run_receiver_usdt_indexer(params: RunReceiverUsdtParams) -> Result<
         range_concurrency = receiver_usdt_cfg.range_concurrency,
         backfill_concurrency = receiver_usdt_cfg.backfill_concurrency,
         discovery_interval_secs = receiver_usdt_cfg.discovery_interval.as_secs(),
+        extra_tokens = receiver_usdt_cfg.tokens.len(),
         "receiver usdt transfer indexer starting"
     );
 
@@ -382,13 +466,19 @@ enum RunnerMode {
     Backfill,
 }
 
-async fn list_historical_controller_usdt_tokens_up_to(
+async fn list_tracked_tokens_up_to(
     dbh: &db::Db,
     deployment_block: u64,
     to_block: u64,
+    receiver_tokens: &[ReceiverToken],
 ) -> Result<Vec<alloy::primitives::Address>> {
     let points = receiverdb::usdt_set_points_up_to(dbh, deployment_block, to_block).await?;
     let mut out = Vec::new();
+    for token in receiver_tokens {
+        if !out.contains(&token.evm) {
+            out.push(token.evm);
+        }
+    }
     for p in points {
         let evm = crate::domain::TronAddress::parse_text(&p.usdt_tron)
             .or_else(|_| crate::domain::TronAddress::parse_text(&p.usdt_evm))
@@ -433,6 +523,25 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
     );
 
     let batch_size = receiver_usdt_cfg.to_batch_size.max(1);
+    let mut receiver_tokens = receiver_usdt_cfg
+        .tokens
+        .iter()
+        .map(|t| ReceiverToken {
+            evm: t.evm(),
+            tron: t.to_base58check(),
+        })
+        .collect::<Vec<_>>();
+    // The controller's current USDT is already indexed through its `UsdtSet` history; a
+    // configured copy would only get a redundant cursor and catch-up scan.
+    if let Some(usdt) = current_controller_usdt(dbh, controller_cfg.deployment_block).await? {
+        receiver_tokens.retain(|t| {
+            let is_usdt = t.evm == usdt;
+            if is_usdt {
+                info!(token = %t.tron, "TRC20_TOKENS entry is the controller's USDT; not tracking it separately");
+            }
+            !is_usdt
+        });
+    }
     let mut process_ctx = ProcessCtx {
         dbh,
         shutdown,
@@ -443,6 +552,7 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
         telemetry: &telemetry,
         mode: label,
         chain_id_i64,
+        chunk_cap: None,
     };
 
     match mode {
@@ -450,6 +560,25 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
             let mut from_block =
                 receiverdb::ensure_tail_cursor(dbh, controller_cfg.deployment_block).await?;
 
+            let mut token_cursors = Vec::with_capacity(receiver_tokens.len());
+            for token in &receiver_tokens {
+                let next_block = receiverdb::ensure_token_tail_cursor(
+                    dbh,
+                    &token.tron,
+                    controller_cfg.deployment_block,
+                )
+                .await?;
+                info!(
+                    token = %token.tron,
+                    tail_next_block = next_block,
+                    "receiver token tail cursor ready"
+                );
+                token_cursors.push(TokenCursor {
+                    token: token.clone(),
+                    next_block,
+                });
+            }
+
             let mut ticker =
                 time::interval(receiver_usdt_cfg.poll_interval.max(Duration::from_secs(1)));
             ticker.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
@@ -462,11 +591,22 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
                     _ = ticker.tick() => {}
                 }
 
-                let Some(head) =
-                    r#async::await_or_cancel(shutdown, head_cache.get(&telemetry, "head")).await?
-                else {
-                    return Ok(());
-                };
+                let head =
+                    match r#async::await_or_cancel(shutdown, head_cache.get(&telemetry, "head"))
+                        .await
+                    {
+                        Ok(Some(head)) => head,
+                        Ok(None) => return Ok(()),
+                        Err(e) if rpc_error::is_retryable(&e) => {
+                            warn!(
+                                mode = label,
+                                err = %e,
+                                "transient RPC error; skipping this tick"
+                            );
+                            continue;
+                        }
+                        Err(e) => return Err(e),
+                    };
 
                 // Cache receiver map across ticks; refresh only if watchlist changed.
                 let watchlist_epoch = receiverdb::watchlist_last_updated_at_epoch(dbh)
@@ -499,34 +639,86 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
                     receiver_count,
                     batch_size,
                 );
-                if from_block > safe_head || receiver_count == 0 {
+                if receiver_count == 0 {
                     continue;
                 }
 
-                let Some(next) = scan_chunks(
-                    &mut process_ctx,
-                    &snapshot.receiver_map,
-                    &snapshot.to_addrs,
-                    from_block,
-                    safe_head,
-                    1,
-                    receiver_usdt_cfg.tail_chunk_blocks,
-                    true,
-                )
-                .await?
-                else {
-                    return Ok(());
-                };
-                from_block = next;
-                receiverdb::update_tail_cursor(dbh, from_block).await?;
-                report_receiver_usdt_progress(
-                    process_ctx.progress,
-                    head,
-                    safe_head,
-                    from_block,
-                    receiver_count,
-                    batch_size,
-                );
+                if from_block <= safe_head {
+                    let joint_tokens = token_cursors
+                        .iter()
+                        .filter(|c| c.next_block >= from_block)
+                        .map(|c| c.token.clone())
+                        .collect::<Vec<_>>();
+                    let Some(next) = scan_chunks(
+                        &mut process_ctx,
+                        &snapshot.receiver_map,
+                        &snapshot.to_addrs,
+                        from_block,
+                        safe_head,
+                        1,
+                        receiver_usdt_cfg.tail_chunk_blocks,
+                        true,
+                        TokenSelection {
+                            usdt: true,
+                            extra: &joint_tokens,
+                        },
+                    )
+                    .await?
+                    else {
+                        return Ok(());
+                    };
+                    let scanned_from = from_block;
+                    from_block = next;
+                    receiverdb::update_tail_cursor(dbh, from_block).await?;
+                    for cursor in token_cursors
+                        .iter_mut()
+                        .filter(|c| c.next_block >= scanned_from)
+                    {
+                        cursor.next_block = from_block;
+                        receiverdb::update_token_tail_cursor(dbh, &cursor.token.tron, from_block)
+                            .await?;
+                    }
+                    report_receiver_usdt_progress(
+                        process_ctx.progress,
+                        head,
+                        safe_head,
+                        from_block,
+                        receiver_count,
+                        batch_size,
+                    );
+                }
+
+                // Tokens behind the USDT cursor (e.g. newly configured) catch up after the USDT
+                // tail scan, so they never delay it. They stop short of the USDT cursor, so
+                // receiver backfills bounded by it cover them too, and join the tail scan once
+                // they reach it.
+                for cursor in &mut token_cursors {
+                    let Some((catch_up_from, catch_up_to)) =
+                        token_catch_up_range(cursor.next_block, from_block, safe_head)
+                    else {
+                        continue;
+                    };
+                    let Some(next) = scan_chunks(
+                        &mut process_ctx,
+                        &snapshot.receiver_map,
+                        &snapshot.to_addrs,
+                        catch_up_from,
+                        catch_up_to,
+                        receiver_usdt_cfg.range_concurrency,
+                        receiver_usdt_cfg.tail_chunk_blocks,
+                        true,
+                        TokenSelection {
+                            usdt: false,
+                            extra: std::slice::from_ref(&cursor.token),
+                        },
+                    )
+                    .await?
+                    else {
+                        return Ok(());
+                    };
+                    cursor.next_block = next;
+                    receiverdb::update_token_tail_cursor(dbh, &cursor.token.tron, next).await?;
+                }
             }
         }
         RunnerMode::Backfill => {
@@ -555,11 +747,22 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
                 let stop_at_or_above = work.stop_at_or_above;
 
                 // Backfill one chunk for this cohort.
-                let Some(head) =
-                    r#async::await_or_cancel(shutdown, head_cache.get(&telemetry, "head")).await?
-                else {
-                    return Ok(());
-                };
+                let head =
+                    match r#async::await_or_cancel(shutdown, head_cache.get(&telemetry, "head"))
+                        .await
+                    {
+                        Ok(Some(head)) => head,
+                        Ok(None) => return Ok(()),
+                        Err(e) if rpc_error::is_retryable(&e) => {
+                            warn!(
+                                mode = label,
+                                err = %e,
+                                "transient RPC error; skipping this tick"
+                            );
+                            continue;
+                        }
+                        Err(e) => return Err(e),
+                    };
                 let safe_head = head.saturating_sub(controller_cfg.confirmations);
 
                 if start_block > safe_head {
@@ -575,19 +778,21 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
                 }
 
                 // Optimization: for newly discovered receivers, if current balanceOf is 0 for all
-                // historically configured USDT tokens, then by protocol invariant no transfers
-                // have ever happened to that receiver, so backfill can be skipped.
+                // historically configured USDT tokens and all configured extra tokens, then by
+                // protocol invariant no transfers have ever happened to that receiver, so backfill
+                // can be skipped.
                 //
                 // This is safe because the Tron-side controller sweep leaves 1 base unit behind
-                // after the first non-zero deposit, so balance never returns to 0.
-                let usdt_tokens = list_historical_controller_usdt_tokens_up_to(
+                // after the first non-zero deposit (for any token), so balance never returns to 0.
+                let tracked_tokens = list_tracked_tokens_up_to(
                     dbh,
                     controller_cfg.deployment_block,
                     safe_head,
+                    &receiver_tokens,
                 )
                 .await?;
-                let usdt_tokens = std::sync::Arc::new(usdt_tokens);
-                if !usdt_tokens.is_empty() {
+                let tracked_tokens = std::sync::Arc::new(tracked_tokens);
+                if !tracked_tokens.is_empty() {
                     let provider = provider.clone();
                     let shutdown = shutdown.clone();
                     let telemetry = telemetry.clone();
@@ -603,13 +808,13 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
                             let provider = provider.clone();
                             let shutdown = shutdown.clone();
                             let telemetry = telemetry.clone();
-                            let usdt_tokens = usdt_tokens.clone();
+                            let tracked_tokens = tracked_tokens.clone();
                             async move {
                                 if shutdown.is_cancelled() {
                                     return Ok::<_, anyhow::Error>((receiver_salt, true));
                                 }
 
-                                for token in usdt_tokens.iter().copied() {
+                                for token in tracked_tokens.iter().copied() {
                                     let contract = untron_v3_bindings::erc20::ERC20::new(
                                         token,
                                         provider.clone(),
@@ -628,7 +833,7 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
                                                 .await
                                                 .map_err(|e| {
                                                     telemetry.rpc_error("eth_call", "erc20.balanceOf");
-                                                    anyhow::Error::new(e).context("eth_call(erc20.balanceOf)")
+                                                    anyhow::Error::new(RpcError::from(e)).context("eth_call(erc20.balanceOf)")
                                                 })
                                         })
                                         .await?
@@ -722,6 +927,10 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
                         1,
                         receiver_usdt_cfg.chunk_blocks,
                         false,
+                        TokenSelection {
+                            usdt: true,
+                            extra: &receiver_tokens,
+                        },
                     )
                     .await?
                     else {
@@ -755,6 +964,10 @@ async fn runner_loop(ctx: LoopCtx<'_>, mode: RunnerMode) -> Result<()> {
                     1,
                     receiver_usdt_cfg.chunk_blocks,
                     false,
+                    TokenSelection {
+                        usdt: true,
+                        extra: &receiver_tokens,
+                    },
                 )
                 .await?
                 else {
@@ -793,6 +1006,7 @@ async fn process_block_ranges(
     to_addrs: &[alloy::primitives::Address],
     ranges: &[ReceiverUsdtBlockRange],
     unfiltered: bool,
+    tokens: TokenSelection<'_>,
 ) -> Result<Option<()>> {
     // In filtered mode, batch recipients to avoid huge topic arrays. In unfiltered mode the RPC
     // takes no `to` topic so the full watchlist is matched in a single call per segment.
@@ -801,40 +1015,50 @@ async fn process_block_ranges(
     let watchlist_size = to_addrs.len();
     let mut jobs = Vec::new();
 
-    for range in ranges {
-        // Split by controller USDT token changes, so transfers are indexed under the correct token address.
-        let points =
-            receiverdb::usdt_set_points_up_to(ctx.dbh, range.from_block, range.to_block).await?;
-        let segments = compute_usdt_segments(points, range.from_block, range.to_block)?;
+    let mut push_jobs = |token_evm, token_tron: &str, from_block, to_block| {
+        if unfiltered {
+            jobs.push(ReceiverUsdtFetchJob {
+                token_evm,
+                token_tron: token_tron.to_string(),
+                from_block,
+                to_block,
+                to_addrs: Vec::new(),
+            });
+        } else {
+            for chunk in to_addrs.chunks(batch_size) {
+                jobs.push(ReceiverUsdtFetchJob {
+                    token_evm,
+                    token_tron: token_tron.to_string(),
+                    from_block,
+                    to_block,
+                    to_addrs: chunk.to_vec(),
+                });
+            }
+        }
+    };
 
-        if segments.is_empty() {
-            // No known USDT token configured in this window; skip.
-            continue;
+    for range in ranges {
+        let mut segments = Vec::new();
+        if tokens.usdt {
+            // Split by controller USDT token changes, so transfers are indexed under the correct token address.
+            let points =
+                receiverdb::usdt_set_points_up_to(ctx.dbh, range.from_block, range.to_block)
+                    .await?;
+            segments = compute_usdt_segments(points, range.from_block, range.to_block)?;
         }
 
-        for (token_evm, token_tron, seg_from, seg_to) in segments {
+        for (token_evm, token_tron, seg_from, seg_to) in &segments {
             if seg_from > seg_to {
                 continue;
             }
+            push_jobs(*token_evm, token_tron, *seg_from, *seg_to);
+        }
 
-            if unfiltered {
-                jobs.push(ReceiverUsdtFetchJob {
-                    token_evm,
-                    token_tron: token_tron.clone(),
-                    from_block: seg_from,
-                    to_block: seg_to,
-                    to_addrs: Vec::new(),
-                });
-            } else {
-                for chunk in to_addrs.chunks(batch_size) {
-                    jobs.push(ReceiverUsdtFetchJob {
-                        token_evm,
-                        token_tron: token_tron.clone(),
-                        from_block: seg_from,
-                        to_block: seg_to,
-                        to_addrs: chunk.to_vec(),
-                    });
-                }
+        for token in tokens.extra {
+            for (from_block, to_block) in
+                outside_usdt_segments(token.evm, &segments, range.from_block, range.to_block)
+            {
+                push_jobs(token.evm, &token.tron, from_block, to_block);
             }
         }
     }
@@ -1046,3 +1270,99 @@ fn compute_usdt_segments(
 
     Ok(segments)
 }
+
+async fn current_controller_usdt(
+    dbh: &db::Db,
+    deployment_block: u64,
+) -> Result<Option<alloy::primitives::Address>> {
+    let points = receiverdb::usdt_set_points_up_to(dbh, deployment_block, i64::MAX as u64).await?;
+    let Some(p) = points.last() else {
+        return Ok(None);
+    };
+    let usdt = crate::domain::TronAddress::parse_text(&p.usdt_tron)
+        .or_else(|_| crate::domain::TronAddress::parse_text(&p.usdt_evm))
+        .context("parse controller usdt address")?;
+    Ok(Some(usdt.evm()))
+}
+
+/// Blocks a token behind the USDT tail cursor (`usdt_next`) catches up on: up to just before
+/// that cursor. `None` once it has reached the cursor and scans together with USDT.
+fn token_catch_up_range(token_next: u64, usdt_next: u64, safe_head: u64) -> Option<(u64, u64)> {
+    if token_next >= usdt_next {
+        return None;
+    }
+    let to_block = usdt_next.saturating_sub(1).min(safe_head);
+    (token_next <= to_block).then_some((token_next, to_block))
+}
+
+/// Parts of `from_block..=to_block` where `token` isn't the controller's USDT. Where it is,
+/// the USDT `segments` already index it.
+fn outside_usdt_segments(
+    token: alloy::primitives::Address,
+    segments: &[(alloy::primitives::Address, String, u64, u64)],
+    from_block: u64,
+    to_block: u64,
+) -> Vec<(u64, u64)> {
+    let mut out = Vec::new();
+    let mut next = from_block;
+    for (evm, _, seg_from, seg_to) in segments {
+        if *evm != token || seg_from > seg_to {
+            continue;
+        }
+        if *seg_from > next {
+            out.push((next, seg_from - 1));
+        }
+        next = next.max(seg_to.saturating_add(1));
+    }
+    if next <= to_block {
+        out.push((next, to_block));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::Address;
+
+    #[test]
+    fn token_catch_up_stops_at_the_usdt_cursor_and_then_joins_it() {
+        // Lagging token: up to the block before the USDT cursor, or the safe head if lower.
+        assert_eq!(token_catch_up_range(100, 500, 1_000), Some((100, 499)));
+        assert_eq!(token_catch_up_range(100, 500, 300), Some((100, 300)));
+        assert_eq!(token_catch_up_range(400, 500, 300), None);
+
+        // Caught up (the scan above returns 500): it scans with USDT from now on.
+        assert_eq!(token_catch_up_range(500, 500, 1_000), None);
+        assert_eq!(token_catch_up_range(600, 500, 1_000), None);
+    }
+
+    #[test]
+    fn configured_token_skips_blocks_where_it_is_usdt() {
+        let (a, b, extra) = (
+            Address::repeat_byte(0xaa),
+            Address::repeat_byte(0xbb),
+            Address::repeat_byte(0xcc),
+        );
+        let segments = [
+            (a, "A".to_string(), 100, 150),
+            (b, "B".to_string(), 150, 200),
+        ];
+
+        assert_eq!(
+            outside_usdt_segments(extra, &segments, 100, 200),
+            vec![(100, 200)]
+        );
+        // `a` was USDT until 150; the rest of the window still needs scanning for it.
+        assert_eq!(
+            outside_usdt_segments(a, &segments, 100, 200),
+            vec![(151, 200)]
+        );
+        assert_eq!(
+            outside_usdt_segments(b, &segments, 100, 200),
+            vec![(100, 149)]
+        );
+        assert!(outside_usdt_segments(a, &segments[..1], 100, 150).is_empty());
+        assert_eq!(outside_usdt_segments(a, &[], 100, 200), vec![(100, 200)]);
+    }
+}
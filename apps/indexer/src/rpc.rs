@@ -12,14 +12,15 @@ use opentelemetry::{
 };
 use std::{
     sync::{
-        Arc,
+        Arc, Mutex, PoisonError,
         atomic::{AtomicU64, Ordering},
     },
     time::Duration,
 };
 use untron_rpc_fallback::{
-    FallbackAttemptStatus, FallbackHttpTransport, FallbackObserver, RateLimitedTransport,
-    RpcRateLimiter,
+    EndpointStats, FallbackAttemptStatus, FallbackHttpOptions, FallbackHttpTransport,
+    FallbackObserver, HeadSubscription, HedgePolicy, QuorumPolicy, RateLimitedTransport,
+    RpcRateLimiter, RpcRateLimits, SubscriptionOptions, subscribe_new_heads,
 };
 
 #[derive(Debug, Clone)]
@@ -34,12 +35,28 @@ pub struct RpcConfig {
     pub urls: Vec<String>,
     pub retry: RetryConfig,
     pub max_requests_per_second: Option<u32>,
+    /// Consensus reads across `urls` for selected methods.
+    pub quorum: Option<QuorumPolicy>,
+    /// Hedged reads for selected methods.
+    pub hedge: Option<HedgePolicy>,
+    /// WS/IPC endpoints for the `newHeads` subscription. Empty disables it.
+    pub ws_urls: Vec<String>,
+    /// HTTP head polling interval while no WS/IPC endpoint is usable.
+    pub head_poll_interval: Duration,
+    /// Compute-unit budget enforced for each endpoint separately (pinned and fallback
+    /// requests to the same endpoint share it).
+    pub endpoint_limits: Option<RpcRateLimits>,
 }
 
 #[derive(Clone)]
 pub struct RpcProviders {
     pub fallback: DynProvider,
     pub pinned: Vec<DynProvider>,
+    /// Pinned endpoints that must each report a different block hash before a reorg is acted
+    /// on. 0 when quorum reads of `eth_getBlockByNumber` already confirmed it on `fallback`.
+    pub reorg_confirmations: usize,
+    /// New chain heads, when `ws_urls` are configured.
+    pub heads: Option<HeadSubscription>,
 }
 
 impl RpcProviders {
@@ -69,6 +86,7 @@ pub(crate) async fn build_providers(
 
     let mut pinned = Vec::with_capacity(cfg.urls.len());
     let mut healthy_urls = Vec::with_capacity(cfg.urls.len());
+    let mut endpoint_limiters = Vec::with_capacity(cfg.urls.len());
     let mut last_connect_err: Option<anyhow::Error> = None;
 
     for url in &cfg.urls {
@@ -80,6 +98,14 @@ pub(crate) async fn build_providers(
                     Some(limiter) => RateLimitedTransport::new(transport, limiter).boxed(),
                     None => transport,
                 };
+                let transport = match &cfg.endpoint_limits {
+                    Some(limits) => {
+                        let limiter = RpcRateLimiter::with_limits(limits.clone())?;
+                        endpoint_limiters.push(limiter.clone());
+                        RateLimitedTransport::new(transport, limiter).boxed()
+                    }
+                    None => transport,
+                };
 
                 let client = RpcClient::builder()
                     .layer(retry_layer.clone())
@@ -114,24 +140,54 @@ pub(crate) async fn build_providers(
         cfg.urls.len() as u64,
         healthy_urls.len() as u64,
     ));
-    let fallback_transport = FallbackHttpTransport::new_with_observer_and_limiter(
+    let fallback_transport = FallbackHttpTransport::new_with_options(
         fallback_urls,
-        Duration::from_millis(per_try_timeout_ms),
-        Some(fallback_observer),
+        FallbackHttpOptions {
+            per_try_timeout: Duration::from_millis(per_try_timeout_ms),
+            quorum: cfg.quorum.clone(),
+            hedge: cfg.hedge.clone(),
+            endpoint_limiters,
+            ..FallbackHttpOptions::default()
+        },
+        Some(fallback_observer.clone()),
         rate_limiter,
     )?;
 
     let client = RpcClient::builder()
         .layer(retry_layer)
         .transport(fallback_transport, false);
+    let heads = (!cfg.ws_urls.is_empty()).then(|| {
+        subscribe_new_heads(
+            cfg.ws_urls.clone(),
+            client.clone(),
+            SubscriptionOptions {
+                poll_interval: cfg.head_poll_interval,
+                ..SubscriptionOptions::default()
+            },
+            Some(fallback_observer),
+        )
+    });
     let provider = ProviderBuilder::default().connect_client(client);
+    let reorg_confirmations = reorg_confirmations(cfg.quorum.as_ref(), pinned.len());
 
     Ok(RpcProviders {
         fallback: DynProvider::new(provider),
         pinned,
+        reorg_confirmations,
+        heads,
     })
 }
 
+/// Independent pinned reads needed to confirm a block hash mismatch: the quorum's `min_agree`
+/// (2 without one), capped at the endpoints available.
+fn reorg_confirmations(quorum: Option<&QuorumPolicy>, pinned: usize) -> usize {
+    match quorum {
+        Some(q) if q.methods.iter().any(|m| m == "eth_getBlockByNumber") => 0,
+        Some(q) => q.min_agree.min(pinned),
+        None => pinned.min(2),
+    }
+}
+
 #[derive(Clone)]
 struct RpcFallbackTelemetry {
     inner: Arc<RpcFallbackTelemetryInner>,
@@ -143,8 +199,16 @@ struct RpcFallbackTelemetryInner {
     attempt_ms: Histogram<u64>,
     switches_total: Counter<u64>,
     all_failed_total: Counter<u64>,
+    disagreements_total: Counter<u64>,
+    hedges_total: Counter<u64>,
+    circuit_transitions_total: Counter<u64>,
+    subscription_switches_total: Counter<u64>,
+    /// Latest health per endpoint index, read by the gauges below.
+    endpoint_stats: Arc<Mutex<Vec<Option<EndpointStats>>>>,
     _g_configured_endpoints: ObservableGauge<u64>,
     _g_healthy_endpoints: ObservableGauge<u64>,
+    _g_endpoint_latency_ewma_ms: ObservableGauge<f64>,
+    _g_endpoint_error_rate: ObservableGauge<f64>,
 }
 
 impl RpcFallbackTelemetry {
@@ -172,6 +236,22 @@ impl RpcFallbackTelemetry {
             .u64_counter("indexer.rpc_fallback_all_failed_total")
             .with_description("Fallback requests where all endpoints failed")
             .build();
+        let disagreements_total = meter
+            .u64_counter("indexer.rpc_quorum_disagreements_total")
+            .with_description("Quorum reads where endpoints returned differing responses")
+            .build();
+        let hedges_total = meter
+            .u64_counter("indexer.rpc_fallback_hedges_total")
+            .with_description("Slow requests duplicated onto a second endpoint")
+            .build();
+        let circuit_transitions_total = meter
+            .u64_counter("indexer.rpc_circuit_transitions_total")
+            .with_description("Circuit breaker ejections (open) and recoveries (closed)")
+            .build();
+        let subscription_switches_total = meter
+            .u64_counter("indexer.rpc_subscription_switches_total")
+            .with_description("Subscription moves between WS/IPC endpoints and HTTP polling")
+            .build();
 
         let configured_endpoints = Arc::new(AtomicU64::new(configured));
         let healthy_endpoints = Arc::new(AtomicU64::new(healthy));
@@ -196,6 +276,39 @@ impl RpcFallbackTelemetry {
             })
             .build();
 
+        let endpoint_stats = Arc::new(Mutex::new(Vec::new()));
+
+        let attrs_clone = attrs.clone();
+        let stats_clone = endpoint_stats.clone();
+        let _g_endpoint_latency_ewma_ms = meter
+            .f64_observable_gauge("indexer.rpc_endpoint_latency_ewma_ms")
+            .with_description("Fallback endpoint latency EWMA used for ranking")
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                let stats = stats_clone.lock().unwrap_or_else(PoisonError::into_inner);
+                for (idx, stats) in stats.iter().enumerate() {
+                    if let Some(ms) = stats.and_then(|s| s.latency_ewma_ms) {
+                        observer.observe(ms, &endpoint_attrs(&attrs_clone, idx));
+                    }
+                }
+            })
+            .build();
+
+        let attrs_clone = attrs.clone();
+        let stats_clone = endpoint_stats.clone();
+        let _g_endpoint_error_rate = meter
+            .f64_observable_gauge("indexer.rpc_endpoint_error_rate")
+            .with_description("Fallback endpoint error-rate EWMA (0..1) used for ranking")
+            .with_callback(move |observer| {
+                let stats = stats_clone.lock().unwrap_or_else(PoisonError::into_inner);
+                for (idx, stats) in stats.iter().enumerate() {
+                    if let Some(stats) = stats {
+                        observer.observe(stats.error_rate, &endpoint_attrs(&attrs_clone, idx));
+                    }
+                }
+            })
+            .build();
+
         Self {
             inner: Arc::new(RpcFallbackTelemetryInner {
                 attrs,
@@ -203,11 +316,24 @@ impl RpcFallbackTelemetry {
                 attempt_ms,
                 switches_total,
                 all_failed_total,
+                disagreements_total,
+                hedges_total,
+                circuit_transitions_total,
+                subscription_switches_total,
+                endpoint_stats,
                 _g_configured_endpoints,
                 _g_healthy_endpoints,
+                _g_endpoint_latency_ewma_ms,
+                _g_endpoint_error_rate,
             }),
         }
     }
+
+    fn circuit_transition(&self, endpoint_idx: usize, state: &'static str) {
+        let mut attrs = endpoint_attrs(&self.inner.attrs, endpoint_idx);
+        attrs.push(KeyValue::new("state", state));
+        self.inner.circuit_transitions_total.add(1, &attrs);
+    }
 }
 
 impl FallbackObserver for RpcFallbackTelemetry {
@@ -222,6 +348,7 @@ impl FallbackObserver for RpcFallbackTelemetry {
             FallbackAttemptStatus::Ok => "ok",
             FallbackAttemptStatus::Err => "err",
             FallbackAttemptStatus::Timeout => "timeout",
+            FallbackAttemptStatus::RateLimited => "rate_limited",
         };
         let attrs = [
             self.inner.attrs[0].clone(),
@@ -256,4 +383,71 @@ impl FallbackObserver for RpcFallbackTelemetry {
         ];
         self.inner.all_failed_total.add(1, &attrs);
     }
+
+    fn on_disagreement(&self, method: &str, agreed: &[usize], _dissented: &[usize]) {
+        let attrs = [
+            self.inner.attrs[0].clone(),
+            self.inner.attrs[1].clone(),
+            KeyValue::new("method", method.to_string()),
+            KeyValue::new("quorum", if agreed.is_empty() { "none" } else { "reached" }),
+        ];
+        self.inner.disagreements_total.add(1, &attrs);
+    }
+
+    fn on_hedge(&self, method: &str, primary_idx: usize, hedge_idx: usize) {
+        let attrs = [
+            self.inner.attrs[0].clone(),
+            self.inner.attrs[1].clone(),
+            KeyValue::new("method", method.to_string()),
+            KeyValue::new(
+                "primary_idx",
+                i64::try_from(primary_idx).unwrap_or_default(),
+            ),
+            KeyValue::new("hedge_idx", i64::try_from(hedge_idx).unwrap_or_default()),
+        ];
+        self.inner.hedges_total.add(1, &attrs);
+    }
+
+    fn on_health(&self, endpoint_idx: usize, stats: &EndpointStats) {
+        let mut all = self
+            .inner
+            .endpoint_stats
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if all.len() <= endpoint_idx {
+            all.resize(endpoint_idx + 1, None);
+        }
+        all[endpoint_idx] = Some(*stats);
+    }
+
+    fn on_circuit_open(&self, endpoint_idx: usize, _cooldown: Duration) {
+        self.circuit_transition(endpoint_idx, "open");
+    }
+
+    fn on_circuit_close(&self, endpoint_idx: usize) {
+        self.circuit_transition(endpoint_idx, "closed");
+    }
+
+    fn on_subscription_source(&self, subscription: &str, ws_idx: Option<usize>) {
+        let attrs = [
+            self.inner.attrs[0].clone(),
+            self.inner.attrs[1].clone(),
+            KeyValue::new("subscription", subscription.to_string()),
+            KeyValue::new("source", if ws_idx.is_some() { "ws" } else { "http" }),
+            KeyValue::new(
+                "ws_idx",
+                ws_idx.and_then(|idx| i64::try_from(idx).ok()).unwrap_or(-1),
+            ),
+        ];
+        self.inner.subscription_switches_total.add(1, &attrs);
+    }
+}
+
+fn endpoint_attrs(base: &[KeyValue], endpoint_idx: usize) -> Vec<KeyValue> {
+    let mut attrs = base.to_vec();
+    attrs.push(KeyValue::new(
+        "endpoint_idx",
+        i64::try_from(endpoint_idx).unwrap_or_default(),
+    ));
+    attrs
 }
@@ -1,4 +1,4 @@
-use crate::shared::rpc_telemetry::RpcTelemetry;
+use crate::shared::{rpc_error::RpcError, rpc_telemetry::RpcTelemetry};
 use alloy::providers::{DynProvider, Provider};
 use anyhow::Result;
 use std::sync::Arc;
@@ -35,11 +35,16 @@ impl HeadCache {
         let start = Instant::now();
         let head = self.provider.get_block_number().await.map_err(|e| {
             telemetry.rpc_error("eth_blockNumber", purpose);
-            anyhow::Error::new(e).context("eth_blockNumber")
+            anyhow::Error::new(RpcError::from(e)).context("eth_blockNumber")
         })?;
         let ms = start.elapsed().as_millis() as u64;
         telemetry.rpc_call("eth_blockNumber", purpose, true, ms);
         *guard = Some((head, Instant::now()));
         Ok(head)
     }
+
+    /// Caches a head learned without an RPC (e.g. from a `newHeads` subscription).
+    pub async fn observe(&self, head: u64) {
+        *self.inner.lock().await = Some((head, Instant::now()));
+    }
 }
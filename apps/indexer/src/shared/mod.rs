@@ -2,5 +2,7 @@ pub mod r#async;
 pub mod head_cache;
 pub mod logs;
 pub mod progress;
+pub mod rpc_error;
+pub mod rpc_retry;
 pub mod rpc_telemetry;
 pub mod timestamps;
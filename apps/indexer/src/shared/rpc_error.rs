@@ -0,0 +1,74 @@
+use alloy::transports::TransportError;
+use std::fmt;
+
+pub use untron_rpc_fallback::RpcErrorKind;
+
+/// A failed RPC call, classified where it was made.
+///
+/// Call sites wrap the alloy error in this before adding context, so retry and chunk-size
+/// decisions can use [`kind`] instead of inspecting error messages.
+#[derive(Debug)]
+pub struct RpcError {
+    kind: RpcErrorKind,
+    source: TransportError,
+}
+
+impl RpcError {
+    pub fn kind(&self) -> RpcErrorKind {
+        self.kind
+    }
+}
+
+impl From<TransportError> for RpcError {
+    fn from(source: TransportError) -> Self {
+        Self {
+            kind: RpcErrorKind::from_transport_error(&source),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} rpc error: {}", self.kind, self.source)
+    }
+}
+
+impl std::error::Error for RpcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Classification of the RPC failure behind `err`, or `None` if no RPC call failed (database
+/// errors, decode errors, invariant violations).
+pub fn kind(err: &anyhow::Error) -> Option<RpcErrorKind> {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<RpcError>())
+        .map(RpcError::kind)
+}
+
+pub fn is_retryable(err: &anyhow::Error) -> bool {
+    kind(err).is_some_and(RpcErrorKind::is_retryable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::rpc::json_rpc::ErrorPayload;
+
+    #[test]
+    fn kind_is_found_under_context() {
+        let err = TransportError::ErrorResp(ErrorPayload {
+            code: -32005,
+            message: "query returned more than 10000 results".into(),
+            data: None,
+        });
+        let err = anyhow::Error::new(RpcError::from(err)).context("eth_getLogs [1..2]");
+        assert_eq!(kind(&err), Some(RpcErrorKind::RangeTooLarge));
+        assert!(!is_retryable(&err));
+
+        // Non-RPC failures are never retried as if the provider were at fault.
+        assert_eq!(kind(&anyhow::anyhow!("request timed out (429)")), None);
+    }
+}
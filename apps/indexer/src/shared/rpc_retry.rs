@@ -0,0 +1,104 @@
+use crate::shared::rpc_error::RpcErrorKind;
+use std::time::Duration;
+
+const MAX_TRANSIENT_RETRIES: u32 = 3;
+const TRANSIENT_BACKOFF_INITIAL: Duration = Duration::from_millis(250);
+const TRANSIENT_BACKOFF_MAX: Duration = Duration::from_secs(2);
+const RATE_LIMIT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// Attempt count and exponential backoff for retrying one request after retryable RPC errors.
+///
+/// Transient errors get `MAX_TRANSIENT_RETRIES` retries per request. Rate limits are retried
+/// without a cap: shrinking the request would only multiply the calls the provider is already
+/// refusing.
+#[derive(Debug)]
+pub struct RpcRetry {
+    attempts: u32,
+    backoff: Duration,
+}
+
+impl RpcRetry {
+    pub fn new() -> Self {
+        Self {
+            attempts: 0,
+            backoff: TRANSIENT_BACKOFF_INITIAL,
+        }
+    }
+
+    /// Starts over, after a success or when the request itself changes.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Delay before retrying after a `kind` failure, or `None` if the request should not be
+    /// retried as is.
+    pub fn next_backoff(&mut self, kind: RpcErrorKind) -> Option<Duration> {
+        if !kind.is_retryable()
+            || (kind != RpcErrorKind::RateLimited && self.attempts >= MAX_TRANSIENT_RETRIES)
+        {
+            return None;
+        }
+        Some(self.backoff(kind))
+    }
+
+    /// Delay before the next attempt, for callers that retry without a cap.
+    pub fn backoff(&mut self, kind: RpcErrorKind) -> Duration {
+        let max = match kind {
+            RpcErrorKind::RateLimited => RATE_LIMIT_BACKOFF_MAX,
+            _ => TRANSIENT_BACKOFF_MAX,
+        };
+        self.attempts = self.attempts.saturating_add(1);
+        let backoff = self.backoff;
+        self.backoff = (self.backoff * 2).min(max);
+        backoff
+    }
+}
+
+impl Default for RpcRetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transient_retries_are_capped_and_rate_limits_are_not() {
+        let mut retry = RpcRetry::new();
+        let backoffs = (0..4)
+            .map(|_| retry.next_backoff(RpcErrorKind::Transient))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            backoffs,
+            [
+                Some(Duration::from_millis(250)),
+                Some(Duration::from_millis(500)),
+                Some(Duration::from_secs(1)),
+                None,
+            ]
+        );
+
+        for _ in 0..10 {
+            assert!(retry.next_backoff(RpcErrorKind::RateLimited).is_some());
+        }
+        assert_eq!(
+            retry.next_backoff(RpcErrorKind::RateLimited),
+            Some(RATE_LIMIT_BACKOFF_MAX)
+        );
+        assert_eq!(retry.next_backoff(RpcErrorKind::Permanent), None);
+        assert_eq!(retry.next_backoff(RpcErrorKind::RangeTooLarge), None);
+
+        retry.reset();
+        assert_eq!(retry.attempts(), 0);
+        assert_eq!(
+            retry.next_backoff(RpcErrorKind::Transient),
+            Some(TRANSIENT_BACKOFF_INITIAL)
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::shared::rpc_telemetry::RpcTelemetry;
+use crate::shared::{rpc_error::RpcError, rpc_telemetry::RpcTelemetry};
 use alloy::providers::Provider;
 use anyhow::{Context, Result};
 use futures::{StreamExt, stream};
@@ -121,11 +121,15 @@ impl TimestampState {
                                         start.elapsed().as_millis() as u64,
                                     );
                                 }
-                                anyhow::Error::new(e)
+                                anyhow::Error::new(RpcError::from(e))
                             })
                             .with_context(|| format!("eth_getBlockByNumber({block_number})"))?;
                         let Some(block) = block else {
-                            anyhow::bail!("block {block_number} not found");
+                            // A lagging node returns null for blocks it hasn't seen yet.
+                            return Err(anyhow::Error::new(RpcError::from(
+                                alloy::transports::TransportError::NullResp,
+                            ))
+                            .context(format!("block {block_number} not found")));
                         };
                         if let Some(rpc) = rpc {
                             rpc.rpc_call(
@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::time::Duration;
-use tron::JsonApiRentalProviderConfig;
+use tron::{JsonApiRentalProviderConfig, TronGrpcPoolOptions};
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
@@ -12,13 +12,20 @@ pub struct AppConfig {
 
 #[derive(Debug, Clone)]
 pub struct TronConfig {
+    /// Node endpoints in failover order. Plain URLs are gRPC; `rest+https://host` selects the
+    /// `/wallet/*` HTTP API (see `tron::HTTP_URL_PREFIX`).
     pub grpc_urls: Vec<String>,
     pub api_key: Option<String>,
     pub api_key_header: String,
+    /// Endpoint health scoring, head-lag detection and read hedging across `grpc_urls`.
+    pub endpoint_pool: TronGrpcPoolOptions,
     pub private_key: [u8; 32],
     pub usdt_contract_address: String,
 
     pub energy_rental_providers: Vec<JsonApiRentalProviderConfig>,
+    /// Optional Stake 2.0 account whose staked energy is delegated to `private_key`'s address
+    /// per transfer and reclaimed once it confirms. Ranked alongside rental providers by cost.
+    pub energy_staker_private_key: Option<[u8; 32]>,
     pub energy_rental_settle_delay: Duration,
 }
 
@@ -55,12 +62,18 @@ struct Env {
     tron_api_key: Option<String>,
     #[serde(default = "default_tron_api_key_header")]
     tron_api_key_header: String,
+    tron_read_timeout_ms: u64,
+    tron_read_hedge_after_ms: u64,
+    tron_max_head_lag_blocks: u64,
+    tron_head_probe_interval_secs: u64,
     tron_private_key_hex: String,
     tron_usdt_contract_address: String,
 
     #[serde(default)]
     tron_energy_rental_apis_json: String,
     #[serde(default)]
+    tron_energy_staker_private_key_hex: String,
+    #[serde(default)]
     tron_energy_rental_settle_delay_secs: u64,
 
     oneclick_base_url: String,
@@ -96,9 +109,14 @@ impl Default for Env {
             tron_grpc_urls: String::new(),
             tron_api_key: None,
             tron_api_key_header: default_tron_api_key_header(),
+            tron_read_timeout_ms: 30_000,
+            tron_read_hedge_after_ms: 2_000,
+            tron_max_head_lag_blocks: 20,
+            tron_head_probe_interval_secs: 30,
             tron_private_key_hex: String::new(),
             tron_usdt_contract_address: "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".to_string(),
             tron_energy_rental_apis_json: String::new(),
+            tron_energy_staker_private_key_hex: String::new(),
             tron_energy_rental_settle_delay_secs: 6,
             oneclick_base_url: "https://1click.chaindefuser.com".to_string(),
             oneclick_bearer_token: String::new(),
@@ -191,6 +209,13 @@ pub fn load_config() -> Result<AppConfig> {
                 let h = env.tron_api_key_header.trim();
                 if h.is_empty() { default_tron_api_key_header() } else { h.to_string() }
             },
+            endpoint_pool: TronGrpcPoolOptions {
+                per_try_timeout: Duration::from_millis(env.tron_read_timeout_ms.max(1)),
+                hedge_after: (env.tron_read_hedge_after_ms > 0)
+                    .then(|| Duration::from_millis(env.tron_read_hedge_after_ms)),
+                max_head_lag_blocks: env.tron_max_head_lag_blocks,
+                head_probe_interval: Duration::from_secs(env.tron_head_probe_interval_secs),
+            },
             private_key: crate::util::parse_hex_32(
                 "TRON_PRIVATE_KEY_HEX",
                 &env.tron_private_key_hex,
@@ -199,6 +224,17 @@ pub fn load_config() -> Result<AppConfig> {
             energy_rental_providers: parse_tron_energy_rental_apis_json(
                 &env.tron_energy_rental_apis_json,
             )?,
+            energy_staker_private_key: {
+                let k = env.tron_energy_staker_private_key_hex.trim();
+                if k.is_empty() {
+                    None
+                } else {
+                    Some(crate::util::parse_hex_32(
+                        "TRON_ENERGY_STAKER_PRIVATE_KEY_HEX",
+                        k,
+                    )?)
+                }
+            },
             energy_rental_settle_delay: Duration::from_secs(
                 env.tron_energy_rental_settle_delay_secs,
             ),
@@ -361,4 +397,24 @@ mod tests {
             "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"
         );
     }
+
+    #[test]
+    fn load_config_zero_hedge_delay_disables_hedging() {
+        let _guard = env_lock().lock().unwrap();
+        set_required_env_for_success();
+        clear_env(&["TRON_READ_HEDGE_AFTER_MS"]);
+        let cfg = load_config().unwrap();
+        assert_eq!(
+            cfg.tron.endpoint_pool.hedge_after,
+            Some(Duration::from_millis(2_000))
+        );
+
+        // Safety: tests take a global env lock; no concurrent env access within this test process.
+        unsafe {
+            std::env::set_var("TRON_READ_HEDGE_AFTER_MS", "0");
+        }
+        let cfg = load_config().unwrap();
+        clear_env(&["TRON_READ_HEDGE_AFTER_MS"]);
+        assert_eq!(cfg.tron.endpoint_pool.hedge_after, None);
+    }
 }
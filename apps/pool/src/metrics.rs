@@ -3,6 +3,7 @@ use opentelemetry::{
     metrics::{Counter, Histogram},
 };
 use std::sync::Arc;
+use tron::{TronAttemptStatus, TronPoolObserver};
 
 #[derive(Clone)]
 pub struct PoolTelemetry {
@@ -39,6 +40,17 @@ struct Inner {
 
     oneclick_backoffs_total: Counter<u64>,
     oneclick_backoff_secs: Histogram<u64>,
+
+    tron_endpoint_attempts_total: Counter<u64>,
+    tron_endpoint_switches_total: Counter<u64>,
+    tron_endpoint_all_failed_total: Counter<u64>,
+    tron_endpoint_hedges_total: Counter<u64>,
+    tron_endpoint_attempt_ms: Histogram<u64>,
+    tron_endpoint_head_lag_blocks: Histogram<u64>,
+
+    energy_source_decisions_total: Counter<u64>,
+    energy_quote_sun: Histogram<u64>,
+    energy_cost_sun: Histogram<u64>,
 }
 
 impl PoolTelemetry {
@@ -149,6 +161,48 @@ impl PoolTelemetry {
             .with_unit("s")
             .build();
 
+        let tron_endpoint_attempts_total = meter
+            .u64_counter("pool.tron_endpoint_attempts_total")
+            .with_description("Total Tron calls per endpoint")
+            .build();
+        let tron_endpoint_switches_total = meter
+            .u64_counter("pool.tron_endpoint_switches_total")
+            .with_description("Total Tron preferred-endpoint switches")
+            .build();
+        let tron_endpoint_all_failed_total = meter
+            .u64_counter("pool.tron_endpoint_all_failed_total")
+            .with_description("Total Tron calls that failed on every endpoint")
+            .build();
+        let tron_endpoint_hedges_total = meter
+            .u64_counter("pool.tron_endpoint_hedges_total")
+            .with_description("Total Tron reads hedged onto a second endpoint")
+            .build();
+        let tron_endpoint_attempt_ms = meter
+            .u64_histogram("pool.tron_endpoint_attempt_ms")
+            .with_description("Tron call attempt runtime per endpoint")
+            .with_unit("ms")
+            .build();
+        let tron_endpoint_head_lag_blocks = meter
+            .u64_histogram("pool.tron_endpoint_head_lag_blocks")
+            .with_description("Tron endpoint head lag behind the best endpoint")
+            .with_unit("blocks")
+            .build();
+
+        let energy_source_decisions_total = meter
+            .u64_counter("pool.energy_source_decisions_total")
+            .with_description("Total energy source decisions (rental provider or TRX burn)")
+            .build();
+        let energy_quote_sun = meter
+            .u64_histogram("pool.energy_quote_sun")
+            .with_description("Energy rental price quoted per provider")
+            .with_unit("sun")
+            .build();
+        let energy_cost_sun = meter
+            .u64_histogram("pool.energy_cost_sun")
+            .with_description("Cost of the chosen energy source")
+            .with_unit("sun")
+            .build();
+
         Self {
             inner: Arc::new(Inner {
                 ticks_total,
@@ -173,6 +227,15 @@ impl PoolTelemetry {
                 oneclick_status_poll_ms,
                 oneclick_backoffs_total,
                 oneclick_backoff_secs,
+                tron_endpoint_attempts_total,
+                tron_endpoint_switches_total,
+                tron_endpoint_all_failed_total,
+                tron_endpoint_hedges_total,
+                tron_endpoint_attempt_ms,
+                tron_endpoint_head_lag_blocks,
+                energy_source_decisions_total,
+                energy_quote_sun,
+                energy_cost_sun,
             }),
         }
     }
@@ -284,4 +347,73 @@ impl PoolTelemetry {
             .oneclick_backoff_secs
             .record(secs, &[KeyValue::new("reason", reason)]);
     }
+
+    pub fn energy_quote_sun(&self, source: &str, sun: u64) {
+        self.inner
+            .energy_quote_sun
+            .record(sun, &[KeyValue::new("source", source.to_string())]);
+    }
+
+    /// `source` is a rental provider name, or `"burn"` when paying for energy with TRX.
+    pub fn energy_source_decision(&self, source: &str, cost_sun: Option<u64>) {
+        let attrs = [KeyValue::new("source", source.to_string())];
+        self.inner.energy_source_decisions_total.add(1, &attrs);
+        if let Some(cost_sun) = cost_sun {
+            self.inner.energy_cost_sun.record(cost_sun, &attrs);
+        }
+    }
+}
+
+impl TronPoolObserver for PoolTelemetry {
+    fn on_attempt(&self, op: &str, endpoint_idx: usize, status: TronAttemptStatus, ms: u64) {
+        let status = match status {
+            TronAttemptStatus::Ok => "ok",
+            TronAttemptStatus::Err => "err",
+            TronAttemptStatus::Timeout => "timeout",
+        };
+        let attrs = [
+            KeyValue::new("op", op.to_string()),
+            KeyValue::new(
+                "endpoint_idx",
+                i64::try_from(endpoint_idx).unwrap_or_default(),
+            ),
+            KeyValue::new("status", status),
+        ];
+        self.inner.tron_endpoint_attempts_total.add(1, &attrs);
+        self.inner.tron_endpoint_attempt_ms.record(ms, &attrs);
+    }
+
+    fn on_switch(&self, op: &str, from_idx: usize, to_idx: usize) {
+        let attrs = [
+            KeyValue::new("op", op.to_string()),
+            KeyValue::new("from_idx", i64::try_from(from_idx).unwrap_or_default()),
+            KeyValue::new("to_idx", i64::try_from(to_idx).unwrap_or_default()),
+        ];
+        self.inner.tron_endpoint_switches_total.add(1, &attrs);
+    }
+
+    fn on_all_failed(&self, op: &str) {
+        self.inner
+            .tron_endpoint_all_failed_total
+            .add(1, &[KeyValue::new("op", op.to_string())]);
+    }
+
+    fn on_hedge(&self, op: &str, primary_idx: usize, hedge_idx: usize) {
+        let attrs = [
+            KeyValue::new("op", op.to_string()),
+            KeyValue::new("from_idx", i64::try_from(primary_idx).unwrap_or_default()),
+            KeyValue::new("to_idx", i64::try_from(hedge_idx).unwrap_or_default()),
+        ];
+        self.inner.tron_endpoint_hedges_total.add(1, &attrs);
+    }
+
+    fn on_head_lag(&self, endpoint_idx: usize, lag_blocks: u64) {
+        self.inner.tron_endpoint_head_lag_blocks.record(
+            lag_blocks,
+            &[KeyValue::new(
+                "endpoint_idx",
+                i64::try_from(endpoint_idx).unwrap_or_default(),
+            )],
+        );
+    }
 }
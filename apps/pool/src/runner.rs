@@ -10,14 +10,18 @@ use one_click_sdk_rs::{
 };
 use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use time::{OffsetDateTime, format_description::well_known::Rfc3339};
 use tokio::sync::Mutex;
 use tokio::time::MissedTickBehavior;
 use tokio_util::sync::CancellationToken;
 use tron::{
-    FeeLimitPolicy, JsonApiRentalProvider, RentalContext, RentalResourceKind, TronAddress,
-    TronGrpc, TronWallet, wallet::trc20_balance_of,
+    EnergyLease, EnergyQuote, EnergySource, FeeLimitPolicy, JsonApiRentalProvider, RentalContext,
+    RentalResourceKind, StakedEnergyDelegator, TronAddress, TronApi, TronClient, TronGrpcPool,
+    TronWallet, quote_energy_sources, rank_energy_sources,
+    resources::{parse_chain_fees, quote_fee_limit_sun},
+    wallet::trc20_balance_of,
 };
 
 fn build_oneclick_client(user_agent: &str, bearer_token: Option<&str>) -> Result<reqwest::Client> {
@@ -45,15 +49,11 @@ pub struct PoolService {
     cfg: AppConfig,
     telemetry: PoolTelemetry,
 
-    tron: TronGrpc,
-    tron_urls: Vec<String>,
-    tron_url_cursor: usize,
-    tron_api_key: Option<String>,
-    tron_api_key_header: String,
-    tron_wallet: TronWallet,
+    tron: TronGrpcPool,
+    tron_wallet: Arc<TronWallet>,
     tron_usdt: TronAddress,
-    energy_rental: Vec<JsonApiRentalProvider>,
-    energy_rental_cursor: usize,
+    energy_rental: Arc<[EnergySource]>,
+    energy_rental_cursor: Arc<AtomicUsize>,
 
     oneclick: OneClickConfiguration,
     watch_tx: tokio::sync::mpsc::Sender<WatchRequest>,
@@ -62,41 +62,44 @@ pub struct PoolService {
 
 impl PoolService {
     pub async fn new(cfg: AppConfig, telemetry: PoolTelemetry) -> Result<Self> {
-        let tron_urls = cfg.tron.grpc_urls.clone();
-        let tron_api_key = cfg.tron.api_key.clone();
-        let tron_api_key_header = cfg.tron.api_key_header.clone();
-        let mut tron = None;
-        let mut tron_url_cursor = 0usize;
-        let mut last_connect_err: Option<anyhow::Error> = None;
-        for (idx, url) in tron_urls.iter().enumerate() {
-            match TronGrpc::connect(url, tron_api_key.as_deref(), &tron_api_key_header).await {
-                Ok(g) => {
-                    tron = Some(g);
-                    tron_url_cursor = idx;
-                    break;
-                }
-                Err(err) => {
-                    tracing::warn!(tron_grpc = %url, err = %err, "failed to connect Tron gRPC endpoint");
-                    last_connect_err = Some(err);
-                }
-            }
-        }
-        let tron = tron.context("connect TRON gRPC")?;
-        if let Some(err) = last_connect_err {
-            tracing::info!(err = %err, "using fallback Tron gRPC endpoint");
-        }
+        let tron = TronGrpcPool::connect(
+            cfg.tron.grpc_urls.clone(),
+            cfg.tron.api_key.clone(),
+            cfg.tron.api_key_header.clone(),
+            cfg.tron.endpoint_pool.clone(),
+            Some(Arc::new(telemetry.clone())),
+        )
+        .await?;
+        Self::with_tron(cfg, telemetry, tron)
+    }
 
-        let tron_wallet = TronWallet::new(cfg.tron.private_key)?;
+    /// Builds the service around an already-connected endpoint pool.
+    fn with_tron(cfg: AppConfig, telemetry: PoolTelemetry, tron: TronGrpcPool) -> Result<Self> {
+        let tron_wallet = Arc::new(TronWallet::new(cfg.tron.private_key)?);
         let tron_usdt = TronAddress::parse_text(&cfg.tron.usdt_contract_address)
             .context("parse TRON_USDT_CONTRACT_ADDRESS")?;
 
-        let energy_rental = cfg
-            .tron
-            .energy_rental_providers
-            .clone()
-            .into_iter()
-            .map(JsonApiRentalProvider::new)
-            .collect::<Vec<_>>();
+        let mut energy_rental = Vec::new();
+        if let Some(key) = cfg.tron.energy_staker_private_key {
+            let staker =
+                Arc::new(TronWallet::new(key).context("TRON_ENERGY_STAKER_PRIVATE_KEY_HEX")?);
+            if staker.address() == tron_wallet.address() {
+                anyhow::bail!(
+                    "TRON_ENERGY_STAKER_PRIVATE_KEY_HEX must differ from TRON_PRIVATE_KEY_HEX (Tron rejects self-delegation)"
+                );
+            }
+            energy_rental.push(EnergySource::Staked(StakedEnergyDelegator::new(
+                "staked", staker,
+            )));
+        }
+        energy_rental.extend(
+            cfg.tron
+                .energy_rental_providers
+                .clone()
+                .into_iter()
+                .map(|p| EnergySource::JsonApi(JsonApiRentalProvider::new(p))),
+        );
+        let energy_rental = Arc::<[EnergySource]>::from(energy_rental);
         if !energy_rental.is_empty() {
             let names = energy_rental
                 .iter()
@@ -125,14 +128,10 @@ impl PoolService {
             cfg,
             telemetry,
             tron,
-            tron_urls,
-            tron_url_cursor,
-            tron_api_key,
-            tron_api_key_header,
             tron_wallet,
             tron_usdt,
             energy_rental,
-            energy_rental_cursor: 0,
+            energy_rental_cursor: Arc::new(AtomicUsize::new(0)),
             oneclick,
             watch_tx,
             backoff,
@@ -340,72 +339,14 @@ impl PoolService {
         Ok(())
     }
 
-    async fn trc20_balance(&mut self) -> Result<alloy::primitives::U256> {
-        let len = self.tron_urls.len();
-        if len == 0 {
-            anyhow::bail!("no TRON_GRPC_URLS configured");
-        }
-
-        let attempts = if len == 1 { 2 } else { len };
-        for attempt in 0..attempts {
-            let idx = (self.tron_url_cursor + attempt) % len;
-            let force_reconnect = len == 1 && attempt > 0;
-            if let Err(err) = self.ensure_tron_connected(idx, force_reconnect).await {
-                tracing::warn!(
-                    tron_grpc = %self.tron_urls[idx],
-                    op = "trc20_balance_of",
-                    err = %err,
-                    "failed to connect Tron gRPC endpoint"
-                );
-                continue;
-            }
-
-            match trc20_balance_of(
-                &mut self.tron,
-                self.tron_usdt,
-                self.tron_wallet.address(),
-                self.tron_wallet.address(),
-            )
+    async fn trc20_balance(&self) -> Result<alloy::primitives::U256> {
+        let token = self.tron_usdt;
+        let owner = self.tron_wallet.address();
+        self.tron
+            .with_read("trc20_balance_of", |tron| {
+                Box::pin(async move { trc20_balance_of(tron, token, owner, owner).await })
+            })
             .await
-            {
-                Ok(v) => return Ok(v),
-                Err(err) => {
-                    tracing::warn!(
-                        tron_grpc = %self.tron_urls[self.tron_url_cursor],
-                        op = "trc20_balance_of",
-                        err = %err,
-                        "tron operation failed; trying next endpoint"
-                    );
-                }
-            }
-        }
-
-        anyhow::bail!("all TRON_GRPC_URLS endpoints failed for op=trc20_balance_of")
-    }
-
-    async fn ensure_tron_connected(&mut self, idx: usize, force_reconnect: bool) -> Result<()> {
-        let len = self.tron_urls.len();
-        if len == 0 {
-            anyhow::bail!("no TRON_GRPC_URLS configured");
-        }
-        let idx = idx % len;
-        if idx == self.tron_url_cursor && !force_reconnect {
-            return Ok(());
-        }
-
-        let url = self.tron_urls[idx].clone();
-        let grpc = TronGrpc::connect(
-            &url,
-            self.tron_api_key.as_deref(),
-            &self.tron_api_key_header,
-        )
-        .await
-        .with_context(|| format!("connect TRON gRPC: {url}"))?;
-
-        self.tron = grpc;
-        self.tron_url_cursor = idx;
-        tracing::info!(tron_grpc = %url, "switched Tron gRPC endpoint");
-        Ok(())
     }
 
     async fn create_quote(&self, amount: alloy::primitives::U256) -> Result<QuoteResponse> {
@@ -441,72 +382,46 @@ impl PoolService {
     }
 
     async fn broadcast_trc20_transfer(
-        &mut self,
+        &self,
         token_contract: TronAddress,
         recipient: TronAddress,
         amount: alloy::primitives::U256,
     ) -> Result<[u8; 32]> {
         let data = encode_trc20_transfer(recipient.evm(), amount);
-
-        let len = self.tron_urls.len();
-        if len == 0 {
-            anyhow::bail!("no TRON_GRPC_URLS configured");
-        }
-
-        let attempts = if len == 1 { 2 } else { len };
-        for attempt in 0..attempts {
-            let idx = (self.tron_url_cursor + attempt) % len;
-            let force_reconnect = len == 1 && attempt > 0;
-            if let Err(err) = self.ensure_tron_connected(idx, force_reconnect).await {
-                tracing::warn!(
-                    tron_grpc = %self.tron_urls[idx],
-                    op = "broadcast_trc20_transfer",
-                    err = %err,
-                    "failed to connect Tron gRPC endpoint"
-                );
-                continue;
-            }
-
-            let res = {
-                let tron = &mut self.tron;
-                let wallet = &self.tron_wallet;
-                let energy_rental = &self.energy_rental;
-                let energy_rental_cursor = &mut self.energy_rental_cursor;
-                let telemetry = &self.telemetry;
-                broadcast_trc20_transfer_single(
-                    tron,
-                    wallet,
-                    energy_rental,
-                    energy_rental_cursor,
-                    telemetry,
-                    self.cfg.tron.energy_rental_settle_delay,
-                    token_contract,
-                    data.clone(),
-                )
-                .await
-            };
-
-            match res {
-                Ok(v) => return Ok(v),
-                Err(err) => {
-                    tracing::warn!(
-                        tron_grpc = %self.tron_urls[self.tron_url_cursor],
-                        op = "broadcast_trc20_transfer",
-                        err = %err,
-                        "tron operation failed; trying next endpoint"
-                    );
-                }
-            }
-        }
-
-        anyhow::bail!("all TRON_GRPC_URLS endpoints failed for op=broadcast_trc20_transfer")
+        let energy_rental_settle_delay = self.cfg.tron.energy_rental_settle_delay;
+
+        self.tron
+            .with_failover("broadcast_trc20_transfer", |tron| {
+                let wallet = self.tron_wallet.clone();
+                let energy_rental = self.energy_rental.clone();
+                let energy_rental_cursor = self.energy_rental_cursor.clone();
+                let telemetry = self.telemetry.clone();
+                let data = data.clone();
+                Box::pin(async move {
+                    let mut cursor = energy_rental_cursor.load(Ordering::Relaxed);
+                    let res = broadcast_trc20_transfer_single(
+                        tron,
+                        &wallet,
+                        &energy_rental,
+                        &mut cursor,
+                        &telemetry,
+                        energy_rental_settle_delay,
+                        token_contract,
+                        data,
+                    )
+                    .await;
+                    energy_rental_cursor.store(cursor, Ordering::Relaxed);
+                    res
+                })
+            })
+            .await
     }
 }
 
 async fn broadcast_trc20_transfer_single(
-    tron: &mut TronGrpc,
+    tron: &mut TronClient,
     wallet: &TronWallet,
-    energy_rental: &[JsonApiRentalProvider],
+    energy_rental: &[EnergySource],
     energy_rental_cursor: &mut usize,
     telemetry: &PoolTelemetry,
     energy_rental_settle_delay: Duration,
@@ -528,6 +443,7 @@ async fn broadcast_trc20_transfer_single(
         .await?;
 
     // Attempt energy rental for the shortfall (best-effort, fall back to paying TRX).
+    let mut staked_lease = None;
     if !energy_rental.is_empty() {
         let res = tron
             .get_account_resource(wallet.address().prefixed_bytes().to_vec())
@@ -559,47 +475,68 @@ async fn broadcast_trc20_transfer_single(
                 address_hex41: addr_hex41,
                 address_evm_hex: addr_evm_hex,
                 txid: Some(txid_hex),
+                order_id: None,
             };
 
+            // Rank sources cheapest-first against burning TRX for the shortfall.
+            let fees = parse_chain_fees(&tron.get_chain_parameters().await?)?;
+            let burn_sun = quote_fee_limit_sun(shortfall, 0, fees);
+            let quotes = quote_energy_sources(energy_rental, &*tron, &ctx, fees).await;
+            for (source, quote) in energy_rental.iter().zip(&quotes) {
+                if let EnergyQuote::Sun(sun) = *quote {
+                    telemetry.energy_quote_sun(source.name(), sun);
+                }
+            }
+
             let len = energy_rental.len();
             let start_cursor = *energy_rental_cursor;
-            let order = rental_try_indices(start_cursor, len);
+            let order =
+                rank_energy_sources(&rental_try_indices(start_cursor, len), &quotes, burn_sun);
+            tracing::info!(burn_sun, quotes = ?quotes, order = ?order, "energy source ranking");
 
             let mut ok = false;
-            for (attempt, idx) in order.into_iter().enumerate() {
+            for idx in order {
                 let p = &energy_rental[idx];
+                *energy_rental_cursor = rental_cursor_after_attempts(idx, len, 1);
                 let rent_start = std::time::Instant::now();
-                match p.rent(&ctx).await {
-                    Ok(attempt_res) if attempt_res.ok => {
+                match p.acquire(tron, &ctx).await {
+                    Ok(lease) if lease.attempt.ok => {
+                        let attempt_res = &lease.attempt;
                         telemetry.energy_rental_ms(
                             &attempt_res.provider,
                             true,
                             rent_start.elapsed().as_millis() as u64,
                         );
+                        let cost_sun = attempt_res.cost_sun.or(match quotes[idx] {
+                            EnergyQuote::Sun(sun) => Some(sun),
+                            _ => None,
+                        });
+                        telemetry.energy_source_decision(p.name(), cost_sun);
                         tracing::info!(
                             provider = %attempt_res.provider,
                             order_id = attempt_res.order_id.as_deref().unwrap_or(""),
                             energy = shortfall,
+                            cost_sun,
+                            burn_sun,
                             "energy rental ok"
                         );
-                        *energy_rental_cursor =
-                            rental_cursor_after_attempts(start_cursor, len, attempt + 1);
+                        if lease.needs_release() {
+                            staked_lease = Some((p.clone(), lease));
+                        }
                         ok = true;
                         break;
                     }
-                    Ok(attempt_res) => {
+                    Ok(lease) => {
                         telemetry.energy_rental_ms(
-                            &attempt_res.provider,
+                            &lease.attempt.provider,
                             false,
                             rent_start.elapsed().as_millis() as u64,
                         );
                         tracing::warn!(
-                            provider = %attempt_res.provider,
-                            err = attempt_res.error.as_deref().unwrap_or("rental failed"),
+                            provider = %lease.attempt.provider,
+                            err = lease.attempt.error.as_deref().unwrap_or("rental failed"),
                             "energy rental failed; trying next provider"
                         );
-                        *energy_rental_cursor =
-                            rental_cursor_after_attempts(start_cursor, len, attempt + 1);
                     }
                     Err(err) => {
                         telemetry.energy_rental_ms(
@@ -612,26 +549,30 @@ async fn broadcast_trc20_transfer_single(
                             err = %err,
                             "energy rental errored; trying next provider"
                         );
-                        *energy_rental_cursor =
-                            rental_cursor_after_attempts(start_cursor, len, attempt + 1);
                     }
                 }
             }
 
             if !ok {
+                telemetry.energy_source_decision("burn", Some(burn_sun));
                 tracing::warn!(
                     energy = shortfall,
-                    "all energy rental providers failed; falling back to paying TRX fees"
+                    burn_sun,
+                    "no energy source covered the shortfall cheaper than burning; falling back to paying TRX fees"
                 );
             } else if !energy_rental_settle_delay.is_zero() {
                 let addr = wallet.address().prefixed_bytes().to_vec();
-                wait_for_energy_available_after_rental(
+                if let Err(err) = wait_for_energy_available_after_rental(
                     tron,
                     addr,
                     signed.energy_required,
                     energy_rental_settle_delay,
                 )
-                .await?;
+                .await
+                {
+                    release_staked_energy(tron, staked_lease, None);
+                    return Err(err);
+                }
             }
         } else if shortfall > 0 {
             tracing::info!(
@@ -642,8 +583,16 @@ async fn broadcast_trc20_transfer_single(
         }
     }
 
-    let ret = tron.broadcast_transaction(signed.tx).await?;
+    let ret = match tron.broadcast_transaction(signed.tx).await {
+        Ok(ret) => ret,
+        Err(err) => {
+            // The tx may still have reached the network; let the lease wait for it.
+            release_staked_energy(tron, staked_lease, Some(signed.txid));
+            return Err(err);
+        }
+    };
     if !ret.result {
+        release_staked_energy(tron, staked_lease, None);
         tracing::warn!(
             tron_return_code = ret.code,
             tron_return_message_hex = %format!("0x{}", hex::encode(&ret.message)),
@@ -657,6 +606,7 @@ async fn broadcast_trc20_transfer_single(
         );
     }
 
+    release_staked_energy(tron, staked_lease, Some(signed.txid));
     tracing::info!(
         txid = %hex::encode(signed.txid),
         fee_limit_sun = signed.fee_limit_sun,
@@ -667,8 +617,20 @@ async fn broadcast_trc20_transfer_single(
     Ok(signed.txid)
 }
 
+/// Hands a staked energy delegation back once `txid` confirms, or right away if the tx never
+/// went out. Rental leases need no release and never reach here.
+fn release_staked_energy(
+    tron: &TronClient,
+    lease: Option<(EnergySource, EnergyLease)>,
+    txid: Option<[u8; 32]>,
+) {
+    if let Some((source, lease)) = lease {
+        source.spawn_release(tron.clone(), lease, txid);
+    }
+}
+
 async fn wait_for_energy_available_after_rental(
-    tron: &mut TronGrpc,
+    tron: &mut TronClient,
     address: Vec<u8>,
     energy_required: u64,
     max_wait: Duration,
@@ -853,6 +815,107 @@ mod tests {
         assert!(!lower2.contains("authorization:"));
     }
 
+    fn mock_config(private_key: [u8; 32], usdt: TronAddress) -> AppConfig {
+        AppConfig {
+            tron: crate::config::TronConfig {
+                grpc_urls: vec!["down".to_string(), "up".to_string()],
+                api_key: None,
+                api_key_header: tron::DEFAULT_API_KEY_HEADER.to_string(),
+                endpoint_pool: Default::default(),
+                private_key,
+                usdt_contract_address: usdt.to_string(),
+                energy_rental_providers: Vec::new(),
+                energy_staker_private_key: None,
+                energy_rental_settle_delay: Duration::ZERO,
+            },
+            oneclick: crate::config::OneClickConfig {
+                base_url: "http://oneclick.invalid".to_string(),
+                bearer_token: None,
+                origin_asset: "origin".to_string(),
+                destination_asset: "destination".to_string(),
+                beneficiary: "beneficiary".to_string(),
+                slippage_bps: 100.0,
+                deadline_secs: 900,
+                referral: None,
+                status_poll_interval: Duration::from_secs(10),
+                status_max_wait: Duration::from_secs(60),
+                backoff_base: Duration::from_secs(60),
+                backoff_max: Duration::from_secs(3600),
+            },
+            jobs: crate::config::JobConfig {
+                poll_interval: Duration::from_secs(15),
+                usdt_balance_threshold: "0".to_string(),
+                usdt_balance_keep_usdt: "1".to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn broadcast_fails_over_to_a_healthy_mock_node() {
+        let down = tron_mock::MockTronNode::new();
+        down.produce_block();
+        down.set_unavailable(true);
+        let up = tron_mock::MockTronNode::new();
+        up.produce_block();
+        up.set_auto_produce(true);
+
+        let key = [0x11u8; 32];
+        let owner = TronWallet::new(key).unwrap().address();
+        let usdt = TronAddress::from_evm(Address::repeat_byte(0x22));
+        let deposit = TronAddress::from_evm(Address::repeat_byte(0x33));
+        up.set_account(
+            owner,
+            tron_mock::MockAccount {
+                balance_sun: 200_000_000,
+                ..Default::default()
+            },
+        );
+        up.set_trc20_balance(usdt, owner, U256::from(1_000u64));
+
+        let client = |node: &tron_mock::MockTronNode| {
+            TronClient::Grpc(tron_mock::MockTronServer::connect_in_memory(node).unwrap())
+        };
+        let tron = TronGrpcPool::from_clients(
+            vec![
+                ("down".to_string(), client(&down)),
+                ("up".to_string(), client(&up)),
+            ],
+            tron::TronGrpcPoolOptions {
+                hedge_after: None,
+                head_probe_interval: Duration::ZERO,
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+        let service =
+            PoolService::with_tron(mock_config(key, usdt), PoolTelemetry::new(), tron).unwrap();
+
+        let txid = service
+            .broadcast_trc20_transfer(usdt, deposit, U256::from(400u64))
+            .await
+            .unwrap();
+        assert!(
+            up.transaction_info(txid)
+                .is_some_and(|info| info.block_number > 0)
+        );
+        assert_eq!(up.trc20_balance(usdt, deposit), U256::from(400u64));
+        assert!(down.pending_txids().is_empty());
+        assert_eq!(service.tron.preferred_index(), 1);
+        assert_eq!(service.trc20_balance().await.unwrap(), U256::from(600u64));
+
+        // A call that would revert is refused before anything is broadcast.
+        up.set_revert(usdt, "paused");
+        assert!(
+            service
+                .broadcast_trc20_transfer(usdt, deposit, U256::from(1u64))
+                .await
+                .is_err()
+        );
+        assert_eq!(up.trc20_balance(usdt, deposit), U256::from(400u64));
+        assert!(up.pending_txids().is_empty());
+    }
+
     #[test]
     fn encode_trc20_transfer_layout() {
         let to = Address::from_slice(&[0x11u8; 20]);
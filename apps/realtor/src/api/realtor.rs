@@ -321,12 +321,12 @@ pub async fn post_realtor(
         let data = call.abi_encode();
 
         let t_lock = Instant::now();
-        let mut sender = state.sender.lock().await;
+        let sender = state.sender.lock().await;
         tracing::info!(ms = t_lock.elapsed().as_millis() as u64, "post_realtor: acquired sender lock");
 
         let t_userop = Instant::now();
         let (userop_hash, nonce, send_attempts) = send_userop(
-            &mut sender,
+            &sender,
             state.cfg.hub.untron_v3,
             data,
             state.cfg.hub.bundler_timeout,
@@ -3,7 +3,7 @@ use aa::Safe4337UserOpSender;
 use alloy::primitives::Address;
 
 pub(super) async fn send_userop(
-    sender: &mut Safe4337UserOpSender,
+    sender: &Safe4337UserOpSender,
     to: Address,
     data: Vec<u8>,
     timeout: std::time::Duration,
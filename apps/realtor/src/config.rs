@@ -381,6 +381,8 @@ pub fn load_config() -> Result<AppConfig> {
                 &env.hub_safe_module_setup_address,
             )?,
             salt_nonce: alloy::primitives::U256::ZERO,
+            owners: Vec::new(),
+            threshold: 1,
         })
     };
     let hub_owner_private_key =
@@ -11,8 +11,8 @@ use crate::indexer::IndexerApi;
 use crate::metrics::RealtorTelemetry;
 use aa::paymaster::PaymasterService;
 use aa::{
-    PaymasterFinalizationMode, Safe4337UserOpSender, Safe4337UserOpSenderConfig,
-    Safe4337UserOpSenderOptions,
+    EntryPointVersion, GasEstimationOptions, PaymasterFinalizationMode, Safe4337UserOpSender,
+    Safe4337UserOpSenderConfig, Safe4337UserOpSenderOptions, SmartAccountKind,
 };
 use alloy::primitives::{Address, B256};
 use alloy::providers::{DynProvider, Provider, ProviderBuilder};
@@ -64,11 +64,14 @@ async fn main() -> anyhow::Result<()> {
         rpc_url: cfg.hub.rpc_url.clone(),
         chain_id: cfg.hub.chain_id,
         entrypoint: cfg.hub.entrypoint,
+        entrypoint_version: EntryPointVersion::V07,
+        account: SmartAccountKind::Safe4337,
         safe: cfg.hub.safe,
         safe_4337_module: cfg.hub.safe_4337_module,
         safe_deployment: cfg.hub.safe_deployment.clone(),
         bundler_urls: cfg.hub.bundler_urls.clone(),
         owner_private_key: cfg.hub.owner_private_key,
+        cosigners: Vec::new(),
         paymasters: cfg
             .hub
             .paymasters
@@ -81,6 +84,7 @@ async fn main() -> anyhow::Result<()> {
         options: Safe4337UserOpSenderOptions {
             check_bundler_entrypoints: false,
             paymaster_finalization: PaymasterFinalizationMode::AlwaysFetchFinal,
+            gas: GasEstimationOptions::default(),
         },
     };
     let sender = Safe4337UserOpSender::new(sender_cfg).await?;
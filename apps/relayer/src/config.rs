@@ -1,9 +1,12 @@
-use aa::SafeDeterministicDeploymentConfig;
+use aa::{
+    EntryPointVersion, GasEstimationOptions, SafeDeterministicDeploymentConfig, SmartAccountKind,
+    UserOpReplacementOptions,
+};
 use alloy::primitives::{Address, U256};
 use anyhow::{Context, Result};
 use serde::Deserialize;
-use std::time::Duration;
-use tron::{JsonApiRentalProviderConfig, TronAddress};
+use std::{path::PathBuf, str::FromStr, time::Duration};
+use tron::{JsonApiRentalProviderConfig, TronAddress, TronGrpcPoolOptions, TronHeaderProfile};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct PaymasterServiceConfig {
@@ -12,6 +15,15 @@ pub struct PaymasterServiceConfig {
     pub context: serde_json::Value,
 }
 
+/// Remote Safe owner that co-signs SafeOps over HTTP (see `aa::HttpSafeOpSigner`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct SafeCosignerConfig {
+    pub url: String,
+    pub owner: Address,
+    #[serde(default = "default_safe_cosigner_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub indexer: IndexerConfig,
@@ -34,6 +46,8 @@ pub struct HubConfig {
     pub untron_v3: Address,
 
     pub entrypoint: Address,
+    pub entrypoint_version: EntryPointVersion,
+    pub account: SmartAccountKind,
     pub safe: Option<Address>,
     pub safe_4337_module: Address,
     pub safe_deployment: Option<SafeDeterministicDeploymentConfig>,
@@ -45,6 +59,12 @@ pub struct HubConfig {
     pub direct_tx_private_key: Option<[u8; 32]>,
 
     pub paymasters: Vec<PaymasterServiceConfig>,
+    /// Extra Safe owners asked to sign when the Safe's threshold is above 1.
+    pub cosigners: Vec<SafeCosignerConfig>,
+    pub userop_gas: GasEstimationOptions,
+    pub userop_replacement: UserOpReplacementOptions,
+    /// Submit each job kind's userops on its own ERC-4337 nonce key.
+    pub nonce_lanes: bool,
 
     pub uniswap_v4: Option<UniswapV4Config>,
 }
@@ -71,15 +91,22 @@ pub struct UniswapV4AllowedPool {
 
 #[derive(Debug, Clone)]
 pub struct TronConfig {
+    /// Node endpoints in failover order. Plain URLs are gRPC; `rest+https://host` selects the
+    /// `/wallet/*` HTTP API (see `tron::HTTP_URL_PREFIX`). Proofs are only built over gRPC, so
+    /// at least one must be gRPC.
     pub grpc_urls: Vec<String>,
     pub api_key: Option<String>,
     /// Metadata header name to send the API key under. Default `tron-pro-api-key` (TronGrid /
     /// java-tron). QuickNode expects `x-token`.
     pub api_key_header: String,
+    /// Endpoint health scoring, head-lag detection and read hedging across `grpc_urls`.
+    pub endpoint_pool: TronGrpcPoolOptions,
     pub private_key: [u8; 32],
     pub controller_address: String,
 
     pub block_lag: u64,
+    /// Header varint layout the hub's `StatefulTronTxReader` deployment was built for.
+    pub header_profile: TronHeaderProfile,
     /// Plan-side spacing (in Tron blocks) between consecutive `pull_from_receivers` plans.
     /// Sized to cover the indexer's typical ingest lag: a successful pull lands in a Tron
     /// block, but receivers' balances and projected_demand don't reflect it until the
@@ -92,8 +119,14 @@ pub struct TronConfig {
     pub pull_from_receivers_energy_limit: u64,
     /// Optional list of external energy rental providers.
     pub energy_rental_providers: Vec<JsonApiRentalProviderConfig>,
+    /// Optional Stake 2.0 account whose staked energy is delegated to `private_key`'s address
+    /// per tx and reclaimed once the tx confirms. Ranked alongside rental providers by cost.
+    pub energy_staker_private_key: Option<[u8; 32]>,
     /// Max time to poll Tron until rented energy is reflected in AccountResource.
     pub energy_rental_confirm_max_wait: Duration,
+    /// Optional file persisting the last 24h of rental orders, so the rental budget survives
+    /// restarts. Unset keeps the history in memory only.
+    pub energy_rental_history_path: Option<PathBuf>,
     /// Headroom (parts-per-million) on top of the energy-based fee_limit quote. 100_000 = +10%.
     pub fee_limit_headroom_ppm: u64,
     /// Hard ceiling on the computed fee_limit (sun), regardless of quote.
@@ -102,9 +135,9 @@ pub struct TronConfig {
     /// to rent (rounded up to the provider minimum), and if every provider fails the broadcast
     /// is aborted instead of burning wallet TRX.
     pub require_energy_rental: bool,
-    /// Layer 3 breaker: max rental attempts per hour. 0 disables the cap.
+    /// Layer 3 breaker: max paid rental orders per hour (failed/refunded excluded). 0 disables.
     pub rental_cap_per_hour: u32,
-    /// Layer 3 breaker: max rental attempts per day. 0 disables the cap.
+    /// Layer 3 breaker: max paid rental orders per day (failed/refunded excluded). 0 disables.
     pub rental_cap_per_day: u32,
     /// Cooldown after tripping any rental cap. Zero disables the cooldown path (not recommended).
     pub rental_cooldown_after_trip: Duration,
@@ -158,6 +191,18 @@ struct Env {
 
     hub_entrypoint_address: String,
 
+    // "0.7" (default) or "0.8".
+    #[serde(default)]
+    hub_entrypoint_version: String,
+
+    // "safe4337" (default) or "eip7702" (send from the owner EOA delegated to
+    // HUB_EIP7702_DELEGATE_ADDRESS; needs EntryPoint v0.8).
+    #[serde(default)]
+    hub_account_kind: String,
+
+    #[serde(default)]
+    hub_eip7702_delegate_address: String,
+
     hub_safe_address: String,
 
     hub_safe_4337_module_address: String,
@@ -174,6 +219,15 @@ struct Env {
     #[serde(default)]
     hub_safe_module_setup_address: String,
 
+    // Owners and threshold for a newly deployed Safe; empty owners = the hub owner key alone.
+    #[serde(default)]
+    hub_safe_owners: String,
+
+    hub_safe_threshold: u64,
+
+    #[serde(default)]
+    hub_safe_cosigners_json: String,
+
     hub_owner_private_key_hex: String,
 
     #[serde(default)]
@@ -185,6 +239,35 @@ struct Env {
     #[serde(default)]
     hub_paymasters_json: String,
 
+    // Percent applied to each bundler gas estimate (110 = +10%).
+    hub_userop_call_gas_multiplier_pct: u64,
+
+    hub_userop_verification_gas_multiplier_pct: u64,
+
+    hub_userop_pre_verification_gas_multiplier_pct: u64,
+
+    // Optional per-field caps; a userop whose estimate exceeds one is not sent.
+    hub_userop_max_call_gas: Option<u64>,
+
+    hub_userop_max_verification_gas: Option<u64>,
+
+    hub_userop_max_pre_verification_gas: Option<u64>,
+
+    // Estimate gas locally (eth_estimateGas + calldata and Arbitrum L1 pricing) when every bundler
+    // fails to. Unavailable on OP-stack chains.
+    hub_userop_local_gas_fallback: bool,
+
+    // Resubmit a pending userop's nonce with bumped fees after it goes this long unmined (0 = never).
+    hub_userop_replace_after_secs: u64,
+
+    hub_userop_replace_bump_pct: u64,
+
+    hub_userop_max_replacements: u32,
+
+    // Give each hub job kind its own ERC-4337 nonce key so their userops are sequenced
+    // independently and can be in flight at the same time.
+    hub_nonce_lanes: bool,
+
     #[serde(default)]
     uniswap_v4_pool_manager_address: String,
 
@@ -214,12 +297,27 @@ struct Env {
     #[serde(default = "default_tron_api_key_header")]
     tron_api_key_header: String,
 
+    #[serde(default = "default_tron_read_timeout_ms")]
+    tron_read_timeout_ms: u64,
+
+    #[serde(default = "default_tron_read_hedge_after_ms")]
+    tron_read_hedge_after_ms: u64,
+
+    #[serde(default = "default_tron_max_head_lag_blocks")]
+    tron_max_head_lag_blocks: u64,
+
+    #[serde(default = "default_tron_head_probe_interval_secs")]
+    tron_head_probe_interval_secs: u64,
+
     tron_private_key_hex: String,
 
     tron_controller_address: String,
 
     tron_block_lag: u64,
 
+    #[serde(default = "default_tron_header_profile")]
+    tron_header_profile: String,
+
     #[serde(default = "default_tron_pull_plan_lag_blocks")]
     tron_pull_plan_lag_blocks: u64,
 
@@ -229,9 +327,15 @@ struct Env {
     #[serde(default)]
     tron_energy_rental_apis_json: String,
 
+    #[serde(default)]
+    tron_energy_staker_private_key_hex: String,
+
     #[serde(default = "default_tron_energy_rental_confirm_max_wait_secs")]
     tron_energy_rental_confirm_max_wait_secs: u64,
 
+    #[serde(default)]
+    tron_energy_rental_history_path: String,
+
     #[serde(default = "default_tron_fee_limit_headroom_ppm")]
     tron_fee_limit_headroom_ppm: u64,
 
@@ -294,16 +398,33 @@ impl Default for Env {
             hub_chain_id: None,
             hub_untron_v3_address: String::new(),
             hub_entrypoint_address: String::new(),
+            hub_entrypoint_version: String::new(),
+            hub_account_kind: String::new(),
+            hub_eip7702_delegate_address: String::new(),
             hub_safe_address: String::new(),
             hub_safe_4337_module_address: String::new(),
             hub_multisend_address: String::new(),
             hub_safe_proxy_factory_address: String::new(),
             hub_safe_singleton_address: String::new(),
             hub_safe_module_setup_address: String::new(),
+            hub_safe_owners: String::new(),
+            hub_safe_threshold: 1,
+            hub_safe_cosigners_json: String::new(),
             hub_owner_private_key_hex: String::new(),
             hub_direct_tx_private_key_hex: String::new(),
             hub_bundler_urls: String::new(),
             hub_paymasters_json: String::new(),
+            hub_userop_call_gas_multiplier_pct: 110,
+            hub_userop_verification_gas_multiplier_pct: 110,
+            hub_userop_pre_verification_gas_multiplier_pct: 110,
+            hub_userop_max_call_gas: None,
+            hub_userop_max_verification_gas: None,
+            hub_userop_max_pre_verification_gas: None,
+            hub_userop_local_gas_fallback: true,
+            hub_userop_replace_after_secs: 180,
+            hub_userop_replace_bump_pct: 20,
+            hub_userop_max_replacements: 3,
+            hub_nonce_lanes: false,
             uniswap_v4_pool_manager_address: String::new(),
             uniswap_v4_swap_router_address: String::new(),
             uniswap_v4_position_manager_address: String::new(),
@@ -314,13 +435,20 @@ impl Default for Env {
             tron_grpc_urls: String::new(),
             tron_api_key: None,
             tron_api_key_header: default_tron_api_key_header(),
+            tron_read_timeout_ms: default_tron_read_timeout_ms(),
+            tron_read_hedge_after_ms: default_tron_read_hedge_after_ms(),
+            tron_max_head_lag_blocks: default_tron_max_head_lag_blocks(),
+            tron_head_probe_interval_secs: default_tron_head_probe_interval_secs(),
             tron_private_key_hex: String::new(),
             tron_controller_address: String::new(),
             tron_block_lag: 0,
+            tron_header_profile: default_tron_header_profile(),
             tron_pull_plan_lag_blocks: default_tron_pull_plan_lag_blocks(),
             tron_pull_from_receivers_energy_limit: default_tron_pull_from_receivers_energy_limit(),
             tron_energy_rental_apis_json: String::new(),
+            tron_energy_staker_private_key_hex: String::new(),
             tron_energy_rental_confirm_max_wait_secs: 6,
+            tron_energy_rental_history_path: String::new(),
             tron_fee_limit_headroom_ppm: default_tron_fee_limit_headroom_ppm(),
             tron_fee_limit_ceiling_sun: default_tron_fee_limit_ceiling_sun(),
             tron_require_energy_rental: false,
@@ -368,6 +496,28 @@ fn default_tron_api_key_header() -> String {
     tron::DEFAULT_API_KEY_HEADER.to_string()
 }
 
+fn default_tron_read_timeout_ms() -> u64 {
+    // Bounds a whole read op, and proof builds fetch ~20 blocks per tx.
+    30_000
+}
+
+fn default_tron_read_hedge_after_ms() -> u64 {
+    2_000
+}
+
+fn default_tron_max_head_lag_blocks() -> u64 {
+    // ~1 minute of Tron blocks.
+    20
+}
+
+fn default_tron_head_probe_interval_secs() -> u64 {
+    30
+}
+
+fn default_tron_header_profile() -> String {
+    "mainnet".to_string()
+}
+
 fn default_tron_fee_limit_headroom_ppm() -> u64 {
     // +10% on top of the energy-based quote.
     100_000
@@ -409,6 +559,10 @@ fn default_tron_tx_cap_per_kind_per_hour() -> u32 {
     3
 }
 
+fn default_safe_cosigner_timeout_secs() -> u64 {
+    10
+}
+
 fn parse_address(label: &str, s: &str) -> Result<Address> {
     s.parse::<Address>()
         .with_context(|| format!("invalid {label}: {s}"))
@@ -535,6 +689,26 @@ fn parse_paymasters_json(s: &str) -> Result<Vec<PaymasterServiceConfig>> {
     Ok(v)
 }
 
+fn parse_safe_cosigners_json(s: &str) -> Result<Vec<SafeCosignerConfig>> {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut v: Vec<SafeCosignerConfig> =
+        serde_json::from_str(trimmed).context("parse HUB_SAFE_COSIGNERS_JSON")?;
+    for c in &mut v {
+        c.url = c.url.trim().to_string();
+        if c.url.is_empty() {
+            anyhow::bail!("HUB_SAFE_COSIGNERS_JSON contains an empty url");
+        }
+        if c.owner == Address::ZERO {
+            anyhow::bail!("HUB_SAFE_COSIGNERS_JSON cosigner.owner must be set");
+        }
+        c.timeout_secs = c.timeout_secs.max(1);
+    }
+    Ok(v)
+}
+
 fn parse_tron_energy_rental_apis_json(s: &str) -> Result<Vec<JsonApiRentalProviderConfig>> {
     let trimmed = s.trim();
     if trimmed.is_empty() {
@@ -641,6 +815,15 @@ pub fn load_config() -> Result<AppConfig> {
     if tron_grpc_urls.is_empty() {
         anyhow::bail!("TRON_GRPC_URLS (or TRON_GRPC_URL) must be set");
     }
+    if tron_grpc_urls
+        .iter()
+        .all(|url| url.starts_with(tron::HTTP_URL_PREFIX))
+    {
+        anyhow::bail!(
+            "TRON_GRPC_URLS needs at least one gRPC endpoint: Tron proofs can't be built over the {}… HTTP API",
+            tron::HTTP_URL_PREFIX
+        );
+    }
     if env.tron_private_key_hex.trim().is_empty() {
         anyhow::bail!("TRON_PRIVATE_KEY_HEX must be set");
     }
@@ -650,14 +833,48 @@ pub fn load_config() -> Result<AppConfig> {
 
     let hub_untron_v3 = parse_address("HUB_UNTRON_V3_ADDRESS", &env.hub_untron_v3_address)?;
     let hub_entrypoint = parse_address("HUB_ENTRYPOINT_ADDRESS", &env.hub_entrypoint_address)?;
+    let hub_entrypoint_version = if env.hub_entrypoint_version.trim().is_empty() {
+        EntryPointVersion::V07
+    } else {
+        env.hub_entrypoint_version
+            .parse()
+            .context("parse HUB_ENTRYPOINT_VERSION")?
+    };
+    let hub_account = match env.hub_account_kind.trim().to_ascii_lowercase().as_str() {
+        "" | "safe" | "safe4337" => SmartAccountKind::Safe4337,
+        "eip7702" | "7702" => SmartAccountKind::Eip7702 {
+            delegate: parse_address(
+                "HUB_EIP7702_DELEGATE_ADDRESS",
+                &env.hub_eip7702_delegate_address,
+            )?,
+        },
+        other => {
+            anyhow::bail!("unsupported HUB_ACCOUNT_KIND: {other} (expected safe4337 or eip7702)")
+        }
+    };
+    if matches!(hub_account, SmartAccountKind::Eip7702 { .. })
+        && hub_entrypoint_version != EntryPointVersion::V08
+    {
+        anyhow::bail!("HUB_ACCOUNT_KIND=eip7702 requires HUB_ENTRYPOINT_VERSION=0.8");
+    }
+    if hub_account == SmartAccountKind::Safe4337 && hub_entrypoint_version != EntryPointVersion::V07
+    {
+        anyhow::bail!("HUB_ACCOUNT_KIND=safe4337 requires HUB_ENTRYPOINT_VERSION=0.7");
+    }
+    let is_safe_account = hub_account == SmartAccountKind::Safe4337;
+
     let hub_safe = parse_optional_address("HUB_SAFE_ADDRESS", &env.hub_safe_address)?;
-    let hub_module = parse_address(
-        "HUB_SAFE_4337_MODULE_ADDRESS",
-        &env.hub_safe_4337_module_address,
-    )?;
+    let hub_module = if is_safe_account {
+        parse_address(
+            "HUB_SAFE_4337_MODULE_ADDRESS",
+            &env.hub_safe_4337_module_address,
+        )?
+    } else {
+        Address::ZERO
+    };
     let hub_multisend =
         parse_optional_address("HUB_MULTISEND_ADDRESS", &env.hub_multisend_address)?;
-    let hub_safe_deployment = if hub_safe.is_some() {
+    let hub_safe_deployment = if hub_safe.is_some() || !is_safe_account {
         None
     } else {
         Some(SafeDeterministicDeploymentConfig {
@@ -674,6 +891,11 @@ pub fn load_config() -> Result<AppConfig> {
                 &env.hub_safe_module_setup_address,
             )?,
             salt_nonce: alloy::primitives::U256::ZERO,
+            owners: parse_csv_optional(&env.hub_safe_owners)
+                .iter()
+                .map(|v| parse_address("HUB_SAFE_OWNERS", v))
+                .collect::<Result<_>>()?,
+            threshold: env.hub_safe_threshold,
         })
     };
     let hub_owner_private_key =
@@ -686,6 +908,23 @@ pub fn load_config() -> Result<AppConfig> {
 
     let bundlers = parse_csv("HUB_BUNDLER_URLS", &env.hub_bundler_urls)?;
     let paymasters = parse_paymasters_json(&env.hub_paymasters_json)?;
+    let cosigners = parse_safe_cosigners_json(&env.hub_safe_cosigners_json)?;
+    let userop_gas = GasEstimationOptions {
+        call_gas_pct: env.hub_userop_call_gas_multiplier_pct.max(100),
+        verification_gas_pct: env.hub_userop_verification_gas_multiplier_pct.max(100),
+        pre_verification_gas_pct: env.hub_userop_pre_verification_gas_multiplier_pct.max(100),
+        max_call_gas: env.hub_userop_max_call_gas,
+        max_verification_gas: env.hub_userop_max_verification_gas,
+        max_pre_verification_gas: env.hub_userop_max_pre_verification_gas,
+        local_fallback: env.hub_userop_local_gas_fallback,
+        ..GasEstimationOptions::default()
+    };
+    let userop_replacement = UserOpReplacementOptions {
+        stall_timeout: (env.hub_userop_replace_after_secs > 0)
+            .then(|| Duration::from_secs(env.hub_userop_replace_after_secs)),
+        bump_pct: env.hub_userop_replace_bump_pct,
+        max_replacements: env.hub_userop_max_replacements,
+    };
 
     let allowed_v4_pools = parse_uniswap_v4_allowed_pools_json(&env.uniswap_v4_allowed_pools_json)?;
     let uniswap_v4 = if allowed_v4_pools.is_empty() {
@@ -723,6 +962,8 @@ pub fn load_config() -> Result<AppConfig> {
             chain_id: env.hub_chain_id,
             untron_v3: hub_untron_v3,
             entrypoint: hub_entrypoint,
+            entrypoint_version: hub_entrypoint_version,
+            account: hub_account,
             safe: hub_safe,
             safe_4337_module: hub_module,
             safe_deployment: hub_safe_deployment,
@@ -731,6 +972,10 @@ pub fn load_config() -> Result<AppConfig> {
             owner_private_key: hub_owner_private_key,
             direct_tx_private_key: hub_direct_tx_private_key,
             paymasters,
+            cosigners,
+            userop_gas,
+            userop_replacement,
+            nonce_lanes: env.hub_nonce_lanes,
             uniswap_v4,
         },
         tron: TronConfig {
@@ -740,17 +985,33 @@ pub fn load_config() -> Result<AppConfig> {
                 let h = env.tron_api_key_header.trim();
                 if h.is_empty() { default_tron_api_key_header() } else { h.to_string() }
             },
+            endpoint_pool: TronGrpcPoolOptions {
+                per_try_timeout: Duration::from_millis(env.tron_read_timeout_ms.max(1)),
+                hedge_after: (env.tron_read_hedge_after_ms > 0)
+                    .then(|| Duration::from_millis(env.tron_read_hedge_after_ms)),
+                max_head_lag_blocks: env.tron_max_head_lag_blocks,
+                head_probe_interval: Duration::from_secs(env.tron_head_probe_interval_secs),
+            },
             private_key: parse_hex_32("TRON_PRIVATE_KEY_HEX", &env.tron_private_key_hex)?,
             controller_address: env.tron_controller_address,
             block_lag: env.tron_block_lag,
+            header_profile: TronHeaderProfile::from_str(&env.tron_header_profile)
+                .context("invalid TRON_HEADER_PROFILE")?,
             pull_plan_lag_blocks: env.tron_pull_plan_lag_blocks,
             pull_from_receivers_energy_limit: env.tron_pull_from_receivers_energy_limit,
             energy_rental_providers: parse_tron_energy_rental_apis_json(
                 &env.tron_energy_rental_apis_json,
             )?,
+            energy_staker_private_key: parse_optional_hex_32(
+                "TRON_ENERGY_STAKER_PRIVATE_KEY_HEX",
+                &env.tron_energy_staker_private_key_hex,
+            )?,
             energy_rental_confirm_max_wait: Duration::from_secs(
                 env.tron_energy_rental_confirm_max_wait_secs,
             ),
+            energy_rental_history_path: Some(env.tron_energy_rental_history_path.trim())
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from),
             fee_limit_headroom_ppm: env.tron_fee_limit_headroom_ppm,
             fee_limit_ceiling_sun: env.tron_fee_limit_ceiling_sun,
             require_energy_rental: env.tron_require_energy_rental,
@@ -851,6 +1112,24 @@ mod tests {
         assert!(err.contains("must be a JSON object"));
     }
 
+    #[test]
+    fn parse_safe_cosigners_json_validates_url_and_owner() {
+        assert!(parse_safe_cosigners_json("   ").unwrap().is_empty());
+
+        let ok = r#"[{"url":" https://cosigner.example ","owner":"0x0000000000000000000000000000000000000001"}]"#;
+        let v = parse_safe_cosigners_json(ok).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].url, "https://cosigner.example");
+        assert_eq!(v[0].timeout_secs, 10);
+
+        let err = parse_safe_cosigners_json(
+            r#"[{"url":"x","owner":"0x0000000000000000000000000000000000000000"}]"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("owner must be set"));
+    }
+
     #[test]
     fn parse_tron_energy_rental_apis_json_empty_ok() {
         assert!(parse_tron_energy_rental_apis_json("   ")
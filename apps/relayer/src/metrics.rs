@@ -3,6 +3,7 @@ use opentelemetry::{
     metrics::{Counter, Histogram},
 };
 use std::sync::Arc;
+use tron::{TronAttemptStatus, TronPoolObserver};
 
 #[derive(Clone)]
 pub struct RelayerTelemetry {
@@ -14,17 +15,33 @@ struct Inner {
     job_errors_total: Counter<u64>,
     hub_userops_total: Counter<u64>,
     hub_userop_errors_total: Counter<u64>,
+    hub_userop_replacements_total: Counter<u64>,
     tron_txs_total: Counter<u64>,
     tron_tx_errors_total: Counter<u64>,
+    tron_endpoint_attempts_total: Counter<u64>,
+    tron_endpoint_switches_total: Counter<u64>,
+    tron_endpoint_all_failed_total: Counter<u64>,
+    tron_endpoint_hedges_total: Counter<u64>,
 
     job_ms: Histogram<u64>,
     hub_submit_ms: Histogram<u64>,
     tron_broadcast_ms: Histogram<u64>,
     indexer_http_ms: Histogram<u64>,
     hub_rpc_ms: Histogram<u64>,
+    hub_userop_gas_limit: Histogram<u64>,
+    hub_userop_gas_used: Histogram<u64>,
+    hub_userop_gas_used_pct: Histogram<u64>,
+    hub_userop_gas_estimate: Histogram<u64>,
+    hub_userop_gas_used_of_estimate_pct: Histogram<u64>,
     tron_proof_ms: Histogram<u64>,
     receiver_usdt_tail_lag_blocks: Histogram<u64>,
     indexer_stream_head_lag_blocks: Histogram<u64>,
+    tron_endpoint_attempt_ms: Histogram<u64>,
+    tron_endpoint_head_lag_blocks: Histogram<u64>,
+
+    energy_source_decisions_total: Counter<u64>,
+    energy_quote_sun: Histogram<u64>,
+    energy_cost_sun: Histogram<u64>,
 }
 
 impl RelayerTelemetry {
@@ -47,6 +64,10 @@ impl RelayerTelemetry {
             .u64_counter("relayer.hub_userop_errors_total")
             .with_description("Total hub user operation submission errors")
             .build();
+        let hub_userop_replacements_total = meter
+            .u64_counter("relayer.hub_userop_replacements_total")
+            .with_description("Total fee-bumped replacements of stalled hub user operations")
+            .build();
         let tron_txs_total = meter
             .u64_counter("relayer.tron_txs_total")
             .with_description("Total Tron transactions broadcast")
@@ -56,6 +77,23 @@ impl RelayerTelemetry {
             .with_description("Total Tron transaction errors")
             .build();
 
+        let tron_endpoint_attempts_total = meter
+            .u64_counter("relayer.tron_endpoint_attempts_total")
+            .with_description("Total Tron read attempts per endpoint")
+            .build();
+        let tron_endpoint_switches_total = meter
+            .u64_counter("relayer.tron_endpoint_switches_total")
+            .with_description("Total Tron preferred-endpoint switches")
+            .build();
+        let tron_endpoint_all_failed_total = meter
+            .u64_counter("relayer.tron_endpoint_all_failed_total")
+            .with_description("Total Tron reads that failed on every endpoint")
+            .build();
+        let tron_endpoint_hedges_total = meter
+            .u64_counter("relayer.tron_endpoint_hedges_total")
+            .with_description("Total Tron reads hedged onto a second endpoint")
+            .build();
+
         let job_ms = meter
             .u64_histogram("relayer.job_ms")
             .with_description("Per-job runtime")
@@ -86,6 +124,36 @@ impl RelayerTelemetry {
             .with_unit("ms")
             .build();
 
+        let hub_userop_gas_limit = meter
+            .u64_histogram("relayer.hub_userop_gas_limit")
+            .with_description("Total gas limits of mined hub userops")
+            .with_unit("gas")
+            .build();
+
+        let hub_userop_gas_used = meter
+            .u64_histogram("relayer.hub_userop_gas_used")
+            .with_description("actualGasUsed of mined hub userops")
+            .with_unit("gas")
+            .build();
+
+        let hub_userop_gas_used_pct = meter
+            .u64_histogram("relayer.hub_userop_gas_used_pct")
+            .with_description("actualGasUsed as a percentage of the userop's total gas limits")
+            .with_unit("%")
+            .build();
+
+        let hub_userop_gas_estimate = meter
+            .u64_histogram("relayer.hub_userop_gas_estimate")
+            .with_description("Raw gas estimates of mined hub userops, before multipliers")
+            .with_unit("gas")
+            .build();
+
+        let hub_userop_gas_used_of_estimate_pct = meter
+            .u64_histogram("relayer.hub_userop_gas_used_of_estimate_pct")
+            .with_description("actualGasUsed as a percentage of the userop's raw gas estimate")
+            .with_unit("%")
+            .build();
+
         let tron_proof_ms = meter
             .u64_histogram("relayer.tron_proof_ms")
             .with_description("Tron proof build runtime")
@@ -104,22 +172,64 @@ impl RelayerTelemetry {
             .with_unit("blocks")
             .build();
 
+        let tron_endpoint_attempt_ms = meter
+            .u64_histogram("relayer.tron_endpoint_attempt_ms")
+            .with_description("Tron read attempt runtime per endpoint")
+            .with_unit("ms")
+            .build();
+
+        let tron_endpoint_head_lag_blocks = meter
+            .u64_histogram("relayer.tron_endpoint_head_lag_blocks")
+            .with_description("Tron endpoint head lag behind the best endpoint")
+            .with_unit("blocks")
+            .build();
+
+        let energy_source_decisions_total = meter
+            .u64_counter("relayer.energy_source_decisions_total")
+            .with_description("Total energy source decisions (rental provider or TRX burn)")
+            .build();
+        let energy_quote_sun = meter
+            .u64_histogram("relayer.energy_quote_sun")
+            .with_description("Energy rental price quoted per provider")
+            .with_unit("sun")
+            .build();
+        let energy_cost_sun = meter
+            .u64_histogram("relayer.energy_cost_sun")
+            .with_description("Cost of the chosen energy source")
+            .with_unit("sun")
+            .build();
+
         Self {
             inner: Arc::new(Inner {
                 jobs_total,
                 job_errors_total,
                 hub_userops_total,
                 hub_userop_errors_total,
+                hub_userop_replacements_total,
                 tron_txs_total,
                 tron_tx_errors_total,
+                tron_endpoint_attempts_total,
+                tron_endpoint_switches_total,
+                tron_endpoint_all_failed_total,
+                tron_endpoint_hedges_total,
                 job_ms,
                 hub_submit_ms,
                 tron_broadcast_ms,
                 indexer_http_ms,
                 hub_rpc_ms,
+                hub_userop_gas_limit,
+                hub_userop_gas_used,
+                hub_userop_gas_used_pct,
+                hub_userop_gas_estimate,
+                hub_userop_gas_used_of_estimate_pct,
                 tron_proof_ms,
                 receiver_usdt_tail_lag_blocks,
                 indexer_stream_head_lag_blocks,
+                tron_endpoint_attempt_ms,
+                tron_endpoint_head_lag_blocks,
+                energy_source_decisions_total,
+                energy_quote_sun,
+                energy_cost_sun,
             }),
         }
     }
@@ -147,6 +257,28 @@ impl RelayerTelemetry {
         self.inner.hub_userop_errors_total.add(1, &[]);
     }
 
+    pub fn hub_userop_replaced(&self, ok: bool) {
+        let attrs = [KeyValue::new("status", if ok { "ok" } else { "err" })];
+        self.inner.hub_userop_replacements_total.add(1, &attrs);
+    }
+
+    pub fn hub_userop_gas(&self, source: &'static str, estimate: u64, limit: u64, used: u64) {
+        let attrs = [KeyValue::new("source", source)];
+        self.inner.hub_userop_gas_estimate.record(estimate, &attrs);
+        self.inner.hub_userop_gas_limit.record(limit, &attrs);
+        self.inner.hub_userop_gas_used.record(used, &attrs);
+        if limit > 0 {
+            self.inner
+                .hub_userop_gas_used_pct
+                .record(used.saturating_mul(100) / limit, &attrs);
+        }
+        if estimate > 0 {
+            self.inner
+                .hub_userop_gas_used_of_estimate_pct
+                .record(used.saturating_mul(100) / estimate, &attrs);
+        }
+    }
+
     pub fn tron_tx_ok(&self) {
         self.inner.tron_txs_total.add(1, &[]);
     }
@@ -201,4 +333,72 @@ impl RelayerTelemetry {
             .indexer_stream_head_lag_blocks
             .record(lag_blocks, &attrs);
     }
+
+    pub fn energy_quote_sun(&self, source: &str, sun: u64) {
+        self.inner
+            .energy_quote_sun
+            .record(sun, &[KeyValue::new("source", source.to_string())]);
+    }
+
+    /// `source` is a rental provider name, or `"burn"` when paying for energy with TRX.
+    pub fn energy_source_decision(&self, source: &str, cost_sun: Option<u64>) {
+        let attrs = [KeyValue::new("source", source.to_string())];
+        self.inner.energy_source_decisions_total.add(1, &attrs);
+        if let Some(cost_sun) = cost_sun {
+            self.inner.energy_cost_sun.record(cost_sun, &attrs);
+        }
+    }
+}
+
+impl TronPoolObserver for RelayerTelemetry {
+    fn on_attempt(&self, op: &str, endpoint_idx: usize, status: TronAttemptStatus, ms: u64) {
+        let status = match status {
+            TronAttemptStatus::Ok => "ok",
+            TronAttemptStatus::Err => "err",
+            TronAttemptStatus::Timeout => "timeout",
+        };
+        let attrs = [
+            KeyValue::new("op", op.to_string()),
+            KeyValue::new(
+                "endpoint_idx",
+                i64::try_from(endpoint_idx).unwrap_or_default(),
+            ),
+            KeyValue::new("status", status),
+        ];
+        self.inner.tron_endpoint_attempts_total.add(1, &attrs);
+        self.inner.tron_endpoint_attempt_ms.record(ms, &attrs);
+    }
+
+    fn on_switch(&self, op: &str, from_idx: usize, to_idx: usize) {
+        let attrs = [
+            KeyValue::new("op", op.to_string()),
+            KeyValue::new("from_idx", i64::try_from(from_idx).unwrap_or_default()),
+            KeyValue::new("to_idx", i64::try_from(to_idx).unwrap_or_default()),
+        ];
+        self.inner.tron_endpoint_switches_total.add(1, &attrs);
+    }
+
+    fn on_all_failed(&self, op: &str) {
+        let attrs = [KeyValue::new("op", op.to_string())];
+        self.inner.tron_endpoint_all_failed_total.add(1, &attrs);
+    }
+
+    fn on_hedge(&self, op: &str, primary_idx: usize, hedge_idx: usize) {
+        let attrs = [
+            KeyValue::new("op", op.to_string()),
+            KeyValue::new("from_idx", i64::try_from(primary_idx).unwrap_or_default()),
+            KeyValue::new("to_idx", i64::try_from(hedge_idx).unwrap_or_default()),
+        ];
+        self.inner.tron_endpoint_hedges_total.add(1, &attrs);
+    }
+
+    fn on_head_lag(&self, endpoint_idx: usize, lag_blocks: u64) {
+        let attrs = [KeyValue::new(
+            "endpoint_idx",
+            i64::try_from(endpoint_idx).unwrap_or_default(),
+        )];
+        self.inner
+            .tron_endpoint_head_lag_blocks
+            .record(lag_blocks, &attrs);
+    }
 }
@@ -1,5 +1,6 @@
 mod executors;
 mod model;
+mod rental_orders;
 mod tasks;
 mod util;
 
@@ -7,11 +8,11 @@ use crate::uniswap_v4::UniswapV4Client;
 use crate::{config::AppConfig, indexer::IndexerApi, metrics::RelayerTelemetry};
 use aa::paymaster::PaymasterService;
 use aa::{
-    PaymasterFinalizationMode, Safe4337UserOpSender, Safe4337UserOpSenderConfig,
-    Safe4337UserOpSenderOptions,
+    HttpSafeOpSigner, NonceLane, PaymasterFinalizationMode, Safe4337UserOpSender,
+    Safe4337UserOpSenderConfig, Safe4337UserOpSenderOptions, SafeOpSigner,
 };
 use alloy::{
-    primitives::{Address, B256, FixedBytes, U256, keccak256},
+    primitives::{Address, B256, Bytes, FixedBytes, U256, keccak256},
     providers::{DynProvider, Provider, ProviderBuilder},
 };
 use anyhow::{Context, Result};
@@ -20,19 +21,24 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
-use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
 use tron::{
-    JsonApiRentalProvider, TronAddress, TronGrpc, TronTxProofBuilder, TronWallet,
-    resources::parse_chain_fees,
+    EnergySource, JsonApiRentalProvider, StakedEnergyDelegator, TronAddress, TronApi, TronClient,
+    TronGrpcPool, TronHeaderProfile, TronTxProofBuilder, TronWallet, resources::parse_chain_fees,
+};
+use untron_v3_bindings::{
+    stateful_tron_tx_reader::StatefulTronTxReader::{
+        StatefulTronTxReaderErrors, StatefulTronTxReaderInstance,
+    },
+    untron_v3::UntronV3::UntronV3Instance,
 };
-use untron_v3_bindings::untron_v3::UntronV3::UntronV3Instance;
 
 use self::{
     executors::{DirectHubExecutor, HubExecutor, TronExecutor},
     model::{Plan, StateUpdate},
+    rental_orders::RentalOrderHistory,
     tasks::HubIntent,
-    util::run_job,
+    util::{HUB_PROOF_BLOCKS, run_job},
 };
 use futures::future::BoxFuture;
 
@@ -130,11 +136,7 @@ pub struct RelayerContext {
     receiver_init_code_hash: B256,
 
     pub tron_controller: TronAddress,
-    active_tron_read_grpc: Arc<Mutex<TronGrpc>>,
-    tron_read_grpc_urls: Vec<String>,
-    tron_read_grpc_url_cursor: Arc<Mutex<usize>>,
-    tron_read_grpc_api_key: Option<String>,
-    tron_read_grpc_api_key_header: String,
+    tron_read: TronGrpcPool,
     pub tron_write: TronExecutor,
     pub tron_proof: Arc<TronTxProofBuilder>,
 }
@@ -154,10 +156,12 @@ pub struct RelayerState {
     fill_cursor: usize,
 
     // Layer 3 breaker: rental-rate cap.
-    // Tracks every energy-rental attempt (successful or not) over the last 24h.
+    // Tracks every energy-rental order over the last 24h; only orders that cost (or may still
+    // cost) money count toward the cap, so failed and refunded orders drop out.
     // When the hour/day cap is reached we set `rental_paused_until` to block further writes
-    // until a cooldown elapses. Counters reset on relayer restart — safe by design.
-    rental_attempts: std::collections::VecDeque<Instant>,
+    // until a cooldown elapses. The order history survives restarts only when
+    // TRON_ENERGY_RENTAL_HISTORY_PATH is set; the cooldown itself always resets.
+    rental_orders: RentalOrderHistory,
     rental_paused_until: Option<Instant>,
 
     // Layer 4 breaker: per-kind tx-rate cap. Tracks every `broadcast_trigger_smart_contract`
@@ -171,7 +175,8 @@ pub struct RelayerState {
     hub_job_consecutive_failures: HashMap<&'static str, u32>,
     last_tron_head: u64,
 
-    hub_pending_nonce: Option<U256>,
+    /// Our latest submitted-but-unmined userop per hub nonce lane.
+    hub_pending_userops: HashMap<NonceLane, HubPendingUserop>,
     hub_direct_relay_pending_tx: Option<B256>,
     hub_usdt_balance_cache: Option<HubUsdtBalanceCache>,
     hub_head_block_cache: Option<HubHeadBlockCache>,
@@ -204,6 +209,53 @@ pub(crate) struct PullInFlight {
     pub expected_in_amount: U256,
 }
 
+#[derive(Debug, Clone)]
+struct HubPendingUserop {
+    /// Latest submission for the nonce (the original or its newest replacement).
+    submission: aa::Safe4337UserOpSubmission,
+    /// Hashes of earlier submissions it replaced; any of them may still be the one mined.
+    superseded: Vec<String>,
+    last_sent_at: Instant,
+    replacements: u32,
+    /// Sent for an intent that [spends Safe funds](tasks::HubIntent::spends_safe_funds).
+    spends_safe_funds: bool,
+}
+
+impl HubPendingUserop {
+    fn new(submission: aa::Safe4337UserOpSubmission) -> Self {
+        Self {
+            submission,
+            superseded: Vec::new(),
+            last_sent_at: Instant::now(),
+            replacements: 0,
+            spends_safe_funds: false,
+        }
+    }
+
+    fn userop_hashes(&self) -> Vec<String> {
+        std::iter::once(self.submission.userop_hash.clone())
+            .chain(self.superseded.iter().rev().cloned())
+            .collect()
+    }
+}
+
+/// Hub nonce lanes that still have one of our userops unmined.
+#[derive(Debug, Default)]
+struct HubNonceLocks {
+    /// Skip all hub work: the nonce couldn't be read, or a userop is pending while every job
+    /// shares the default lane.
+    all: bool,
+    lanes: HashSet<NonceLane>,
+    /// A pending userop spends Safe funds, so no other such intent may be planned yet.
+    safe_funds: bool,
+}
+
+impl HubNonceLocks {
+    fn blocks(&self, lane: NonceLane) -> bool {
+        self.all || self.lanes.contains(&lane)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct HubUsdtBalanceCache {
     balance: U256,
@@ -256,6 +308,34 @@ struct HubLpAllowedCache {
 }
 
 impl RelayerState {
+    /// A fresh state with an in-memory rental history.
+    #[cfg(test)]
+    pub(crate) fn empty() -> Self {
+        Self {
+            delayed_tron: HashMap::new(),
+            tip_proof_resend_after: HashMap::new(),
+            rebalance_in_flight: None,
+            pull_in_flight: None,
+            rebalance_cursor: 0,
+            energy_rental_cursor: 0,
+            fill_cursor: 0,
+            rental_orders: RentalOrderHistory::in_memory(),
+            rental_paused_until: None,
+            tx_attempts_per_kind: HashMap::new(),
+            tx_paused_until_per_kind: HashMap::new(),
+            hub_job_backoff_until_tron_head: HashMap::new(),
+            hub_job_consecutive_failures: HashMap::new(),
+            last_tron_head: 0,
+            hub_pending_userops: HashMap::new(),
+            hub_direct_relay_pending_tx: None,
+            hub_usdt_balance_cache: None,
+            hub_head_block_cache: None,
+            hub_swap_executor_cache: None,
+            hub_safe_erc20_balance_cache: HashMap::new(),
+            hub_lp_allowed_cache: None,
+        }
+    }
+
     pub fn invalidate_hub_usdt_balance_cache(&mut self) {
         self.hub_usdt_balance_cache = None;
     }
@@ -509,6 +589,67 @@ impl RelayerContext {
         UntronV3Instance::new(self.hub_contract_address, self.hub_provider.clone())
     }
 
+    /// Simulates `readTriggerSmartContract` on the hub's `tronReader` with `header` (encoded
+    /// under the configured profile) in every slot, and fails unless the reader parsed it.
+    ///
+    /// The reader parses headers at fixed offsets and does not expose its layout, so a proof
+    /// simulation is the only way to tell. Only the checks the reader runs on a parsed header
+    /// (block sequence, SR membership, signature) count as success; any other revert, including
+    /// one this build can't decode, fails the check.
+    async fn ensure_tron_reader_accepts_header(&self, header: Vec<u8>) -> Result<()> {
+        let reader_address = self
+            .hub_contract()
+            .tronReader()
+            .call()
+            .await
+            .context("hub tronReader")?;
+        let reader = StatefulTronTxReaderInstance::new(reader_address, self.hub_provider.clone());
+        let header = Bytes::from(header);
+        let res = reader
+            .readTriggerSmartContract(
+                std::array::from_fn(|_| header.clone()),
+                Bytes::new(),
+                Vec::new(),
+                U256::ZERO,
+            )
+            .call()
+            .await;
+        let err = match res {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
+        use StatefulTronTxReaderErrors as E;
+        match err.as_decoded_interface_error::<StatefulTronTxReaderErrors>() {
+            Some(E::InvalidEncodedBlockLength(e)) => anyhow::bail!(
+                "Tron reader {reader_address} rejects {}-byte headers; TRON_HEADER_PROFILE does not match its layout",
+                e.got
+            ),
+            Some(
+                E::InvalidHeaderPrefix(_)
+                | E::InvalidWitnessAddressPrefix(_)
+                | E::TimestampOverflow(_),
+            ) => anyhow::bail!(
+                "Tron reader {reader_address} cannot parse headers encoded as {}; TRON_HEADER_PROFILE does not match its layout",
+                self.cfg.tron.header_profile
+            ),
+            // `_verifyBlock` reverts with these only after `_parseTronBlock` returned; the
+            // repeated header fails the sequence check at slot 1 at the latest.
+            Some(
+                E::InvalidBlockSequence(_)
+                | E::UnknownSr(_)
+                | E::DuplicateSr(_)
+                | E::InvalidWitnessSignature(_),
+            ) => Ok(()),
+            Some(other) => anyhow::bail!(
+                "Tron reader {reader_address} rejected a {}-encoded header with {other:?}; cannot confirm it parses TRON_HEADER_PROFILE",
+                self.cfg.tron.header_profile
+            ),
+            None => {
+                Err(anyhow::Error::new(err).context("simulate tronReader.readTriggerSmartContract"))
+            }
+        }
+    }
+
     /// Local-only CREATE2 prediction of a Tron receiver address for `salt`. Mirrors the hub's
     /// `predictReceiverAddress(CONTROLLER_ADDRESS, salt)` view function bit-for-bit, using
     /// the Tron CREATE2 prefix (`0x41`) and the boot-time-cached receiver init-code hash.
@@ -523,80 +664,20 @@ impl RelayerContext {
         Address::from_slice(&h.as_slice()[12..])
     }
 
-    async fn ensure_tron_read_connected(&self, idx: usize, force_reconnect: bool) -> Result<()> {
-        let len = self.tron_read_grpc_urls.len();
-        if len == 0 {
-            anyhow::bail!("no TRON_GRPC_URLS configured");
-        }
-        let idx = idx % len;
-
-        let cur = *self.tron_read_grpc_url_cursor.lock().await;
-        if idx == cur && !force_reconnect {
-            return Ok(());
-        }
-
-        let url = self.tron_read_grpc_urls[idx].clone();
-        let grpc = TronGrpc::connect(
-            &url,
-            self.tron_read_grpc_api_key.as_deref(),
-            &self.tron_read_grpc_api_key_header,
-        )
-        .await
-        .with_context(|| format!("connect TRON gRPC: {url}"))?;
-
-        {
-            let mut guard = self.active_tron_read_grpc.lock().await;
-            *guard = grpc;
-        }
-        *self.tron_read_grpc_url_cursor.lock().await = idx;
-        tracing::info!(tron_grpc = %url, "switched relayer Tron read endpoint");
-        Ok(())
+    pub async fn with_tron_read_retry<T, F>(&self, op_name: &'static str, op: F) -> Result<T>
+    where
+        F: for<'a> FnMut(&'a mut TronClient) -> BoxFuture<'a, Result<T>>,
+    {
+        self.tron_read.with_read(op_name, op).await
     }
 
-    pub async fn with_tron_read_retry<T, F>(&self, op_name: &'static str, mut op: F) -> Result<T>
+    /// For building proofs, which are slow by nature and need canonical transaction bytes:
+    /// fails over gRPC endpoint by endpoint without hedging or a per-try timeout.
+    pub async fn with_tron_proof_failover<T, F>(&self, op_name: &'static str, op: F) -> Result<T>
     where
-        F: for<'a> FnMut(&'a mut TronGrpc) -> BoxFuture<'a, Result<T>>,
+        F: for<'a> FnMut(&'a mut TronClient) -> BoxFuture<'a, Result<T>>,
     {
-        let len = self.tron_read_grpc_urls.len();
-        if len == 0 {
-            anyhow::bail!("no TRON_GRPC_URLS configured");
-        }
-
-        let start = *self.tron_read_grpc_url_cursor.lock().await;
-        let attempts = if len == 1 { 2 } else { len };
-        let mut last_err: Option<anyhow::Error> = None;
-
-        for attempt in 0..attempts {
-            let idx = (start + attempt) % len;
-            let force_reconnect = len == 1 && attempt > 0;
-
-            if let Err(err) = self.ensure_tron_read_connected(idx, force_reconnect).await {
-                tracing::warn!(
-                    tron_grpc = %self.tron_read_grpc_urls[idx],
-                    op = op_name,
-                    err = %err,
-                    "failed to connect Tron gRPC endpoint"
-                );
-                last_err = Some(err);
-                continue;
-            }
-
-            let mut grpc = self.active_tron_read_grpc.lock().await;
-            match op(&mut grpc).await {
-                Ok(v) => return Ok(v),
-                Err(err) => {
-                    tracing::warn!(
-                        tron_grpc = %self.tron_read_grpc_urls[idx],
-                        op = op_name,
-                        err = %err,
-                        "tron read operation failed; trying next endpoint"
-                    );
-                    last_err = Some(err);
-                }
-            }
-        }
-
-        Err(last_err.unwrap_or_else(|| anyhow::anyhow!("all TRON_GRPC_URLS endpoints failed")))
+        self.tron_read.with_grpc_failover(op_name, op).await
     }
 }
 
@@ -653,11 +734,26 @@ impl Relayer {
             rpc_url: cfg.hub.rpc_url.clone(),
             chain_id: Some(hub_chain_id),
             entrypoint: cfg.hub.entrypoint,
+            entrypoint_version: cfg.hub.entrypoint_version,
+            account: cfg.hub.account,
             safe: cfg.hub.safe,
             safe_4337_module: cfg.hub.safe_4337_module,
             safe_deployment: cfg.hub.safe_deployment.clone(),
             bundler_urls: cfg.hub.bundler_urls.clone(),
             owner_private_key: cfg.hub.owner_private_key,
+            cosigners: cfg
+                .hub
+                .cosigners
+                .iter()
+                .map(|c| {
+                    HttpSafeOpSigner::new(
+                        c.url.clone(),
+                        c.owner,
+                        Duration::from_secs(c.timeout_secs),
+                    )
+                    .map(|s| Arc::new(s) as Arc<dyn SafeOpSigner>)
+                })
+                .collect::<Result<_>>()?,
             paymasters: cfg
                 .hub
                 .paymasters
@@ -670,6 +766,7 @@ impl Relayer {
             options: Safe4337UserOpSenderOptions {
                 check_bundler_entrypoints: true,
                 paymaster_finalization: PaymasterFinalizationMode::SkipIfStubFinal,
+                gas: cfg.hub.userop_gas.clone(),
             },
         };
 
@@ -677,64 +774,63 @@ impl Relayer {
         let hub_safe = hub_sender_inner.safe_address();
         tracing::info!(safe = %hub_safe, "hub safe ready");
         cfg.hub.safe = Some(hub_safe);
-        let hub_sender = Arc::new(Mutex::new(hub_sender_inner));
+        let hub_sender = Arc::new(hub_sender_inner);
         let hub_direct = cfg
             .hub
             .direct_tx_private_key
             .map(|pk| DirectHubExecutor::new(&cfg.hub.rpc_url, hub_chain_id, pk))
             .transpose()
             .context("init direct hub executor")?;
-        let hub = HubExecutor::new(hub_sender, hub_direct, telemetry.clone());
-
-        let grpc_urls = cfg.tron.grpc_urls.clone();
-        let mut active_tron_read_grpc = None;
-        let mut tron_read_grpc_idx = 0usize;
-        let mut last_connect_err: Option<anyhow::Error> = None;
-        for (idx, url) in grpc_urls.iter().enumerate() {
-            match TronGrpc::connect(url, cfg.tron.api_key.as_deref(), &cfg.tron.api_key_header)
-                .await
-            {
-                Ok(g) => {
-                    active_tron_read_grpc = Some(g);
-                    tron_read_grpc_idx = idx;
-                    break;
-                }
-                Err(err) => {
-                    tracing::warn!(tron_grpc = %url, err = %err, "failed to connect Tron gRPC endpoint");
-                    last_connect_err = Some(err);
-                }
-            }
-        }
-        let active_tron_read_grpc = active_tron_read_grpc.context("connect TRON gRPC")?;
-        let active_tron_read_grpc = Arc::new(Mutex::new(active_tron_read_grpc));
-        let tron_read_grpc_url_cursor = Arc::new(Mutex::new(tron_read_grpc_idx));
-        if let Some(err) = last_connect_err {
-            tracing::info!(err = %err, "using fallback Tron gRPC endpoint");
-        }
+        let hub = HubExecutor::new(
+            hub_sender,
+            hub_direct,
+            telemetry.clone(),
+            cfg.hub.nonce_lanes,
+        );
+
+        let tron_read = TronGrpcPool::connect(
+            cfg.tron.grpc_urls.clone(),
+            cfg.tron.api_key.clone(),
+            cfg.tron.api_key_header.clone(),
+            cfg.tron.endpoint_pool.clone(),
+            Some(Arc::new(telemetry.clone())),
+        )
+        .await?;
 
         let tron_controller = TronAddress::parse_text(&cfg.tron.controller_address)
             .context("parse TRON_CONTROLLER_ADDRESS")?;
         let tron_wallet = Arc::new(TronWallet::new(cfg.tron.private_key)?);
-        let tron_grpc_write = {
-            let g = active_tron_read_grpc.lock().await;
-            Arc::new(Mutex::new(g.clone()))
-        };
-        let energy_rental = cfg
-            .tron
-            .energy_rental_providers
-            .clone()
-            .into_iter()
-            .map(JsonApiRentalProvider::new)
-            .collect::<Vec<_>>();
+        let mut energy_rental = Vec::new();
+        if let Some(key) = cfg.tron.energy_staker_private_key {
+            let staker =
+                Arc::new(TronWallet::new(key).context("TRON_ENERGY_STAKER_PRIVATE_KEY_HEX")?);
+            if staker.address() == tron_wallet.address() {
+                anyhow::bail!(
+                    "TRON_ENERGY_STAKER_PRIVATE_KEY_HEX must differ from TRON_PRIVATE_KEY_HEX (Tron rejects self-delegation)"
+                );
+            }
+            tracing::info!(staker = %staker.address(), "staked energy delegation configured");
+            energy_rental.push(EnergySource::Staked(StakedEnergyDelegator::new(
+                "staked", staker,
+            )));
+        }
+        energy_rental.extend(
+            cfg.tron
+                .energy_rental_providers
+                .clone()
+                .into_iter()
+                .map(|p| EnergySource::JsonApi(JsonApiRentalProvider::new(p))),
+        );
         if cfg.tron.require_energy_rental && energy_rental.is_empty() {
             anyhow::bail!(
-                "TRON_REQUIRE_ENERGY_RENTAL=true but TRON_ENERGY_RENTAL_APIS_JSON configures no providers"
+                "TRON_REQUIRE_ENERGY_RENTAL=true but neither TRON_ENERGY_RENTAL_APIS_JSON nor TRON_ENERGY_STAKER_PRIVATE_KEY_HEX configures an energy source"
             );
         }
         let chain_fees = {
-            let mut g = active_tron_read_grpc.lock().await;
-            let params = g
-                .get_chain_parameters()
+            let params = tron_read
+                .with_read("get_chain_parameters", |tron| {
+                    Box::pin(async move { tron.get_chain_parameters().await })
+                })
                 .await
                 .context("get_chain_parameters (startup)")?;
             parse_chain_fees(&params).context("parse_chain_fees (startup)")?
@@ -746,12 +842,12 @@ impl Relayer {
             fee_limit_ceiling_sun = cfg.tron.fee_limit_ceiling_sun,
             "loaded Tron chain fees"
         );
+        let rental_orders = match cfg.tron.energy_rental_history_path.clone() {
+            Some(path) => RentalOrderHistory::open(path)?,
+            None => RentalOrderHistory::in_memory(),
+        };
         let tron_write = TronExecutor::new(
-            tron_grpc_write,
-            grpc_urls.clone(),
-            cfg.tron.api_key.clone(),
-            cfg.tron.api_key_header.clone(),
-            tron_read_grpc_idx,
+            tron_read.clone(),
             tron_wallet.clone(),
             energy_rental,
             cfg.tron.energy_rental_confirm_max_wait,
@@ -767,9 +863,10 @@ impl Relayer {
             cfg.tron.fee_limit_ceiling_sun,
             telemetry.clone(),
         );
-        let tron_proof = Arc::new(TronTxProofBuilder::new(cfg.jobs.tron_finality_blocks));
-        let tron_read_grpc_api_key = cfg.tron.api_key.clone();
-        let tron_read_grpc_api_key_header = cfg.tron.api_key_header.clone();
+        let tron_proof = Arc::new(
+            TronTxProofBuilder::new(cfg.jobs.tron_finality_blocks)
+                .with_header_profile(cfg.tron.header_profile),
+        );
 
         let ctx = RelayerContext {
             cfg,
@@ -781,23 +878,35 @@ impl Relayer {
             uniswap_v4,
             receiver_init_code_hash,
             tron_controller,
-            active_tron_read_grpc,
-            tron_read_grpc_urls: grpc_urls,
-            tron_read_grpc_url_cursor,
-            tron_read_grpc_api_key,
-            tron_read_grpc_api_key_header,
+            tron_read,
             tron_write,
             tron_proof,
         };
 
         // Startup read probe should use the same endpoint failover path as runtime reads.
-        ctx.with_tron_read_retry("startup_get_now_block2", |tron| {
-            Box::pin(async move {
-                let _ = tron.get_now_block2().await?;
-                Ok(())
+        let head_header = ctx
+            .with_tron_read_retry("startup_get_now_block2", |tron| {
+                Box::pin(async move {
+                    let head = tron.get_now_block2().await?;
+                    head.block_header.context("missing block_header")
+                })
             })
-        })
-        .await?;
+            .await?;
+        // Headers that no longer fit the reader's layout would make every proof unusable.
+        let head_profile = TronHeaderProfile::of_header(&head_header)?;
+        if head_profile != ctx.cfg.tron.header_profile {
+            anyhow::bail!(
+                "Tron head header encodes as {head_profile}, but TRON_HEADER_PROFILE is {}",
+                ctx.cfg.tron.header_profile
+            );
+        }
+        let encoded_header = ctx.tron_proof.encode_header(&head_header)?;
+        ctx.ensure_tron_reader_accepts_header(encoded_header)
+            .await
+            .context("TRON_HEADER_PROFILE does not match the hub's Tron reader")?;
+        ctx.tron_proof
+            .ensure_reader_block_count(HUB_PROOF_BLOCKS)
+            .context("TRON_FINALITY_BLOCKS does not match the hub's Tron reader")?;
 
         Ok(Self {
             ctx,
@@ -809,14 +918,14 @@ impl Relayer {
                 rebalance_cursor: 0,
                 energy_rental_cursor: 0,
                 fill_cursor: 0,
-                rental_attempts: std::collections::VecDeque::new(),
+                rental_orders,
                 rental_paused_until: None,
                 tx_attempts_per_kind: HashMap::new(),
                 tx_paused_until_per_kind: HashMap::new(),
                 hub_job_backoff_until_tron_head: HashMap::new(),
                 hub_job_consecutive_failures: HashMap::new(),
                 last_tron_head: 0,
-                hub_pending_nonce: None,
+                hub_pending_userops: HashMap::new(),
                 hub_direct_relay_pending_tx: None,
                 hub_usdt_balance_cache: None,
                 hub_head_block_cache: None,
@@ -1188,11 +1297,11 @@ impl Relayer {
 
         let telemetry = self.ctx.telemetry.clone();
 
-        let hub_locked = self.hub_locked_or_assume_locked().await;
+        let hub_locks = self.hub_nonce_locks().await;
 
         self.run_controller_tip_proof(&telemetry, &tick).await;
 
-        let planned = self.plan_tick(&tick, hub_locked).await?;
+        let planned = self.plan_tick(&tick, &hub_locks).await?;
         self.execute_hub_jobs(&telemetry, planned.hub_candidates)
             .await;
         self.execute_liquidity_tron(&telemetry, &tick, planned.pull_intent)
@@ -1230,12 +1339,23 @@ impl Relayer {
         Ok(Tick { tron_head })
     }
 
-    async fn hub_locked_or_assume_locked(&mut self) -> bool {
-        match self.hub_locked().await {
-            Ok(v) => v,
+    async fn hub_nonce_locks(&mut self) -> HubNonceLocks {
+        match self.locked_hub_lanes().await {
+            Ok(lanes) => HubNonceLocks {
+                all: !self.ctx.hub.nonce_lanes() && !lanes.is_empty(),
+                lanes,
+                safe_funds: self
+                    .state
+                    .hub_pending_userops
+                    .values()
+                    .any(|p| p.spends_safe_funds),
+            },
             Err(err) => {
                 tracing::warn!(err = %err, "failed to query hub AA nonce; skipping hub jobs");
-                true
+                HubNonceLocks {
+                    all: true,
+                    ..HubNonceLocks::default()
+                }
             }
         }
     }
@@ -1265,7 +1385,8 @@ impl Relayer {
         .await;
     }
 
-    async fn plan_tick(&mut self, tick: &Tick, hub_locked: bool) -> Result<PlannedTick> {
+    async fn plan_tick(&mut self, tick: &Tick, hub_locks: &HubNonceLocks) -> Result<PlannedTick> {
+        let hub_locked = hub_locks.all;
         self.state.last_tron_head = tick.tron_head;
         let hub_state = self.ctx.indexer.relayer_hub_state().await?;
 
@@ -1351,6 +1472,25 @@ impl Relayer {
             }
         }
 
+        hub_candidates.retain(|c| {
+            let locked = hub_locks.blocks(self.ctx.hub.lane(c.job_name));
+            if locked {
+                tracing::debug!(
+                    job = c.job_name,
+                    "hub nonce lane has a pending userop; skipping candidate"
+                );
+                return false;
+            }
+            if hub_locks.safe_funds && c.intent.spends_safe_funds() {
+                tracing::debug!(
+                    job = c.job_name,
+                    "pending hub userop spends Safe funds; skipping candidate"
+                );
+                return false;
+            }
+            true
+        });
+
         Ok(PlannedTick {
             hub_candidates,
             pull_intent,
@@ -1362,14 +1502,39 @@ impl Relayer {
         telemetry: &RelayerTelemetry,
         hub_candidates: Vec<HubCandidate>,
     ) {
-        let Some(chosen) = choose_hub_candidate(hub_candidates) else {
-            return;
+        let chosen = if self.ctx.hub.nonce_lanes() {
+            // Every job kind has its own nonce lane, so their userops don't queue behind
+            // (or AA25 against) each other. Intents spending Safe funds were all planned from
+            // the same balances, though, so only the most urgent of them goes out.
+            let mut all = hub_candidates;
+            all.sort_by_key(|c| c.priority);
+            let mut spending = false;
+            all.retain(|c| {
+                if !c.intent.spends_safe_funds() {
+                    return true;
+                }
+                if spending {
+                    tracing::debug!(
+                        job = c.job_name,
+                        priority = c.priority,
+                        "hub intent ready but skipped (one Safe-funded userop per tick)"
+                    );
+                    return false;
+                }
+                spending = true;
+                true
+            });
+            all
+        } else {
+            choose_hub_candidate(hub_candidates).into_iter().collect()
         };
-        let name = chosen.job_name;
-        let _ = run_job(telemetry, name, || async {
-            tasks::execute_hub_intent(&self.ctx, &mut self.state, name, chosen.intent).await
-        })
-        .await;
+        for candidate in chosen {
+            let name = candidate.job_name;
+            let _ = run_job(telemetry, name, || async {
+                tasks::execute_hub_intent(&self.ctx, &mut self.state, name, candidate.intent).await
+            })
+            .await;
+        }
     }
 
     async fn execute_liquidity_tron(
@@ -1393,16 +1558,55 @@ impl Relayer {
         .await;
     }
 
-    async fn hub_locked(&mut self) -> Result<bool> {
-        let Some(pending) = self.state.hub_pending_nonce else {
-            return Ok(false);
-        };
-        let current = self.ctx.hub.current_nonce().await?;
-        if current > pending {
-            self.state.hub_pending_nonce = None;
-            return Ok(false);
+    /// Lanes whose pending userop hasn't been mined yet. Mined ones are cleared (and their
+    /// gas reported) along the way; stalled ones are replaced with bumped fees.
+    async fn locked_hub_lanes(&mut self) -> Result<HashSet<NonceLane>> {
+        let replacement = self.ctx.cfg.hub.userop_replacement.clone();
+        let mut locked = HashSet::new();
+        let lanes: Vec<NonceLane> = self.state.hub_pending_userops.keys().copied().collect();
+        for lane in lanes {
+            let current = self.ctx.hub.current_nonce(lane).await?;
+            let Some(pending) = self.state.hub_pending_userops.get_mut(&lane) else {
+                continue;
+            };
+            if current > pending.submission.nonce {
+                if let Some(pending) = self.state.hub_pending_userops.remove(&lane)
+                    && let Err(err) = self.ctx.hub.report_userop_gas(&pending).await
+                {
+                    tracing::warn!(userop_hash = %pending.submission.userop_hash, err = %format!("{err:#}"), "failed to report hub userop gas");
+                }
+                continue;
+            }
+            locked.insert(lane);
+
+            if let Some(stall) = replacement.stall_timeout
+                && pending.last_sent_at.elapsed() >= stall
+                && pending.replacements < replacement.max_replacements
+            {
+                // Reset the stall clock even on failure so a rejecting bundler isn't hammered.
+                pending.last_sent_at = Instant::now();
+                match self
+                    .ctx
+                    .hub
+                    .replace_userop(&pending.submission, replacement.bump_pct)
+                    .await
+                {
+                    Ok(sub) => {
+                        pending.replacements += 1;
+                        let replaced = std::mem::replace(&mut pending.submission, sub);
+                        pending.superseded.push(replaced.userop_hash);
+                    }
+                    Err(err) => {
+                        tracing::warn!(
+                            userop_hash = %pending.submission.userop_hash,
+                            err = %format!("{err:#}"),
+                            "failed to replace stalled hub userop"
+                        );
+                    }
+                }
+            }
         }
-        Ok(true)
+        Ok(locked)
     }
 
     async fn relay_controller_chain_locked(&mut self) -> Result<bool> {
@@ -1647,35 +1851,9 @@ fn choose_hub_candidate(mut candidates: Vec<HubCandidate>) -> Option<HubCandidat
 mod tests {
     use super::*;
 
-    fn empty_state() -> RelayerState {
-        RelayerState {
-            delayed_tron: HashMap::new(),
-            tip_proof_resend_after: HashMap::new(),
-            rebalance_in_flight: None,
-            pull_in_flight: None,
-            rebalance_cursor: 0,
-            energy_rental_cursor: 0,
-            fill_cursor: 0,
-            rental_attempts: std::collections::VecDeque::new(),
-            rental_paused_until: None,
-            tx_attempts_per_kind: HashMap::new(),
-            tx_paused_until_per_kind: HashMap::new(),
-            hub_job_backoff_until_tron_head: HashMap::new(),
-            hub_job_consecutive_failures: HashMap::new(),
-            last_tron_head: 0,
-            hub_pending_nonce: None,
-            hub_direct_relay_pending_tx: None,
-            hub_usdt_balance_cache: None,
-            hub_head_block_cache: None,
-            hub_swap_executor_cache: None,
-            hub_safe_erc20_balance_cache: HashMap::new(),
-            hub_lp_allowed_cache: None,
-        }
-    }
-
     #[test]
     fn tron_delay_plans_set_and_clear_updates() {
-        let mut state = empty_state();
+        let mut state = RelayerState::empty();
 
         let (ready, updates) = state.plan_tron_delay("k", 5, 10);
         assert!(!ready);
@@ -1696,7 +1874,7 @@ mod tests {
 
     #[test]
     fn tron_delay_keeps_earliest_deadline() {
-        let mut state = empty_state();
+        let mut state = RelayerState::empty();
 
         state.apply_updates([StateUpdate::DelayedTronSet {
             key: "k",
@@ -1721,8 +1899,10 @@ mod tests {
                 swap_executor: alloy::primitives::Address::ZERO,
             }),
             HubCandidate::new(HubIntent::PreEntitle {
-                receiver_salt: alloy::primitives::FixedBytes::ZERO,
-                txid: [0u8; 32],
+                targets: vec![tasks::PreEntitleTarget {
+                    receiver_salt: alloy::primitives::FixedBytes::ZERO,
+                    txid: [0u8; 32],
+                }],
             }),
             HubCandidate::new(HubIntent::ProcessControllerEvents),
         ];
@@ -1,6 +1,6 @@
-use super::RelayerState;
+use super::{HubPendingUserop, RelayerState, rental_orders::RentalOrderHistory};
 use crate::metrics::RelayerTelemetry;
-use aa::Safe4337UserOpSender;
+use aa::{NonceLane, Safe4337UserOpSender, Safe4337UserOpSubmission};
 use alloy::{
     network::EthereumWallet,
     primitives::{Address, B256, U256},
@@ -14,8 +14,10 @@ use std::time::Duration;
 use std::time::Instant;
 use tokio::sync::Mutex;
 use tron::{
-    ChainFees, FeeLimitPolicy, JsonApiRentalProvider, RentalContext, RentalResourceKind,
-    TronAddress, TronGrpc, TronWallet, protocol::TriggerSmartContract,
+    ChainFees, EnergyLease, EnergyQuote, EnergySource, FeeLimitPolicy, RentalContext,
+    RentalOrderStatus, RentalResourceKind, TronAddress, TronApi, TronClient, TronGrpcPool,
+    TronWallet, protocol::TriggerSmartContract, quote_energy_sources, rank_energy_sources,
+    resources::quote_fee_limit_sun,
 };
 
 #[derive(Clone)]
@@ -119,28 +121,44 @@ fn is_userop_gas_cap_error(err: &anyhow::Error) -> bool {
 
 #[derive(Clone)]
 pub struct HubExecutor {
-    sender: Arc<Mutex<Safe4337UserOpSender>>,
+    sender: Arc<Safe4337UserOpSender>,
     direct: Option<DirectHubExecutor>,
     telemetry: RelayerTelemetry,
+    nonce_lanes: bool,
 }
 
 impl HubExecutor {
     pub fn new(
-        sender: Arc<Mutex<Safe4337UserOpSender>>,
+        sender: Arc<Safe4337UserOpSender>,
         direct: Option<DirectHubExecutor>,
         telemetry: RelayerTelemetry,
+        nonce_lanes: bool,
     ) -> Self {
         Self {
             sender,
             direct,
             telemetry,
+            nonce_lanes,
         }
     }
 
-    pub async fn current_nonce(&self) -> Result<U256> {
+    /// Whether each job submits on its own nonce lane (see [`Self::lane`]).
+    pub fn nonce_lanes(&self) -> bool {
+        self.nonce_lanes
+    }
+
+    /// Nonce lane `job_name`'s userops are sent on.
+    pub fn lane(&self, job_name: &'static str) -> NonceLane {
+        if self.nonce_lanes {
+            NonceLane::named(job_name)
+        } else {
+            NonceLane::DEFAULT
+        }
+    }
+
+    pub async fn current_nonce(&self, lane: NonceLane) -> Result<U256> {
         let start = Instant::now();
-        let sender = self.sender.lock().await;
-        let res = sender.current_nonce().await;
+        let res = self.sender.current_nonce_in_lane(lane).await;
         self.telemetry.hub_rpc_ms(
             "EntryPoint.getNonce",
             res.is_ok(),
@@ -178,17 +196,17 @@ impl HubExecutor {
         let data_for_direct = data.clone();
         let data_for_send = data;
 
+        let lane = self.lane(job_name);
         let start = Instant::now();
-        let submission = {
-            let mut sender = self.sender.lock().await;
-            // Use the cache-driven, AA25-retrying entrypoint. The chain `getNonce` happens
-            // inside; we lose the standalone "EntryPoint.getNonce" RPC-timing metric here,
-            // but the overall `hub_submit_ms` below still covers it, and `current_nonce()`
-            // calls from `hub_locked()` keep emitting the metric independently.
-            sender
-                .send_call_operation(to, data_for_send, operation)
-                .await
-        };
+        // Use the cache-driven, AA25-retrying entrypoint. The chain `getNonce` happens
+        // inside; we lose the standalone "EntryPoint.getNonce" RPC-timing metric here,
+        // but the overall `hub_submit_ms` below still covers it, and `current_nonce()`
+        // calls from `locked_hub_lanes()` keep emitting the metric independently.
+        // The sender isn't locked, so other lanes' sends and replacements proceed meanwhile.
+        let submission = self
+            .sender
+            .send_call_operation_in_lane(lane, to, data_for_send, operation)
+            .await;
 
         match submission {
             Ok(sub) => {
@@ -197,9 +215,11 @@ impl HubExecutor {
                 self.telemetry.hub_userop_ok();
                 state.invalidate_hub_usdt_balance_cache();
                 state.invalidate_hub_safe_erc20_balance_cache();
-                state.hub_pending_nonce = Some(sub.nonce);
                 state.hub_job_on_success(job_name);
                 tracing::info!(userop_hash = %sub.userop_hash, job = %job_name, intent = %intent_name, "submitted hub userop");
+                state
+                    .hub_pending_userops
+                    .insert(lane, HubPendingUserop::new(sub));
                 Ok(())
             }
             Err(err) => {
@@ -256,10 +276,7 @@ impl HubExecutor {
 
                 if debug_dump_calldata {
                     // Safe4337 smart account address; useful for reproducing the call via `cast call --from`.
-                    let safe = {
-                        let sender = self.sender.lock().await;
-                        sender.safe_address()
-                    };
+                    let safe = self.sender.safe_address();
                     let hex0x =
                         format!("0x{}", hex::encode(data_for_log.as_deref().unwrap_or(&[])));
                     // Some log backends drop/trim very long lines; emit chunked logs.
@@ -307,6 +324,47 @@ impl HubExecutor {
         }
     }
 
+    /// Resubmits a stalled userop's nonce with bumped fees.
+    pub async fn replace_userop(
+        &self,
+        prev: &Safe4337UserOpSubmission,
+        bump_pct: u64,
+    ) -> Result<Safe4337UserOpSubmission> {
+        let res = self.sender.replace_user_operation(prev, bump_pct).await;
+        self.telemetry.hub_userop_replaced(res.is_ok());
+        res
+    }
+
+    /// Records the mined userop's `actualGasUsed` (whichever of the original and its
+    /// replacements landed) against its raw gas estimate and the limits it was sent with.
+    pub async fn report_userop_gas(&self, pending: &HubPendingUserop) -> Result<()> {
+        let landed = self
+            .sender
+            .landed_user_operation(&pending.userop_hashes())
+            .await?;
+        let Some((userop_hash, receipt)) = landed else {
+            // Not indexed by the bundler (yet); nothing to compare.
+            return Ok(());
+        };
+        let gas = &pending.submission.gas;
+        let estimate = u64::try_from(gas.estimate).unwrap_or(u64::MAX);
+        let limit = u64::try_from(gas.total()).unwrap_or(u64::MAX);
+        let used = u64::try_from(receipt.actual_gas_used).unwrap_or(u64::MAX);
+        self.telemetry
+            .hub_userop_gas(gas.source.as_str(), estimate, limit, used);
+        tracing::info!(
+            userop_hash = %userop_hash,
+            replacements = pending.replacements,
+            source = gas.source.as_str(),
+            gas_estimate = estimate,
+            gas_limit = limit,
+            gas_used = used,
+            success = receipt.success,
+            "hub userop mined"
+        );
+        Ok(())
+    }
+
     pub async fn relay_controller_chain_direct_pending(&self, tx_hash: B256) -> Result<bool> {
         let Some(direct) = &self.direct else {
             return Ok(false);
@@ -317,13 +375,11 @@ impl HubExecutor {
 
 #[derive(Clone)]
 pub struct TronExecutor {
-    active_grpc: Arc<Mutex<TronGrpc>>,
-    grpc_urls: Vec<String>,
-    grpc_api_key: Option<String>,
-    grpc_api_key_header: String,
-    grpc_url_cursor: Arc<Mutex<usize>>,
+    tron: TronGrpcPool,
     wallet: Arc<TronWallet>,
-    energy_rental: Vec<JsonApiRentalProvider>,
+    energy_rental: Arc<[EnergySource]>,
+    /// Held by the running background rental-order reconciliation, if any.
+    rental_reconcile: Arc<Mutex<()>>,
     energy_rental_confirm_max_wait: Duration,
     require_energy_rental: bool,
     rental_cap_per_hour: u32,
@@ -339,13 +395,9 @@ pub struct TronExecutor {
 impl TronExecutor {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        active_grpc: Arc<Mutex<TronGrpc>>,
-        grpc_urls: Vec<String>,
-        grpc_api_key: Option<String>,
-        grpc_api_key_header: String,
-        initial_grpc_url_cursor: usize,
+        tron: TronGrpcPool,
         wallet: Arc<TronWallet>,
-        energy_rental: Vec<JsonApiRentalProvider>,
+        energy_rental: Vec<EnergySource>,
         energy_rental_confirm_max_wait: Duration,
         require_energy_rental: bool,
         rental_cap_per_hour: u32,
@@ -360,13 +412,10 @@ impl TronExecutor {
         telemetry: RelayerTelemetry,
     ) -> Self {
         Self {
-            active_grpc,
-            grpc_urls,
-            grpc_api_key,
-            grpc_api_key_header,
-            grpc_url_cursor: Arc::new(Mutex::new(initial_grpc_url_cursor)),
+            tron,
             wallet,
-            energy_rental,
+            energy_rental: energy_rental.into(),
+            rental_reconcile: Arc::new(Mutex::new(())),
             energy_rental_confirm_max_wait,
             require_energy_rental,
             rental_cap_per_hour,
@@ -399,58 +448,26 @@ impl TronExecutor {
         self.enforce_kind_budget(state, kind)?;
 
         let start = Instant::now();
-        let len = self.grpc_urls.len();
-        if len == 0 {
-            anyhow::bail!("no TRON_GRPC_URLS configured");
-        }
-
-        let start_cursor = *self.grpc_url_cursor.lock().await;
-        let attempts = if len == 1 { 2 } else { len };
-        let mut txid: Result<[u8; 32]> = Err(anyhow::anyhow!(
-            "all TRON_GRPC_URLS endpoints failed for op=broadcast_trigger_smart_contract"
-        ));
-
-        for attempt in 0..attempts {
-            let idx = (start_cursor + attempt) % len;
-            let force_reconnect = len == 1 && attempt > 0;
-
-            if let Err(err) = self.ensure_tron_connected(idx, force_reconnect).await {
-                tracing::warn!(
-                    tron_grpc = %self.grpc_urls[idx],
-                    op = "broadcast_trigger_smart_contract",
-                    err = %err,
-                    "failed to connect Tron gRPC endpoint"
-                );
-                txid = Err(err);
-                continue;
-            }
-
-            let mut grpc = self.active_grpc.lock().await;
-            match self
-                .broadcast_trigger_smart_contract_managed(
-                    &mut grpc,
-                    state,
-                    contract,
-                    data.clone(),
-                    call_value_sun,
-                )
-                .await
-            {
-                Ok(v) => {
-                    txid = Ok(v);
-                    break;
-                }
-                Err(err) => {
-                    tracing::warn!(
-                        tron_grpc = %self.grpc_urls[idx],
-                        op = "broadcast_trigger_smart_contract",
-                        err = %err,
-                        "tron write operation failed; trying next endpoint"
-                    );
-                    txid = Err(err);
-                }
-            }
-        }
+        // Attempts run one at a time; the lock only lets each attempt's future borrow `state`.
+        let state = Mutex::new(state);
+        let txid = self
+            .tron
+            .with_failover_scoped("broadcast_trigger_smart_contract", |mut grpc| {
+                let state = &state;
+                let data = data.clone();
+                Box::pin(async move {
+                    let mut state = state.lock().await;
+                    self.broadcast_trigger_smart_contract_managed(
+                        &mut grpc,
+                        &mut state,
+                        contract,
+                        data,
+                        call_value_sun,
+                    )
+                    .await
+                })
+            })
+            .await;
 
         match txid {
             Ok(txid) => {
@@ -474,127 +491,54 @@ impl TronExecutor {
         data: Vec<u8>,
         call_value_sun: i64,
     ) -> Result<u64> {
-        let len = self.grpc_urls.len();
-        if len == 0 {
-            anyhow::bail!("no TRON_GRPC_URLS configured");
-        }
-
         let owner = self.wallet.address().prefixed_bytes().to_vec();
         let contract_addr = contract.prefixed_bytes().to_vec();
 
-        let start_cursor = *self.grpc_url_cursor.lock().await;
-        let attempts = if len == 1 { 2 } else { len };
-        let mut last_err: Option<anyhow::Error> = None;
-
-        for attempt in 0..attempts {
-            let idx = (start_cursor + attempt) % len;
-            let force_reconnect = len == 1 && attempt > 0;
-
-            if let Err(err) = self.ensure_tron_connected(idx, force_reconnect).await {
-                tracing::warn!(
-                    tron_grpc = %self.grpc_urls[idx],
-                    op = "estimate_trigger_smart_contract_energy",
-                    err = %err,
-                    "failed to connect Tron gRPC endpoint"
-                );
-                last_err = Some(err);
-                continue;
-            }
-
-            let mut grpc = self.active_grpc.lock().await;
-            let resp = match grpc
-                .estimate_energy(TriggerSmartContract {
+        self.tron
+            .with_read("estimate_trigger_smart_contract_energy", |grpc| {
+                let req = TriggerSmartContract {
                     owner_address: owner.clone(),
                     contract_address: contract_addr.clone(),
                     call_value: call_value_sun,
                     data: data.clone(),
                     call_token_value: 0,
                     token_id: 0,
-                })
-                .await
-            {
-                Ok(v) => v,
-                Err(err) => {
-                    tracing::warn!(
-                        tron_grpc = %self.grpc_urls[idx],
-                        op = "estimate_trigger_smart_contract_energy",
-                        err = %err,
-                        "tron write operation failed; trying next endpoint"
-                    );
-                    last_err = Some(err);
-                    continue;
-                }
-            };
-
-            // Tron returns Ok with `result.result=false` and `energy_required=0` when the
-            // simulator hits its CPU budget (OutOfTimeException). Callers that treat the
-            // returned u64 as authoritative would silently underestimate — bypassing the
-            // splitter's energy_limit cap and broadcasting the full receiver set. Surface
-            // estimator failure as an Err so the splitter falls back to its binary-search
-            // path, which treats Err as "too big" and shrinks.
-            if let Some(ret) = &resp.result
-                && !ret.result
-            {
-                let msg = String::from_utf8_lossy(&ret.message);
-                tracing::warn!(
-                    tron_grpc = %self.grpc_urls[idx],
-                    op = "estimate_trigger_smart_contract_energy",
-                    code = ret.code,
-                    sim_message = %msg,
-                    "estimator returned unsuccessful result; treating as estimator failure"
-                );
-                last_err = Some(anyhow::anyhow!(
-                    "estimate_energy unsuccessful: code={} msg={}",
-                    ret.code,
-                    msg
-                ));
-                continue;
-            }
-
-            let energy_required =
-                u64::try_from(resp.energy_required).context("energy_required out of range")?;
-            return Ok(energy_required);
-        }
-
-        Err(last_err.unwrap_or_else(|| {
-            anyhow::anyhow!(
-                "all TRON_GRPC_URLS endpoints failed for op=estimate_trigger_smart_contract_energy"
-            )
-        }))
-    }
-
-    async fn ensure_tron_connected(&self, idx: usize, force_reconnect: bool) -> Result<()> {
-        let len = self.grpc_urls.len();
-        if len == 0 {
-            anyhow::bail!("no TRON_GRPC_URLS configured");
-        }
-        let idx = idx % len;
-
-        let cur = *self.grpc_url_cursor.lock().await;
-        if idx == cur && !force_reconnect {
-            return Ok(());
-        }
+                };
+                Box::pin(async move {
+                    let resp = grpc.estimate_energy(req).await?;
+
+                    // Tron returns Ok with `result.result=false` and `energy_required=0` when
+                    // the simulator hits its CPU budget (OutOfTimeException). Callers that treat
+                    // the returned u64 as authoritative would silently underestimate — bypassing
+                    // the splitter's energy_limit cap and broadcasting the full receiver set.
+                    // Surface estimator failure as an Err so the splitter falls back to its
+                    // binary-search path, which treats Err as "too big" and shrinks.
+                    if let Some(ret) = &resp.result
+                        && !ret.result
+                    {
+                        let msg = String::from_utf8_lossy(&ret.message);
+                        tracing::warn!(
+                            op = "estimate_trigger_smart_contract_energy",
+                            code = ret.code,
+                            sim_message = %msg,
+                            "estimator returned unsuccessful result; treating as estimator failure"
+                        );
+                        anyhow::bail!(
+                            "estimate_energy unsuccessful: code={} msg={}",
+                            ret.code,
+                            msg
+                        );
+                    }
 
-        let url = self.grpc_urls[idx].clone();
-        let grpc = TronGrpc::connect(
-            &url,
-            self.grpc_api_key.as_deref(),
-            &self.grpc_api_key_header,
-        )
-        .await
-        .with_context(|| format!("connect TRON gRPC: {url}"))?;
-        {
-            let mut guard = self.active_grpc.lock().await;
-            *guard = grpc;
-        }
-        *self.grpc_url_cursor.lock().await = idx;
-        tracing::info!(tron_grpc = %url, "switched relayer Tron write endpoint");
-        Ok(())
+                    u64::try_from(resp.energy_required).context("energy_required out of range")
+                })
+            })
+            .await
     }
 
     async fn broadcast_trigger_smart_contract_managed(
         &self,
-        grpc: &mut TronGrpc,
+        grpc: &mut TronClient,
         state: &mut RelayerState,
         contract: TronAddress,
         data: Vec<u8>,
@@ -698,6 +642,7 @@ impl TronExecutor {
         // Cover any energy shortfall from rental providers. When `require_energy_rental` is set,
         // sub-minimum shortfalls still rent (rounded up to the provider minimum), and if rental
         // fails the broadcast is aborted instead of burning wallet TRX.
+        let mut staked_lease = None;
         if !self.energy_rental.is_empty() {
             let res = grpc
                 .get_account_resource(self.wallet.address().prefixed_bytes().to_vec())
@@ -759,12 +704,9 @@ impl TronExecutor {
             );
 
             if should_attempt_rental {
-                // Layer 3 breaker: rental-rate cap. Refuses to initiate a rental if we've
-                // already fired too many this hour/day, or if we're in post-trip cooldown.
-                // This is the guard that makes the 200-rentals-in-10-min scenario impossible.
-                self.enforce_rental_budget(state)?;
-
                 let rent_amount = shortfall.max(MIN_ENERGY_RENTAL_AMOUNT);
+                // Energy to wait for before broadcasting; less when an order fills partially.
+                let mut energy_target = signed.energy_required;
                 let addr = self.wallet.address();
                 let addr_hex41 = format!("0x{}", hex::encode(addr.prefixed_bytes()));
                 let addr_evm_hex = format!("0x{}", hex::encode(addr.evm().as_slice()));
@@ -777,98 +719,230 @@ impl TronExecutor {
                     address_hex41: addr_hex41,
                     address_evm_hex: addr_evm_hex,
                     txid: Some(txid_hex),
+                    order_id: None,
                 };
 
+                // Settle orders left pending by earlier broadcasts in the background; the budget
+                // below sees whatever earlier rounds found failed or refunded.
+                self.spawn_reconcile_rental_orders(&state.rental_orders);
+
+                // Layer 3 breaker: rental-rate cap. Refuses to initiate a rental if we've
+                // already spent on too many this hour/day, or if we're in post-trip cooldown.
+                // This is the guard that makes the 200-rentals-in-10-min scenario impossible.
+                let budget_order = self.enforce_rental_budget(state, rent_amount)?;
+
+                // Rank sources cheapest-first against burning TRX for the shortfall. With
+                // `require_energy_rental` burning is not an option, so every available source
+                // stays in the running regardless of price.
+                let burn_sun = match self.fee_limit_policy.fees {
+                    Some(fees) if !self.require_energy_rental => {
+                        quote_fee_limit_sun(shortfall, 0, fees)
+                    }
+                    _ => u64::MAX,
+                };
+                let fees = self.fee_limit_policy.fees.unwrap_or(ChainFees {
+                    energy_fee_sun_per_energy: 0,
+                    tx_fee_sun_per_byte: 0,
+                });
+                let quotes = quote_energy_sources(&self.energy_rental, &*grpc, &ctx, fees).await;
+                for (source, quote) in self.energy_rental.iter().zip(&quotes) {
+                    if let EnergyQuote::Sun(sun) = *quote {
+                        self.telemetry.energy_quote_sun(source.name(), sun);
+                    }
+                }
+
                 let len = self.energy_rental.len();
                 let start_cursor = state.energy_rental_cursor;
-                let order = rental_try_indices(start_cursor, len);
+                let order =
+                    rank_energy_sources(&rental_try_indices(start_cursor, len), &quotes, burn_sun);
+                tracing::info!(
+                    burn_sun,
+                    quotes = ?quotes,
+                    order = ?order,
+                    "energy source ranking"
+                );
 
                 let mut ok = false;
+                let mut errored = false;
+                let mut rental_order = None;
                 let mut rental_txids = Vec::new();
-                for (attempt, idx) in order.into_iter().enumerate() {
+                for idx in order {
                     let p = &self.energy_rental[idx];
-                    match p.rent(&ctx).await {
-                        Ok(attempt_res) if attempt_res.ok => {
+                    state.energy_rental_cursor = rental_cursor_after_attempts(idx, len, 1);
+                    match p.acquire(grpc, &ctx).await {
+                        Ok(lease) if lease.attempt.ok => {
+                            let attempt_res = &lease.attempt;
                             rental_txids =
                                 parse_rental_order_txids(attempt_res.order_id.as_deref());
+                            let cost_sun = attempt_res.cost_sun.or(match quotes[idx] {
+                                EnergyQuote::Sun(sun) => Some(sun),
+                                _ => None,
+                            });
+                            self.telemetry.energy_source_decision(p.name(), cost_sun);
                             tracing::info!(
                                 provider = %attempt_res.provider,
                                 order_id = attempt_res.order_id.as_deref().unwrap_or(""),
                                 parsed_rental_txids = rental_txids.len(),
                                 energy = rent_amount,
+                                cost_sun,
+                                burn_sun,
                                 "energy rental ok"
                             );
-                            state.energy_rental_cursor =
-                                rental_cursor_after_attempts(start_cursor, len, attempt + 1);
+                            if lease.needs_release() {
+                                // Our own stake: nothing spent, so nothing to budget.
+                                state.rental_orders.remove(budget_order);
+                                staked_lease = Some((p.clone(), lease));
+                            } else {
+                                state.rental_orders.accepted(
+                                    budget_order,
+                                    p.name(),
+                                    attempt_res.order_id.clone(),
+                                    cost_sun,
+                                    RentalOrderStatus::Pending,
+                                    &ctx,
+                                );
+                                rental_order = attempt_res
+                                    .order_id
+                                    .clone()
+                                    .map(|order_id| (p.clone(), order_id));
+                            }
                             ok = true;
                             break;
                         }
-                        Ok(attempt_res) => {
+                        Ok(lease) => {
                             tracing::warn!(
-                                provider = %attempt_res.provider,
-                                err = attempt_res.error.as_deref().unwrap_or("rental failed"),
+                                provider = %lease.attempt.provider,
+                                err = lease.attempt.error.as_deref().unwrap_or("rental failed"),
                                 "energy rental failed; trying next provider"
                             );
-                            state.energy_rental_cursor =
-                                rental_cursor_after_attempts(start_cursor, len, attempt + 1);
                         }
                         Err(err) => {
+                            errored = true;
                             tracing::warn!(provider = %p.name(), err = %err, "energy rental errored; trying next provider");
-                            state.energy_rental_cursor =
-                                rental_cursor_after_attempts(start_cursor, len, attempt + 1);
                         }
                     }
                 }
 
+                if ok && rental_order.is_none() && staked_lease.is_none() {
+                    // No order id to poll; take the provider's acceptance at its word.
+                    state
+                        .rental_orders
+                        .set_status(budget_order, RentalOrderStatus::Delegated);
+                } else if !ok && !errored {
+                    // Every provider explicitly declined, so nothing was spent. A transport error
+                    // leaves the outcome unknown and the reservation keeps counting.
+                    state
+                        .rental_orders
+                        .set_status(budget_order, RentalOrderStatus::Failed);
+                }
+
                 if !ok {
                     if self.require_energy_rental {
                         anyhow::bail!(
-                            "TRON_REQUIRE_ENERGY_RENTAL=true and all {} rental provider(s) failed; aborting broadcast to preserve wallet TRX (energy_required={}, energy_available={}, shortfall={})",
+                            "TRON_REQUIRE_ENERGY_RENTAL=true and all {} energy source(s) failed or were unavailable; aborting broadcast to preserve wallet TRX (energy_required={}, energy_available={}, shortfall={})",
                             self.energy_rental.len(),
                             signed.energy_required,
                             energy_available,
                             shortfall,
                         );
                     }
+                    // Without chain fees the burn cost is unknown (`u64::MAX` sentinel).
+                    self.telemetry
+                        .energy_source_decision("burn", (burn_sun != u64::MAX).then_some(burn_sun));
                     tracing::warn!(
                         energy = rent_amount,
-                        "all energy rental providers failed; falling back to paying TRX fees"
+                        burn_sun,
+                        "no energy source covered the shortfall cheaper than burning; falling back to paying TRX fees"
                     );
-                } else if !self.energy_rental_confirm_max_wait.is_zero() {
-                    if rental_txids.is_empty() {
-                        if self.require_energy_rental {
+                } else if let Some((source, order_id)) = &rental_order {
+                    let status = wait_for_rental_order_settled(
+                        source,
+                        &ctx,
+                        order_id,
+                        self.energy_rental_confirm_max_wait,
+                    )
+                    .await
+                    // Untracked orders are taken at their word, as before status polling.
+                    .unwrap_or(RentalOrderStatus::Delegated);
+                    state.rental_orders.set_status(budget_order, status);
+                    if !status.is_spend() {
+                        anyhow::bail!(
+                            "energy rental order {order_id} from {} ended {status:?}; refusing to broadcast without the energy",
+                            source.name()
+                        );
+                    }
+                    if let RentalOrderStatus::PartiallyFilled { filled_energy } = status {
+                        let filled = filled_energy.unwrap_or(0);
+                        if self.require_energy_rental && filled < shortfall {
                             anyhow::bail!(
-                                "TRON_REQUIRE_ENERGY_RENTAL=true but rental provider returned no parseable txid/order_id; refusing to broadcast because rental inclusion cannot be proven"
+                                "TRON_REQUIRE_ENERGY_RENTAL=true and energy rental order {order_id} from {} filled only {filled} of {rent_amount} energy (shortfall={shortfall}); aborting broadcast to preserve wallet TRX",
+                                source.name()
                             );
                         }
                         tracing::warn!(
-                            "rental provider returned no parseable txid/order_id; falling back to resource-only settlement check"
+                            provider = %source.name(),
+                            order_id = %order_id,
+                            filled_energy = ?filled_energy,
+                            energy = rent_amount,
+                            shortfall,
+                            "energy rental order partially filled; burning TRX for the rest"
                         );
-                    } else {
-                        wait_for_rental_txs_confirmed(
+                        energy_target = energy_available
+                            .saturating_add(filled)
+                            .min(signed.energy_required);
+                    }
+                }
+
+                if ok && !self.energy_rental_confirm_max_wait.is_zero() {
+                    let settled = async {
+                        if rental_txids.is_empty() {
+                            if self.require_energy_rental {
+                                anyhow::bail!(
+                                    "TRON_REQUIRE_ENERGY_RENTAL=true but rental provider returned no parseable txid/order_id; refusing to broadcast because rental inclusion cannot be proven"
+                                );
+                            }
+                            tracing::warn!(
+                                "rental provider returned no parseable txid/order_id; falling back to resource-only settlement check"
+                            );
+                        } else {
+                            wait_for_rental_txs_confirmed(
+                                grpc,
+                                &rental_txids,
+                                self.energy_rental_confirm_max_wait,
+                            )
+                            .await?;
+                        }
+
+                        let addr = self.wallet.address().prefixed_bytes().to_vec();
+                        wait_for_energy_available_after_rental(
                             grpc,
-                            &rental_txids,
+                            addr,
+                            energy_target,
                             self.energy_rental_confirm_max_wait,
                         )
-                        .await?;
+                        .await
+                    }
+                    .await;
+                    if let Err(err) = settled {
+                        release_staked_energy(grpc, staked_lease, None);
+                        return Err(err);
                     }
-
-                    let addr = self.wallet.address().prefixed_bytes().to_vec();
-                    wait_for_energy_available_after_rental(
-                        grpc,
-                        addr,
-                        signed.energy_required,
-                        self.energy_rental_confirm_max_wait,
-                    )
-                    .await?;
                 }
             }
         } else if self.require_energy_rental {
             anyhow::bail!("TRON_REQUIRE_ENERGY_RENTAL=true but no rental providers are configured",);
         }
 
-        let ret = grpc.broadcast_transaction(signed.tx).await?;
+        let ret = match grpc.broadcast_transaction(signed.tx).await {
+            Ok(ret) => ret,
+            Err(err) => {
+                // The tx may still have reached the network; let the lease wait for it.
+                release_staked_energy(grpc, staked_lease, Some(signed.txid));
+                return Err(err);
+            }
+        };
         if !ret.result {
+            release_staked_energy(grpc, staked_lease, None);
             tracing::warn!(
                 tron_return_code = ret.code,
                 tron_return_message_hex = %format!("0x{}", hex::encode(&ret.message)),
@@ -882,6 +956,7 @@ impl TronExecutor {
             );
         }
 
+        release_staked_energy(grpc, staked_lease, Some(signed.txid));
         Ok(signed.txid)
     }
 
@@ -945,24 +1020,16 @@ impl TronExecutor {
     }
 
     /// Enforces the rental-rate breaker. On trip, records the cooldown in `state` and bails.
-    /// Called immediately before any attempt to contact a rental provider.
-    fn enforce_rental_budget(&self, state: &mut RelayerState) -> Result<()> {
+    /// Called immediately before any attempt to contact a rental provider; on success returns
+    /// the id of the order reserved in `state.rental_orders` for this attempt.
+    fn enforce_rental_budget(&self, state: &mut RelayerState, energy: u64) -> Result<u64> {
         let now = Instant::now();
-
-        // Evict attempts older than 24h so the deque doesn't grow without bound.
-        let day = Duration::from_secs(24 * 3600);
-        while state
-            .rental_attempts
-            .front()
-            .is_some_and(|t| now.duration_since(*t) > day)
-        {
-            state.rental_attempts.pop_front();
-        }
+        let spent = state.rental_orders.spend_instants(now);
 
         let decision = evaluate_rental_budget(
             now,
             state.rental_paused_until,
-            &state.rental_attempts,
+            &spent,
             self.rental_cap_per_hour,
             self.rental_cap_per_day,
         );
@@ -971,12 +1038,12 @@ impl TronExecutor {
             RentalBudgetDecision::Cooldown { remaining_secs } => {
                 tracing::error!(
                     cooldown_remaining_secs = remaining_secs,
-                    recent_rental_attempts = state.rental_attempts.len(),
+                    recent_rental_orders = spent.len(),
                     "rental breaker tripped; refusing to initiate another rental"
                 );
                 anyhow::bail!(
-                    "rental breaker in cooldown for {remaining_secs}s more (recent attempts: {})",
-                    state.rental_attempts.len()
+                    "rental breaker in cooldown for {remaining_secs}s more (recent orders: {})",
+                    spent.len()
                 );
             }
             RentalBudgetDecision::Trip { reason } => {
@@ -984,7 +1051,7 @@ impl TronExecutor {
                 state.rental_paused_until = Some(now + cooldown);
                 tracing::error!(
                     reason,
-                    recent_rental_attempts = state.rental_attempts.len(),
+                    recent_rental_orders = spent.len(),
                     cap_per_hour = self.rental_cap_per_hour,
                     cap_per_day = self.rental_cap_per_day,
                     cooldown_secs = cooldown.as_secs(),
@@ -996,20 +1063,69 @@ impl TronExecutor {
                 );
             }
             RentalBudgetDecision::Proceed => {
-                // Record the attempt we're about to make. Done before calling providers so a
-                // provider error still counts toward the cap — a flapping provider can't
-                // bypass the cap by failing every call.
-                state.rental_attempts.push_back(now);
+                // Reserve the order we're about to place. Done before calling providers so it
+                // counts until we learn otherwise: explicit rejections, failures and refunds
+                // release it, but a provider erroring mid-request still counts, so a flapping
+                // provider can't bypass the cap.
+                let order = state.rental_orders.reserve(energy);
                 tracing::debug!(
-                    recent_rental_attempts = state.rental_attempts.len(),
+                    recent_rental_orders = spent.len() + 1,
                     cap_per_hour = self.rental_cap_per_hour,
                     cap_per_day = self.rental_cap_per_day,
-                    "rental attempt recorded against budget"
+                    "rental order reserved against budget"
                 );
-                Ok(())
+                Ok(order)
             }
         }
     }
+
+    /// Polls providers, in the background, for orders still pending from earlier broadcasts.
+    /// Each order is polled with the context it was placed with. Best-effort: orders that
+    /// can't be checked stay pending and keep counting until they age out. At most one round
+    /// runs at a time.
+    fn spawn_reconcile_rental_orders(&self, orders: &RentalOrderHistory) {
+        let Ok(running) = self.rental_reconcile.clone().try_lock_owned() else {
+            return;
+        };
+        let pending = orders.pending();
+        if pending.is_empty() {
+            return;
+        }
+        let (orders, sources) = (orders.clone(), self.energy_rental.clone());
+        tokio::spawn(async move {
+            let _running = running;
+            for order in pending {
+                let (Some(provider), Some(order_id), Some(ctx)) =
+                    (&order.provider, &order.order_id, &order.ctx)
+                else {
+                    continue;
+                };
+                let Some(source) = sources.iter().find(|s| s.name() == provider) else {
+                    continue;
+                };
+                match source.order_status(ctx, order_id).await {
+                    Ok(Some(status)) if status != order.status => {
+                        tracing::info!(
+                            provider = %provider,
+                            order_id = %order_id,
+                            status = ?status,
+                            "energy rental order status changed"
+                        );
+                        orders.set_status(order.id, status);
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        tracing::debug!(
+                            provider = %provider,
+                            order_id = %order_id,
+                            err = %format!("{err:#}"),
+                            "failed to poll energy rental order status"
+                        );
+                    }
+                }
+            }
+        });
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -1081,6 +1197,18 @@ fn evaluate_rental_budget(
     RentalBudgetDecision::Proceed
 }
 
+/// Hands a staked energy delegation back once `txid` confirms, or right away if the tx never
+/// went out. Rental leases need no release and never reach here.
+fn release_staked_energy(
+    grpc: &TronClient,
+    lease: Option<(EnergySource, EnergyLease)>,
+    txid: Option<[u8; 32]>,
+) {
+    if let Some((source, lease)) = lease {
+        source.spawn_release(grpc.clone(), lease, txid);
+    }
+}
+
 fn parse_rental_order_txids(order_id: Option<&str>) -> Vec<[u8; 32]> {
     let Some(order_id) = order_id else {
         return Vec::new();
@@ -1107,7 +1235,7 @@ fn parse_rental_order_txids(order_id: Option<&str>) -> Vec<[u8; 32]> {
     out
 }
 
-async fn tron_head_block_for_rental_wait(grpc: &mut TronGrpc) -> Result<u64> {
+async fn tron_head_block_for_rental_wait(grpc: &mut TronClient) -> Result<u64> {
     let b = grpc.get_now_block2().await.context("get now block")?;
     let raw = b
         .block_header
@@ -1117,8 +1245,67 @@ async fn tron_head_block_for_rental_wait(grpc: &mut TronGrpc) -> Result<u64> {
     u64::try_from(raw.number).context("now block number out of range")
 }
 
+/// Polls `order_id`'s status until the provider reports a terminal state or `max_wait`
+/// elapses (then it is still `Pending`). `None` when the provider can't report order status.
+async fn wait_for_rental_order_settled(
+    source: &EnergySource,
+    ctx: &RentalContext,
+    order_id: &str,
+    max_wait: Duration,
+) -> Option<RentalOrderStatus> {
+    let start = tokio::time::Instant::now();
+    let mut delay = Duration::from_millis(250);
+    let max_delay = Duration::from_secs(2);
+    let mut tries: u32 = 0;
+
+    loop {
+        tries = tries.saturating_add(1);
+        match source.order_status(ctx, order_id).await {
+            Ok(None) => return None,
+            Ok(Some(status)) if status.is_terminal() => {
+                tracing::info!(
+                    provider = %source.name(),
+                    order_id,
+                    status = ?status,
+                    tries,
+                    elapsed_ms = start.elapsed().as_millis() as u64,
+                    "energy rental order settled"
+                );
+                return Some(status);
+            }
+            Ok(Some(_)) => {}
+            Err(err) => {
+                tracing::debug!(
+                    provider = %source.name(),
+                    order_id,
+                    err = %format!("{err:#}"),
+                    tries,
+                    "failed to poll energy rental order status"
+                );
+            }
+        }
+
+        if start.elapsed() >= max_wait {
+            tracing::warn!(
+                provider = %source.name(),
+                order_id,
+                tries,
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "energy rental order still pending; relying on the on-chain settlement checks"
+            );
+            return Some(RentalOrderStatus::Pending);
+        }
+
+        tokio::time::sleep(delay).await;
+        delay = delay.saturating_mul(2);
+        if delay > max_delay {
+            delay = max_delay;
+        }
+    }
+}
+
 async fn wait_for_rental_txs_confirmed(
-    grpc: &mut TronGrpc,
+    grpc: &mut TronClient,
     rental_txids: &[[u8; 32]],
     max_wait: Duration,
 ) -> Result<()> {
@@ -1212,7 +1399,7 @@ async fn wait_for_rental_txs_confirmed(
 }
 
 async fn wait_for_energy_available_after_rental(
-    grpc: &mut TronGrpc,
+    grpc: &mut TronClient,
     address: Vec<u8>,
     energy_required: u64,
     max_wait: Duration,
@@ -1479,4 +1666,190 @@ mod tests {
             RentalBudgetDecision::Proceed
         );
     }
+
+    fn mock_executor(nodes: &[&tron_mock::MockTronNode], wallet: Arc<TronWallet>) -> TronExecutor {
+        let clients = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let grpc = tron_mock::MockTronServer::connect_in_memory(node).unwrap();
+                (format!("tron-mock-{i}"), TronClient::Grpc(grpc))
+            })
+            .collect();
+        let options = tron::TronGrpcPoolOptions {
+            hedge_after: None,
+            head_probe_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        TronExecutor::new(
+            TronGrpcPool::from_clients(clients, options, None).unwrap(),
+            wallet,
+            Vec::new(),
+            Duration::ZERO,
+            false,
+            0,
+            0,
+            Duration::ZERO,
+            // Mock block timestamps are fixed in the past, so the staleness guard stays off.
+            Duration::ZERO,
+            true,
+            0,
+            ChainFees {
+                energy_fee_sun_per_energy: 100,
+                tx_fee_sun_per_byte: 1_000,
+            },
+            200_000,
+            100_000_000,
+            RelayerTelemetry::new(),
+        )
+    }
+
+    fn trc20_transfer_data(to: TronAddress, amount: u64) -> Vec<u8> {
+        let mut data = tron::SELECTOR_TRANSFER.to_vec();
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(to.evm().as_slice());
+        data.extend_from_slice(&U256::from(amount).to_be_bytes::<32>());
+        data
+    }
+
+    /// A producing mock node where `wallet` holds TRX and 1000 units of `token`.
+    fn funded_mock_node(wallet: &TronWallet, token: TronAddress) -> tron_mock::MockTronNode {
+        let node = tron_mock::MockTronNode::new();
+        node.produce_block();
+        node.set_auto_produce(true);
+        node.set_account(
+            wallet.address(),
+            tron_mock::MockAccount {
+                balance_sun: 200_000_000,
+                ..Default::default()
+            },
+        );
+        node.set_trc20_balance(token, wallet.address(), U256::from(1_000u64));
+        node
+    }
+
+    #[tokio::test]
+    async fn broadcast_settles_on_mock_node_and_stops_at_preflight_revert() {
+        let wallet = Arc::new(TronWallet::new([0x11u8; 32]).unwrap());
+        let token = TronAddress::from_evm(Address::repeat_byte(0x22));
+        let to = TronAddress::from_evm(Address::repeat_byte(0x33));
+        let node = funded_mock_node(&wallet, token);
+
+        let executor = mock_executor(&[&node], wallet);
+        let mut state = RelayerState::empty();
+
+        let data = trc20_transfer_data(to, 400);
+        let txid = executor
+            .broadcast_trigger_smart_contract(&mut state, "test", token, data.clone(), 0)
+            .await
+            .unwrap();
+        assert!(
+            node.transaction_info(txid)
+                .is_some_and(|info| info.block_number > 0)
+        );
+        assert_eq!(node.trc20_balance(token, to), U256::from(400u64));
+
+        node.set_revert(token, "paused");
+        let err = executor
+            .broadcast_trigger_smart_contract(&mut state, "test", token, data, 0)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("preflight simulation reports revert"),
+            "{err:#}"
+        );
+        assert!(node.pending_txids().is_empty());
+        assert_eq!(node.trc20_balance(token, to), U256::from(400u64));
+    }
+
+    #[tokio::test]
+    async fn broadcast_fails_over_to_a_healthy_mock_node() {
+        let wallet = Arc::new(TronWallet::new([0x11u8; 32]).unwrap());
+        let token = TronAddress::from_evm(Address::repeat_byte(0x22));
+        let to = TronAddress::from_evm(Address::repeat_byte(0x33));
+        let down = funded_mock_node(&wallet, token);
+        down.set_unavailable(true);
+        let up = funded_mock_node(&wallet, token);
+
+        let executor = mock_executor(&[&down, &up], wallet);
+        let mut state = RelayerState::empty();
+        let txid = executor
+            .broadcast_trigger_smart_contract(
+                &mut state,
+                "test",
+                token,
+                trc20_transfer_data(to, 400),
+                0,
+            )
+            .await
+            .unwrap();
+        assert!(up.transaction_info(txid).is_some());
+        assert_eq!(up.trc20_balance(token, to), U256::from(400u64));
+        assert_eq!(down.trc20_balance(token, to), U256::ZERO);
+        assert_eq!(executor.tron.preferred_index(), 1);
+    }
+
+    fn connect_mock(node: &tron_mock::MockTronNode) -> TronClient {
+        TronClient::Grpc(tron_mock::MockTronServer::connect_in_memory(node).unwrap())
+    }
+
+    #[tokio::test]
+    async fn rental_wait_returns_once_delegation_is_a_block_deep() {
+        let node = tron_mock::MockTronNode::new();
+        node.produce_block();
+        let rental_txid = node.submit(tron::protocol::Transaction {
+            raw_data: Some(tron::protocol::transaction::Raw {
+                timestamp: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let mut grpc = connect_mock(&node);
+
+        let err =
+            wait_for_rental_txs_confirmed(&mut grpc, &[rental_txid], Duration::from_millis(300))
+                .await
+                .unwrap_err();
+        assert!(err.to_string().contains("included 0/1"), "{err:#}");
+
+        node.produce_blocks(2);
+        wait_for_rental_txs_confirmed(&mut grpc, &[rental_txid], Duration::from_secs(5))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn energy_wait_sees_delegated_energy() {
+        let node = tron_mock::MockTronNode::new();
+        let owner = TronAddress::from_evm(Address::repeat_byte(0x44));
+        node.set_account(
+            owner,
+            tron_mock::MockAccount {
+                energy_limit: 50_000,
+                energy_used: 10_000,
+                ..Default::default()
+            },
+        );
+        let mut grpc = connect_mock(&node);
+        let address = owner.prefixed_bytes().to_vec();
+
+        wait_for_energy_available_after_rental(
+            &mut grpc,
+            address.clone(),
+            40_000,
+            Duration::from_secs(2),
+        )
+        .await
+        .unwrap();
+        assert!(
+            wait_for_energy_available_after_rental(
+                &mut grpc,
+                address,
+                40_001,
+                Duration::from_millis(200)
+            )
+            .await
+            .is_err()
+        );
+    }
 }
@@ -0,0 +1,353 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tron::{RentalContext, RentalOrderStatus};
+
+/// Orders older than this no longer count toward any budget window and are dropped.
+const RETENTION: Duration = Duration::from_secs(24 * 3600);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RentalOrder {
+    /// Local sequence number; provider order ids are optional and not unique across providers.
+    pub id: u64,
+    pub created_at_unix_secs: u64,
+    /// Unset until a provider accepts the order.
+    pub provider: Option<String>,
+    pub order_id: Option<String>,
+    pub energy: u64,
+    pub cost_sun: Option<u64>,
+    pub status: RentalOrderStatus,
+    /// What the order was placed with, so its status is polled with the same placeholders.
+    /// Unset for orders recorded before this was kept; those are not polled.
+    #[serde(default)]
+    pub ctx: Option<RentalContext>,
+}
+
+/// Rolling 24h history of energy rental orders backing the rental budget.
+///
+/// With a `path`, the history is rewritten to disk on every change and reloaded on startup, so
+/// a restart neither forgets spend nor resets the budget. Writes run on the blocking pool.
+///
+/// Cheap to clone; clones share the same history.
+#[derive(Clone)]
+pub struct RentalOrderHistory {
+    path: Option<PathBuf>,
+    inner: Arc<Mutex<Orders>>,
+    /// Sequence number of the snapshot last written to `path`.
+    written: Arc<Mutex<u64>>,
+}
+
+struct Orders {
+    orders: VecDeque<RentalOrder>,
+    next_id: u64,
+    /// Bumped on every change, so a slow write never overwrites a newer snapshot.
+    seq: u64,
+}
+
+impl RentalOrderHistory {
+    pub fn in_memory() -> Self {
+        Self::new(None, VecDeque::new())
+    }
+
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let orders: VecDeque<RentalOrder> = match std::fs::read(&path) {
+            Ok(body) => serde_json::from_slice(&body)
+                .with_context(|| format!("parse rental order history {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => VecDeque::new(),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("read rental order history {}", path.display()));
+            }
+        };
+        let history = Self::new(Some(path), orders);
+        history.evict_expired(&mut history.lock(), unix_now_secs());
+        Ok(history)
+    }
+
+    fn new(path: Option<PathBuf>, orders: VecDeque<RentalOrder>) -> Self {
+        let next_id = orders.iter().map(|o| o.id + 1).max().unwrap_or(0);
+        Self {
+            path,
+            inner: Arc::new(Mutex::new(Orders {
+                orders,
+                next_id,
+                seq: 0,
+            })),
+            written: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Records a rental we are about to attempt. It counts as spend until resolved otherwise.
+    pub fn reserve(&self, energy: u64) -> u64 {
+        let mut inner = self.lock();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.orders.push_back(RentalOrder {
+            id,
+            created_at_unix_secs: unix_now_secs(),
+            provider: None,
+            order_id: None,
+            energy,
+            cost_sun: None,
+            status: RentalOrderStatus::Pending,
+            ctx: None,
+        });
+        self.persist(&mut inner);
+        id
+    }
+
+    /// Attaches the provider's accepted order, placed with `ctx`, to reservation `id`.
+    pub fn accepted(
+        &self,
+        id: u64,
+        provider: &str,
+        order_id: Option<String>,
+        cost_sun: Option<u64>,
+        status: RentalOrderStatus,
+        ctx: &RentalContext,
+    ) {
+        let mut inner = self.lock();
+        if let Some(order) = inner.orders.iter_mut().find(|o| o.id == id) {
+            order.provider = Some(provider.to_string());
+            order.order_id = order_id;
+            order.cost_sun = cost_sun;
+            order.status = status;
+            order.ctx = Some(ctx.clone());
+            self.persist(&mut inner);
+        }
+    }
+
+    pub fn set_status(&self, id: u64, status: RentalOrderStatus) {
+        let mut inner = self.lock();
+        if let Some(order) = inner.orders.iter_mut().find(|o| o.id == id)
+            && order.status != status
+        {
+            order.status = status;
+            self.persist(&mut inner);
+        }
+    }
+
+    /// Drops reservation `id` entirely, e.g. when the energy came from our own stake.
+    pub fn remove(&self, id: u64) {
+        let mut inner = self.lock();
+        let len = inner.orders.len();
+        inner.orders.retain(|o| o.id != id);
+        if inner.orders.len() != len {
+            self.persist(&mut inner);
+        }
+    }
+
+    /// Accepted orders whose outcome the provider may still report.
+    pub fn pending(&self) -> Vec<RentalOrder> {
+        self.lock()
+            .orders
+            .iter()
+            .filter(|o| {
+                o.status == RentalOrderStatus::Pending
+                    && o.provider.is_some()
+                    && o.order_id.is_some()
+                    && o.ctx.is_some()
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// When each order that cost (or may still cost) us money was placed, oldest first, as
+    /// `Instant`s relative to `now`.
+    pub fn spend_instants(&self, now: Instant) -> VecDeque<Instant> {
+        let now_unix = unix_now_secs();
+        let mut inner = self.lock();
+        self.evict_expired(&mut inner, now_unix);
+        inner
+            .orders
+            .iter()
+            .filter(|o| o.status.is_spend())
+            .map(|o| {
+                let age = Duration::from_secs(now_unix.saturating_sub(o.created_at_unix_secs));
+                now.checked_sub(age).unwrap_or(now)
+            })
+            .collect()
+    }
+
+    fn evict_expired(&self, inner: &mut Orders, now_unix: u64) {
+        let len = inner.orders.len();
+        inner
+            .orders
+            .retain(|o| now_unix.saturating_sub(o.created_at_unix_secs) <= RETENTION.as_secs());
+        if inner.orders.len() != len {
+            self.persist(inner);
+        }
+    }
+
+    /// Writes a snapshot of `inner` on the blocking pool (inline outside a runtime).
+    fn persist(&self, inner: &mut Orders) {
+        let Some(path) = self.path.clone() else {
+            return;
+        };
+        inner.seq += 1;
+        let seq = inner.seq;
+        let body = match serde_json::to_vec_pretty(&inner.orders) {
+            Ok(body) => body,
+            Err(err) => {
+                tracing::warn!(err = %err, "failed to serialize rental order history");
+                return;
+            }
+        };
+        let written = self.written.clone();
+        let write = move || {
+            let mut written = written.lock().unwrap_or_else(PoisonError::into_inner);
+            if *written >= seq {
+                return;
+            }
+            if let Err(err) = write_atomic(&path, &body) {
+                tracing::warn!(
+                    path = %path.display(),
+                    err = %format!("{err:#}"),
+                    "failed to persist rental order history"
+                );
+                return;
+            }
+            *written = seq;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(rt) => drop(rt.spawn_blocking(write)),
+            Err(_) => write(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Orders> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn write_atomic(path: &Path, body: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("create rental history dir {}", parent.display()))?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, body).with_context(|| format!("write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("rename to {}", path.display()))?;
+    Ok(())
+}
+
+fn unix_now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tron::RentalResourceKind;
+
+    fn ctx(amount: u64) -> RentalContext {
+        RentalContext {
+            resource: RentalResourceKind::Energy,
+            amount,
+            address_base58check: "T...".to_string(),
+            address_hex41: "0x41".to_string(),
+            address_evm_hex: "0x".to_string(),
+            txid: Some(format!("0x{}", "ab".repeat(32))),
+            order_id: None,
+        }
+    }
+
+    #[test]
+    fn only_spend_counts_toward_budget() {
+        let history = RentalOrderHistory::in_memory();
+        let failed = history.reserve(65_000);
+        let refunded = history.reserve(65_000);
+        let delegated = history.reserve(65_000);
+        let partial = history.reserve(65_000);
+        let _in_flight = history.reserve(65_000);
+
+        history.set_status(failed, RentalOrderStatus::Failed);
+        history.accepted(
+            refunded,
+            "p1",
+            Some("o1".to_string()),
+            Some(3_000_000),
+            RentalOrderStatus::Pending,
+            &ctx(65_000),
+        );
+        history.accepted(
+            delegated,
+            "p1",
+            Some("o2".to_string()),
+            None,
+            RentalOrderStatus::Delegated,
+            &ctx(65_000),
+        );
+        history.accepted(
+            partial,
+            "p1",
+            Some("o3".to_string()),
+            None,
+            RentalOrderStatus::PartiallyFilled {
+                filled_energy: Some(30_000),
+            },
+            &ctx(65_000),
+        );
+        assert_eq!(history.pending().len(), 1);
+        assert_eq!(history.pending()[0].ctx, Some(ctx(65_000)));
+        history.set_status(refunded, RentalOrderStatus::Refunded);
+
+        assert_eq!(history.spend_instants(Instant::now()).len(), 3);
+        assert!(history.pending().is_empty());
+    }
+
+    #[test]
+    fn history_round_trips_through_disk() {
+        let path = std::env::temp_dir().join(format!(
+            "relayer-rental-orders-{}-{}.json",
+            std::process::id(),
+            unix_now_secs()
+        ));
+        let history = RentalOrderHistory::open(path.clone()).unwrap();
+        let a = history.reserve(65_000);
+        let b = history.reserve(70_000);
+        history.remove(a);
+        history.accepted(
+            b,
+            "p1",
+            Some("o1".to_string()),
+            None,
+            RentalOrderStatus::Pending,
+            &ctx(70_000),
+        );
+
+        let reopened = RentalOrderHistory::open(path.clone()).unwrap();
+        assert_eq!(reopened.lock().orders, history.lock().orders);
+        assert_eq!(reopened.pending()[0].ctx, Some(ctx(70_000)));
+        assert_eq!(reopened.reserve(1), b + 1);
+        assert_eq!(reopened.spend_instants(Instant::now()).len(), 2);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn orders_saved_without_a_context_load_but_are_not_polled() {
+        let json = serde_json::json!([{
+            "id": 3,
+            "created_at_unix_secs": unix_now_secs(),
+            "provider": "p1",
+            "order_id": "o1",
+            "energy": 65_000,
+            "cost_sun": null,
+            "status": "pending",
+        }]);
+        let orders: VecDeque<RentalOrder> = serde_json::from_value(json).unwrap();
+        let history = RentalOrderHistory::new(None, orders);
+        assert!(history.pending().is_empty());
+        assert_eq!(history.spend_instants(Instant::now()).len(), 1);
+        assert_eq!(history.reserve(1), 4);
+    }
+}
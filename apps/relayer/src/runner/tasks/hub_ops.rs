@@ -1,4 +1,4 @@
-use super::HubIntent;
+use super::{HubIntent, MAX_PRE_ENTITLE_BATCH, PreEntitleTarget};
 use anyhow::{Context, Result};
 use serde::Serialize;
 use untron_v3_bindings::untron_v3::UntronV3::{
@@ -9,15 +9,20 @@ use untron_v3_bindings::untron_v3::UntronV3::{
 use crate::evm::{IERC20, MultiSend, MultiSendTx, encode_multisend_transactions};
 use crate::indexer::RelayerHubState;
 use crate::runner::model::Plan;
-use crate::runner::util::{number_to_u256, parse_bytes32, parse_txid32};
+use crate::runner::util::{hub_proof_blocks, number_to_u256, parse_bytes32, parse_txid32};
 use crate::runner::{RelayerContext, RelayerState, Tick};
 use alloy::{
     primitives::{Address, FixedBytes, U256},
+    providers::Provider,
+    rpc::json_rpc::ErrorPayload,
+    rpc::types::eth::transaction::{TransactionInput, TransactionRequest},
     sol_types::SolCall,
 };
 use std::path::PathBuf;
 use std::time::Instant;
-use tron::{DecodedTrc20Call, TronAddress, decode_trc20_call_data, decode_trigger_smart_contract};
+use tron::{
+    DecodedTrc20Call, TronAddress, TronApi, decode_trc20_call_data, decode_trigger_smart_contract,
+};
 
 #[derive(Serialize)]
 struct RelayControllerDebugEvent {
@@ -173,7 +178,18 @@ pub async fn plan_pre_entitle(ctx: &RelayerContext, tick: &Tick) -> Result<Plan<
         None
     };
 
+    // Without multisend every preEntitle is its own userop, so there is nothing to batch.
+    let batch_limit = if ctx.cfg.hub.multisend.is_some() {
+        MAX_PRE_ENTITLE_BATCH
+    } else {
+        1
+    };
+    let mut targets: Vec<PreEntitleTarget> = Vec::new();
+
     for row in rows.into_iter().take(20) {
+        if targets.len() >= batch_limit {
+            break;
+        }
         let receiver_salt_hex = row.receiver_salt.as_deref().unwrap_or_default();
         let receiver_salt = parse_bytes32(
             row.receiver_salt
@@ -338,6 +354,17 @@ pub async fn plan_pre_entitle(ctx: &RelayerContext, tick: &Tick) -> Result<Plan<
                             number_to_u256(lease_id_num).context("parse expected_lease_id")?;
 
                         if principal >= raw_amount {
+                            // Keep the row subjective: send the objective batch collected so far
+                            // and pick this row up on the next tick.
+                            if !targets.is_empty() {
+                                tracing::debug!(
+                                    txid = %txid_hex,
+                                    receiver_salt = %receiver_salt_hex,
+                                    batched = targets.len(),
+                                    "pre-entitle decision: defer subjectivePreEntitle until the objective batch is sent"
+                                );
+                                break;
+                            }
                             tracing::info!(
                                 txid = %txid_hex,
                                 receiver_salt = %receiver_salt_hex,
@@ -400,13 +427,16 @@ pub async fn plan_pre_entitle(ctx: &RelayerContext, tick: &Tick) -> Result<Plan<
             block_number = row.block_number,
             "pre-entitle decision: preEntitle (objective proof)"
         );
-        return Ok(Plan::intent(HubIntent::PreEntitle {
+        targets.push(PreEntitleTarget {
             receiver_salt,
             txid,
-        }));
+        });
     }
 
-    Ok(Plan::none())
+    if targets.is_empty() {
+        return Ok(Plan::none());
+    }
+    Ok(Plan::intent(HubIntent::PreEntitle { targets }))
 }
 
 pub async fn plan_deposit_lp(
@@ -461,13 +491,14 @@ pub async fn execute_hub_intent(
         HubIntent::DepositLp { .. } => "depositLp",
         HubIntent::FillClaims { .. } => "fill",
     };
+    let spends_safe_funds = intent.spends_safe_funds();
 
     let (to, operation, data) = match intent {
         HubIntent::RelayControllerEventChain { proof_txid, events } => {
             let start = Instant::now();
             let tron_proof = ctx.tron_proof.clone();
             let bundle_res = ctx
-                .with_tron_read_retry("build_proof", |tron| {
+                .with_tron_proof_failover("build_proof", |tron| {
                     let tron_proof = tron_proof.clone();
                     Box::pin(async move { tron_proof.build(tron, proof_txid).await })
                 })
@@ -475,8 +506,7 @@ pub async fn execute_hub_intent(
             ctx.telemetry
                 .tron_proof_ms(bundle_res.is_ok(), start.elapsed().as_millis() as u64);
             let bundle = bundle_res?;
-            let blocks: [alloy::primitives::Bytes; 20] =
-                std::array::from_fn(|i| bundle.blocks[i].clone().into());
+            let blocks = hub_proof_blocks(&bundle)?;
 
             let data = relayControllerEventChainCall {
                 blocks,
@@ -517,32 +547,96 @@ pub async fn execute_hub_intent(
             }
             .abi_encode(),
         ),
-        HubIntent::PreEntitle {
-            receiver_salt,
-            txid,
-        } => {
+        HubIntent::PreEntitle { targets } => {
             let start = Instant::now();
             let tron_proof = ctx.tron_proof.clone();
-            let bundle_res = ctx
-                .with_tron_read_retry("build_proof", |tron| {
+            let txids: Vec<[u8; 32]> = targets.iter().map(|t| t.txid).collect();
+            let bundles_res = ctx
+                .with_tron_proof_failover("build_proofs", |tron| {
                     let tron_proof = tron_proof.clone();
-                    Box::pin(async move { tron_proof.build(tron, txid).await })
+                    let txids = txids.clone();
+                    Box::pin(async move { tron_proof.build_many(tron, &txids).await })
                 })
                 .await;
+            let bundles = match bundles_res {
+                Ok(bundles) => bundles,
+                Err(err) => {
+                    ctx.telemetry
+                        .tron_proof_ms(false, start.elapsed().as_millis() as u64);
+                    return Err(err);
+                }
+            };
+
+            // A failed proof only drops its own target; the rest of the batch still goes out.
+            let mut calls: Vec<(&PreEntitleTarget, Vec<u8>)> = Vec::with_capacity(targets.len());
+            let mut first_err = None;
+            for (target, bundle_res) in targets.iter().zip(bundles) {
+                let bundle = match bundle_res {
+                    Ok(bundle) => bundle,
+                    Err(err) => {
+                        tracing::warn!(
+                            txid = %format!("0x{}", hex::encode(target.txid)),
+                            receiver_salt = %target.receiver_salt,
+                            err = %format!("{err:#}"),
+                            "pre-entitle proof failed; dropping from batch"
+                        );
+                        first_err.get_or_insert(err);
+                        continue;
+                    }
+                };
+                calls.push((
+                    target,
+                    preEntitleCall {
+                        receiverSalt: target.receiver_salt,
+                        blocks: hub_proof_blocks(&bundle)?,
+                        encodedTx: bundle.encoded_tx.into(),
+                        proof: bundle.proof,
+                        index: bundle.index,
+                    }
+                    .abi_encode(),
+                ));
+            }
             ctx.telemetry
-                .tron_proof_ms(bundle_res.is_ok(), start.elapsed().as_millis() as u64);
-            let bundle = bundle_res?;
-            let blocks: [alloy::primitives::Bytes; 20] =
-                std::array::from_fn(|i| bundle.blocks[i].clone().into());
-            let data = preEntitleCall {
-                receiverSalt: receiver_salt,
-                blocks,
-                encodedTx: bundle.encoded_tx.into(),
-                proof: bundle.proof,
-                index: bundle.index,
+                .tron_proof_ms(!calls.is_empty(), start.elapsed().as_millis() as u64);
+            if let Some(err) = first_err.filter(|_| calls.is_empty()) {
+                return Err(err.context("build pre-entitle proofs"));
+            }
+
+            let mut calls = if calls.len() > 1 {
+                drop_reverting_pre_entitles(ctx, calls).await?
+            } else {
+                calls.into_iter().map(|(_, data)| data).collect()
+            };
+            if calls.is_empty() {
+                anyhow::bail!("every batched preEntitle reverts in simulation");
+            }
+
+            if calls.len() == 1 {
+                (ctx.hub_contract_address, 0u8, calls.remove(0))
+            } else {
+                let multisend = ctx
+                    .cfg
+                    .hub
+                    .multisend
+                    .context("batched preEntitle requires HUB_MULTISEND_ADDRESS")?;
+                tracing::info!(count = calls.len(), "prepared batched preEntitle intent");
+                let txs = calls
+                    .into_iter()
+                    .map(|data| MultiSendTx {
+                        operation: 0,
+                        to: ctx.hub_contract_address,
+                        value: U256::ZERO,
+                        data: data.into(),
+                    })
+                    .collect::<Vec<_>>();
+                let packed = encode_multisend_transactions(&txs);
+                let multisend_data = MultiSend::multiSendCall {
+                    transactions: packed.into(),
+                }
+                .abi_encode();
+
+                (multisend, 1u8, multisend_data)
             }
-            .abi_encode();
-            (ctx.hub_contract_address, 0u8, data)
         }
         HubIntent::SubjectivePreEntitle {
             txid,
@@ -677,7 +771,52 @@ pub async fn execute_hub_intent(
     );
     ctx.hub
         .submit(state, job_name, name, to, data, operation)
-        .await
+        .await?;
+    if let Some(pending) = state.hub_pending_userops.get_mut(&ctx.hub.lane(job_name)) {
+        pending.spends_safe_funds = spends_safe_funds;
+    }
+    Ok(())
+}
+
+/// Simulates each batched `preEntitle` from the Safe and drops the ones that revert.
+///
+/// MultiSend reverts as a whole, so one bad target would fail every batch it joins while the
+/// planner keeps picking the same rows.
+async fn drop_reverting_pre_entitles(
+    ctx: &RelayerContext,
+    calls: Vec<(&PreEntitleTarget, Vec<u8>)>,
+) -> Result<Vec<Vec<u8>>> {
+    let safe = ctx.cfg.hub.safe.context("hub safe address not resolved")?;
+    let mut kept = Vec::with_capacity(calls.len());
+    for (target, data) in calls {
+        let tx = TransactionRequest {
+            from: Some(safe),
+            to: Some(ctx.hub_contract_address.into()),
+            input: TransactionInput::new(data.clone().into()),
+            ..Default::default()
+        };
+        match ctx.hub_provider.call(tx).await {
+            Ok(_) => kept.push(data),
+            Err(err) if err.as_error_resp().is_some_and(is_revert) => {
+                tracing::warn!(
+                    txid = %format!("0x{}", hex::encode(target.txid)),
+                    receiver_salt = %target.receiver_salt,
+                    err = %err,
+                    "pre-entitle reverts in simulation; dropping from batch"
+                );
+            }
+            Err(err) => {
+                return Err(anyhow::Error::new(err).context("simulate batched preEntitle"));
+            }
+        }
+    }
+    Ok(kept)
+}
+
+/// An `eth_call` failure that is the call's own revert: JSON-RPC code 3 or attached revert
+/// data. Anything else (a lagging node, a rate limit) says nothing about the target.
+fn is_revert(err: &ErrorPayload) -> bool {
+    err.code == 3 || err.as_revert_data().is_some()
 }
 
 #[cfg(test)]
@@ -689,4 +828,25 @@ mod tests {
         assert!(pre_entitle_finalized(10, 12, 1, 1));
         assert!(!pre_entitle_finalized(10, 11, 1, 1));
     }
+
+    #[test]
+    fn only_call_reverts_drop_a_pre_entitle() {
+        let payload = |body: &str| serde_json::from_str::<ErrorPayload>(body).unwrap();
+        assert!(is_revert(&payload(
+            r#"{"code":3,"message":"execution reverted","data":"0x08c379a0"}"#
+        )));
+        assert!(is_revert(&payload(
+            r#"{"code":3,"message":"execution reverted"}"#
+        )));
+        assert!(is_revert(&payload(
+            r#"{"code":-32000,"message":"execution reverted","data":"0xdeadbeef"}"#
+        )));
+        // Endpoint trouble whose message merely mentions a revert must not drop the call.
+        assert!(!is_revert(&payload(
+            r#"{"code":-32000,"message":"header not found; cannot check for revert"}"#
+        )));
+        assert!(!is_revert(&payload(
+            r#"{"code":-32005,"message":"rate limited, request reverted to queue"}"#
+        )));
+    }
 }
@@ -2,7 +2,7 @@ use super::{HubIntent, TronIntent};
 use anyhow::{Context, Result};
 use std::collections::{HashMap, HashSet};
 use tron::{
-    DecodedTrc20Call, TronAddress, decode_trc20_call_data, decode_trigger_smart_contract,
+    DecodedTrc20Call, TronAddress, TronApi, decode_trc20_call_data, decode_trigger_smart_contract,
     wallet::encode_pull_from_receivers,
 };
 
@@ -25,6 +25,9 @@ pub const JOB_FILL_CLAIMS: &str = "fill_claims";
 pub const JOB_PULL_FROM_RECEIVERS: &str = "pull_from_receivers";
 pub const JOB_CONTROLLER_REBALANCE: &str = "controller_rebalance";
 
+/// Max objective `preEntitle` calls bundled into one hub userop.
+const MAX_PRE_ENTITLE_BATCH: usize = 8;
+
 fn tron_block_finalized(
     block_number: u64,
     tron_head: u64,
@@ -37,6 +40,12 @@ fn tron_block_finalized(
         <= tron_head
 }
 
+#[derive(Debug, Clone)]
+pub struct PreEntitleTarget {
+    pub receiver_salt: FixedBytes<32>,
+    pub txid: [u8; 32],
+}
+
 #[derive(Debug, Clone)]
 pub enum HubIntent {
     RelayControllerEventChain {
@@ -44,9 +53,9 @@ pub enum HubIntent {
         events: Vec<ControllerEvent>,
     },
     ProcessControllerEvents,
+    /// Objective pre-entitles proven in one pass; more than one target is sent as a multisend.
     PreEntitle {
-        receiver_salt: FixedBytes<32>,
-        txid: [u8; 32],
+        targets: Vec<PreEntitleTarget>,
     },
     SubjectivePreEntitle {
         txid: [u8; 32],
@@ -68,6 +77,17 @@ pub enum HubIntent {
     },
 }
 
+impl HubIntent {
+    /// Moves the Safe's tokens or LP principal. These are planned from balances read at the
+    /// start of the tick, so only one may be in flight at a time even with nonce lanes.
+    pub fn spends_safe_funds(&self) -> bool {
+        matches!(
+            self,
+            Self::SubjectivePreEntitle { .. } | Self::DepositLp { .. } | Self::FillClaims { .. }
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TronIntent {
     ProveControllerTip {
@@ -1,8 +1,12 @@
 use crate::metrics::RelayerTelemetry;
-use alloy::primitives::{FixedBytes, U256};
+use alloy::primitives::{Bytes, FixedBytes, U256};
 use anyhow::{Context, Result};
 use std::time::Instant;
 use tracing::Instrument;
+use tron::TronTxProofBundle;
+
+/// Number of encoded Tron headers the hub's `preEntitle` / `relayControllerEventChain` take.
+pub(super) const HUB_PROOF_BLOCKS: usize = 20;
 
 pub(super) async fn run_job<T, F, Fut>(
     telemetry: &RelayerTelemetry,
@@ -73,6 +77,14 @@ pub(super) fn u256_to_u64(v: U256) -> Option<u64> {
     Some(v.to::<u64>())
 }
 
+pub(super) fn hub_proof_blocks(bundle: &TronTxProofBundle) -> Result<[Bytes; HUB_PROOF_BLOCKS]> {
+    let blocks: Vec<Bytes> = bundle.blocks.iter().cloned().map(Bytes::from).collect();
+    let len = blocks.len();
+    blocks.try_into().map_err(|_| {
+        anyhow::anyhow!("proof bundle has {len} headers, hub expects {HUB_PROOF_BLOCKS}")
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,6 +121,22 @@ mod tests {
         assert_eq!(v, U256::from(12345u64));
     }
 
+    #[test]
+    fn hub_proof_blocks_requires_exact_count() {
+        let mut bundle = TronTxProofBundle {
+            blocks: vec![vec![0x01]; HUB_PROOF_BLOCKS],
+            encoded_tx: Vec::new(),
+            proof: Vec::new(),
+            index: U256::ZERO,
+        };
+        let blocks = hub_proof_blocks(&bundle).unwrap();
+        assert_eq!(blocks[19].as_ref(), &[0x01]);
+
+        bundle.blocks.pop();
+        let err = hub_proof_blocks(&bundle).unwrap_err().to_string();
+        assert!(err.contains("19 headers"));
+    }
+
     #[test]
     fn u256_to_u64_bounds() {
         assert_eq!(u256_to_u64(U256::from(u64::MAX)), Some(u64::MAX));
@@ -0,0 +1,170 @@
+use crate::contracts::{IMultiSend, ISimple7702Account};
+use alloy::primitives::{Address, Bytes, U256, address};
+use alloy::sol_types::SolCall;
+use anyhow::{Context, Result};
+use std::str::FromStr;
+
+/// `initCode` prefix telling EntryPoint v0.8 to hash the sender's EIP-7702 delegate in its
+/// place. Bundlers take it as the `factory` field.
+pub(crate) const EIP7702_INITCODE_MARKER: Address =
+    address!("7702000000000000000000000000000000000000");
+
+/// Code of an EOA delegated via EIP-7702 is this prefix followed by the delegate address.
+const EIP7702_DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// EntryPoint release userops are built, hashed and submitted for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntryPointVersion {
+    #[default]
+    V07,
+    V08,
+}
+
+impl FromStr for EntryPointVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().trim_start_matches(['v', 'V']) {
+            "0.7" | "07" => Ok(Self::V07),
+            "0.8" | "08" => Ok(Self::V08),
+            other => anyhow::bail!("unsupported entrypoint version: {other} (expected 0.7 or 0.8)"),
+        }
+    }
+}
+
+/// Smart account the userops are sent from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SmartAccountKind {
+    /// Safe proxy with the Safe 4337 module enabled; the owner signs `SafeOp`s.
+    #[default]
+    Safe4337,
+    /// The owner EOA itself, delegated via EIP-7702 to `delegate` (a `Simple7702Account`-style
+    /// implementation exposing `execute`/`executeBatch`). Requires EntryPoint v0.8.
+    Eip7702 { delegate: Address },
+}
+
+pub(crate) fn is_delegated_to(code: &[u8], delegate: Address) -> bool {
+    code.len() == EIP7702_DELEGATION_PREFIX.len() + 20
+        && code.starts_with(&EIP7702_DELEGATION_PREFIX)
+        && &code[EIP7702_DELEGATION_PREFIX.len()..] == delegate.as_slice()
+}
+
+/// Calldata for an EIP-7702 account performing what the Safe path expresses as
+/// `executeUserOp(to, 0, data, operation)`.
+///
+/// Delegatecalls are only understood for Safe `MultiSend` batches, which become one
+/// `executeBatch`; the account has no delegatecall of its own.
+pub(crate) fn eip7702_call_data(to: Address, data: Vec<u8>, operation: u8) -> Result<Bytes> {
+    let call_data = match operation {
+        0 => ISimple7702Account::executeCall {
+            target: to,
+            value: U256::ZERO,
+            data: data.into(),
+        }
+        .abi_encode(),
+        1 => {
+            let batch = IMultiSend::multiSendCall::abi_decode(&data)
+                .context("EIP-7702 accounts only support delegatecalls into MultiSend")?;
+            let calls = decode_multisend_transactions(&batch.transactions)?;
+            ISimple7702Account::executeBatchCall { calls }.abi_encode()
+        }
+        other => anyhow::bail!("unsupported operation: {other}"),
+    };
+    Ok(call_data.into())
+}
+
+/// Inverse of the relayer's `encode_multisend_transactions`; every entry must be a plain call.
+fn decode_multisend_transactions(mut packed: &[u8]) -> Result<Vec<ISimple7702Account::Call>> {
+    const HEADER_LEN: usize = 1 + 20 + 32 + 32;
+
+    let mut calls = Vec::new();
+    while !packed.is_empty() {
+        anyhow::ensure!(
+            packed.len() >= HEADER_LEN,
+            "truncated MultiSend transaction"
+        );
+        let operation = packed[0];
+        anyhow::ensure!(
+            operation == 0,
+            "nested delegatecall in MultiSend batch (operation {operation})"
+        );
+        let target = Address::from_slice(&packed[1..21]);
+        let value = U256::from_be_slice(&packed[21..53]);
+        let len = usize::try_from(U256::from_be_slice(&packed[53..85]))
+            .context("MultiSend data length overflows usize")?;
+        let rest = &packed[HEADER_LEN..];
+        anyhow::ensure!(rest.len() >= len, "truncated MultiSend transaction data");
+        calls.push(ISimple7702Account::Call {
+            target,
+            value,
+            data: Bytes::copy_from_slice(&rest[..len]),
+        });
+        packed = &rest[len..];
+    }
+    Ok(calls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multisend_entry(operation: u8, to: Address, data: &[u8]) -> Vec<u8> {
+        let mut out = vec![operation];
+        out.extend_from_slice(to.as_slice());
+        out.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
+        out.extend_from_slice(&U256::from(data.len()).to_be_bytes::<32>());
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    fn multisend_batches_become_execute_batch() {
+        let a = Address::repeat_byte(0x11);
+        let b = Address::repeat_byte(0x22);
+        let mut packed = multisend_entry(0, a, &[0xaa, 0xbb]);
+        packed.extend(multisend_entry(0, b, &[]));
+        let data = IMultiSend::multiSendCall {
+            transactions: packed.clone().into(),
+        }
+        .abi_encode();
+
+        let call_data = eip7702_call_data(Address::repeat_byte(0x99), data, 1).unwrap();
+        let batch = ISimple7702Account::executeBatchCall::abi_decode(&call_data).unwrap();
+        assert_eq!(batch.calls.len(), 2);
+        assert_eq!(batch.calls[0].target, a);
+        assert_eq!(batch.calls[0].data.as_ref(), &[0xaa, 0xbb]);
+        assert_eq!(batch.calls[1].target, b);
+        assert!(batch.calls[1].data.is_empty());
+
+        // A nested delegatecall can't be expressed as a plain call.
+        let nested = IMultiSend::multiSendCall {
+            transactions: multisend_entry(1, a, &[]).into(),
+        }
+        .abi_encode();
+        assert!(eip7702_call_data(Address::ZERO, nested, 1).is_err());
+        assert!(eip7702_call_data(Address::ZERO, vec![1, 2, 3], 1).is_err());
+    }
+
+    #[test]
+    fn delegation_designator_must_name_the_delegate() {
+        let delegate = Address::repeat_byte(0x42);
+        let mut code = EIP7702_DELEGATION_PREFIX.to_vec();
+        code.extend_from_slice(delegate.as_slice());
+        assert!(is_delegated_to(&code, delegate));
+        assert!(!is_delegated_to(&code, Address::repeat_byte(0x43)));
+        assert!(!is_delegated_to(&[], delegate));
+    }
+
+    #[test]
+    fn entrypoint_version_parses() {
+        assert_eq!(
+            "0.7".parse::<EntryPointVersion>().unwrap(),
+            EntryPointVersion::V07
+        );
+        assert_eq!(
+            "v0.8".parse::<EntryPointVersion>().unwrap(),
+            EntryPointVersion::V08
+        );
+        assert!("0.6".parse::<EntryPointVersion>().is_err());
+    }
+}
@@ -1,11 +1,14 @@
 use alloy::{
+    eips::eip7702::SignedAuthorization,
     primitives::{Address, Bytes},
     providers::{DynProvider, Provider, ProviderBuilder},
     rpc::client::{BuiltInConnectionString, RpcClient},
 };
 use alloy_provider::ext::Erc4337Api;
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 use alloy::rpc::types::eth::erc4337::PackedUserOperation;
@@ -20,7 +23,7 @@ const RPC_TIMEOUT: Duration = Duration::from_secs(10);
 pub struct BundlerPool {
     urls: Vec<String>,
     providers: Vec<DynProvider>,
-    next_idx: usize,
+    next_idx: Arc<AtomicUsize>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,6 +35,17 @@ pub(crate) struct UserOperationGasEstimationV07 {
     pub paymaster_post_op_gas_limit: alloy::primitives::U256,
 }
 
+/// Bundler JSON for a userop: alloy's `PackedUserOperation` plus the EIP-7702 authorization
+/// the bundler must include in its `handleOps` transaction (ERC-7769 `eip7702Auth`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UserOperationParam {
+    #[serde(flatten)]
+    user_op: PackedUserOperation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eip7702_auth: Option<SignedAuthorization>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SendUserOperationResponseObj {
@@ -154,24 +168,30 @@ impl BundlerPool {
         Ok(Self {
             urls: healthy_urls,
             providers,
-            next_idx: 0,
+            next_idx: Arc::new(AtomicUsize::new(0)),
         })
     }
 
-    fn mark_success(&mut self, idx: usize) {
+    fn mark_success(&self, idx: usize) {
         if !self.providers.is_empty() {
-            self.next_idx = (idx + 1) % self.providers.len();
+            self.next_idx
+                .store((idx + 1) % self.providers.len(), Ordering::Relaxed);
         }
     }
 
     pub(crate) async fn estimate_user_operation_gas(
-        &mut self,
+        &self,
         user_op: &PackedUserOperation,
+        eip7702_auth: Option<&SignedAuthorization>,
         entry_point: Address,
     ) -> Result<UserOperationGasEstimationV07> {
         let mut last_err: Option<anyhow::Error> = None;
+        let param = UserOperationParam {
+            user_op: user_op.clone(),
+            eip7702_auth: eip7702_auth.cloned(),
+        };
 
-        let order = rotate_order(self.next_idx, self.providers.len());
+        let order = rotate_order(self.next_idx.load(Ordering::Relaxed), self.providers.len());
         for idx in order {
             let url = &self.urls[idx];
             let url = redact_url(url);
@@ -182,7 +202,7 @@ impl BundlerPool {
             // `UserOperationGasEstimation` type currently models v0.6 response names.
             let fut = provider.raw_request(
                 "eth_estimateUserOperationGas".into(),
-                (user_op.clone(), entry_point),
+                (param.clone(), entry_point),
             );
 
             match tokio::time::timeout(RPC_TIMEOUT, fut).await {
@@ -227,13 +247,18 @@ impl BundlerPool {
     }
 
     pub(crate) async fn send_user_operation(
-        &mut self,
+        &self,
         user_op: &PackedUserOperation,
+        eip7702_auth: Option<&SignedAuthorization>,
         entry_point: Address,
     ) -> Result<SendUserOperationResponse> {
         let mut last_err: Option<anyhow::Error> = None;
+        let param = UserOperationParam {
+            user_op: user_op.clone(),
+            eip7702_auth: eip7702_auth.cloned(),
+        };
 
-        let order = rotate_order(self.next_idx, self.providers.len());
+        let order = rotate_order(self.next_idx.load(Ordering::Relaxed), self.providers.len());
         for idx in order {
             let url = &self.urls[idx];
             let url = redact_url(url);
@@ -241,10 +266,8 @@ impl BundlerPool {
 
             // Use a tolerant response type: some bundlers return `{ userOpHash }`, others return
             // the hash string directly.
-            let fut = provider.raw_request(
-                "eth_sendUserOperation".into(),
-                (user_op.clone(), entry_point),
-            );
+            let fut =
+                provider.raw_request("eth_sendUserOperation".into(), (param.clone(), entry_point));
 
             match tokio::time::timeout(RPC_TIMEOUT, fut).await {
                 Ok(Ok(v)) => {
@@ -280,10 +303,10 @@ impl BundlerPool {
             .unwrap_or_else(|| anyhow::anyhow!("all bundlers failed for eth_sendUserOperation")))
     }
 
-    pub(crate) async fn supported_entry_points(&mut self) -> Result<Vec<Address>> {
+    pub(crate) async fn supported_entry_points(&self) -> Result<Vec<Address>> {
         let mut last_err: Option<anyhow::Error> = None;
 
-        let order = rotate_order(self.next_idx, self.providers.len());
+        let order = rotate_order(self.next_idx.load(Ordering::Relaxed), self.providers.len());
         for idx in order {
             let url = &self.urls[idx];
             let url = redact_url(url);
@@ -323,12 +346,12 @@ impl BundlerPool {
     }
 
     pub async fn get_user_operation_receipt(
-        &mut self,
+        &self,
         user_op_hash: Bytes,
     ) -> Result<Option<UserOperationReceipt>> {
         let mut last_err: Option<anyhow::Error> = None;
 
-        let order = rotate_order(self.next_idx, self.providers.len());
+        let order = rotate_order(self.next_idx.load(Ordering::Relaxed), self.providers.len());
         for idx in order {
             let url = &self.urls[idx];
             let url = redact_url(url);
@@ -424,6 +447,51 @@ mod tests {
         assert_eq!(rotate_order(5, 3), vec![2, 0, 1]);
     }
 
+    #[test]
+    fn eip7702_auth_is_sent_alongside_the_userop_fields() {
+        use alloy::eips::eip7702::Authorization;
+        use alloy::primitives::{Signature, U256};
+
+        let user_op = PackedUserOperation {
+            sender: Address::repeat_byte(0x33),
+            nonce: U256::ZERO,
+            factory: None,
+            factory_data: None,
+            call_data: Bytes::new(),
+            call_gas_limit: U256::ZERO,
+            verification_gas_limit: U256::ZERO,
+            pre_verification_gas: U256::ZERO,
+            max_fee_per_gas: U256::ZERO,
+            max_priority_fee_per_gas: U256::ZERO,
+            paymaster: None,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            paymaster_data: None,
+            signature: Bytes::new(),
+        };
+        let plain = serde_json::to_value(UserOperationParam {
+            user_op: user_op.clone(),
+            eip7702_auth: None,
+        })
+        .unwrap();
+        assert!(plain.get("sender").is_some());
+        assert!(plain.get("eip7702Auth").is_none());
+
+        let auth = Authorization {
+            chain_id: U256::from(10u64),
+            address: Address::repeat_byte(0x44),
+            nonce: 3,
+        }
+        .into_signed(Signature::new(U256::from(1u64), U256::from(2u64), false));
+        let with_auth = serde_json::to_value(UserOperationParam {
+            user_op,
+            eip7702_auth: Some(auth),
+        })
+        .unwrap();
+        assert_eq!(with_auth["eip7702Auth"]["nonce"], "0x3");
+        assert_eq!(with_auth["eip7702Auth"]["yParity"], "0x0");
+    }
+
     #[test]
     fn send_user_operation_response_accepts_object_or_hash_string() {
         let obj = r#"{"userOpHash":"0x1234"}"#;
@@ -13,6 +13,7 @@ alloy::sol! {
         function executeUserOp(address to, uint256 value, bytes data, uint8 operation) external;
     }
 
+    #[sol(rpc)]
     interface ISafe {
         function setup(
             address[] calldata owners,
@@ -55,6 +56,22 @@ alloy::sol! {
         function enableModules(address[] calldata modules) external;
     }
 
+    interface IMultiSend {
+        function multiSend(bytes transactions) external;
+    }
+
+    /// `BaseAccount` surface of EntryPoint v0.8's `Simple7702Account` and compatible delegates.
+    interface ISimple7702Account {
+        struct Call {
+            address target;
+            uint256 value;
+            bytes data;
+        }
+
+        function execute(address target, uint256 value, bytes calldata data) external;
+        function executeBatch(Call[] calldata calls) external;
+    }
+
     /// EIP-712 struct hashed by the module (see `Safe4337Module._getSafeOp`).
     struct SafeOp {
         address safe;
@@ -71,4 +88,16 @@ alloy::sol! {
         uint48 validUntil;
         address entryPoint;
     }
+
+    /// EIP-712 struct behind EntryPoint v0.8's `getUserOpHash` (see `UserOperationLib.hash`).
+    struct PackedUserOperation {
+        address sender;
+        uint256 nonce;
+        bytes initCode;
+        bytes callData;
+        bytes32 accountGasLimits;
+        uint256 preVerificationGas;
+        bytes32 gasFees;
+        bytes paymasterAndData;
+    }
 }
@@ -1,7 +1,9 @@
-use alloy::primitives::{Address, Bytes, TxKind, U256};
+use alloy::primitives::{Address, Bytes, TxKind, U256, address};
 use alloy::providers::{DynProvider, Provider};
-use alloy::sol_types::SolCall;
-use anyhow::Result;
+use alloy::sol_types::{SolCall, SolValue};
+use anyhow::{Context, Result};
+
+use crate::bundler_pool::UserOperationGasEstimationV07;
 
 use alloy::rpc::types::eth::erc4337::PackedUserOperation;
 
@@ -21,11 +23,56 @@ alloy::sol! {
         bytes signature;
     }
 
+    struct ReturnInfo {
+        uint256 preOpGas;
+        uint256 prefund;
+        uint256 accountValidationData;
+        uint256 paymasterValidationData;
+        bytes paymasterContext;
+    }
+
+    struct StakeInfo {
+        uint256 stake;
+        uint256 unstakeDelaySec;
+    }
+
+    struct AggregatorStakeInfo {
+        address aggregator;
+        StakeInfo stakeInfo;
+    }
+
+    /// Ref: ERC-4337 `EntryPointSimulations` v0.7 (`IEntryPointSimulations.ValidationResult`).
+    struct ValidationResult {
+        ReturnInfo returnInfo;
+        StakeInfo senderInfo;
+        StakeInfo factoryInfo;
+        StakeInfo paymasterInfo;
+        AggregatorStakeInfo aggregatorInfo;
+    }
+
     interface IEntryPointSimulations {
-        function simulateValidation(PackedUserOperationV07 userOp) external;
+        function simulateValidation(PackedUserOperationV07 userOp)
+            external
+            returns (ValidationResult memory);
+    }
+
+    /// Arbitrum's virtual `NodeInterface`, answered by the node itself on `eth_call`.
+    interface INodeInterface {
+        function gasEstimateL1Component(address to, bool contractCreation, bytes data)
+            external
+            payable
+            returns (uint64 gasEstimateForL1, uint256 baseFee, uint256 l1BaseFeeEstimate);
     }
 }
 
+const NODE_INTERFACE: Address = address!("00000000000000000000000000000000000000c8");
+/// OP-stack `GasPriceOracle` predeploy; has code only on OP-stack chains.
+const OP_GAS_PRICE_ORACLE: Address = address!("420000000000000000000000000000000000000f");
+
+/// Validation budget the simulated op gets, so the measurement isn't cut short by the
+/// still-unestimated limit.
+const SIMULATION_VERIFICATION_GAS: u64 = 2_000_000;
+
 fn pack_128(high: U256, low: U256) -> [u8; 32] {
     // pack two uint128 values into a bytes32: (high << 128) | low
     let mut out = [0u8; 32];
@@ -97,36 +144,232 @@ fn to_packed_v07(op: &PackedUserOperation) -> PackedUserOperationV07 {
     }
 }
 
-/// Best-effort: run EntryPoint.simulateValidation via eth_call to obtain a richer revert.
+/// Best-effort: run `EntryPoint.simulateValidation` via eth_call and return the gas validation
+/// used (`preOpGas` without `preVerificationGas`), or `None` if the call didn't return a
+/// `ValidationResult`.
+///
+/// Only nodes serving `EntryPointSimulations` at the EntryPoint address return one. Otherwise
+/// the revert is still logged: many bundlers omit `error.data`, and a normal RPC provider tends
+/// to include the revert bytes.
 ///
-/// Many bundlers omit `error.data`. This uses a normal RPC provider, which tends to include
-/// revert bytes.
-pub async fn debug_simulate_validation(
+/// The op is simulated with a generous verification limit and zero fees, so neither the
+/// unestimated limit nor the prefund cuts the measurement short.
+pub(crate) async fn simulate_validation(
     provider: &DynProvider,
     entrypoint: Address,
     op: &PackedUserOperation,
-) -> Result<()> {
-    let packed = to_packed_v07(op);
+) -> Option<u64> {
+    let packed = to_packed_v07(&PackedUserOperation {
+        verification_gas_limit: U256::from(SIMULATION_VERIFICATION_GAS),
+        pre_verification_gas: U256::ZERO,
+        max_fee_per_gas: U256::ZERO,
+        max_priority_fee_per_gas: U256::ZERO,
+        ..op.clone()
+    });
     let call = IEntryPointSimulations::simulateValidationCall { userOp: packed };
     let calldata = call.abi_encode();
 
-    // We expect this to revert (by design). We only care about the error payload.
     let req = alloy::rpc::types::TransactionRequest {
         to: Some(TxKind::Call(entrypoint)),
         input: Some(calldata.into()).into(),
         ..Default::default()
     };
 
-    // We expect this to revert (by design). If it succeeds, that's still useful info.
-    match provider.call(req).await {
-        Ok(_bytes) => {
-            tracing::warn!("simulateValidation eth_call unexpectedly succeeded (no revert)");
-        }
+    let ret = match provider.call(req).await {
+        Ok(ret) => ret,
         Err(err) => {
-            // This is the payload we care about; caller should log `err` with `Debug` at callsite.
+            // The revert payload is what we're after when the bundler's error lacks it.
             tracing::error!(raw_err = ?err, "simulateValidation eth_call reverted");
+            return None;
         }
+    };
+    let gas = validation_gas(&ret);
+    if gas.is_none() {
+        tracing::warn!("simulateValidation eth_call returned no usable ValidationResult");
+    }
+    gas
+}
+
+/// `preOpGas` of an encoded `ValidationResult`, for an op simulated without
+/// `preVerificationGas`.
+fn validation_gas(ret: &[u8]) -> Option<u64> {
+    let result = IEntryPointSimulations::simulateValidationCall::abi_decode_returns(ret).ok()?;
+    let gas = result.returnInfo.preOpGas;
+    (!gas.is_zero()).then(|| gas.saturating_to())
+}
+
+/// Bundler-free estimate for when every bundler failed to estimate: `eth_estimateGas` of the
+/// account call as the EntryPoint makes it, `verification_gas` for validation (measured by
+/// [`simulate_validation`] where possible), and the calldata-based `preVerificationGas` plus
+/// the L1 data gas from [`l1_data_gas`].
+///
+/// Not usable while the op still deploys or delegates the account: there is no code to run
+/// the call against yet, nor on OP-stack chains.
+pub(crate) async fn estimate_locally(
+    provider: &DynProvider,
+    entrypoint: Address,
+    op: &PackedUserOperation,
+    verification_gas: u64,
+) -> Result<UserOperationGasEstimationV07> {
+    anyhow::ensure!(
+        op.factory.is_none(),
+        "cannot estimate locally before the account exists"
+    );
+
+    let req = alloy::rpc::types::TransactionRequest {
+        from: Some(entrypoint),
+        to: Some(TxKind::Call(op.sender)),
+        input: Some(op.call_data.clone()).into(),
+        ..Default::default()
+    };
+    let call_gas = provider
+        .estimate_gas(req)
+        .await
+        .context("eth_estimateGas account call")?;
+    let l1_gas = l1_data_gas(provider, entrypoint, op).await?;
+
+    Ok(UserOperationGasEstimationV07 {
+        pre_verification_gas: U256::from(pre_verification_gas(op).saturating_add(l1_gas)),
+        verification_gas_limit: U256::from(verification_gas),
+        call_gas_limit: U256::from(call_gas),
+        paymaster_verification_gas_limit: op.paymaster_verification_gas_limit.unwrap_or_default(),
+        paymaster_post_op_gas_limit: op.paymaster_post_op_gas_limit.unwrap_or_default(),
+    })
+}
+
+/// L2 gas the bundle transaction spends posting `op` to L1, which [`pre_verification_gas`]
+/// leaves out: priced by Arbitrum's `NodeInterface`, zero on chains without one.
+///
+/// Errors on OP-stack chains, whose L1 fee is charged in wei outside the gas limit and isn't
+/// priced here, so the local fallback stays off there.
+async fn l1_data_gas(
+    provider: &DynProvider,
+    entrypoint: Address,
+    op: &PackedUserOperation,
+) -> Result<u64> {
+    let call = INodeInterface::gasEstimateL1ComponentCall {
+        to: entrypoint,
+        contractCreation: false,
+        data: bundled_op(op).into(),
+    };
+    let req = alloy::rpc::types::TransactionRequest {
+        to: Some(TxKind::Call(NODE_INTERFACE)),
+        input: Some(call.abi_encode().into()).into(),
+        ..Default::default()
+    };
+    let ret = provider
+        .call(req)
+        .await
+        .context("eth_call NodeInterface.gasEstimateL1Component")?;
+    // Elsewhere the address has no code and the call returns nothing.
+    if let Ok(component) = INodeInterface::gasEstimateL1ComponentCall::abi_decode_returns(&ret) {
+        return Ok(component.gasEstimateForL1);
     }
 
-    Ok(())
+    let oracle = provider
+        .get_code_at(OP_GAS_PRICE_ORACLE)
+        .await
+        .context("eth_getCode(GasPriceOracle)")?;
+    anyhow::ensure!(
+        oracle.is_empty(),
+        "cannot estimate locally on an OP-stack chain: its L1 data fee isn't priced"
+    );
+    Ok(0)
+}
+
+/// The op as a bundle carries it, assuming a 65-byte signature.
+fn bundled_op(op: &PackedUserOperation) -> Vec<u8> {
+    const SIG_SIZE: usize = 65;
+
+    let mut packed = to_packed_v07(op);
+    packed.signature = Bytes::from(vec![1u8; SIG_SIZE]);
+    packed.abi_encode()
+}
+
+/// eth-infinitism's default `calcPreVerificationGas` for a bundle of one: the op's calldata
+/// plus fixed per-bundle and per-op overheads.
+pub(crate) fn pre_verification_gas(op: &PackedUserOperation) -> u64 {
+    const FIXED: u64 = 21_000;
+    const PER_USER_OP: u64 = 18_300;
+    const PER_USER_OP_WORD: u64 = 4;
+    const ZERO_BYTE: u64 = 4;
+    const NON_ZERO_BYTE: u64 = 16;
+
+    let encoded = bundled_op(op);
+
+    let call_data_cost: u64 = encoded
+        .iter()
+        .map(|&b| if b == 0 { ZERO_BYTE } else { NON_ZERO_BYTE })
+        .sum();
+    let words = encoded.len().div_ceil(32) as u64;
+    call_data_cost + FIXED + PER_USER_OP + PER_USER_OP_WORD * words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation_gas_is_read_from_the_simulation_result() {
+        let stake = || StakeInfo {
+            stake: U256::ZERO,
+            unstakeDelaySec: U256::ZERO,
+        };
+        let result = ValidationResult {
+            returnInfo: ReturnInfo {
+                preOpGas: U256::from(61_234u64),
+                prefund: U256::ZERO,
+                accountValidationData: U256::ZERO,
+                paymasterValidationData: U256::ZERO,
+                paymasterContext: Bytes::new(),
+            },
+            senderInfo: stake(),
+            factoryInfo: stake(),
+            paymasterInfo: stake(),
+            aggregatorInfo: AggregatorStakeInfo {
+                aggregator: Address::ZERO,
+                stakeInfo: stake(),
+            },
+        };
+        let ret = IEntryPointSimulations::simulateValidationCall::abi_encode_returns(&result);
+        assert_eq!(validation_gas(&ret), Some(61_234));
+        assert_eq!(validation_gas(&[]), None);
+    }
+
+    #[test]
+    fn pre_verification_gas_grows_with_calldata() {
+        let op = PackedUserOperation {
+            sender: Address::repeat_byte(0x33),
+            nonce: U256::ZERO,
+            factory: None,
+            factory_data: None,
+            call_data: Bytes::new(),
+            call_gas_limit: U256::ZERO,
+            verification_gas_limit: U256::ZERO,
+            pre_verification_gas: U256::ZERO,
+            max_fee_per_gas: U256::ZERO,
+            max_priority_fee_per_gas: U256::ZERO,
+            paymaster: None,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            paymaster_data: None,
+            signature: Bytes::new(),
+        };
+        let base = pre_verification_gas(&op);
+        assert!(base > 21_000 + 18_300);
+
+        let zeros = PackedUserOperation {
+            call_data: Bytes::from(vec![0u8; 32]),
+            ..op.clone()
+        };
+        let non_zeros = PackedUserOperation {
+            call_data: Bytes::from(vec![0xffu8; 32]),
+            ..op
+        };
+        assert!(pre_verification_gas(&zeros) > base);
+        assert_eq!(
+            pre_verification_gas(&non_zeros) - pre_verification_gas(&zeros),
+            32 * (16 - 4)
+        );
+    }
 }
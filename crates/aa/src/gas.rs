@@ -0,0 +1,224 @@
+use crate::bundler_pool::UserOperationGasEstimationV07;
+use alloy::primitives::U256;
+use alloy::rpc::types::eth::erc4337::PackedUserOperation;
+use anyhow::{Context, Result};
+
+/// How bundler (or local) gas estimates become userop gas limits.
+///
+/// Each `*_pct` multiplies its estimate (110 = +10%). A `max_*` cap bounds the result; an
+/// estimate already above its cap fails the userop instead of sending it underfunded.
+#[derive(Debug, Clone)]
+pub struct GasEstimationOptions {
+    pub call_gas_pct: u64,
+    pub verification_gas_pct: u64,
+    pub pre_verification_gas_pct: u64,
+    pub paymaster_verification_gas_pct: u64,
+    pub max_call_gas: Option<u64>,
+    pub max_verification_gas: Option<u64>,
+    pub max_pre_verification_gas: Option<u64>,
+    /// Estimate locally when every bundler fails to, unless they failed with an `AAxx`
+    /// validation error (which a local estimate can't fix). Never used on OP-stack chains,
+    /// whose L1 data fee the local estimate can't price.
+    pub local_fallback: bool,
+    /// Verification budget assumed by the local estimate, before `verification_gas_pct`, when
+    /// `simulateValidation` can't measure it.
+    pub local_verification_gas: u64,
+}
+
+impl Default for GasEstimationOptions {
+    fn default() -> Self {
+        Self {
+            call_gas_pct: 110,
+            verification_gas_pct: 110,
+            pre_verification_gas_pct: 110,
+            paymaster_verification_gas_pct: 110,
+            max_call_gas: None,
+            max_verification_gas: None,
+            max_pre_verification_gas: None,
+            local_fallback: true,
+            local_verification_gas: 150_000,
+        }
+    }
+}
+
+impl GasEstimationOptions {
+    /// Writes the account gas limits derived from `estimate` into `op`.
+    pub(crate) fn apply(
+        &self,
+        estimate: &UserOperationGasEstimationV07,
+        op: &mut PackedUserOperation,
+    ) -> Result<()> {
+        op.call_gas_limit = scale(
+            estimate.call_gas_limit,
+            self.call_gas_pct,
+            self.max_call_gas,
+        )
+        .context("callGasLimit")?;
+        op.verification_gas_limit = scale(
+            estimate.verification_gas_limit,
+            self.verification_gas_pct,
+            self.max_verification_gas,
+        )
+        .context("verificationGasLimit")?;
+        op.pre_verification_gas = scale(
+            estimate.pre_verification_gas,
+            self.pre_verification_gas_pct,
+            self.max_pre_verification_gas,
+        )
+        .context("preVerificationGas")?;
+        Ok(())
+    }
+}
+
+pub(crate) fn scale(estimate: U256, pct: u64, cap: Option<u64>) -> Result<U256> {
+    let scaled = estimate
+        .checked_mul(U256::from(pct))
+        .map(|v| v / U256::from(100u64))
+        .context("overflow scaling gas estimate")?;
+    let Some(cap) = cap.map(U256::from) else {
+        return Ok(scaled);
+    };
+    anyhow::ensure!(
+        estimate <= cap,
+        "estimate {estimate} exceeds the configured cap {cap}"
+    );
+    Ok(scaled.min(cap))
+}
+
+/// Where a submitted userop's gas limits came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasEstimateSource {
+    Bundler,
+    Local,
+}
+
+impl GasEstimateSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bundler => "bundler",
+            Self::Local => "local",
+        }
+    }
+}
+
+/// Gas limits a userop was submitted with, for comparing against `actualGasUsed` once mined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserOpGasLimits {
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub paymaster_verification_gas_limit: U256,
+    pub paymaster_post_op_gas_limit: U256,
+    /// Sum of the estimate's limits before multipliers, caps and paymaster stub values.
+    pub estimate: U256,
+    pub source: GasEstimateSource,
+}
+
+impl UserOpGasLimits {
+    pub(crate) fn of(
+        op: &PackedUserOperation,
+        estimate: &UserOperationGasEstimationV07,
+        source: GasEstimateSource,
+    ) -> Self {
+        Self {
+            call_gas_limit: op.call_gas_limit,
+            verification_gas_limit: op.verification_gas_limit,
+            pre_verification_gas: op.pre_verification_gas,
+            paymaster_verification_gas_limit: op
+                .paymaster_verification_gas_limit
+                .unwrap_or_default(),
+            paymaster_post_op_gas_limit: op.paymaster_post_op_gas_limit.unwrap_or_default(),
+            estimate: estimate
+                .call_gas_limit
+                .saturating_add(estimate.verification_gas_limit)
+                .saturating_add(estimate.pre_verification_gas)
+                .saturating_add(estimate.paymaster_verification_gas_limit)
+                .saturating_add(estimate.paymaster_post_op_gas_limit),
+            source,
+        }
+    }
+
+    /// Upper bound on the gas the EntryPoint may charge for the op.
+    pub fn total(&self) -> U256 {
+        self.call_gas_limit
+            .saturating_add(self.verification_gas_limit)
+            .saturating_add(self.pre_verification_gas)
+            .saturating_add(self.paymaster_verification_gas_limit)
+            .saturating_add(self.paymaster_post_op_gas_limit)
+    }
+}
+
+/// `AA1x`..`AA9x` EntryPoint validation failures: the op itself is rejected, so estimating
+/// it differently won't help.
+pub(crate) fn is_validation_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        let msg = cause.to_string();
+        msg.as_bytes()
+            .windows(4)
+            .any(|w| w[0] == b'A' && w[1] == b'A' && w[2].is_ascii_digit() && w[3].is_ascii_digit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_applies_multiplier_then_cap() {
+        let v = U256::from(100_000u64);
+        assert_eq!(scale(v, 110, None).unwrap(), U256::from(110_000u64));
+        assert_eq!(
+            scale(v, 150, Some(120_000)).unwrap(),
+            U256::from(120_000u64)
+        );
+        // A cap below the raw estimate can't be satisfied.
+        assert!(scale(v, 110, Some(90_000)).is_err());
+    }
+
+    #[test]
+    fn limits_keep_the_raw_estimate() {
+        let estimate = UserOperationGasEstimationV07 {
+            pre_verification_gas: U256::from(50_000u64),
+            verification_gas_limit: U256::from(100_000u64),
+            call_gas_limit: U256::from(200_000u64),
+            paymaster_verification_gas_limit: U256::ZERO,
+            paymaster_post_op_gas_limit: U256::ZERO,
+        };
+        let mut op = PackedUserOperation {
+            sender: Default::default(),
+            nonce: U256::ZERO,
+            factory: None,
+            factory_data: None,
+            call_data: Default::default(),
+            call_gas_limit: U256::ZERO,
+            verification_gas_limit: U256::ZERO,
+            pre_verification_gas: U256::ZERO,
+            max_fee_per_gas: U256::ZERO,
+            max_priority_fee_per_gas: U256::ZERO,
+            paymaster: None,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            paymaster_data: None,
+            signature: Default::default(),
+        };
+        GasEstimationOptions::default()
+            .apply(&estimate, &mut op)
+            .unwrap();
+
+        let gas = UserOpGasLimits::of(&op, &estimate, GasEstimateSource::Local);
+        assert_eq!(gas.estimate, U256::from(350_000u64));
+        assert_eq!(gas.total(), U256::from(385_000u64));
+    }
+
+    #[test]
+    fn validation_errors_are_recognized() {
+        let aa25 = anyhow::anyhow!(
+            "UserOperation reverted during simulation with reason: AA25 invalid account nonce"
+        );
+        assert!(is_validation_error(
+            &aa25.context("eth_estimateUserOperationGas")
+        ));
+        assert!(!is_validation_error(&anyhow::anyhow!("timed out")));
+        assert!(!is_validation_error(&anyhow::anyhow!("AAVE pool")));
+    }
+}
@@ -1,10 +1,15 @@
+mod account;
 mod bundler_pool;
 mod contracts;
 mod entrypoint_sim;
+mod gas;
+mod nonce;
 mod packing;
 pub mod paymaster;
+mod replacement;
 mod safe;
 mod sender;
+mod signer;
 mod signing;
 
 pub use sender::{
@@ -12,8 +17,20 @@ pub use sender::{
     Safe4337UserOpSenderOptions, Safe4337UserOpSubmission,
 };
 
+pub use account::{EntryPointVersion, SmartAccountKind};
+
+pub use gas::{GasEstimateSource, GasEstimationOptions, UserOpGasLimits};
+
+pub use nonce::{NonceKey, NonceLane};
+
+pub use replacement::UserOpReplacementOptions;
+
 pub use safe::{Safe4337Config, SafeDeterministicDeploymentConfig};
 
+pub use signer::{
+    HttpSafeOpSigner, LocalSafeOpSigner, SafeOpSigner, SafeOpSigningRequest, SignFuture,
+};
+
 /// Wait for an ERC-4337 UserOperation receipt via bundler RPC (eth_getUserOperationReceipt).
 ///
 /// This is useful when callers do not want to hold a `Safe4337UserOpSender` mutex guard while waiting.
@@ -30,7 +47,7 @@ pub async fn wait_user_operation_receipt(
     let bytes = hex::decode(hex_str).context("invalid userop hash hex")?;
     let userop_hash = alloy::primitives::Bytes::from(bytes);
 
-    let mut pool = bundler_pool::BundlerPool::new(bundler_urls).await?;
+    let pool = bundler_pool::BundlerPool::new(bundler_urls).await?;
 
     let deadline = std::time::Instant::now() + timeout;
     let mut backoff = Duration::from_millis(250);
@@ -0,0 +1,146 @@
+use alloy::primitives::{U256, Uint, keccak256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Upper 192 bits of an ERC-4337 nonce.
+pub type NonceKey = Uint<192, 3>;
+
+/// An ERC-4337 nonce key ("2D nonce"). The EntryPoint sequences each key independently, so
+/// userops on different lanes neither wait for nor invalidate each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct NonceLane(NonceKey);
+
+impl NonceLane {
+    /// Key 0, the only lane used before lanes existed.
+    pub const DEFAULT: Self = Self(NonceKey::ZERO);
+
+    pub const fn new(key: NonceKey) -> Self {
+        Self(key)
+    }
+
+    /// Stable lane for a label (e.g. a job name): the first 24 bytes of `keccak256(label)`.
+    pub fn named(label: &str) -> Self {
+        Self(NonceKey::from_be_slice(&keccak256(label.as_bytes())[..24]))
+    }
+
+    pub fn key(self) -> NonceKey {
+        self.0
+    }
+
+    /// Lane a full 256-bit nonce belongs to.
+    pub fn of(nonce: U256) -> Self {
+        Self(NonceKey::from(nonce >> 64))
+    }
+}
+
+/// Locally-tracked next nonce per lane, ahead of chain while our userops sit in the
+/// bundler's mempool. A missing entry means "stale, re-read chain".
+#[derive(Default)]
+pub(crate) struct NonceLanes {
+    next: Mutex<HashMap<NonceLane, U256>>,
+}
+
+impl NonceLanes {
+    /// Reserve the lane's next nonce: the max of the cached next nonce and `chain` (the
+    /// lane's `getNonce`). The cache moves past it before returning, so concurrent acquires
+    /// on the lane get distinct nonces.
+    pub(crate) fn acquire(&self, lane: NonceLane, chain: U256) -> U256 {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let nonce = match next.get(&lane) {
+            Some(&c) if c > chain => c,
+            _ => chain,
+        };
+        next.insert(lane, nonce.saturating_add(U256::from(1u64)));
+        nonce
+    }
+
+    /// Hand back a reserved nonce whose userop never reached the bundler. Rolls the lane back
+    /// if nothing was acquired after it; otherwise the lane would have a gap, so its cache is
+    /// dropped and the next acquire re-reads chain.
+    pub(crate) fn release(&self, unused: U256) {
+        let lane = NonceLane::of(unused);
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        if next.get(&lane) == Some(&unused.saturating_add(U256::from(1u64))) {
+            next.insert(lane, unused);
+        } else {
+            next.remove(&lane);
+        }
+    }
+
+    /// Advance the lane of `used` past it after the bundler accepted the userop.
+    pub(crate) fn commit(&self, used: U256) {
+        let following = used.saturating_add(U256::from(1u64));
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = next.entry(NonceLane::of(used)).or_insert(following);
+        if *entry < following {
+            *entry = following;
+        }
+    }
+
+    pub(crate) fn invalidate(&self, lane: NonceLane) {
+        self.next
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&lane);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lanes_occupy_the_upper_192_bits() {
+        let lane = NonceLane::named("fill_claims");
+        assert_ne!(lane, NonceLane::DEFAULT);
+        assert_eq!(lane, NonceLane::named("fill_claims"));
+        assert_ne!(lane, NonceLane::named("deposit_lp"));
+
+        let nonce = (U256::from(lane.key()) << 64) | U256::from(7u64);
+        assert_eq!(NonceLane::of(nonce), lane);
+        assert_eq!(NonceLane::of(U256::from(7u64)), NonceLane::DEFAULT);
+    }
+
+    #[test]
+    fn lanes_are_tracked_independently() {
+        let lanes = NonceLanes::default();
+        let a = NonceLane::DEFAULT;
+        let b = NonceLane::named("b");
+        let b0 = U256::from(b.key()) << 64;
+
+        assert_eq!(lanes.acquire(a, U256::from(5u64)), U256::from(5u64));
+        lanes.commit(U256::from(5u64));
+        assert_eq!(lanes.acquire(b, b0), b0);
+        lanes.commit(b0);
+
+        // Chain hasn't caught up with either lane's pending userop.
+        assert_eq!(lanes.acquire(a, U256::from(5u64)), U256::from(6u64));
+        assert_eq!(lanes.acquire(b, b0), b0 + U256::from(1u64));
+
+        lanes.invalidate(a);
+        assert_eq!(lanes.acquire(a, U256::from(5u64)), U256::from(5u64));
+        assert_eq!(lanes.acquire(b, b0), b0 + U256::from(2u64));
+    }
+
+    #[test]
+    fn concurrent_acquires_get_distinct_nonces() {
+        let lanes = NonceLanes::default();
+        let a = NonceLane::DEFAULT;
+        let chain = U256::from(3u64);
+
+        assert_eq!(lanes.acquire(a, chain), U256::from(3u64));
+        assert_eq!(lanes.acquire(a, chain), U256::from(4u64));
+
+        // The last reservation rolls back cleanly; an earlier one would leave a gap.
+        lanes.release(U256::from(4u64));
+        assert_eq!(lanes.acquire(a, chain), U256::from(4u64));
+        lanes.release(U256::from(3u64));
+        assert_eq!(lanes.acquire(a, chain), U256::from(3u64));
+
+        // Committing out of order never moves the lane backwards.
+        assert_eq!(lanes.acquire(a, chain), U256::from(4u64));
+        lanes.commit(U256::from(4u64));
+        lanes.commit(U256::from(3u64));
+        assert_eq!(lanes.acquire(a, chain), U256::from(5u64));
+    }
+}
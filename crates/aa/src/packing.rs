@@ -58,6 +58,16 @@ pub(crate) fn pack_paymaster_and_data(
     }
 }
 
+/// `bytes32` holding two `uint128`s, `high` first (`accountGasLimits`, `gasFees`).
+pub(crate) fn pack_u128_pair(high: U256, low: U256) -> Result<[u8; 32]> {
+    let high = u128::try_from(high).context("high half overflows uint128")?;
+    let low = u128::try_from(low).context("low half overflows uint128")?;
+    let mut out = [0u8; 32];
+    out[..16].copy_from_slice(&high.to_be_bytes());
+    out[16..].copy_from_slice(&low.to_be_bytes());
+    Ok(out)
+}
+
 pub(crate) fn ensure_u48(v: u64, label: &'static str) -> Result<()> {
     if v > 0xFFFF_FFFF_FFFF {
         anyhow::bail!("{label} must fit in uint48");
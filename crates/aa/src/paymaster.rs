@@ -1,9 +1,12 @@
 use crate::packing::redact_url;
+use alloy::eips::eip7702::SignedAuthorization;
 use alloy::primitives::{Address, Bytes, U256};
 use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -40,6 +43,10 @@ pub struct PaymasterUserOp {
     pub paymaster_post_op_gas_limit: Option<U256>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub paymaster_data: Option<Bytes>,
+
+    /// Set while an EIP-7702 account still has to be delegated by this userop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eip7702_auth: Option<SignedAuthorization>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -107,13 +114,13 @@ pub struct PaymasterDataResult {
 pub struct PaymasterPool {
     http: Client,
     selector: PaymasterSelector,
-    next_id: u64,
+    next_id: Arc<AtomicU64>,
 }
 
 #[derive(Debug, Clone)]
 struct PaymasterSelector {
     services: Vec<PaymasterService>,
-    next_idx: usize,
+    next_idx: Arc<AtomicUsize>,
 }
 
 impl PaymasterSelector {
@@ -123,21 +130,23 @@ impl PaymasterSelector {
         }
         Ok(Self {
             services,
-            next_idx: 0,
+            next_idx: Arc::new(AtomicUsize::new(0)),
         })
     }
 
     fn order(&self) -> impl Iterator<Item = usize> + '_ {
-        (0..self.services.len()).map(move |o| (self.next_idx + o) % self.services.len())
+        let start = self.next_idx.load(Ordering::Relaxed);
+        (0..self.services.len()).map(move |o| (start + o) % self.services.len())
     }
 
     fn service(&self, idx: usize) -> Option<&PaymasterService> {
         self.services.get(idx)
     }
 
-    fn mark_success(&mut self, idx: usize) {
+    fn mark_success(&self, idx: usize) {
         if !self.services.is_empty() {
-            self.next_idx = (idx + 1) % self.services.len();
+            self.next_idx
+                .store((idx + 1) % self.services.len(), Ordering::Relaxed);
         }
     }
 }
@@ -151,7 +160,7 @@ impl PaymasterPool {
         Ok(Self {
             http,
             selector: PaymasterSelector::new(services)?,
-            next_id: 1,
+            next_id: Arc::new(AtomicU64::new(1)),
         })
     }
 
@@ -163,12 +172,12 @@ impl PaymasterPool {
         self.selector.service(idx)
     }
 
-    pub fn mark_success(&mut self, idx: usize) {
+    pub fn mark_success(&self, idx: usize) {
         self.selector.mark_success(idx);
     }
 
     pub async fn get_stub_data(
-        &mut self,
+        &self,
         idx: usize,
         user_op: &PaymasterUserOp,
         entry_point: Address,
@@ -189,7 +198,7 @@ impl PaymasterPool {
     }
 
     pub async fn get_data(
-        &mut self,
+        &self,
         idx: usize,
         user_op: &PaymasterUserOp,
         entry_point: Address,
@@ -210,13 +219,12 @@ impl PaymasterPool {
     }
 
     async fn jsonrpc<T: for<'de> Deserialize<'de>>(
-        &mut self,
+        &self,
         url: &str,
         method: &str,
         params: serde_json::Value,
     ) -> Result<T> {
-        let id = self.next_id;
-        self.next_id = self.next_id.saturating_add(1);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
         let paymaster = redact_url(url);
 
         let body = serde_json::json!({
@@ -389,7 +397,7 @@ mod tests {
                 context: serde_json::json!({}),
             },
         ];
-        let mut sel = PaymasterSelector::new(services).unwrap();
+        let sel = PaymasterSelector::new(services).unwrap();
 
         let order = sel.order().collect::<Vec<_>>();
         assert_eq!(order, vec![0, 1, 2]);
@@ -0,0 +1,82 @@
+use alloy::primitives::U256;
+use alloy::rpc::types::eth::erc4337::PackedUserOperation;
+use std::time::Duration;
+
+/// Bundlers (ERC-7562 mempool rules) only let a userop replace one they already hold for the
+/// same sender and nonce when both fee fields rise by at least this much.
+pub(crate) const MIN_REPLACEMENT_BUMP_PCT: u64 = 10;
+
+/// Replace-by-fee for userops a bundler sits on (e.g. during gas spikes).
+#[derive(Debug, Clone)]
+pub struct UserOpReplacementOptions {
+    /// Resubmit the same nonce with bumped fees once a userop has gone this long without a
+    /// receipt. `None` disables replacement.
+    pub stall_timeout: Option<Duration>,
+    /// Fee increase per replacement in percent; raised to the bundlers' 10% minimum.
+    pub bump_pct: u64,
+    pub max_replacements: u32,
+}
+
+impl Default for UserOpReplacementOptions {
+    fn default() -> Self {
+        Self {
+            stall_timeout: None,
+            bump_pct: 20,
+            max_replacements: 3,
+        }
+    }
+}
+
+/// Raises both fees by `pct` (at least [`MIN_REPLACEMENT_BUMP_PCT`], rounded up) and to no
+/// less than the current network fees, keeping `maxFee >= maxPriorityFee`.
+pub(crate) fn bump_fees(
+    op: &mut PackedUserOperation,
+    pct: u64,
+    network_max_fee: U256,
+    network_priority_fee: U256,
+) {
+    let pct = U256::from(100 + pct.max(MIN_REPLACEMENT_BUMP_PCT));
+    let bump = |v: U256| (v.saturating_mul(pct) + U256::from(99u64)) / U256::from(100u64);
+
+    op.max_priority_fee_per_gas = bump(op.max_priority_fee_per_gas).max(network_priority_fee);
+    op.max_fee_per_gas = bump(op.max_fee_per_gas)
+        .max(network_max_fee)
+        .max(op.max_priority_fee_per_gas);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Address, Bytes};
+
+    #[test]
+    fn bump_meets_replacement_minimum_and_network_fees() {
+        let mut op = PackedUserOperation {
+            sender: Address::ZERO,
+            nonce: U256::ZERO,
+            factory: None,
+            factory_data: None,
+            call_data: Bytes::new(),
+            call_gas_limit: U256::ZERO,
+            verification_gas_limit: U256::ZERO,
+            pre_verification_gas: U256::ZERO,
+            max_fee_per_gas: U256::from(1_000u64),
+            max_priority_fee_per_gas: U256::from(101u64),
+            paymaster: None,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            paymaster_data: None,
+            signature: Bytes::new(),
+        };
+
+        // A 5% request is raised to 10%, rounding up.
+        bump_fees(&mut op, 5, U256::ZERO, U256::ZERO);
+        assert_eq!(op.max_fee_per_gas, U256::from(1_100u64));
+        assert_eq!(op.max_priority_fee_per_gas, U256::from(112u64));
+
+        // Network fees above the bump win, and maxFee stays >= priority.
+        bump_fees(&mut op, 10, U256::from(500u64), U256::from(5_000u64));
+        assert_eq!(op.max_priority_fee_per_gas, U256::from(5_000u64));
+        assert_eq!(op.max_fee_per_gas, U256::from(5_000u64));
+    }
+}
@@ -14,6 +14,28 @@ pub struct SafeDeterministicDeploymentConfig {
     pub singleton: Address,
     pub module_setup: Address,
     pub salt_nonce: U256,
+    /// Safe owners; empty means just the deployer (the sender's owner key).
+    pub owners: Vec<Address>,
+    /// Signatures required per SafeOp; 0 is treated as 1.
+    pub threshold: u64,
+}
+
+impl SafeDeterministicDeploymentConfig {
+    /// Owner set and threshold the Safe is set up with when `deployer` deploys it.
+    pub fn owners_and_threshold(&self, deployer: Address) -> Result<(Vec<Address>, u64)> {
+        let owners = if self.owners.is_empty() {
+            vec![deployer]
+        } else {
+            self.owners.clone()
+        };
+        let threshold = self.threshold.max(1);
+        anyhow::ensure!(
+            threshold <= owners.len() as u64,
+            "safe threshold {threshold} exceeds its {} owners",
+            owners.len()
+        );
+        Ok((owners, threshold))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -54,18 +76,19 @@ async fn wallet_provider_for_rpc(rpc_url: &str, private_key: [u8; 32]) -> Result
 }
 
 pub fn build_safe_4337_initializer(
-    owner: Address,
+    deployer: Address,
     cfg: &Safe4337Config,
     deploy: &SafeDeterministicDeploymentConfig,
-) -> Bytes {
+) -> Result<Bytes> {
+    let (owners, threshold) = deploy.owners_and_threshold(deployer)?;
     let enable_4337 = ISafeModuleSetup::enableModulesCall {
         modules: vec![cfg.safe_4337_module],
     }
     .abi_encode();
 
     let initializer = ISafe::setupCall {
-        owners: vec![owner],
-        threshold: U256::from(1u64),
+        owners,
+        threshold: U256::from(threshold),
         to: deploy.module_setup,
         data: enable_4337.into(),
         fallbackHandler: cfg.safe_4337_module,
@@ -75,7 +98,7 @@ pub fn build_safe_4337_initializer(
     }
     .abi_encode();
 
-    initializer.into()
+    Ok(initializer.into())
 }
 
 pub async fn fetch_proxy_init_code_hash(
@@ -165,7 +188,7 @@ pub async fn ensure_safe_deployed(
         PrivateKeySigner::from_bytes(&owner_private_key.into()).context("invalid private key")?;
     let owner = signer.address();
 
-    let initializer = build_safe_4337_initializer(owner, safe_4337, deploy);
+    let initializer = build_safe_4337_initializer(owner, safe_4337, deploy)?;
     let init_code_hash =
         fetch_proxy_init_code_hash(&read, deploy.proxy_factory, deploy.singleton)
             .await
@@ -271,9 +294,11 @@ mod tests {
             singleton: Address::repeat_byte(0x66),
             module_setup,
             salt_nonce: U256::ZERO,
+            owners: Vec::new(),
+            threshold: 1,
         };
 
-        let initializer = build_safe_4337_initializer(owner, &safe_4337, &deploy);
+        let initializer = build_safe_4337_initializer(owner, &safe_4337, &deploy).unwrap();
         let decoded = ISafe::setupCall::abi_decode(initializer.as_ref()).unwrap();
         assert_eq!(decoded.owners, vec![owner]);
         assert_eq!(decoded.threshold, U256::from(1u64));
@@ -288,6 +313,35 @@ mod tests {
         assert_eq!(enable.modules, vec![module]);
     }
 
+    #[test]
+    fn initializer_sets_up_multi_owner_threshold() {
+        let owners = vec![
+            Address::repeat_byte(0x11),
+            Address::repeat_byte(0x12),
+            Address::repeat_byte(0x13),
+        ];
+        let safe_4337 = Safe4337Config {
+            entrypoint: Address::repeat_byte(0x22),
+            safe_4337_module: Address::repeat_byte(0x33),
+        };
+        let mut deploy = SafeDeterministicDeploymentConfig {
+            proxy_factory: Address::repeat_byte(0x55),
+            singleton: Address::repeat_byte(0x66),
+            module_setup: Address::repeat_byte(0x44),
+            salt_nonce: U256::ZERO,
+            owners: owners.clone(),
+            threshold: 2,
+        };
+
+        let initializer = build_safe_4337_initializer(owners[0], &safe_4337, &deploy).unwrap();
+        let decoded = ISafe::setupCall::abi_decode(initializer.as_ref()).unwrap();
+        assert_eq!(decoded.owners, owners);
+        assert_eq!(decoded.threshold, U256::from(2u64));
+
+        deploy.threshold = 4;
+        assert!(build_safe_4337_initializer(owners[0], &safe_4337, &deploy).is_err());
+    }
+
     #[test]
     fn create_proxy_with_nonce_calldata_matches_selector() {
         let singleton = Address::repeat_byte(0x66);
@@ -1,9 +1,25 @@
-use crate::bundler_pool::BundlerPool;
-use crate::contracts::{IEntryPointDeposits, IEntryPointNonces, Safe4337Module};
+use crate::account::{
+    EIP7702_INITCODE_MARKER, EntryPointVersion, SmartAccountKind, eip7702_call_data,
+    is_delegated_to,
+};
+use crate::bundler_pool::{BundlerPool, UserOperationGasEstimationV07};
+use crate::contracts::{IEntryPointDeposits, IEntryPointNonces, ISafe, Safe4337Module};
+use crate::gas::{
+    GasEstimateSource, GasEstimationOptions, UserOpGasLimits, is_validation_error, scale,
+};
+use crate::nonce::{NonceLane, NonceLanes};
 use crate::packing::{add_gas_buffer, hex_bytes0x, redact_url};
 use crate::paymaster::{PaymasterPool, PaymasterService, PaymasterUserOp};
+use crate::replacement::bump_fees;
 use crate::safe::{Safe4337Config, SafeDeterministicDeploymentConfig, ensure_safe_deployed};
-use crate::signing::sign_userop_with_key;
+use crate::signer::{
+    LocalSafeOpSigner, SafeOpSigner, SafeOpSigningRequest, concat_owner_signatures,
+    placeholder_owner_signatures,
+};
+use crate::signing::{
+    safe_userop_signature, safeop_digest, sign_eip7702_authorization, sign_userop_v08_with_key,
+};
+use alloy::eips::eip7702::SignedAuthorization;
 use alloy::sol_types::SolCall;
 use alloy::{
     primitives::{Address, Bytes, U256},
@@ -12,11 +28,11 @@ use alloy::{
 };
 use anyhow::{Context, Result};
 use k256::ecdsa::SigningKey;
+use std::sync::Arc;
 use std::time::Duration;
 
 use alloy::rpc::types::eth::erc4337::{PackedUserOperation, UserOperationReceipt};
 
-const GAS_BUFFER_PCT: u64 = 10;
 const PAYMASTER_POST_OP_GAS_BUFFER_PCT: u64 = 10;
 
 // ERC-4337 bundler sanity checks expect paymasterVerificationGasLimit < MAX_VERIFICATION_GAS (500_000).
@@ -39,6 +55,7 @@ pub enum PaymasterFinalizationMode {
 pub struct Safe4337UserOpSenderOptions {
     pub check_bundler_entrypoints: bool,
     pub paymaster_finalization: PaymasterFinalizationMode,
+    pub gas: GasEstimationOptions,
 }
 
 impl Default for Safe4337UserOpSenderOptions {
@@ -46,6 +63,7 @@ impl Default for Safe4337UserOpSenderOptions {
         Self {
             check_bundler_entrypoints: false,
             paymaster_finalization: PaymasterFinalizationMode::AlwaysFetchFinal,
+            gas: GasEstimationOptions::default(),
         }
     }
 }
@@ -55,11 +73,18 @@ pub struct Safe4337UserOpSenderConfig {
     pub rpc_url: String,
     pub chain_id: Option<u64>,
     pub entrypoint: Address,
+    pub entrypoint_version: EntryPointVersion,
+    /// With `Eip7702` userops are sent from the owner EOA itself and the `safe*` fields are
+    /// ignored.
+    pub account: SmartAccountKind,
     pub safe: Option<Address>,
     pub safe_4337_module: Address,
     pub safe_deployment: Option<SafeDeterministicDeploymentConfig>,
     pub bundler_urls: Vec<String>,
     pub owner_private_key: [u8; 32],
+    /// Further Safe owners asked to sign when the Safe's threshold is above 1; the
+    /// `owner_private_key` owner always signs first.
+    pub cosigners: Vec<Arc<dyn SafeOpSigner>>,
     pub paymasters: Vec<PaymasterService>,
     pub options: Safe4337UserOpSenderOptions,
 }
@@ -69,15 +94,18 @@ pub struct Safe4337UserOpSender {
     provider: DynProvider,
     chain_id: u64,
     owner_key: SigningKey,
+    /// Safe owners that sign SafeOps, local owner first; `threshold` of them must succeed.
+    signers: Vec<Arc<dyn SafeOpSigner>>,
+    threshold: usize,
     safe: Address,
     bundlers: BundlerPool,
     paymasters: Option<PaymasterPool>,
-    /// Locally-tracked next nonce to use, ahead of chain when one of our prior userops is
-    /// still in the bundler's mempool. `None` means "stale, re-read chain". Eliminates the
-    /// AA25 race when send rate exceeds bundler bundle latency: chain `getNonce` doesn't see
-    /// pending mempool userops, so without this two consecutive sends would both pick the
-    /// same nonce and the second hits AA25 in the simulator.
-    next_nonce: tokio::sync::Mutex<Option<U256>>,
+    /// Locally-tracked next nonce per lane, ahead of chain when one of our prior userops is
+    /// still in the bundler's mempool. Eliminates the AA25 race when send rate exceeds
+    /// bundler bundle latency: chain `getNonce` doesn't see pending mempool userops, so
+    /// without this two consecutive sends on a lane would both pick the same nonce and the
+    /// second hits AA25 in the simulator.
+    nonces: NonceLanes,
 }
 
 #[derive(Debug, Clone)]
@@ -86,10 +114,34 @@ pub struct Safe4337UserOpSubmission {
     pub nonce: U256,
     /// Number of eth_sendUserOperation attempts performed (including the successful one).
     pub send_attempts: u64,
+    /// Gas limits the userop was submitted with.
+    pub gas: UserOpGasLimits,
+    /// The userop as accepted by the bundler, kept for replacing it with higher fees.
+    pub userop: PackedUserOperation,
+    pub eip7702_auth: Option<SignedAuthorization>,
+    paymaster_idx: Option<usize>,
 }
 
 impl Safe4337UserOpSender {
-    async fn preflight_safe_execute_userop_call(&self, call_data: Bytes) -> Result<()> {
+    async fn preflight_account_call(&self, call_data: Bytes) -> Result<()> {
+        if let SmartAccountKind::Eip7702 { delegate } = self.cfg.account {
+            let code = self
+                .provider
+                .get_code_at(self.safe)
+                .await
+                .context("eth_getCode(account)")?;
+            if !is_delegated_to(&code, delegate) {
+                // Without the delegation designator the call would run no code and pass
+                // vacuously; the delegation only lands with the userop itself.
+                tracing::debug!(
+                    account = %self.safe,
+                    delegate = %delegate,
+                    "account not delegated yet; skipping preflight eth_call"
+                );
+                return Ok(());
+            }
+        }
+
         let tx = TransactionRequest {
             from: Some(self.cfg.entrypoint),
             to: Some(self.safe.into()),
@@ -104,10 +156,11 @@ impl Safe4337UserOpSender {
                 tracing::error!(
                     safe = %self.safe,
                     entrypoint = %self.cfg.entrypoint,
+                    account = ?self.cfg.account,
                     err = %err_fmt,
-                    "preflight eth_call for Safe4337 executeUserOp failed"
+                    "preflight eth_call for account execute failed"
                 );
-                Err(anyhow::Error::new(err).context("preflight eth_call safe executeUserOp"))
+                Err(anyhow::Error::new(err).context("preflight eth_call account execute"))
             }
         }
     }
@@ -131,9 +184,28 @@ impl Safe4337UserOpSender {
         let owner_key =
             SigningKey::from_slice(&cfg.owner_private_key).context("invalid owner private key")?;
 
-        let safe = match cfg.safe {
-            Some(addr) if addr != Address::ZERO => addr,
-            _ => {
+        anyhow::ensure!(
+            cfg.account != SmartAccountKind::Safe4337
+                || cfg.entrypoint_version == EntryPointVersion::V07,
+            "Safe4337 accounts require EntryPoint v0.7"
+        );
+        if let SmartAccountKind::Eip7702 { delegate } = cfg.account {
+            anyhow::ensure!(
+                cfg.entrypoint_version == EntryPointVersion::V08,
+                "EIP-7702 accounts require EntryPoint v0.8"
+            );
+            anyhow::ensure!(
+                delegate != Address::ZERO,
+                "EIP-7702 delegate address must be set"
+            );
+        }
+
+        let safe = match (cfg.account, cfg.safe) {
+            (SmartAccountKind::Eip7702 { .. }, _) => {
+                Address::from_public_key(owner_key.verifying_key())
+            }
+            (SmartAccountKind::Safe4337, Some(addr)) if addr != Address::ZERO => addr,
+            (SmartAccountKind::Safe4337, _) => {
                 let deploy = cfg
                     .safe_deployment
                     .clone()
@@ -154,7 +226,23 @@ impl Safe4337UserOpSender {
             }
         };
 
-        let mut bundlers = BundlerPool::new(cfg.bundler_urls.clone()).await?;
+        let mut signers: Vec<Arc<dyn SafeOpSigner>> =
+            vec![Arc::new(LocalSafeOpSigner::from_key(owner_key.clone()))];
+        signers.extend(cfg.cosigners.iter().cloned());
+        let threshold = match cfg.account {
+            SmartAccountKind::Eip7702 { .. } => {
+                anyhow::ensure!(
+                    cfg.cosigners.is_empty(),
+                    "co-signers are only supported for Safe accounts"
+                );
+                1
+            }
+            SmartAccountKind::Safe4337 => safe_signing_threshold(&provider, safe, &signers)
+                .await
+                .context("check safe owners")?,
+        };
+
+        let bundlers = BundlerPool::new(cfg.bundler_urls.clone()).await?;
         if cfg.options.check_bundler_entrypoints {
             match bundlers.supported_entry_points().await {
                 Ok(eps) => {
@@ -188,72 +276,68 @@ impl Safe4337UserOpSender {
             provider,
             chain_id,
             owner_key,
+            signers,
+            threshold,
             safe,
             bundlers,
             paymasters,
-            next_nonce: tokio::sync::Mutex::new(None),
+            nonces: NonceLanes::default(),
         })
     }
 
-    /// Reads the Safe's nonce from chain. Does not consult the local cache — callers that
-    /// need "what nonce should the next userop use" want [`Self::send_call_operation`]
-    /// (which uses the cache to avoid the bundler-mempool race). Kept for callers that
-    /// genuinely want chain state, e.g. checking whether a previously-submitted userop has
-    /// been included.
+    /// Reads the Safe's default-lane nonce from chain. Does not consult the local cache —
+    /// callers that need "what nonce should the next userop use" want
+    /// [`Self::send_call_operation`] (which uses the cache to avoid the bundler-mempool race).
+    /// Kept for callers that genuinely want chain state, e.g. checking whether a
+    /// previously-submitted userop has been included.
     pub async fn current_nonce(&self) -> Result<U256> {
+        self.current_nonce_in_lane(NonceLane::DEFAULT).await
+    }
+
+    /// [`Self::current_nonce`] for `lane`; the key is included in the returned nonce.
+    pub async fn current_nonce_in_lane(&self, lane: NonceLane) -> Result<U256> {
         self.entrypoint()
-            .getNonce(self.safe, alloy::primitives::Uint::<192, 3>::ZERO)
+            .getNonce(self.safe, lane.key())
             .call()
             .await
             .context("EntryPoint.getNonce")
     }
 
-    /// Pick the nonce to use for the next userop: max of cached "next" and chain. Updates
-    /// the cache to that value so concurrent acquires see it.
-    async fn acquire_nonce(&self) -> Result<U256> {
-        let chain = self.current_nonce().await?;
-        let mut cache = self.next_nonce.lock().await;
-        let nonce = match *cache {
-            Some(c) if c > chain => c,
-            _ => chain,
-        };
-        *cache = Some(nonce);
-        Ok(nonce)
-    }
-
-    /// Advance the cached next-nonce after a userop submission was accepted by the bundler.
-    /// We use saturating_add for safety; in practice nonces never approach U256::MAX.
-    async fn commit_nonce(&self, used: U256) {
-        let next = used.saturating_add(U256::from(1u64));
-        let mut cache = self.next_nonce.lock().await;
-        *cache = Some(match *cache {
-            Some(c) if c > next => c,
-            _ => next,
-        });
+    /// Reserve the nonce for the next userop on `lane`: max of cached "next" and chain. The
+    /// cache moves past it, so concurrent sends on the lane never pick the same nonce.
+    async fn acquire_nonce(&self, lane: NonceLane) -> Result<U256> {
+        let chain = self.current_nonce_in_lane(lane).await?;
+        Ok(self.nonces.acquire(lane, chain))
     }
 
-    /// Drop the cache so the next acquire re-reads chain. Used on AA25, where our cached
-    /// nonce disagrees with the bundler's view of the world (typically because another
+    /// Drop the lane's cache so its next acquire re-reads chain. Used on AA25, where our
+    /// cached nonce disagrees with the bundler's view of the world (typically because another
     /// process sharing this Safe submitted a userop between our cache update and the send).
-    async fn invalidate_nonce(&self) {
-        *self.next_nonce.lock().await = None;
+    fn invalidate_nonce(&self, lane: NonceLane) {
+        self.nonces.invalidate(lane);
     }
 
+    /// The account userops are sent from: the Safe, or the owner EOA for EIP-7702 accounts.
     pub fn safe_address(&self) -> Address {
         self.safe
     }
 
+    /// Single `eth_getUserOperationReceipt` lookup; `None` while the userop isn't mined.
+    pub async fn user_operation_receipt(
+        &self,
+        userop_hash0x: &str,
+    ) -> Result<Option<UserOperationReceipt>> {
+        let userop_hash = parse_userop_hash(userop_hash0x)?;
+        self.bundlers.get_user_operation_receipt(userop_hash).await
+    }
+
     pub async fn wait_user_operation_receipt(
-        &mut self,
+        &self,
         userop_hash0x: &str,
         timeout: Duration,
     ) -> Result<UserOperationReceipt> {
         let start = std::time::Instant::now();
-
-        let userop_hash0x = userop_hash0x.trim();
-        let hex_str = userop_hash0x.strip_prefix("0x").unwrap_or(userop_hash0x);
-        let bytes = hex::decode(hex_str).context("invalid userop hash hex")?;
-        let userop_hash = Bytes::from(bytes);
+        let userop_hash = parse_userop_hash(userop_hash0x)?;
 
         let deadline = std::time::Instant::now() + timeout;
         let mut backoff = Duration::from_millis(250);
@@ -288,51 +372,144 @@ impl Safe4337UserOpSender {
         }
     }
 
-    pub async fn send_call(
-        &mut self,
+    /// Resubmit `prev`'s nonce with fees bumped by `bump_pct` (see
+    /// [`crate::UserOpReplacementOptions::bump_pct`]; at least to current network fees), so a
+    /// bundler holding `prev` swaps it for the new userop. Sponsored userops get fresh
+    /// paymaster data from the paymaster that sponsored `prev`.
+    pub async fn replace_user_operation(
+        &self,
+        prev: &Safe4337UserOpSubmission,
+        bump_pct: u64,
+    ) -> Result<Safe4337UserOpSubmission> {
+        let mut userop = prev.userop.clone();
+        let (network_max_fee, network_priority_fee) = self.network_fees().await?;
+        bump_fees(&mut userop, bump_pct, network_max_fee, network_priority_fee);
+
+        if let Some(idx) = prev.paymaster_idx {
+            let pool = self
+                .paymasters
+                .as_ref()
+                .context("sponsored userop but no paymasters configured")?;
+            let pm_userop = to_paymaster_userop(
+                &userop,
+                self.cfg.options.paymaster_finalization,
+                prev.eip7702_auth.as_ref(),
+            )?;
+            let final_data = pool
+                .get_data(idx, &pm_userop, self.cfg.entrypoint, self.chain_id)
+                .await
+                .context("re-sponsor replacement userop")?;
+            if let Some(p) = final_data.paymaster
+                && Some(p) != userop.paymaster
+            {
+                anyhow::bail!("pm_getPaymasterData returned unexpected paymaster address");
+            }
+            userop.paymaster_data = Some(
+                final_data
+                    .paymaster_data
+                    .context("pm_getPaymasterData missing paymasterData")?,
+            );
+        }
+
+        userop.signature = self.sign_userop(&userop).await?.into();
+        let (resp, send_attempts) = self
+            .send_user_operation_with_retries(&mut userop, prev.eip7702_auth.as_ref())
+            .await
+            .context("bundler send replacement userop")?;
+
+        let userop_hash = hex_bytes0x(&resp.user_op_hash);
+        tracing::info!(
+            safe = %self.safe,
+            nonce = %prev.nonce,
+            replaced = %prev.userop_hash,
+            userop_hash = %userop_hash,
+            max_fee_per_gas = %userop.max_fee_per_gas,
+            max_priority_fee_per_gas = %userop.max_priority_fee_per_gas,
+            "replaced stalled userop with bumped fees"
+        );
+        Ok(Safe4337UserOpSubmission {
+            userop_hash,
+            nonce: prev.nonce,
+            send_attempts,
+            gas: prev.gas,
+            userop,
+            eip7702_auth: prev.eip7702_auth.clone(),
+            paymaster_idx: prev.paymaster_idx,
+        })
+    }
+
+    /// The first of `userop_hashes` (an original and its replacements) that has a receipt.
+    pub async fn landed_user_operation(
+        &self,
+        userop_hashes: &[String],
+    ) -> Result<Option<(String, UserOperationReceipt)>> {
+        for hash in userop_hashes {
+            if let Some(receipt) = self.user_operation_receipt(hash).await? {
+                return Ok(Some((hash.clone(), receipt)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn send_call(&self, to: Address, data: Vec<u8>) -> Result<Safe4337UserOpSubmission> {
+        self.send_call_operation(to, data, 0).await
+    }
+
+    /// Submit a userop on the default nonce lane; see [`Self::send_call_operation_in_lane`].
+    pub async fn send_call_operation(
+        &self,
         to: Address,
         data: Vec<u8>,
+        operation: u8,
     ) -> Result<Safe4337UserOpSubmission> {
-        self.send_call_operation(to, data, 0).await
+        self.send_call_operation_in_lane(NonceLane::DEFAULT, to, data, operation)
+            .await
     }
 
-    /// Submit a userop with cache-driven nonce selection and one-shot AA25 retry.
+    /// Submit a userop on `lane` with cache-driven nonce selection and one-shot AA25 retry.
+    /// Lanes are sequenced independently by the EntryPoint, so a userop stuck in one lane
+    /// doesn't hold back the others.
     ///
     /// On AA25 (cached nonce disagrees with bundler/chain — usually because another process
     /// sharing this Safe slipped a userop in between our acquire and our send), we drop the
     /// cache, sleep briefly to let the bundler bundle, and retry once. Persistent AA25 after
     /// the retry surfaces to the caller — there's a real disagreement that the cache can't
     /// paper over, e.g. someone else is also using this Safe key.
-    pub async fn send_call_operation(
-        &mut self,
+    pub async fn send_call_operation_in_lane(
+        &self,
+        lane: NonceLane,
         to: Address,
         data: Vec<u8>,
         operation: u8,
     ) -> Result<Safe4337UserOpSubmission> {
         let mut tried_aa25_retry = false;
         loop {
-            let nonce = self.acquire_nonce().await?;
+            let nonce = self.acquire_nonce(lane).await?;
             match self
                 .send_call_with_nonce_operation(nonce, to, data.clone(), operation)
                 .await
             {
                 Ok(sub) => {
-                    self.commit_nonce(nonce).await;
+                    self.nonces.commit(nonce);
                     return Ok(sub);
                 }
                 Err(err) => {
                     let is_aa25 = err.chain().any(|c| c.to_string().contains("AA25"));
                     if is_aa25 {
-                        self.invalidate_nonce().await;
+                        self.invalidate_nonce(lane);
                         if !tried_aa25_retry {
                             tried_aa25_retry = true;
                             tracing::warn!(
                                 attempted_nonce = %nonce,
+                                lane = %lane.key(),
                                 "AA25 nonce race detected; invalidating cache and retrying once after 2s"
                             );
                             tokio::time::sleep(Duration::from_secs(2)).await;
                             continue;
                         }
+                    } else {
+                        // The bundler didn't take it; let the next send on the lane reuse the nonce.
+                        self.nonces.release(nonce);
                     }
                     return Err(err);
                 }
@@ -341,7 +518,7 @@ impl Safe4337UserOpSender {
     }
 
     pub async fn send_call_with_nonce(
-        &mut self,
+        &self,
         nonce: U256,
         to: Address,
         data: Vec<u8>,
@@ -351,61 +528,38 @@ impl Safe4337UserOpSender {
     }
 
     pub async fn send_call_with_nonce_operation(
-        &mut self,
+        &self,
         nonce: U256,
         to: Address,
         data: Vec<u8>,
         operation: u8,
     ) -> Result<Safe4337UserOpSubmission> {
-        // Prefer standard EIP-1559 fee estimation (eth_feeHistory). This avoids bundler-specific gas APIs.
-        let (max_fee_per_gas, max_priority_fee_per_gas) = match self
-            .provider
-            .estimate_eip1559_fees()
-            .await
-        {
-            Ok(est) => {
-                let mut max_fee = est.max_fee_per_gas;
-                let max_priority = est.max_priority_fee_per_gas.max(MIN_PRIORITY_FEE_WEI);
-                if max_fee < max_priority {
-                    max_fee = max_priority;
-                }
-                (U256::from(max_fee), U256::from(max_priority))
-            }
-            Err(err) => {
-                // Fallback for non-EIP-1559 chains / RPCs: eth_gasPrice with a 2x buffer.
-                tracing::warn!(err = %err, "estimate_eip1559_fees failed; falling back to eth_gasPrice");
-                let gas_price: u128 = self
-                    .provider
-                    .get_gas_price()
-                    .await
-                    .context("eth_gasPrice")?;
-                let max_fee = gas_price.saturating_mul(2);
-                (
-                    U256::from(max_fee),
-                    U256::from(MIN_PRIORITY_FEE_WEI.min(max_fee)),
-                )
+        let (max_fee_per_gas, max_priority_fee_per_gas) = self.network_fees().await?;
+
+        let call_data: Bytes = match self.cfg.account {
+            SmartAccountKind::Safe4337 => Safe4337Module::executeUserOpCall {
+                to,
+                value: U256::ZERO,
+                data: data.into(),
+                operation,
             }
+            .abi_encode()
+            .into(),
+            SmartAccountKind::Eip7702 { .. } => eip7702_call_data(to, data, operation)?,
         };
+        self.preflight_account_call(call_data.clone()).await?;
 
-        let call_data = Safe4337Module::executeUserOpCall {
-            to,
-            value: U256::ZERO,
-            data: data.into(),
-            operation,
-        }
-        .abi_encode();
-        self.preflight_safe_execute_userop_call(call_data.clone().into())
-            .await?;
-
+        let eip7702_auth = self.eip7702_authorization().await?;
         let base_userop = PackedUserOperation {
             sender: self.safe,
             nonce,
-            factory: None,
-            factory_data: None,
-            call_data: call_data.into(),
-            call_gas_limit: U256::from(50_000_000u64),
-            verification_gas_limit: U256::from(5_000_000u64),
-            pre_verification_gas: U256::from(2_000_000u64),
+            factory: eip7702_auth.as_ref().map(|_| EIP7702_INITCODE_MARKER),
+            factory_data: eip7702_auth.as_ref().map(|_| Bytes::new()),
+            call_data,
+            // Replaced by the (bundler or local) estimate before the userop is sent.
+            call_gas_limit: U256::ZERO,
+            verification_gas_limit: U256::ZERO,
+            pre_verification_gas: U256::ZERO,
             max_fee_per_gas,
             max_priority_fee_per_gas,
             paymaster: None,
@@ -415,7 +569,7 @@ impl Safe4337UserOpSender {
             signature: Bytes::new(),
         };
 
-        if let Some(mut pool) = self.paymasters.take() {
+        if let Some(pool) = &self.paymasters {
             let attempts = pool
                 .order()
                 .filter_map(|idx| pool.service(idx).cloned().map(|svc| (idx, svc)))
@@ -424,12 +578,17 @@ impl Safe4337UserOpSender {
 
             for (idx, svc) in attempts {
                 match self
-                    .send_with_paymaster(&mut pool, idx, &svc, base_userop.clone())
+                    .send_with_paymaster(
+                        pool,
+                        idx,
+                        &svc,
+                        base_userop.clone(),
+                        eip7702_auth.as_ref(),
+                    )
                     .await
                 {
                     Ok(sub) => {
                         pool.mark_success(idx);
-                        self.paymasters = Some(pool);
                         return Ok(sub);
                     }
                     Err(err) => {
@@ -448,7 +607,6 @@ impl Safe4337UserOpSender {
                 failures = ?paymaster_failures,
                 "all configured paymasters failed; falling back to self-paid userop"
             );
-            self.paymasters = Some(pool);
         }
 
         if self.cfg.paymasters.is_empty() {
@@ -459,7 +617,37 @@ impl Safe4337UserOpSender {
             );
         }
 
-        self.send_self_paid(base_userop).await
+        self.send_self_paid(base_userop, eip7702_auth.as_ref())
+            .await
+    }
+
+    /// For an EIP-7702 account not yet delegated to the configured implementation, the
+    /// authorization the next userop has to carry. `None` once the delegation is in place.
+    async fn eip7702_authorization(&self) -> Result<Option<SignedAuthorization>> {
+        let SmartAccountKind::Eip7702 { delegate } = self.cfg.account else {
+            return Ok(None);
+        };
+        let code = self
+            .provider
+            .get_code_at(self.safe)
+            .await
+            .context("eth_getCode(account)")?;
+        if is_delegated_to(&code, delegate) {
+            return Ok(None);
+        }
+
+        let nonce = self
+            .provider
+            .get_transaction_count(self.safe)
+            .await
+            .context("eth_getTransactionCount(account)")?;
+        tracing::info!(
+            account = %self.safe,
+            delegate = %delegate,
+            nonce,
+            "account not delegated yet; attaching EIP-7702 authorization"
+        );
+        sign_eip7702_authorization(&self.owner_key, self.chain_id, delegate, nonce).map(Some)
     }
 
     fn entrypoint(&self) -> IEntryPointNonces::IEntryPointNoncesInstance<&DynProvider> {
@@ -506,67 +694,32 @@ impl Safe4337UserOpSender {
     }
 
     async fn send_self_paid(
-        &mut self,
+        &self,
         mut userop: PackedUserOperation,
+        eip7702_auth: Option<&SignedAuthorization>,
     ) -> Result<Safe4337UserOpSubmission> {
         // If we're here, either no paymasters are configured or they all failed. Surface a clearer
         // error than "eth_estimateUserOperationGas" if the Safe is unfunded.
         self.preflight_self_paid().await?;
 
-        userop.signature = self.sign_userop(&userop)?.into();
-
-        let estimate_res = self
-            .bundlers
-            .estimate_user_operation_gas(&userop, self.cfg.entrypoint)
-            .await;
+        userop.signature = self.estimation_signature(&userop).await?.into();
 
-        let estimate = match estimate_res {
-            Ok(v) => v,
-            Err(e) => {
-                tracing::warn!(
-                    safe = %self.safe,
-                    entrypoint = %self.cfg.entrypoint,
-                    "estimate failed; attempting EntryPoint.simulateValidation eth_call for richer revert"
-                );
-                // Best-effort; ignore errors and keep original estimate error.
-                let _ = crate::entrypoint_sim::debug_simulate_validation(
-                    &self.provider,
-                    self.cfg.entrypoint,
-                    &userop,
-                )
-                .await;
-
-                // Surface the bundler's estimate error to the caller. The previous behavior
-                // of falling through with the base userop's hardcoded gas (50M call + 5M
-                // verification + 2M preverif = 57M total) is structurally broken: Pimlico
-                // caps at 20M per-userOp on Arbitrum, so the fallback always returned a
-                // misleading "gas limits exceed the max gas per userOp" 502 instead of the
-                // real underlying error (typically AA25, a paymaster issue, or a callee
-                // revert). Lowering the fallback isn't viable either — sending a legit
-                // userop with under-estimated gas just lands a reverting tx that drains
-                // funds without creating the lease. Failing fast lets the client retry
-                // cleanly and surfaces real outages in monitoring.
-                tracing::warn!(
-                    safe = %self.safe,
-                    entrypoint = %self.cfg.entrypoint,
-                    err = %format!("{e:#}"),
-                    "self-paid estimate failed; bailing instead of sending with hardcoded gas (would exceed Pimlico's per-userOp cap)"
-                );
-                return Err(e).context("bundler estimate userop gas (self-paid)");
-            }
-        };
-
-        userop.call_gas_limit = add_gas_buffer(estimate.call_gas_limit, GAS_BUFFER_PCT)?;
-        userop.verification_gas_limit =
-            add_gas_buffer(estimate.verification_gas_limit, GAS_BUFFER_PCT)?;
-        userop.pre_verification_gas =
-            add_gas_buffer(estimate.pre_verification_gas, GAS_BUFFER_PCT)?;
+        // Never fall back to hardcoded gas: Pimlico caps at 20M per-userOp on Arbitrum, so a
+        // generous constant gets rejected with a misleading "gas limits exceed the max gas per
+        // userOp", and an under-estimate lands a reverting tx that drains funds without
+        // creating the lease. Without a bundler or local estimate we fail fast instead.
+        let (estimate, source) = self
+            .estimate_gas(&userop, eip7702_auth)
+            .await
+            .context("bundler estimate userop gas (self-paid)")?;
+        self.cfg.options.gas.apply(&estimate, &mut userop)?;
 
-        userop.signature = self.sign_userop(&userop)?.into();
+        userop.signature = self.sign_userop(&userop).await?.into();
 
         let nonce = userop.nonce;
+        let gas = UserOpGasLimits::of(&userop, &estimate, source);
         let (resp, send_attempts) = self
-            .send_user_operation_with_retries(userop)
+            .send_user_operation_with_retries(&mut userop, eip7702_auth)
             .await
             .context("bundler send userop")?;
 
@@ -574,15 +727,20 @@ impl Safe4337UserOpSender {
             userop_hash: hex_bytes0x(&resp.user_op_hash),
             nonce,
             send_attempts,
+            gas,
+            userop,
+            eip7702_auth: eip7702_auth.cloned(),
+            paymaster_idx: None,
         })
     }
 
     async fn send_with_paymaster(
-        &mut self,
-        pool: &mut PaymasterPool,
+        &self,
+        pool: &PaymasterPool,
         idx: usize,
         svc: &PaymasterService,
         mut userop: PackedUserOperation,
+        eip7702_auth: Option<&SignedAuthorization>,
     ) -> Result<Safe4337UserOpSubmission> {
         let paymaster_url = redact_url(&svc.url);
         tracing::info!(
@@ -596,7 +754,11 @@ impl Safe4337UserOpSender {
             "starting paymaster attempt"
         );
 
-        let pm_userop = to_paymaster_userop(&userop, self.cfg.options.paymaster_finalization)?;
+        let pm_userop = to_paymaster_userop(
+            &userop,
+            self.cfg.options.paymaster_finalization,
+            eip7702_auth,
+        )?;
         let stub = pool
             .get_stub_data(idx, &pm_userop, self.cfg.entrypoint, self.chain_id)
             .await?;
@@ -646,46 +808,23 @@ impl Safe4337UserOpSender {
         userop.paymaster_post_op_gas_limit =
             Some(add_gas_buffer(pm_post, PAYMASTER_POST_OP_GAS_BUFFER_PCT)?);
 
-        userop.signature = self.sign_userop(&userop)?.into();
-
-        let estimate_res = self
-            .bundlers
-            .estimate_user_operation_gas(&userop, self.cfg.entrypoint)
-            .await;
+        userop.signature = self.estimation_signature(&userop).await?.into();
 
-        let estimate = match estimate_res {
-            Ok(v) => v,
-            Err(e) => {
-                tracing::warn!(
-                    safe = %self.safe,
-                    entrypoint = %self.cfg.entrypoint,
-                    paymaster = %paymaster_url,
-                    paymaster_address = %paymaster,
-                    err = %format!("{e:#}"),
-                    "estimate failed; attempting EntryPoint.simulateValidation eth_call for richer revert"
-                );
-                let _ = crate::entrypoint_sim::debug_simulate_validation(
-                    &self.provider,
-                    self.cfg.entrypoint,
-                    &userop,
-                )
-                .await;
-                return Err(e).context("bundler estimate userop gas");
-            }
-        };
-
-        userop.call_gas_limit = add_gas_buffer(estimate.call_gas_limit, GAS_BUFFER_PCT)?;
-        userop.verification_gas_limit =
-            add_gas_buffer(estimate.verification_gas_limit, GAS_BUFFER_PCT)?;
-        userop.pre_verification_gas =
-            add_gas_buffer(estimate.pre_verification_gas, GAS_BUFFER_PCT)?;
+        let (estimate, source) = self
+            .estimate_gas(&userop, eip7702_auth)
+            .await
+            .context("bundler estimate userop gas")?;
+        self.cfg.options.gas.apply(&estimate, &mut userop)?;
 
         let pm_ver_base = match userop.paymaster_verification_gas_limit {
             Some(v) if v > estimate.paymaster_verification_gas_limit => v,
             _ => estimate.paymaster_verification_gas_limit,
         };
-        let (pm_ver, capped) =
-            cap_paymaster_verification_gas_limit(add_gas_buffer(pm_ver_base, GAS_BUFFER_PCT)?);
+        let (pm_ver, capped) = cap_paymaster_verification_gas_limit(scale(
+            pm_ver_base,
+            self.cfg.options.gas.paymaster_verification_gas_pct,
+            None,
+        )?);
         if capped {
             tracing::warn!(
                 paymaster = %paymaster_url,
@@ -718,8 +857,11 @@ impl Safe4337UserOpSender {
                         stub_len,
                         "fetching finalized paymaster data"
                     );
-                    let pm_userop_final =
-                        to_paymaster_userop(&userop, self.cfg.options.paymaster_finalization)?;
+                    let pm_userop_final = to_paymaster_userop(
+                        &userop,
+                        self.cfg.options.paymaster_finalization,
+                        eip7702_auth,
+                    )?;
                     let final_data = pool
                         .get_data(idx, &pm_userop_final, self.cfg.entrypoint, self.chain_id)
                         .await?;
@@ -766,8 +908,11 @@ impl Safe4337UserOpSender {
                     stub_len,
                     "fetching finalized paymaster data"
                 );
-                let pm_userop =
-                    to_paymaster_userop(&userop, self.cfg.options.paymaster_finalization)?;
+                let pm_userop = to_paymaster_userop(
+                    &userop,
+                    self.cfg.options.paymaster_finalization,
+                    eip7702_auth,
+                )?;
                 let final_data = pool
                     .get_data(idx, &pm_userop, self.cfg.entrypoint, self.chain_id)
                     .await?;
@@ -803,7 +948,7 @@ impl Safe4337UserOpSender {
         }
 
         // Do not mutate any signed UserOperation fields after paymaster finalization.
-        userop.signature = self.sign_userop(&userop)?.into();
+        userop.signature = self.sign_userop(&userop).await?.into();
         let nonce = userop.nonce;
         tracing::info!(
             safe = %self.safe,
@@ -819,8 +964,9 @@ impl Safe4337UserOpSender {
             paymaster_data_len = ?userop.paymaster_data.as_ref().map(|data| data.len()),
             "submitting sponsored userop"
         );
+        let gas = UserOpGasLimits::of(&userop, &estimate, source);
         let (resp, send_attempts) = self
-            .send_user_operation_with_retries(userop)
+            .send_user_operation_with_retries(&mut userop, eip7702_auth)
             .await
             .context("bundler send userop")?;
 
@@ -839,12 +985,107 @@ impl Safe4337UserOpSender {
             userop_hash: hex_bytes0x(&resp.user_op_hash),
             nonce,
             send_attempts,
+            gas,
+            userop,
+            eip7702_auth: eip7702_auth.cloned(),
+            paymaster_idx: Some(idx),
         })
     }
 
+    /// Bundler gas estimate, falling back to [`crate::entrypoint_sim::estimate_locally`] when
+    /// every bundler fails for a reason other than the op failing validation.
+    async fn estimate_gas(
+        &self,
+        userop: &PackedUserOperation,
+        eip7702_auth: Option<&SignedAuthorization>,
+    ) -> Result<(UserOperationGasEstimationV07, GasEstimateSource)> {
+        let err = match self
+            .bundlers
+            .estimate_user_operation_gas(userop, eip7702_auth, self.cfg.entrypoint)
+            .await
+        {
+            Ok(estimate) => return Ok((estimate, GasEstimateSource::Bundler)),
+            Err(err) => err,
+        };
+
+        tracing::warn!(
+            safe = %self.safe,
+            entrypoint = %self.cfg.entrypoint,
+            paymaster = ?userop.paymaster,
+            err = %format!("{err:#}"),
+            "estimate failed; attempting EntryPoint.simulateValidation eth_call for richer revert"
+        );
+        // Best-effort; keep the original estimate error either way.
+        let simulated_verification_gas =
+            crate::entrypoint_sim::simulate_validation(&self.provider, self.cfg.entrypoint, userop)
+                .await;
+
+        let opts = &self.cfg.options.gas;
+        if !opts.local_fallback || is_validation_error(&err) {
+            return Err(err);
+        }
+        match crate::entrypoint_sim::estimate_locally(
+            &self.provider,
+            self.cfg.entrypoint,
+            userop,
+            simulated_verification_gas.unwrap_or(opts.local_verification_gas),
+        )
+        .await
+        {
+            Ok(estimate) => {
+                tracing::warn!(
+                    safe = %self.safe,
+                    call_gas_limit = %estimate.call_gas_limit,
+                    verification_gas_limit = %estimate.verification_gas_limit,
+                    pre_verification_gas = %estimate.pre_verification_gas,
+                    "bundlers failed to estimate; using local gas estimate"
+                );
+                Ok((estimate, GasEstimateSource::Local))
+            }
+            Err(local_err) => {
+                tracing::warn!(
+                    safe = %self.safe,
+                    err = %format!("{local_err:#}"),
+                    "local gas estimate failed"
+                );
+                Err(err)
+            }
+        }
+    }
+
+    /// `(maxFeePerGas, maxPriorityFeePerGas)` for a new userop at current network prices.
+    async fn network_fees(&self) -> Result<(U256, U256)> {
+        // Prefer standard EIP-1559 fee estimation (eth_feeHistory). This avoids bundler-specific gas APIs.
+        match self.provider.estimate_eip1559_fees().await {
+            Ok(est) => {
+                let mut max_fee = est.max_fee_per_gas;
+                let max_priority = est.max_priority_fee_per_gas.max(MIN_PRIORITY_FEE_WEI);
+                if max_fee < max_priority {
+                    max_fee = max_priority;
+                }
+                Ok((U256::from(max_fee), U256::from(max_priority)))
+            }
+            Err(err) => {
+                // Fallback for non-EIP-1559 chains / RPCs: eth_gasPrice with a 2x buffer.
+                tracing::warn!(err = %err, "estimate_eip1559_fees failed; falling back to eth_gasPrice");
+                let gas_price: u128 = self
+                    .provider
+                    .get_gas_price()
+                    .await
+                    .context("eth_gasPrice")?;
+                let max_fee = gas_price.saturating_mul(2);
+                Ok((
+                    U256::from(max_fee),
+                    U256::from(MIN_PRIORITY_FEE_WEI.min(max_fee)),
+                ))
+            }
+        }
+    }
+
     async fn send_user_operation_with_retries(
-        &mut self,
-        mut userop: PackedUserOperation,
+        &self,
+        userop: &mut PackedUserOperation,
+        eip7702_auth: Option<&SignedAuthorization>,
     ) -> Result<(
         alloy::rpc::types::eth::erc4337::SendUserOperationResponse,
         u64,
@@ -891,7 +1132,7 @@ impl Safe4337UserOpSender {
                     }
 
                     // Any fee change requires re-signing.
-                    userop.signature = self.sign_userop(&userop)?.into();
+                    userop.signature = self.sign_userop(userop).await?.into();
 
                     tracing::warn!(
                         attempt,
@@ -904,7 +1145,7 @@ impl Safe4337UserOpSender {
 
             match self
                 .bundlers
-                .send_user_operation(&userop, self.cfg.entrypoint)
+                .send_user_operation(userop, eip7702_auth, self.cfg.entrypoint)
                 .await
             {
                 Ok(resp) => return Ok((resp, (attempt + 1) as u64)),
@@ -927,7 +1168,7 @@ impl Safe4337UserOpSender {
                             if userop.max_fee_per_gas < userop.max_priority_fee_per_gas {
                                 userop.max_fee_per_gas = userop.max_priority_fee_per_gas;
                             }
-                            userop.signature = self.sign_userop(&userop)?.into();
+                            userop.signature = self.sign_userop(userop).await?.into();
                             tracing::warn!(
                                 attempt,
                                 min_required = %min,
@@ -954,20 +1195,102 @@ impl Safe4337UserOpSender {
         unreachable!("loop returns on success or last error")
     }
 
-    fn sign_userop(&self, userop: &PackedUserOperation) -> Result<Vec<u8>> {
-        sign_userop_with_key(
-            &self.owner_key,
-            self.chain_id,
-            self.cfg.safe_4337_module,
-            self.cfg.entrypoint,
-            userop,
-        )
+    /// Signature for an op that is only estimated. Safes get placeholder owner signatures, so
+    /// co-signers are only asked for the op that is actually sent.
+    async fn estimation_signature(&self, userop: &PackedUserOperation) -> Result<Vec<u8>> {
+        match self.cfg.account {
+            SmartAccountKind::Safe4337 => {
+                safe_userop_signature(&placeholder_owner_signatures(self.threshold))
+            }
+            SmartAccountKind::Eip7702 { .. } => self.sign_userop(userop).await,
+        }
+    }
+
+    async fn sign_userop(&self, userop: &PackedUserOperation) -> Result<Vec<u8>> {
+        match self.cfg.account {
+            SmartAccountKind::Safe4337 => {
+                let request = SafeOpSigningRequest {
+                    chain_id: self.chain_id,
+                    safe_4337_module: self.cfg.safe_4337_module,
+                    entry_point: self.cfg.entrypoint,
+                    safe_op_hash: safeop_digest(
+                        self.chain_id,
+                        self.cfg.safe_4337_module,
+                        self.cfg.entrypoint,
+                        userop,
+                    )?,
+                    user_op: userop.clone(),
+                };
+
+                // Ask owners in order and stop at the threshold; an unreachable co-signer is
+                // tolerated as long as enough others sign.
+                let mut signatures = Vec::with_capacity(self.threshold);
+                for signer in &self.signers {
+                    if signatures.len() >= self.threshold {
+                        break;
+                    }
+                    match signer.sign_safe_op(&request).await {
+                        Ok(sig) => signatures.push((signer.owner(), sig)),
+                        Err(err) => tracing::warn!(
+                            owner = %signer.owner(),
+                            err = %format!("{err:#}"),
+                            "safe owner failed to sign SafeOp"
+                        ),
+                    }
+                }
+
+                safe_userop_signature(&concat_owner_signatures(signatures, self.threshold)?)
+            }
+            SmartAccountKind::Eip7702 { delegate } => sign_userop_v08_with_key(
+                &self.owner_key,
+                self.chain_id,
+                self.cfg.entrypoint,
+                userop,
+                Some(delegate),
+            ),
+        }
     }
 }
 
+/// The Safe's on-chain threshold, after checking every signer is an owner and that there are
+/// enough of them to meet it.
+async fn safe_signing_threshold(
+    provider: &DynProvider,
+    safe: Address,
+    signers: &[Arc<dyn SafeOpSigner>],
+) -> Result<usize> {
+    let contract = ISafe::new(safe, provider);
+    let owners = contract
+        .getOwners()
+        .call()
+        .await
+        .context("Safe.getOwners")?;
+    let threshold = contract
+        .getThreshold()
+        .call()
+        .await
+        .context("Safe.getThreshold")?;
+    let threshold = usize::try_from(threshold).context("safe threshold overflows usize")?;
+
+    for signer in signers {
+        anyhow::ensure!(
+            owners.contains(&signer.owner()),
+            "signer {} is not an owner of safe {safe:#x}",
+            signer.owner()
+        );
+    }
+    anyhow::ensure!(
+        signers.len() >= threshold,
+        "safe {safe:#x} needs {threshold} owner signatures but only {} signers are configured",
+        signers.len()
+    );
+    Ok(threshold)
+}
+
 fn to_paymaster_userop(
     op: &PackedUserOperation,
     mode: PaymasterFinalizationMode,
+    eip7702_auth: Option<&SignedAuthorization>,
 ) -> Result<PaymasterUserOp> {
     Ok(PaymasterUserOp {
         sender: op.sender,
@@ -987,5 +1310,13 @@ fn to_paymaster_userop(
             PaymasterFinalizationMode::SkipIfStubFinal => None,
             PaymasterFinalizationMode::AlwaysFetchFinal => op.paymaster_data.clone(),
         },
+        eip7702_auth: eip7702_auth.cloned(),
     })
 }
+
+fn parse_userop_hash(userop_hash0x: &str) -> Result<Bytes> {
+    let userop_hash0x = userop_hash0x.trim();
+    let hex_str = userop_hash0x.strip_prefix("0x").unwrap_or(userop_hash0x);
+    let bytes = hex::decode(hex_str).context("invalid userop hash hex")?;
+    Ok(Bytes::from(bytes))
+}
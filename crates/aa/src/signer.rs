@@ -0,0 +1,213 @@
+use crate::packing::redact_url;
+use alloy::primitives::{Address, B256, Bytes, Signature, hex};
+use alloy::rpc::types::eth::erc4337::PackedUserOperation;
+use anyhow::{Context, Result};
+use k256::ecdsa::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+pub type SignFuture<'a> = Pin<Box<dyn Future<Output = Result<[u8; 65]>> + Send + 'a>>;
+
+/// What a Safe owner is asked to sign: the SafeOp EIP-712 `safe_op_hash` of `user_op`, with
+/// enough context for a remote co-signer to re-derive and vet it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeOpSigningRequest {
+    pub chain_id: u64,
+    pub safe_4337_module: Address,
+    pub entry_point: Address,
+    pub safe_op_hash: B256,
+    pub user_op: PackedUserOperation,
+}
+
+/// One owner of a multi-owner Safe. The sender collects signatures from its signers until the
+/// Safe's threshold is met.
+pub trait SafeOpSigner: std::fmt::Debug + Send + Sync {
+    /// Safe owner the signature must recover to.
+    fn owner(&self) -> Address;
+
+    /// 65-byte `r || s || v` (v = 27/28) ECDSA signature over `request.safe_op_hash`.
+    fn sign_safe_op<'a>(&'a self, request: &'a SafeOpSigningRequest) -> SignFuture<'a>;
+}
+
+/// Signs with a key held in memory (the relayer's hot owner key).
+pub struct LocalSafeOpSigner {
+    key: SigningKey,
+    owner: Address,
+}
+
+impl LocalSafeOpSigner {
+    pub fn new(private_key: [u8; 32]) -> Result<Self> {
+        let key = SigningKey::from_slice(&private_key).context("invalid owner private key")?;
+        Ok(Self::from_key(key))
+    }
+
+    pub(crate) fn from_key(key: SigningKey) -> Self {
+        let owner = Address::from_public_key(key.verifying_key());
+        Self { key, owner }
+    }
+
+    pub(crate) fn sign_digest(&self, digest: B256) -> Result<[u8; 65]> {
+        let (sig, recid) = self
+            .key
+            .sign_prehash_recoverable(digest.as_slice())
+            .context("sign SafeOp digest")?;
+        let mut out = [0u8; 65];
+        out[..64].copy_from_slice(&sig.to_bytes());
+        out[64] = recid.to_byte() + 27;
+        Ok(out)
+    }
+}
+
+impl std::fmt::Debug for LocalSafeOpSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalSafeOpSigner")
+            .field("owner", &self.owner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SafeOpSigner for LocalSafeOpSigner {
+    fn owner(&self) -> Address {
+        self.owner
+    }
+
+    fn sign_safe_op<'a>(&'a self, request: &'a SafeOpSigningRequest) -> SignFuture<'a> {
+        Box::pin(async move { self.sign_digest(request.safe_op_hash) })
+    }
+}
+
+/// Co-signer service reached over HTTP: `POST url` with a [`SafeOpSigningRequest`] JSON body,
+/// answered with `{"signature": "0x…"}` from `owner`.
+#[derive(Debug, Clone)]
+pub struct HttpSafeOpSigner {
+    url: String,
+    owner: Address,
+    http: reqwest::Client,
+}
+
+impl HttpSafeOpSigner {
+    pub fn new(url: String, owner: Address, timeout: Duration) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .context("build co-signer http client")?;
+        Ok(Self { url, owner, http })
+    }
+
+    async fn request(&self, request: &SafeOpSigningRequest) -> Result<[u8; 65]> {
+        #[derive(Deserialize)]
+        struct SignResponse {
+            signature: Bytes,
+        }
+
+        let resp = self
+            .http
+            .post(&self.url)
+            .json(request)
+            .send()
+            .await
+            .with_context(|| format!("POST {}", redact_url(&self.url)))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("co-signer http {status}: {text}");
+        }
+        let body: SignResponse = resp.json().await.context("decode co-signer response")?;
+        let sig: [u8; 65] = body
+            .signature
+            .as_ref()
+            .try_into()
+            .context("co-signer signature must be 65 bytes")?;
+        // Catch misconfigured co-signers here rather than as an opaque AA24 from the bundler.
+        let recovered = recover_signer(request.safe_op_hash, &sig)?;
+        anyhow::ensure!(
+            recovered == self.owner,
+            "co-signer signature recovers to {recovered}, expected {}",
+            self.owner
+        );
+        Ok(sig)
+    }
+}
+
+impl SafeOpSigner for HttpSafeOpSigner {
+    fn owner(&self) -> Address {
+        self.owner
+    }
+
+    fn sign_safe_op<'a>(&'a self, request: &'a SafeOpSigningRequest) -> SignFuture<'a> {
+        Box::pin(self.request(request))
+    }
+}
+
+pub(crate) fn recover_signer(digest: B256, sig: &[u8; 65]) -> Result<Address> {
+    let parity = match sig[64] {
+        27 | 0 => false,
+        28 | 1 => true,
+        v => anyhow::bail!("unsupported signature v: {v}"),
+    };
+    Signature::from_bytes_and_parity(&sig[..64], parity)
+        .recover_address_from_prehash(&digest)
+        .context("recover SafeOp signer")
+}
+
+/// Safe `checkSignatures` input: `threshold` owner signatures in ascending owner order.
+pub(crate) fn concat_owner_signatures(
+    mut signatures: Vec<(Address, [u8; 65])>,
+    threshold: usize,
+) -> Result<Vec<u8>> {
+    signatures.sort_by_key(|(owner, _)| *owner);
+    signatures.dedup_by_key(|(owner, _)| *owner);
+    anyhow::ensure!(
+        signatures.len() >= threshold,
+        "only {} of {threshold} required Safe owner signatures",
+        signatures.len()
+    );
+    Ok(signatures
+        .into_iter()
+        .take(threshold)
+        .flat_map(|(_, sig)| sig)
+        .collect())
+}
+
+/// A well-formed ECDSA owner signature (`v = 28`) that no known key produced.
+const PLACEHOLDER_OWNER_SIGNATURE: [u8; 65] = hex!(
+    "fffffffffffffffffffffffffffffff000000000000000000000000000000000"
+    "7aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+    "1c"
+);
+
+/// Stand-in for [`concat_owner_signatures`] on ops that are only estimated: same length and
+/// ECDSA shape, so verification gas comes out the same without asking co-signers to sign.
+pub(crate) fn placeholder_owner_signatures(threshold: usize) -> Vec<u8> {
+    PLACEHOLDER_OWNER_SIGNATURE.repeat(threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owner_signatures_are_sorted_and_trimmed_to_threshold() {
+        let signers: Vec<LocalSafeOpSigner> = [3u8, 1, 2]
+            .into_iter()
+            .map(|b| LocalSafeOpSigner::new([b; 32]).unwrap())
+            .collect();
+        let digest = B256::repeat_byte(0xab);
+        let sigs: Vec<_> = signers
+            .iter()
+            .map(|s| (s.owner(), s.sign_digest(digest).unwrap()))
+            .collect();
+
+        let packed = concat_owner_signatures(sigs.clone(), 2).unwrap();
+        assert_eq!(packed.len(), 2 * 65);
+        let first = recover_signer(digest, &packed[..65].try_into().unwrap()).unwrap();
+        let second = recover_signer(digest, &packed[65..].try_into().unwrap()).unwrap();
+        assert!(first < second);
+
+        assert!(concat_owner_signatures(sigs[..1].to_vec(), 2).is_err());
+        assert_eq!(placeholder_owner_signatures(2).len(), packed.len());
+    }
+}
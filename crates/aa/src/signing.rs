@@ -1,10 +1,14 @@
-use alloy::primitives::{Address, FixedBytes, U256};
+use alloy::eips::eip7702::{Authorization, SignedAuthorization};
+use alloy::primitives::{Address, FixedBytes, Signature, U256};
 use alloy::sol_types::{Eip712Domain, SolStruct};
 use anyhow::{Context, Result};
 use k256::ecdsa::SigningKey;
 
-use crate::contracts::SafeOp;
-use crate::packing::{ensure_u48, pack_init_code, pack_paymaster_and_data, u48_be_bytes};
+use crate::account::EIP7702_INITCODE_MARKER;
+use crate::contracts::{PackedUserOperation as PackedUserOperationEip712, SafeOp};
+use crate::packing::{
+    ensure_u48, pack_init_code, pack_paymaster_and_data, pack_u128_pair, u48_be_bytes,
+};
 use alloy::rpc::types::eth::erc4337::PackedUserOperation;
 
 pub(crate) fn safeop_digest(
@@ -61,51 +65,127 @@ pub(crate) fn safeop_digest(
     Ok(safeop.eip712_signing_hash(&domain))
 }
 
-pub(crate) fn sign_userop_with_key(
-    owner_key: &SigningKey,
-    chain_id: u64,
-    safe_4337_module: Address,
-    entry_point: Address,
-    op: &PackedUserOperation,
-) -> Result<Vec<u8>> {
+/// Safe 4337 module `signature` field: `validAfter || validUntil` followed by the owner
+/// signatures over [`safeop_digest`] (see `signer::concat_owner_signatures`).
+pub(crate) fn safe_userop_signature(owner_signatures: &[u8]) -> Result<Vec<u8>> {
     let valid_after: u64 = 0;
     let valid_until: u64 = 0;
     ensure_u48(valid_after, "validAfter")?;
     ensure_u48(valid_until, "validUntil")?;
 
-    let digest = safeop_digest(chain_id, safe_4337_module, entry_point, op)?;
+    let mut out = Vec::with_capacity(12 + owner_signatures.len());
+    out.extend_from_slice(&u48_be_bytes(valid_after));
+    out.extend_from_slice(&u48_be_bytes(valid_until));
+    out.extend_from_slice(owner_signatures);
+    Ok(out)
+}
+
+/// EntryPoint v0.8 `getUserOpHash`: the op's EIP-712 hash under the EntryPoint's domain.
+///
+/// When the op carries `EIP7702_INITCODE_MARKER` as its factory, `eip7702_delegate` is hashed
+/// in its place, as the EntryPoint does with the sender's delegate.
+pub(crate) fn userop_hash_v08(
+    chain_id: u64,
+    entry_point: Address,
+    op: &PackedUserOperation,
+    eip7702_delegate: Option<Address>,
+) -> Result<FixedBytes<32>> {
+    let domain = Eip712Domain::new(
+        Some("ERC4337".into()),
+        Some("1".into()),
+        Some(U256::from(chain_id)),
+        Some(entry_point),
+        None,
+    );
+
+    let factory = match (op.factory, eip7702_delegate) {
+        (Some(factory), Some(delegate)) if factory == EIP7702_INITCODE_MARKER => Some(delegate),
+        (factory, _) => factory,
+    };
+    let init_code = pack_init_code(factory, op.factory_data.as_ref())?;
+    let paymaster_and_data = pack_paymaster_and_data(
+        op.paymaster,
+        op.paymaster_verification_gas_limit,
+        op.paymaster_post_op_gas_limit,
+        op.paymaster_data.as_ref(),
+    )?;
+
+    let packed = PackedUserOperationEip712 {
+        sender: op.sender,
+        nonce: op.nonce,
+        initCode: init_code.into(),
+        callData: op.call_data.clone(),
+        accountGasLimits: pack_u128_pair(op.verification_gas_limit, op.call_gas_limit)
+            .context("accountGasLimits")?
+            .into(),
+        preVerificationGas: op.pre_verification_gas,
+        gasFees: pack_u128_pair(op.max_priority_fee_per_gas, op.max_fee_per_gas)
+            .context("gasFees")?
+            .into(),
+        paymasterAndData: paymaster_and_data.into(),
+    };
+
+    Ok(packed.eip712_signing_hash(&domain))
+}
+
+/// Plain 65-byte signature over the v0.8 userop hash; `Simple7702Account` recovers it and
+/// compares against its own (the owner EOA's) address.
+pub(crate) fn sign_userop_v08_with_key(
+    owner_key: &SigningKey,
+    chain_id: u64,
+    entry_point: Address,
+    op: &PackedUserOperation,
+    eip7702_delegate: Option<Address>,
+) -> Result<Vec<u8>> {
+    let digest = userop_hash_v08(chain_id, entry_point, op, eip7702_delegate)?;
 
     let (sig, recid) = owner_key
         .sign_prehash_recoverable(digest.as_slice())
-        .context("sign SafeOp digest")?;
-
-    let mut sig65 = sig.to_bytes().to_vec();
-    sig65.push(recid.to_byte() + 27);
+        .context("sign userop hash")?;
 
-    let mut out = Vec::with_capacity(12 + sig65.len());
-    out.extend_from_slice(&u48_be_bytes(valid_after));
-    out.extend_from_slice(&u48_be_bytes(valid_until));
-    out.extend_from_slice(&sig65);
+    let mut out = sig.to_bytes().to_vec();
+    out.push(recid.to_byte() + 27);
     Ok(out)
 }
 
+/// Authorization delegating the owner EOA to `delegate`. `nonce` is the EOA's current
+/// transaction nonce: the bundler sends the transaction, so it is not bumped before the
+/// authorization is applied.
+pub(crate) fn sign_eip7702_authorization(
+    owner_key: &SigningKey,
+    chain_id: u64,
+    delegate: Address,
+    nonce: u64,
+) -> Result<SignedAuthorization> {
+    let auth = Authorization {
+        chain_id: U256::from(chain_id),
+        address: delegate,
+        nonce,
+    };
+
+    let (sig, recid) = owner_key
+        .sign_prehash_recoverable(auth.signature_hash().as_slice())
+        .context("sign EIP-7702 authorization")?;
+
+    let bytes = sig.to_bytes();
+    let signature = Signature::new(
+        U256::from_be_slice(&bytes[..32]),
+        U256::from_be_slice(&bytes[32..]),
+        recid.is_y_odd(),
+    );
+    Ok(auth.into_signed(signature))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::signer::LocalSafeOpSigner;
     use alloy::primitives::Bytes;
     use k256::ecdsa::VerifyingKey;
     use k256::ecdsa::signature::hazmat::PrehashVerifier;
 
-    #[test]
-    fn sign_userop_prefix_and_verifies() {
-        let owner_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
-        let verify_key = VerifyingKey::from(&owner_key);
-
-        let chain_id = 10u64;
-        let module = Address::repeat_byte(0x11);
-        let entry = Address::repeat_byte(0x22);
-
-        let op = PackedUserOperation {
+    fn sample_op() -> PackedUserOperation {
+        PackedUserOperation {
             sender: Address::repeat_byte(0x33),
             nonce: U256::from(1u64),
             factory: None,
@@ -121,13 +201,67 @@ mod tests {
             paymaster_post_op_gas_limit: None,
             paymaster_data: None,
             signature: Bytes::new(),
-        };
+        }
+    }
+
+    #[test]
+    fn v08_hash_substitutes_the_eip7702_delegate() {
+        let owner_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let verify_key = VerifyingKey::from(&owner_key);
+        let entry = Address::repeat_byte(0x22);
+        let delegate = Address::repeat_byte(0x44);
 
-        let sig = sign_userop_with_key(&owner_key, chain_id, module, entry, &op).unwrap();
+        let mut marked = sample_op();
+        marked.factory = Some(EIP7702_INITCODE_MARKER);
+        marked.factory_data = Some(Bytes::new());
+        let mut explicit = marked.clone();
+        explicit.factory = Some(delegate);
+
+        let hash = userop_hash_v08(10, entry, &marked, Some(delegate)).unwrap();
+        assert_eq!(hash, userop_hash_v08(10, entry, &explicit, None).unwrap());
+        assert_ne!(hash, userop_hash_v08(10, entry, &marked, None).unwrap());
+        assert_ne!(
+            hash,
+            userop_hash_v08(11, entry, &marked, Some(delegate)).unwrap()
+        );
+
+        let sig = sign_userop_v08_with_key(&owner_key, 10, entry, &marked, Some(delegate)).unwrap();
+        assert_eq!(sig.len(), 65);
+        let sig64 = k256::ecdsa::Signature::from_slice(&sig[..64]).unwrap();
+        verify_key.verify_prehash(hash.as_slice(), &sig64).unwrap();
+    }
+
+    #[test]
+    fn eip7702_authorization_recovers_to_owner() {
+        let owner_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let owner = Address::from_public_key(&VerifyingKey::from(&owner_key));
+        let delegate = Address::repeat_byte(0x44);
+
+        let signed = sign_eip7702_authorization(&owner_key, 10, delegate, 3).unwrap();
+        assert_eq!(signed.address, delegate);
+        assert_eq!(signed.nonce, 3);
+        assert_eq!(signed.recover_authority().unwrap(), owner);
+    }
+
+    #[test]
+    fn sign_userop_prefix_and_verifies() {
+        let owner_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let verify_key = VerifyingKey::from(&owner_key);
+
+        let chain_id = 10u64;
+        let module = Address::repeat_byte(0x11);
+        let entry = Address::repeat_byte(0x22);
+
+        let op = sample_op();
+
+        let digest = safeop_digest(chain_id, module, entry, &op).unwrap();
+        let owner_sig = LocalSafeOpSigner::from_key(owner_key)
+            .sign_digest(digest)
+            .unwrap();
+        let sig = safe_userop_signature(&owner_sig).unwrap();
         assert_eq!(sig.len(), 12 + 65);
         assert_eq!(&sig[0..12], &[0u8; 12]);
 
-        let digest = safeop_digest(chain_id, module, entry, &op).unwrap();
         let sig64 = k256::ecdsa::Signature::from_slice(&sig[12..12 + 64]).unwrap();
         verify_key
             .verify_prehash(digest.as_slice(), &sig64)
@@ -0,0 +1,237 @@
+use crate::http::RateLimited;
+use alloy_json_rpc::RpcError;
+use alloy_transport::{TransportError, TransportErrorKind};
+use std::fmt;
+
+/// How a failed RPC call should be treated: by the fallback transport when picking the next
+/// endpoint, and by callers deciding whether to retry or shrink a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcErrorKind {
+    /// The provider refused the block range or result size; retry with a smaller range.
+    RangeTooLarge,
+    /// The provider is throttling us; back off without adding requests.
+    RateLimited,
+    /// Timeouts, dropped connections, 5xx, lagging nodes; the same request may succeed later.
+    Transient,
+    /// Retrying the same request will not help (bad params, reverts, undecodable responses).
+    Permanent,
+}
+
+impl RpcErrorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RangeTooLarge => "range_too_large",
+            Self::RateLimited => "rate_limited",
+            Self::Transient => "transient",
+            Self::Permanent => "permanent",
+        }
+    }
+
+    /// Whether the same request is worth retrying after a backoff.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::RateLimited | Self::Transient)
+    }
+
+    pub fn from_transport_error(err: &TransportError) -> Self {
+        match err {
+            RpcError::ErrorResp(payload) => Self::from_json_rpc(payload.code, &payload.message),
+            RpcError::Transport(kind) => Self::from_transport_kind(kind),
+            // A null block/receipt is what a node that hasn't caught up yet returns.
+            RpcError::NullResp => Self::Transient,
+            _ => Self::Permanent,
+        }
+    }
+
+    fn from_transport_kind(kind: &TransportErrorKind) -> Self {
+        match kind {
+            TransportErrorKind::HttpError(http) => match http.status {
+                429 => Self::RateLimited,
+                413 => Self::RangeTooLarge,
+                408 | 500..=599 => Self::Transient,
+                _ => Self::Permanent,
+            },
+            TransportErrorKind::Custom(err) if err.is::<RateLimited>() => Self::RateLimited,
+            // Connection/timeout failures (including "all rpc endpoints failed" from the
+            // fallback transport) never reached a node that could judge the request.
+            TransportErrorKind::Custom(_)
+            | TransportErrorKind::BackendGone
+            | TransportErrorKind::MissingBatchResponse(_) => Self::Transient,
+            _ => Self::Permanent,
+        }
+    }
+
+    /// Classifies a JSON-RPC error object. Range-too-large wins over rate limiting, since
+    /// -32005 is both Infura's rate limit and its result-size limit.
+    pub fn from_json_rpc(code: i64, message: &str) -> Self {
+        let message = message.to_ascii_lowercase();
+        if RANGE_TOO_LARGE
+            .iter()
+            .any(|(c, fragment)| *c == code && message.contains(fragment))
+        {
+            return Self::RangeTooLarge;
+        }
+        if RATE_LIMIT_CODES.contains(&code) {
+            return Self::RateLimited;
+        }
+        if TRANSIENT_CODES.contains(&code) {
+            return Self::Transient;
+        }
+        // -32000 ("server error") carries no meaning of its own; providers put it in the message.
+        if code == SERVER_ERROR {
+            if SERVER_ERROR_RATE_LIMITED
+                .iter()
+                .any(|fragment| message.contains(fragment))
+            {
+                return Self::RateLimited;
+            }
+            if SERVER_ERROR_TRANSIENT
+                .iter()
+                .any(|fragment| message.contains(fragment))
+            {
+                return Self::Transient;
+            }
+        }
+        Self::Permanent
+    }
+}
+
+impl fmt::Display for RpcErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `(code, message fragment)` pairs providers use to reject an oversized `eth_getLogs`.
+///
+/// The codes alone are ambiguous: -32602 is also plain invalid params and -32005 is also
+/// Infura's rate limit.
+const RANGE_TOO_LARGE: [(i64, &str); 6] = [
+    // Infura, java-tron: "query returned more than 10000 results"
+    (-32005, "more than"),
+    // java-tron: "exceed max block range: 5000"
+    (-32602, "block range"),
+    // Alchemy: "Log response size exceeded. You can make eth_getLogs requests with ..."
+    (-32602, "response size"),
+    // QuickNode: "eth_getLogs is limited to a 10,000 range"
+    (-32614, ""),
+    // geth/erigon-derived nodes: "block range too large", "query returned more than ..."
+    (-32000, "too large"),
+    (-32000, "more than"),
+];
+
+const SERVER_ERROR: i64 = -32000;
+/// Infura / java-tron "limit exceeded", Alchemy compute-unit throttling.
+const RATE_LIMIT_CODES: [i64; 2] = [-32005, 429];
+/// Internal error, resource unavailable, resource not found (lagging node).
+const TRANSIENT_CODES: [i64; 3] = [-32603, -32002, -32001];
+const SERVER_ERROR_RATE_LIMITED: [&str; 3] = ["rate limit", "too many requests", "limit exceeded"];
+/// Errors specific to the node that answered: lagging (the block isn't there yet), pruned (the
+/// state is gone) or overloaded. Another endpoint may well serve the same request.
+const SERVER_ERROR_TRANSIENT: [&str; 10] = [
+    "header not found",
+    "unknown block",
+    "block not found",
+    // geth: "request beyond head block"
+    "beyond head block",
+    // geth/erigon pruned state: "missing trie node ...", "required historical state unavailable"
+    "missing trie node",
+    "historical state",
+    "timeout",
+    "timed out",
+    "temporarily unavailable",
+    "overloaded",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_json_rpc_errors_by_code_and_provider() {
+        let cases = [
+            (
+                -32005,
+                "query returned more than 10000 results",
+                RpcErrorKind::RangeTooLarge,
+            ),
+            (
+                -32602,
+                "exceed max block range: 5000",
+                RpcErrorKind::RangeTooLarge,
+            ),
+            (
+                -32614,
+                "eth_getLogs is limited to a 10,000 range",
+                RpcErrorKind::RangeTooLarge,
+            ),
+            (
+                -32005,
+                "daily request count exceeded",
+                RpcErrorKind::RateLimited,
+            ),
+            (
+                429,
+                "exceeded its compute units per second",
+                RpcErrorKind::RateLimited,
+            ),
+            (-32000, "header not found", RpcErrorKind::Transient),
+            (
+                -32000,
+                "missing trie node 0x1234 (path )",
+                RpcErrorKind::Transient,
+            ),
+            (
+                -32000,
+                "required historical state unavailable (reexec=128)",
+                RpcErrorKind::Transient,
+            ),
+            (-32000, "request beyond head block", RpcErrorKind::Transient),
+            (-32000, "execution reverted", RpcErrorKind::Permanent),
+            (-32603, "internal error", RpcErrorKind::Transient),
+            (-32602, "invalid argument 0", RpcErrorKind::Permanent),
+            (3, "execution reverted", RpcErrorKind::Permanent),
+            // Message text only counts for the code a provider uses it with.
+            (
+                -32601,
+                "method eth_getLogs timed out",
+                RpcErrorKind::Permanent,
+            ),
+        ];
+        for (code, message, expected) in cases {
+            assert_eq!(
+                RpcErrorKind::from_json_rpc(code, message),
+                expected,
+                "{code} {message}"
+            );
+        }
+    }
+
+    #[test]
+    fn classifies_transport_errors() {
+        let http = |status| {
+            RpcErrorKind::from_transport_error(&TransportErrorKind::http_error(
+                status,
+                String::new(),
+            ))
+        };
+        assert_eq!(http(429), RpcErrorKind::RateLimited);
+        assert_eq!(http(413), RpcErrorKind::RangeTooLarge);
+        assert_eq!(http(503), RpcErrorKind::Transient);
+        assert_eq!(http(401), RpcErrorKind::Permanent);
+
+        let dropped = TransportErrorKind::custom(std::io::Error::other("connection reset"));
+        assert_eq!(
+            RpcErrorKind::from_transport_error(&dropped),
+            RpcErrorKind::Transient
+        );
+
+        let limited = TransportErrorKind::custom(RateLimited {
+            retry_after: None,
+            body: String::new(),
+        });
+        assert_eq!(
+            RpcErrorKind::from_transport_error(&limited),
+            RpcErrorKind::RateLimited
+        );
+    }
+}
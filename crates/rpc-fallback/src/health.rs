@@ -0,0 +1,294 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Weight of the newest sample in an endpoint's latency EWMA.
+const LATENCY_EWMA_ALPHA: f64 = 0.3;
+/// Weight of the newest attempt in an endpoint's error-rate EWMA.
+const ERROR_RATE_EWMA_ALPHA: f64 = 0.2;
+/// Latency-equivalent cost of a 100% error rate when ranking endpoints.
+const ERROR_PENALTY_MS: f64 = 2_000.0;
+/// Assumed latency of an endpoint that has not answered yet, so untried endpoints keep their
+/// configured order.
+const UNPROBED_LATENCY_MS: f64 = 250.0;
+/// The current endpoint keeps priority until another one scores this many times better, so
+/// near-equal nodes don't flap on every latency sample.
+const SWITCH_HYSTERESIS: f64 = 1.5;
+/// Successful latencies kept per endpoint for hedge-delay percentiles.
+const LATENCY_WINDOW: usize = 64;
+/// Fewer samples than this make percentiles too noisy to hedge on.
+const MIN_PERCENTILE_SAMPLES: usize = 16;
+
+/// Ejects an endpoint for `cooldown` after `failure_threshold` consecutive failed attempts.
+///
+/// Once the cooldown has passed the endpoint gets one trial request: success closes the
+/// circuit, failure re-opens it for another cooldown.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerPolicy {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Point-in-time view of one endpoint's health, as reported to `FallbackObserver::on_health`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EndpointStats {
+    /// `None` until the endpoint has answered once.
+    pub latency_ewma_ms: Option<f64>,
+    /// EWMA of failed attempts, 0.0 (healthy) to 1.0.
+    pub error_rate: f64,
+    pub circuit_open: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EndpointHealth {
+    latency_ms: Option<f64>,
+    error_rate: f64,
+    consecutive_failures: u32,
+    /// Set while the circuit breaker has the endpoint ejected (or on trial after that).
+    open_until: Option<Instant>,
+    /// While half-open, when the outstanding trial request times out. Expiring rather than
+    /// being cleared on every path keeps a cancelled trial from wedging the endpoint.
+    trial_until: Option<Instant>,
+    /// Skip the endpoint until then; it rate limited us.
+    backoff_until: Option<Instant>,
+    recent_ms: VecDeque<u64>,
+}
+
+/// Circuit breaker transition caused by one recorded attempt.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum CircuitChange {
+    Opened(Duration),
+    Closed,
+}
+
+impl EndpointHealth {
+    /// Lower is better.
+    fn score(&self) -> f64 {
+        self.latency_ms.unwrap_or(UNPROBED_LATENCY_MS) + self.error_rate * ERROR_PENALTY_MS
+    }
+
+    fn is_open(&self, now: Instant) -> bool {
+        self.open_until.is_some_and(|until| until > now)
+    }
+
+    /// Cooldown over, but not yet closed by a successful trial.
+    fn is_half_open(&self, now: Instant) -> bool {
+        self.open_until.is_some_and(|until| until <= now)
+    }
+
+    fn trial_in_flight(&self, now: Instant) -> bool {
+        self.trial_until.is_some_and(|until| until > now)
+    }
+
+    /// Ejected, or half-open with its one trial request already out.
+    fn is_unavailable(&self, now: Instant) -> bool {
+        self.is_open(now) || (self.is_half_open(now) && self.trial_in_flight(now))
+    }
+
+    /// Whether an attempt may start now. A half-open endpoint admits one trial at a time,
+    /// outstanding until `until` at the latest.
+    pub(crate) fn claim_trial(&mut self, now: Instant, until: Instant) -> bool {
+        if !self.is_half_open(now) {
+            return true;
+        }
+        if self.trial_in_flight(now) {
+            return false;
+        }
+        self.trial_until = Some(until);
+        true
+    }
+
+    pub(crate) fn is_backing_off(&self, now: Instant) -> bool {
+        self.backoff_until.is_some_and(|until| until > now)
+    }
+
+    pub(crate) fn back_off(&mut self, until: Instant) {
+        self.backoff_until = Some(until);
+    }
+
+    pub(crate) fn stats(&self, now: Instant) -> EndpointStats {
+        EndpointStats {
+            latency_ewma_ms: self.latency_ms,
+            error_rate: self.error_rate,
+            circuit_open: self.is_open(now),
+        }
+    }
+
+    pub(crate) fn record_latency(&mut self, ms: f64) {
+        self.latency_ms = Some(match self.latency_ms {
+            Some(prev) => prev + LATENCY_EWMA_ALPHA * (ms - prev),
+            None => ms,
+        });
+    }
+
+    pub(crate) fn record_success(&mut self, ms: u64) -> Option<CircuitChange> {
+        self.record_latency(ms as f64);
+        self.error_rate *= 1.0 - ERROR_RATE_EWMA_ALPHA;
+        self.consecutive_failures = 0;
+        if self.recent_ms.len() == LATENCY_WINDOW {
+            self.recent_ms.pop_front();
+        }
+        self.recent_ms.push_back(ms);
+        self.trial_until = None;
+        self.open_until.take().map(|_| CircuitChange::Closed)
+    }
+
+    /// `ms` is `Some` when the endpoint did answer (slowly or with an error), so it says
+    /// something about its latency.
+    pub(crate) fn record_failure(
+        &mut self,
+        ms: Option<u64>,
+        breaker: Option<&CircuitBreakerPolicy>,
+        now: Instant,
+    ) -> Option<CircuitChange> {
+        if let Some(ms) = ms {
+            self.record_latency(ms as f64);
+        }
+        self.error_rate += ERROR_RATE_EWMA_ALPHA * (1.0 - self.error_rate);
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.trial_until = None;
+
+        let breaker = breaker?;
+        if self.consecutive_failures < breaker.failure_threshold || self.is_open(now) {
+            return None;
+        }
+        self.open_until = Some(now + breaker.cooldown);
+        Some(CircuitChange::Opened(breaker.cooldown))
+    }
+
+    /// The `percentile` (0.0..=1.0) of recent successful latencies, once there are enough.
+    pub(crate) fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.recent_ms.len() < MIN_PERCENTILE_SAMPLES {
+            return None;
+        }
+        let mut sorted = self.recent_ms.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        let rank = (percentile.clamp(0.0, 1.0) * (sorted.len() - 1) as f64).round() as usize;
+        Some(Duration::from_millis(sorted[rank]))
+    }
+}
+
+/// Endpoint indices to try, best first: by score, then by configured order. `preferred` stays
+/// first unless another endpoint beats it by more than `SWITCH_HYSTERESIS`.
+///
+/// Endpoints backing off after a 429 are left out, and so are endpoints with an open circuit
+/// (or a half-open one whose trial is still out) unless every remaining endpoint is in the same
+/// state (better a likely failure than none at all).
+pub(crate) fn rank(health: &[EndpointHealth], preferred: usize, now: Instant) -> Vec<usize> {
+    let mut order = (0..health.len())
+        .filter(|&idx| !health[idx].is_backing_off(now))
+        .collect::<Vec<_>>();
+    if order.iter().any(|&idx| !health[idx].is_unavailable(now)) {
+        order.retain(|&idx| !health[idx].is_unavailable(now));
+    }
+    order.sort_by(|&a, &b| {
+        health[a]
+            .score()
+            .total_cmp(&health[b].score())
+            .then(a.cmp(&b))
+    });
+
+    if let Some(pos) = order.iter().position(|&idx| idx == preferred) {
+        let current = &health[preferred];
+        let best = &health[order[0]];
+        if pos > 0 && current.score() <= best.score() * SWITCH_HYSTERESIS {
+            order.remove(pos);
+            order.insert(0, preferred);
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(latency_ms: f64, error_rate: f64) -> EndpointHealth {
+        EndpointHealth {
+            latency_ms: Some(latency_ms),
+            error_rate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rank_prefers_fast_reliable_endpoints_with_hysteresis() {
+        let now = Instant::now();
+        let h = vec![EndpointHealth::default(); 3];
+        assert_eq!(rank(&h, 0, now), [0, 1, 2]);
+
+        // Endpoint 1 is fastest but fails half the time; it ranks behind both others.
+        let h = vec![health(400.0, 0.0), health(50.0, 0.5), health(80.0, 0.0)];
+        assert_eq!(rank(&h, 2, now), [2, 0, 1]);
+
+        let h = vec![health(100.0, 0.0), health(120.0, 0.0)];
+        assert_eq!(rank(&h, 1, now), [1, 0]);
+        let h = vec![health(100.0, 0.0), health(200.0, 0.0)];
+        assert_eq!(rank(&h, 1, now), [0, 1]);
+    }
+
+    #[test]
+    fn breaker_ejects_until_a_trial_succeeds() {
+        let breaker = CircuitBreakerPolicy {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(30),
+        };
+        let now = Instant::now();
+        let mut h = vec![health(10.0, 0.0), health(300.0, 0.0)];
+
+        assert_eq!(h[0].record_failure(None, Some(&breaker), now), None);
+        assert_eq!(
+            h[0].record_failure(None, Some(&breaker), now),
+            Some(CircuitChange::Opened(breaker.cooldown))
+        );
+        assert_eq!(rank(&h, 0, now), [1]);
+
+        // Every endpoint open: fall back to trying them anyway.
+        h[1].record_failure(None, Some(&breaker), now);
+        h[1].record_failure(None, Some(&breaker), now);
+        assert_eq!(rank(&h, 0, now).len(), 2);
+
+        // After the cooldown a single trial goes out; other requests skip the endpoint until
+        // it answers or times out.
+        let later = now + breaker.cooldown + Duration::from_secs(1);
+        assert!(!h[0].stats(later).circuit_open);
+        let trial_timeout = later + Duration::from_secs(2);
+        assert!(h[0].claim_trial(later, trial_timeout));
+        assert!(!h[0].claim_trial(later, trial_timeout));
+        assert_eq!(rank(&h, 0, later), [1]);
+        assert!(h[0].claim_trial(trial_timeout, trial_timeout + Duration::from_secs(2)));
+
+        // A failed trial re-opens right away; a successful one closes.
+        assert_eq!(
+            h[0].record_failure(None, Some(&breaker), later),
+            Some(CircuitChange::Opened(breaker.cooldown))
+        );
+        assert_eq!(h[1].record_success(20), Some(CircuitChange::Closed));
+        assert_eq!(h[1].record_success(20), None);
+    }
+
+    #[test]
+    fn percentile_needs_enough_samples() {
+        let mut h = EndpointHealth::default();
+        for ms in 1..MIN_PERCENTILE_SAMPLES as u64 {
+            h.record_success(ms * 10);
+        }
+        assert_eq!(h.latency_percentile(0.9), None);
+        h.record_success(1_000);
+        assert_eq!(
+            h.latency_percentile(1.0),
+            Some(Duration::from_millis(1_000))
+        );
+        assert_eq!(h.latency_percentile(0.0), Some(Duration::from_millis(10)));
+    }
+}
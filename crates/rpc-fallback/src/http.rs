@@ -0,0 +1,194 @@
+use crate::RpcErrorKind;
+use alloy_json_rpc::{RequestPacket, ResponsePacket, ResponsePayload};
+use alloy_transport::{TransportError, TransportErrorKind, TransportFut};
+use std::{fmt, task, time::Duration};
+use tower::Service;
+use url::Url;
+
+/// Used when a 429 carries no (parseable) `Retry-After`.
+pub(crate) const DEFAULT_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(1);
+/// Upper bound on any single endpoint backoff, whatever the endpoint asks for.
+pub(crate) const MAX_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Minimal JSON-RPC over HTTP transport.
+///
+/// Unlike alloy's `ReqwestTransport`, it keeps JSON-RPC error bodies sent with a non-2xx
+/// status (so reverts survive intact) and surfaces `Retry-After` on 429s as `RateLimited`.
+#[derive(Clone, Debug)]
+pub(crate) struct HttpEndpoint {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl HttpEndpoint {
+    pub(crate) fn new(url: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+/// The endpoint answered 429 Too Many Requests.
+#[derive(Debug)]
+pub(crate) struct RateLimited {
+    pub(crate) retry_after: Option<Duration>,
+    pub(crate) body: String,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rate limited (429): {}", self.body)
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+impl Service<RequestPacket> for HttpEndpoint {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move {
+            let resp = this
+                .client
+                .post(this.url)
+                .json(&req)
+                .send()
+                .await
+                .map_err(TransportErrorKind::custom)?;
+            let status = resp.status();
+            let retry_after = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_retry_after);
+            let body = resp.bytes().await.map_err(TransportErrorKind::custom)?;
+
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Err(TransportErrorKind::custom(RateLimited {
+                    retry_after,
+                    body: String::from_utf8_lossy(&body).into_owned(),
+                }));
+            }
+            match serde_json::from_slice::<ResponsePacket>(&body) {
+                Ok(packet) => Ok(packet),
+                Err(_) if !status.is_success() => Err(TransportErrorKind::http_error(
+                    status.as_u16(),
+                    String::from_utf8_lossy(&body).into_owned(),
+                )),
+                Err(err) => Err(TransportError::deser_err(
+                    err,
+                    String::from_utf8_lossy(&body),
+                )),
+            }
+        })
+    }
+}
+
+/// `Retry-After` in delay-seconds form. HTTP-date values are ignored (callers fall back to
+/// `DEFAULT_RATE_LIMIT_BACKOFF`); no RPC provider we use sends them.
+fn parse_retry_after(v: &str) -> Option<Duration> {
+    v.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// How a response should be treated by the fallback transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ResponseClass {
+    /// A result, or a JSON-RPC error every endpoint would return as well (revert, bad params).
+    Final,
+    /// A JSON-RPC error specific to this endpoint (lagging, overloaded); try the next one.
+    Retryable,
+    /// The endpoint is throttling us through a JSON-RPC error rather than a 429.
+    RateLimited,
+}
+
+pub(crate) fn classify_response(resp: &ResponsePacket) -> ResponseClass {
+    let errors = match resp {
+        ResponsePacket::Single(r) => std::slice::from_ref(r),
+        ResponsePacket::Batch(rs) => rs.as_slice(),
+    }
+    .iter()
+    .filter_map(|r| match &r.payload {
+        ResponsePayload::Failure(err) => Some(err),
+        ResponsePayload::Success(_) => None,
+    });
+
+    let mut class = ResponseClass::Final;
+    for err in errors {
+        match RpcErrorKind::from_json_rpc(err.code, &err.message) {
+            // Every endpoint would refuse the same range, so it is final.
+            RpcErrorKind::RangeTooLarge | RpcErrorKind::Permanent => {}
+            RpcErrorKind::RateLimited => return ResponseClass::RateLimited,
+            RpcErrorKind::Transient => class = ResponseClass::Retryable,
+        }
+    }
+    class
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: &str) -> ResponsePacket {
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn deterministic_errors_are_final() {
+        let ok = response(r#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#);
+        assert_eq!(classify_response(&ok), ResponseClass::Final);
+
+        let reverted = response(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted","data":"0x08c379a0"}}"#,
+        );
+        assert_eq!(classify_response(&reverted), ResponseClass::Final);
+
+        let bad_params = response(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"invalid argument 0"}}"#,
+        );
+        assert_eq!(classify_response(&bad_params), ResponseClass::Final);
+
+        // -32005 is also a rate limit code, but not with this message.
+        let too_many_logs = response(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"query returned more than 10000 results"}}"#,
+        );
+        assert_eq!(classify_response(&too_many_logs), ResponseClass::Final);
+    }
+
+    #[test]
+    fn endpoint_specific_errors_fail_over() {
+        for message in [
+            "header not found",
+            "request beyond head block",
+            "missing trie node 5b8d1e64d1c0bd8b6c6e54c0b3b8b0a8 (path )",
+            "required historical state unavailable (reexec=128)",
+        ] {
+            let lagging = response(&format!(
+                r#"{{"jsonrpc":"2.0","id":1,"error":{{"code":-32000,"message":"{message}"}}}}"#
+            ));
+            assert_eq!(
+                classify_response(&lagging),
+                ResponseClass::Retryable,
+                "{message}"
+            );
+        }
+
+        let throttled = response(
+            r#"[{"jsonrpc":"2.0","id":1,"result":"0x1"},{"jsonrpc":"2.0","id":2,"error":{"code":-32005,"message":"request limit reached"}}]"#,
+        );
+        assert_eq!(classify_response(&throttled), ResponseClass::RateLimited);
+    }
+
+    #[test]
+    fn retry_after_takes_delay_seconds() {
+        assert_eq!(parse_retry_after(" 7 "), Some(Duration::from_secs(7)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+}
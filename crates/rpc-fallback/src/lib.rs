@@ -1,23 +1,44 @@
-use alloy_json_rpc::RequestPacket;
+mod classify;
+mod health;
+mod http;
+mod subscription;
+
+use alloy_json_rpc::{RequestPacket, ResponsePacket, ResponsePayload, RpcError};
 use alloy_rpc_client::RpcClient;
 use alloy_transport::{BoxTransport, TransportError, TransportErrorKind, TransportFut};
-use alloy_transport_http::ReqwestTransport;
+use futures::stream::{FuturesUnordered, StreamExt};
+use health::{CircuitChange, EndpointHealth, rank};
+use http::{
+    DEFAULT_RATE_LIMIT_BACKOFF, HttpEndpoint, MAX_RATE_LIMIT_BACKOFF, RateLimited, ResponseClass,
+    classify_response,
+};
 use parking_lot::Mutex as ParkingMutex;
-use std::{sync::Arc, task, time::Instant};
+use std::{collections::HashMap, sync::Arc, task, time::Instant};
 use tokio::{
     sync::Mutex as AsyncMutex,
     time::{Duration, timeout},
 };
 use tower::Service;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 use url::Url;
 
+pub use classify::RpcErrorKind;
+pub use health::{CircuitBreakerPolicy, EndpointStats};
+pub use subscription::{
+    HeadSubscription, LogSubscription, SubscriptionOptions, subscribe_logs, subscribe_new_heads,
+};
+
 /// Shared, cloneable JSON-RPC request pacer.
 ///
-/// This uses fixed spacing rather than a bursty token bucket. If configured for
-/// 50 requests/sec, calls are reserved at least 20ms apart across every clone
-/// sharing the same limiter, which prevents short sliding-window bursts above
-/// the upstream RPC's advertised throughput.
+/// Configured for 50 requests/sec, calls are released at least 20ms apart across every clone
+/// sharing the same limiter, which prevents short sliding-window bursts above the upstream
+/// RPC's advertised throughput.
+///
+/// With `RpcRateLimits`, the budget is in compute units instead: each request is charged its
+/// method's cost (batches the sum of their members) before it is sent, and methods can
+/// additionally be capped in requests/sec on their own. Capped methods are the bulk lane: they
+/// only spend the budget while enough is left over for one request of any other method, so a
+/// backfill never makes head polling queue behind it.
 #[derive(Clone, Debug)]
 pub struct RpcRateLimiter {
     inner: Arc<RpcRateLimiterInner>,
@@ -25,37 +46,233 @@ pub struct RpcRateLimiter {
 
 #[derive(Debug)]
 struct RpcRateLimiterInner {
+    budget: Budget,
+    method_costs: HashMap<String, u32>,
+    default_cost: u32,
+    /// Paced per request, for methods with their own cap.
+    method_lanes: HashMap<String, Pacer>,
+}
+
+/// Fixed spacing between releases, for per-method request caps.
+#[derive(Debug)]
+struct Pacer {
     spacing: Duration,
     next_at: AsyncMutex<tokio::time::Instant>,
 }
 
+impl Pacer {
+    fn new(per_second: u32) -> Self {
+        Self {
+            spacing: spacing_for_rate(per_second),
+            next_at: AsyncMutex::new(tokio::time::Instant::now()),
+        }
+    }
+
+    async fn until_ready(&self) {
+        let mut next_at = self.next_at.lock().await;
+        let now = tokio::time::Instant::now();
+
+        if *next_at > now {
+            tokio::time::sleep_until(*next_at).await;
+        }
+
+        // Pace actual releases, not just reservations. If the runtime wakes late,
+        // this prevents queued callers from catching up in a burst.
+        *next_at = tokio::time::Instant::now() + self.spacing;
+    }
+}
+
+/// Pre-paid compute-unit bucket: a request waits until its units have accrued, then takes them.
+#[derive(Debug)]
+struct Budget {
+    /// Time for one unit to accrue.
+    spacing: Duration,
+    /// Most units held at once: the largest single charge plus `reserve`.
+    capacity: u64,
+    /// Units bulk requests must leave in the bucket.
+    reserve: u64,
+    state: ParkingMutex<BudgetState>,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    /// Negative after a charge larger than what was available (an oversized batch).
+    units: f64,
+    updated: tokio::time::Instant,
+}
+
+impl Budget {
+    fn new(units_per_second: u32, largest_cost: u32, reserve: u32) -> Self {
+        let capacity = u64::from(largest_cost.max(1)) + u64::from(reserve);
+        Self {
+            spacing: spacing_for_rate(units_per_second),
+            capacity,
+            reserve: u64::from(reserve),
+            state: ParkingMutex::new(BudgetState {
+                units: capacity as f64,
+                updated: tokio::time::Instant::now(),
+            }),
+        }
+    }
+
+    async fn until_ready(&self, cost: u32, bulk: bool) {
+        while let Some(wait) = self.try_take(tokio::time::Instant::now(), cost, bulk) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes `cost` units if they are available at `now`, otherwise returns how long until
+    /// they will be. Waiters re-check rather than queue, so a cheap request never waits for an
+    /// expensive one ahead of it.
+    fn try_take(&self, now: tokio::time::Instant, cost: u32, bulk: bool) -> Option<Duration> {
+        let mut state = self.state.lock();
+        let accrued = now.saturating_duration_since(state.updated).as_nanos() as f64
+            / self.spacing.as_nanos() as f64;
+        state.units = (state.units + accrued).min(self.capacity as f64);
+        state.updated = now;
+
+        let floor = if bulk { self.reserve } else { 0 };
+        // An oversized batch goes once the bucket is full and leaves it in debt.
+        let needed = (u64::from(cost) + floor).min(self.capacity) as f64;
+        if state.units >= needed {
+            state.units -= f64::from(cost);
+            return None;
+        }
+        let missing_nanos = (needed - state.units) * self.spacing.as_nanos() as f64;
+        Some(Duration::from_nanos(missing_nanos.ceil() as u64))
+    }
+}
+
+/// Compute-unit budget for `RpcRateLimiter::with_limits`, e.g. a provider's CU/s plan.
+#[derive(Debug, Clone)]
+pub struct RpcRateLimits {
+    pub compute_units_per_second: u32,
+    /// Units charged per call, e.g. `eth_getLogs` => 75. Unlisted methods cost `default_cost`.
+    pub method_costs: HashMap<String, u32>,
+    pub default_cost: u32,
+    /// Requests/sec caps for single methods, on top of the budget. Capped methods also give way
+    /// to uncapped ones when the budget runs low, so capping expensive methods keeps a backfill
+    /// from starving cheap calls like head polling.
+    pub method_requests_per_second: HashMap<String, u32>,
+}
+
+impl RpcRateLimits {
+    /// Every method costs one unit and none has its own cap.
+    pub fn new(compute_units_per_second: u32) -> Self {
+        Self {
+            compute_units_per_second,
+            method_costs: HashMap::new(),
+            default_cost: 1,
+            method_requests_per_second: HashMap::new(),
+        }
+    }
+
+    /// Parses `method=value` pairs separated by commas or whitespace, e.g.
+    /// `eth_getLogs=75,eth_blockNumber=10`.
+    pub fn method_map_from_csv(csv: &str) -> anyhow::Result<HashMap<String, u32>> {
+        csv.split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|pair| {
+                let (method, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| anyhow::anyhow!("expected method=value, got '{pair}'"))?;
+                let value = value
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|e| anyhow::anyhow!("invalid value in '{pair}': {e}"))?;
+                Ok((method.trim().to_string(), value))
+            })
+            .collect()
+    }
+}
+
 impl RpcRateLimiter {
     pub fn new(max_requests_per_second: u32) -> anyhow::Result<Self> {
         anyhow::ensure!(
             max_requests_per_second > 0,
             "max_requests_per_second must be greater than zero"
         );
+        Self::with_limits(RpcRateLimits::new(max_requests_per_second))
+    }
+
+    pub fn with_limits(limits: RpcRateLimits) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            limits.compute_units_per_second > 0,
+            "compute_units_per_second must be greater than zero"
+        );
+        let cost_of = |method: &str| {
+            limits
+                .method_costs
+                .get(method)
+                .copied()
+                .unwrap_or(limits.default_cost)
+        };
+        let largest_cost = limits
+            .method_costs
+            .values()
+            .copied()
+            .fold(limits.default_cost, u32::max);
+        // Enough for one request of any uncapped method, whenever some method is capped.
+        let reserve = if limits.method_requests_per_second.is_empty() {
+            0
+        } else {
+            limits
+                .method_costs
+                .keys()
+                .filter(|method| !limits.method_requests_per_second.contains_key(*method))
+                .map(|method| cost_of(method))
+                .fold(limits.default_cost, u32::max)
+        };
+        let method_lanes = limits
+            .method_requests_per_second
+            .into_iter()
+            .map(|(method, rate)| {
+                anyhow::ensure!(rate > 0, "requests/sec cap for {method} must be positive");
+                Ok((method, Pacer::new(rate)))
+            })
+            .collect::<anyhow::Result<_>>()?;
 
-        let spacing = spacing_for_rate(max_requests_per_second);
         Ok(Self {
             inner: Arc::new(RpcRateLimiterInner {
-                spacing,
-                next_at: AsyncMutex::new(tokio::time::Instant::now()),
+                budget: Budget::new(limits.compute_units_per_second, largest_cost, reserve),
+                method_costs: limits.method_costs,
+                default_cost: limits.default_cost,
+                method_lanes,
             }),
         })
     }
 
+    /// Waits until one request of the default cost may be sent.
     pub async fn until_ready(&self) {
-        let mut next_at = self.inner.next_at.lock().await;
-        let now = tokio::time::Instant::now();
+        self.inner
+            .budget
+            .until_ready(self.inner.default_cost, false)
+            .await;
+    }
 
-        if *next_at > now {
-            tokio::time::sleep_until(*next_at).await;
+    /// Waits until `req` may be sent: first for the caps of its methods, then for its cost.
+    pub async fn until_ready_for(&self, req: &RequestPacket) {
+        let mut bulk = false;
+        for method in req.method_names() {
+            if let Some(lane) = self.inner.method_lanes.get(method) {
+                lane.until_ready().await;
+                bulk = true;
+            }
         }
+        self.inner.budget.until_ready(self.cost(req), bulk).await;
+    }
 
-        // Pace actual releases, not just reservations. If the runtime wakes late,
-        // this prevents queued callers from catching up in a burst.
-        *next_at = tokio::time::Instant::now() + self.inner.spacing;
+    fn cost(&self, req: &RequestPacket) -> u32 {
+        req.method_names()
+            .map(|method| {
+                self.inner
+                    .method_costs
+                    .get(method)
+                    .copied()
+                    .unwrap_or(self.inner.default_cost)
+            })
+            .fold(0, u32::saturating_add)
     }
 }
 
@@ -91,7 +308,7 @@ impl Service<RequestPacket> for RateLimitedTransport {
         let limiter = self.limiter.clone();
         let mut inner = self.inner.clone();
         Box::pin(async move {
-            limiter.until_ready().await;
+            limiter.until_ready_for(&req).await;
             tower::ServiceExt::ready(&mut inner).await?;
             inner.call(req).await
         })
@@ -102,12 +319,18 @@ impl Service<RequestPacket> for RateLimitedTransport {
 ///
 /// Design goals (v1):
 /// - minimal surface area: drop-in for `RpcClient::builder().transport(...)`
-/// - fast failover on network/http errors and timeouts
-/// - sticky success: remember the last good endpoint
+/// - fast failover on network/http errors, timeouts and endpoint-specific JSON-RPC errors
+///   (lagging or overloaded nodes)
+/// - deterministic JSON-RPC errors (reverts, invalid params) are returned immediately with
+///   their payload intact, since every other endpoint would answer the same
+/// - endpoints answering 429 are skipped until their `Retry-After` has passed
+/// - health-scored order: endpoints are ranked by latency EWMA plus error-rate EWMA, with the
+///   last good endpoint kept sticky until another one is clearly better
+/// - a circuit breaker ejects endpoints that keep failing for a cooldown
 ///
-/// Non-goals (yet):
-/// - sophisticated health scoring
-/// - per-method policies / circuit breaker metrics
+/// Optionally, methods listed in a `HedgePolicy` are raced against the next-best endpoint
+/// when slow, and methods listed in a `QuorumPolicy` are instead read from several endpoints
+/// at once and only accepted when enough of them agree.
 #[derive(Clone)]
 pub struct FallbackHttpTransport {
     inner: Arc<Inner>,
@@ -115,24 +338,285 @@ pub struct FallbackHttpTransport {
 
 struct Inner {
     transports: Vec<BoxTransport>,
-    /// Index of the preferred transport (sticky on success).
-    preferred: ParkingMutex<usize>,
-    per_try_timeout: Duration,
+    options: FallbackHttpOptions,
+    state: ParkingMutex<State>,
     observer: Option<Arc<dyn FallbackObserver>>,
     limiter: Option<RpcRateLimiter>,
 }
 
+struct State {
+    health: Vec<EndpointHealth>,
+    /// Endpoint that served the last successful request (sticky within hysteresis).
+    preferred: usize,
+    /// Whether quorum reads are currently degraded for lack of usable endpoints.
+    quorum_degraded: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FallbackHttpOptions {
+    pub per_try_timeout: Duration,
+    /// Consensus reads for selected methods. `None` sends every request to one endpoint at a
+    /// time.
+    pub quorum: Option<QuorumPolicy>,
+    /// `None` keeps failing endpoints in rotation (only ranked lower).
+    pub circuit_breaker: Option<CircuitBreakerPolicy>,
+    /// Hedged reads for selected methods. `None` disables hedging.
+    pub hedge: Option<HedgePolicy>,
+    /// One limiter per URL, in order, for per-endpoint budgets (on top of the limiter shared
+    /// by all endpoints). Empty applies none.
+    pub endpoint_limiters: Vec<RpcRateLimiter>,
+}
+
+impl Default for FallbackHttpOptions {
+    fn default() -> Self {
+        Self {
+            per_try_timeout: Duration::from_secs(4),
+            quorum: None,
+            circuit_breaker: None,
+            hedge: None,
+            endpoint_limiters: Vec::new(),
+        }
+    }
+}
+
+/// Starts the same request on the next-best endpoint once the first has been outstanding for
+/// longer than the `percentile` of its recent successful latencies (never sooner than
+/// `min_delay`), and takes whichever answers first. Until an endpoint has enough samples,
+/// half of `per_try_timeout` is used instead.
+///
+/// Only list side-effect-free methods: the slower copy is cancelled, but may already have
+/// reached the endpoint. Batches are never hedged.
+#[derive(Debug, Clone)]
+pub struct HedgePolicy {
+    /// e.g. `eth_call`, `eth_getLogs`, `eth_getBlockByNumber`.
+    pub methods: Vec<String>,
+    /// 0.0..=1.0, e.g. 0.95.
+    pub percentile: f64,
+    pub min_delay: Duration,
+}
+
+impl HedgePolicy {
+    fn applies_to(&self, req: &RequestPacket) -> bool {
+        let RequestPacket::Single(req) = req else {
+            return false;
+        };
+        self.methods.iter().any(|m| m == req.method())
+    }
+}
+
+/// Sends selected read methods to `fanout` endpoints and accepts a response only once
+/// `min_agree` of them returned the same result (JSON-RPC errors agree on their code). Blocks
+/// agree on their hash and number, logs on `(blockHash, logIndex, address, data, topics)`;
+/// other results are compared whole.
+///
+/// Requests reading at a moving block tag (`latest`, `pending`, `safe`, `finalized`, or an
+/// omitted block parameter) are exempt, since endpoints at different heights legitimately
+/// disagree on them; pin reads to a block number or hash to get consensus. Batches are always
+/// exempt.
+///
+/// When fewer than `min_agree` endpoints are usable (the rest backing off or ejected), the read
+/// fails, unless `allow_degraded` lets it settle for agreement of all usable ones.
+#[derive(Debug, Clone)]
+pub struct QuorumPolicy {
+    /// e.g. `eth_getBlockByNumber`, `eth_getLogs`, `eth_call`.
+    pub methods: Vec<String>,
+    /// Endpoints asked up front. Further endpoints are only asked once failures leave the
+    /// in-flight ones unable to reach `min_agree`.
+    pub fanout: usize,
+    pub min_agree: usize,
+    /// Trades the consensus guarantee for availability while endpoints are down: a single
+    /// usable endpoint then answers alone.
+    pub allow_degraded: bool,
+}
+
+const MOVING_BLOCK_TAGS: [&str; 4] = ["latest", "pending", "safe", "finalized"];
+
+impl QuorumPolicy {
+    fn applies_to(&self, req: &RequestPacket) -> bool {
+        let RequestPacket::Single(req) = req else {
+            return false;
+        };
+        if !self.methods.iter().any(|m| m == req.method()) {
+            return false;
+        }
+        let params = match req.params() {
+            Some(raw) => match serde_json::from_str::<Vec<serde_json::Value>>(raw.get()) {
+                Ok(params) => params,
+                // Not positional params; nothing to pin, leave it to the endpoint to reject.
+                Err(_) => return false,
+            },
+            None => Vec::new(),
+        };
+        !reads_moving_block(req.method(), &params)
+    }
+}
+
+/// Position of the block parameter for methods that take one directly.
+fn block_param_index(method: &str) -> Option<usize> {
+    match method {
+        "eth_getBlockByNumber"
+        | "eth_getBlockReceipts"
+        | "eth_getBlockTransactionCountByNumber"
+        | "eth_getTransactionByBlockNumberAndIndex"
+        | "eth_getUncleByBlockNumberAndIndex"
+        | "eth_getUncleCountByBlockNumber"
+        | "debug_traceBlockByNumber"
+        | "trace_block" => Some(0),
+        "eth_call"
+        | "eth_estimateGas"
+        | "eth_createAccessList"
+        | "eth_getBalance"
+        | "eth_getCode"
+        | "eth_getTransactionCount"
+        | "eth_feeHistory"
+        | "debug_traceCall" => Some(1),
+        "eth_getStorageAt" | "eth_getProof" => Some(2),
+        _ => None,
+    }
+}
+
+/// Whether a request's answer depends on which head the endpoint is at.
+fn reads_moving_block(method: &str, params: &[serde_json::Value]) -> bool {
+    if method == "eth_getLogs" {
+        let Some(serde_json::Value::Object(filter)) = params.first() else {
+            return true;
+        };
+        if filter.get("blockHash").is_some_and(|hash| !hash.is_null()) {
+            return false;
+        }
+        // Omitted bounds default to `latest`.
+        return ["fromBlock", "toBlock"]
+            .iter()
+            .any(|bound| is_moving_block(filter.get(*bound)));
+    }
+    block_param_index(method).is_some_and(|idx| is_moving_block(params.get(idx)))
+}
+
+/// A block parameter: a number, a tag, a hash, or an EIP-1898 `{blockNumber|blockHash}` object.
+fn is_moving_block(param: Option<&serde_json::Value>) -> bool {
+    match param {
+        // Omitted block parameters default to `latest`.
+        None | Some(serde_json::Value::Null) => true,
+        Some(serde_json::Value::String(tag)) => MOVING_BLOCK_TAGS.contains(&tag.as_str()),
+        Some(serde_json::Value::Object(selector)) => {
+            if selector
+                .get("blockHash")
+                .is_some_and(|hash| !hash.is_null())
+            {
+                return false;
+            }
+            is_moving_block(selector.get("blockNumber"))
+        }
+        Some(_) => false,
+    }
+}
+
+/// What two endpoints must both return to count as agreeing.
+#[derive(Debug, PartialEq)]
+enum QuorumKey {
+    Ok(serde_json::Value),
+    Err(i64),
+}
+
+fn quorum_key(method: &str, resp: &ResponsePacket) -> Option<QuorumKey> {
+    let ResponsePacket::Single(resp) = resp else {
+        return None;
+    };
+    Some(match &resp.payload {
+        // Compare parsed JSON so formatting and key order don't count as disagreement.
+        ResponsePayload::Success(raw) => QuorumKey::Ok(consensus_fields(
+            method,
+            serde_json::from_str(raw.get()).ok()?,
+        )),
+        ResponsePayload::Failure(err) => QuorumKey::Err(err.code),
+    })
+}
+
+/// The part of a result every endpoint on the same chain returns identically. Blocks and logs
+/// also carry fields that differ by client or are filled in locally (`totalDifficulty`, `size`,
+/// `removed`, `blockTimestamp`, ...), which say nothing about the chain.
+fn consensus_fields(method: &str, result: serde_json::Value) -> serde_json::Value {
+    match (method, result) {
+        (_, serde_json::Value::Null) => serde_json::Value::Null,
+        ("eth_getBlockByNumber" | "eth_getBlockByHash", block) => {
+            serde_json::json!([normalized(block.get("hash")), quantity(block.get("number"))])
+        }
+        ("eth_getLogs", serde_json::Value::Array(logs)) => logs
+            .iter()
+            .map(|log| {
+                serde_json::json!([
+                    normalized(log.get("blockHash")),
+                    quantity(log.get("logIndex")),
+                    normalized(log.get("address")),
+                    normalized(log.get("data")),
+                    normalized(log.get("topics")),
+                ])
+            })
+            .collect(),
+        (_, result) => result,
+    }
+}
+
+/// Hex strings compare case-insensitively.
+fn normalized(value: Option<&serde_json::Value>) -> serde_json::Value {
+    match value {
+        None => serde_json::Value::Null,
+        Some(serde_json::Value::String(s)) => s.to_ascii_lowercase().into(),
+        Some(serde_json::Value::Array(items)) => {
+            items.iter().map(|item| normalized(Some(item))).collect()
+        }
+        Some(other) => other.clone(),
+    }
+}
+
+/// Hex quantities compare by value (`0x0a` and `0xa` agree).
+fn quantity(value: Option<&serde_json::Value>) -> serde_json::Value {
+    value
+        .and_then(serde_json::Value::as_str)
+        .and_then(|s| s.strip_prefix("0x"))
+        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        .map_or_else(|| normalized(value), serde_json::Value::from)
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum FallbackAttemptStatus {
     Ok,
     Err,
     Timeout,
+    /// HTTP 429 or a rate-limit JSON-RPC error; the endpoint is backed off.
+    RateLimited,
+}
+
+/// Outcome of a single endpoint attempt.
+enum Attempt {
+    /// A result or a deterministic JSON-RPC error: hand it to the caller as-is.
+    Done(ResponsePacket),
+    /// A JSON-RPC error specific to this endpoint; kept in case no other endpoint does better.
+    Retryable(ResponsePacket),
+    /// HTTP 429.
+    RateLimited,
+    /// Transport error, non-JSON-RPC HTTP error or timeout.
+    Failed,
 }
 
 pub trait FallbackObserver: Send + Sync {
     fn on_attempt(&self, method: &str, endpoint_idx: usize, status: FallbackAttemptStatus, ms: u64);
     fn on_switch(&self, method: &str, from_idx: usize, to_idx: usize);
     fn on_all_failed(&self, method: &str);
+    /// A quorum read got differing responses. `agreed` is the accepted group (empty if none
+    /// reached quorum); `dissented` are the endpoints that answered something else.
+    fn on_disagreement(&self, _method: &str, _agreed: &[usize], _dissented: &[usize]) {}
+    /// A request was duplicated onto `hedge_idx` because `primary_idx` had not answered in time.
+    fn on_hedge(&self, _method: &str, _primary_idx: usize, _hedge_idx: usize) {}
+    /// Health of `endpoint_idx` after each attempt it served.
+    fn on_health(&self, _endpoint_idx: usize, _stats: &EndpointStats) {}
+    /// The circuit breaker ejected `endpoint_idx` for `cooldown`.
+    fn on_circuit_open(&self, _endpoint_idx: usize, _cooldown: Duration) {}
+    /// A trial request after the cooldown succeeded; `endpoint_idx` is back in rotation.
+    fn on_circuit_close(&self, _endpoint_idx: usize) {}
+    /// A `subscription` (`newHeads`, `logs`) is now held on WS/IPC endpoint `ws_idx`, or
+    /// served by HTTP polling when `None`.
+    fn on_subscription_source(&self, _subscription: &str, _ws_idx: Option<usize>) {}
 }
 
 impl FallbackHttpTransport {
@@ -153,19 +637,65 @@ impl FallbackHttpTransport {
         per_try_timeout: Duration,
         observer: Option<Arc<dyn FallbackObserver>>,
         limiter: Option<RpcRateLimiter>,
+    ) -> anyhow::Result<Self> {
+        let options = FallbackHttpOptions {
+            per_try_timeout,
+            ..FallbackHttpOptions::default()
+        };
+        Self::new_with_options(urls, options, observer, limiter)
+    }
+
+    pub fn new_with_options(
+        urls: Vec<Url>,
+        options: FallbackHttpOptions,
+        observer: Option<Arc<dyn FallbackObserver>>,
+        limiter: Option<RpcRateLimiter>,
     ) -> anyhow::Result<Self> {
         anyhow::ensure!(!urls.is_empty(), "at least one RPC URL is required");
+        if let Some(quorum) = &options.quorum {
+            anyhow::ensure!(quorum.min_agree > 0, "quorum min_agree must be positive");
+            anyhow::ensure!(
+                quorum.fanout >= quorum.min_agree,
+                "quorum fanout ({}) must be at least min_agree ({})",
+                quorum.fanout,
+                quorum.min_agree
+            );
+        }
+        anyhow::ensure!(
+            options.endpoint_limiters.is_empty() || options.endpoint_limiters.len() == urls.len(),
+            "got {} endpoint limiters for {} RPC URLs",
+            options.endpoint_limiters.len(),
+            urls.len()
+        );
+        if let Some(breaker) = &options.circuit_breaker {
+            anyhow::ensure!(
+                breaker.failure_threshold > 0,
+                "circuit breaker failure_threshold must be positive"
+            );
+        }
+        if let Some(hedge) = &options.hedge {
+            anyhow::ensure!(
+                (0.0..=1.0).contains(&hedge.percentile),
+                "hedge percentile must be within 0.0..=1.0, got {}",
+                hedge.percentile
+            );
+        }
 
         let transports = urls
             .into_iter()
-            .map(|u| BoxTransport::new(ReqwestTransport::new(u)))
+            .map(|u| BoxTransport::new(HttpEndpoint::new(u)))
             .collect::<Vec<_>>();
+        let health = vec![EndpointHealth::default(); transports.len()];
 
         Ok(Self {
             inner: Arc::new(Inner {
                 transports,
-                preferred: ParkingMutex::new(0),
-                per_try_timeout,
+                options,
+                state: ParkingMutex::new(State {
+                    health,
+                    preferred: 0,
+                    quorum_degraded: false,
+                }),
                 observer,
                 limiter,
             }),
@@ -198,112 +728,410 @@ impl FallbackHttpTransport {
         RpcClient::builder().transport(self, false)
     }
 
-    fn preferred_index(&self) -> usize {
-        *self.inner.preferred.lock()
+    /// Current health of every endpoint, in configured order.
+    pub fn endpoint_stats(&self) -> Vec<EndpointStats> {
+        let now = Instant::now();
+        self.state().health.iter().map(|h| h.stats(now)).collect()
     }
 
-    fn set_preferred(&self, idx: usize) {
-        *self.inner.preferred.lock() = idx;
+    fn state(&self) -> parking_lot::MutexGuard<'_, State> {
+        self.inner.state.lock()
     }
-}
 
-impl Service<RequestPacket> for FallbackHttpTransport {
-    type Response = alloy_json_rpc::ResponsePacket;
-    type Error = TransportError;
-    type Future = TransportFut<'static>;
+    fn ranked(&self) -> Vec<usize> {
+        let state = self.state();
+        rank(&state.health, state.preferred, Instant::now())
+    }
 
-    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
-        // We'll lazily attempt requests; treat as always-ready.
-        task::Poll::Ready(Ok(()))
+    fn any_backing_off(&self) -> bool {
+        let now = Instant::now();
+        self.state().health.iter().any(|h| h.is_backing_off(now))
     }
 
-    fn call(&mut self, req: RequestPacket) -> Self::Future {
-        let this = self.clone();
-        Box::pin(async move {
-            let n = this.inner.transports.len();
-            let start = this.preferred_index();
-            let method = request_method_label(&req);
+    fn mark_preferred(&self, method: &str, idx: usize) {
+        let prev = std::mem::replace(&mut self.state().preferred, idx);
+        if prev == idx {
+            return;
+        }
+        if let Some(observer) = &self.inner.observer {
+            observer.on_switch(method, prev, idx);
+        }
+        debug!(
+            from = prev,
+            to = idx,
+            "rpc transport failover succeeded; updating preferred"
+        );
+    }
 
-            // Try preferred first, then round-robin.
-            for offset in 0..n {
-                let idx = (start + offset) % n;
+    fn hedge_delay(&self, policy: &HedgePolicy, idx: usize) -> Duration {
+        self.state().health[idx]
+            .latency_percentile(policy.percentile)
+            .unwrap_or(self.inner.options.per_try_timeout / 2)
+            .max(policy.min_delay)
+    }
 
-                let mut t = this.inner.transports[idx].clone();
-                let per_try_timeout = this.inner.per_try_timeout;
-                if let Some(limiter) = &this.inner.limiter {
-                    limiter.until_ready().await;
+    fn back_off(&self, idx: usize, retry_after: Option<Duration>) {
+        let delay = retry_after
+            .unwrap_or(DEFAULT_RATE_LIMIT_BACKOFF)
+            .min(MAX_RATE_LIMIT_BACKOFF);
+        warn!(
+            idx,
+            backoff_ms = delay.as_millis(),
+            "rpc endpoint rate limited; backing off"
+        );
+        self.state().health[idx].back_off(Instant::now() + delay);
+    }
+
+    /// Scores one finished attempt and reports it, plus any circuit breaker transition.
+    fn record(&self, method: &str, idx: usize, status: FallbackAttemptStatus, started: Instant) {
+        let ms = started.elapsed().as_millis() as u64;
+        let now = Instant::now();
+        let breaker = self.inner.options.circuit_breaker.as_ref();
+        let (change, stats) = {
+            let mut state = self.state();
+            let health = &mut state.health[idx];
+            let change = match status {
+                FallbackAttemptStatus::Ok => health.record_success(ms),
+                FallbackAttemptStatus::Err => health.record_failure(None, breaker, now),
+                FallbackAttemptStatus::Timeout => health.record_failure(Some(ms), breaker, now),
+                // Backing off already keeps it out of rotation; a 429 says nothing about health.
+                FallbackAttemptStatus::RateLimited => None,
+            };
+            (change, health.stats(now))
+        };
+
+        match &change {
+            Some(CircuitChange::Opened(cooldown)) => warn!(
+                idx,
+                cooldown_ms = cooldown.as_millis(),
+                error_rate = stats.error_rate,
+                "rpc endpoint keeps failing; circuit opened"
+            ),
+            Some(CircuitChange::Closed) => info!(idx, "rpc endpoint recovered; circuit closed"),
+            None => {}
+        }
+        let Some(observer) = &self.inner.observer else {
+            return;
+        };
+        observer.on_attempt(method, idx, status, ms);
+        observer.on_health(idx, &stats);
+        match change {
+            Some(CircuitChange::Opened(cooldown)) => observer.on_circuit_open(idx, cooldown),
+            Some(CircuitChange::Closed) => observer.on_circuit_close(idx),
+            None => {}
+        }
+    }
+
+    /// Counts the elapsed time of an attempt that lost a hedge race: it was at least that slow.
+    fn note_latency(&self, idx: usize, started: Instant) {
+        let ms = started.elapsed().as_millis() as f64;
+        self.state().health[idx].record_latency(ms);
+    }
+
+    /// One attempt against endpoint `idx`, classified and reported to the observer.
+    async fn try_endpoint(&self, idx: usize, method: &str, req: RequestPacket) -> Attempt {
+        let mut t = self.inner.transports[idx].clone();
+        let per_try_timeout = self.inner.options.per_try_timeout;
+        if let Some(limiter) = &self.inner.limiter {
+            limiter.until_ready_for(&req).await;
+        }
+        if let Some(limiter) = self.inner.options.endpoint_limiters.get(idx) {
+            limiter.until_ready_for(&req).await;
+        }
+
+        // Past its cooldown an ejected endpoint serves a single trial request at a time.
+        let trial_until = Instant::now() + per_try_timeout;
+        if !self.state().health[idx].claim_trial(Instant::now(), trial_until) {
+            debug!(idx, method, "rpc endpoint on trial; skipping");
+            return Attempt::Failed;
+        }
+
+        let attempt_start = Instant::now();
+        let attempt = async {
+            // `BoxTransport` is a `tower::Service`, so we must poll_ready + call.
+            // poll_ready is usually cheap for HTTP.
+            tower::ServiceExt::ready(&mut t).await?;
+            t.call(req).await
+        };
+
+        let (status, outcome) = match timeout(per_try_timeout, attempt).await {
+            Ok(Ok(resp)) => match classify_response(&resp) {
+                ResponseClass::Final => (FallbackAttemptStatus::Ok, Attempt::Done(resp)),
+                ResponseClass::Retryable => {
+                    warn!(idx, method, "rpc endpoint returned a retryable error");
+                    (FallbackAttemptStatus::Err, Attempt::Retryable(resp))
+                }
+                ResponseClass::RateLimited => {
+                    self.back_off(idx, None);
+                    (FallbackAttemptStatus::RateLimited, Attempt::Retryable(resp))
                 }
+            },
+            Ok(Err(e)) => {
+                if let RpcError::Transport(TransportErrorKind::Custom(err)) = &e
+                    && let Some(limited) = err.downcast_ref::<RateLimited>()
+                {
+                    self.back_off(idx, limited.retry_after);
+                    (FallbackAttemptStatus::RateLimited, Attempt::RateLimited)
+                } else {
+                    warn!(idx, err = %e, "rpc transport attempt failed");
+                    (FallbackAttemptStatus::Err, Attempt::Failed)
+                }
+            }
+            Err(_) => {
+                warn!(
+                    idx,
+                    timeout_ms = per_try_timeout.as_millis(),
+                    "rpc transport attempt timed out"
+                );
+                (FallbackAttemptStatus::Timeout, Attempt::Failed)
+            }
+        };
+        self.record(method, idx, status, attempt_start);
+        outcome
+    }
 
-                let attempt_start = Instant::now();
-                let attempt = async {
-                    // `BoxTransport` is a `tower::Service`, so we must poll_ready + call.
-                    // poll_ready is usually cheap for HTTP.
-                    tower::ServiceExt::ready(&mut t).await?;
-                    t.call(req.clone()).await
-                };
+    /// Sends `req` to one endpoint at a time in ranked order until one gives a final answer,
+    /// hedging the first attempt if `HedgePolicy` covers the method.
+    async fn call_with_failover(
+        &self,
+        req: &RequestPacket,
+        method: &str,
+    ) -> Result<ResponsePacket, TransportError> {
+        let mut candidates = self.ranked().into_iter();
+        let launch = move |idx: usize| {
+            let req = req.clone();
+            async move { (idx, self.try_endpoint(idx, method, req).await) }
+        };
+        let mut last_retryable = None;
+        let mut rate_limited = self.any_backing_off();
 
-                match timeout(per_try_timeout, attempt).await {
-                    Ok(Ok(resp)) => {
-                        if let Some(observer) = &this.inner.observer {
-                            observer.on_attempt(
-                                method,
-                                idx,
-                                FallbackAttemptStatus::Ok,
-                                attempt_start.elapsed().as_millis() as u64,
-                            );
-                        }
-                        if idx != start {
-                            if let Some(observer) = &this.inner.observer {
-                                observer.on_switch(method, start, idx);
+        let mut in_flight = FuturesUnordered::new();
+        // Attempts still running, with their start, so a hedge loser's slowness is recorded.
+        let mut running: Vec<(usize, Instant)> = Vec::new();
+        let mut hedge_after = None;
+        if let Some(primary) = candidates.next() {
+            hedge_after = self
+                .inner
+                .options
+                .hedge
+                .as_ref()
+                .filter(|policy| policy.applies_to(req))
+                .map(|policy| (primary, self.hedge_delay(policy, primary)));
+            running.push((primary, Instant::now()));
+            in_flight.push(launch(primary));
+        }
+
+        loop {
+            let next = match hedge_after.take() {
+                Some((primary, delay)) => match timeout(delay, in_flight.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        if let Some(hedge) = candidates.next() {
+                            if let Some(observer) = &self.inner.observer {
+                                observer.on_hedge(method, primary, hedge);
                             }
-                            debug!(
-                                from = start,
-                                to = idx,
-                                "rpc transport failover succeeded; updating preferred"
-                            );
-                        }
-                        this.set_preferred(idx);
-                        return Ok(resp);
-                    }
-                    Ok(Err(e)) => {
-                        if let Some(observer) = &this.inner.observer {
-                            observer.on_attempt(
-                                method,
-                                idx,
-                                FallbackAttemptStatus::Err,
-                                attempt_start.elapsed().as_millis() as u64,
-                            );
+                            debug!(primary, hedge, method, "hedging slow rpc request");
+                            running.push((hedge, Instant::now()));
+                            in_flight.push(launch(hedge));
                         }
-                        warn!(idx, err = %e, "rpc transport attempt failed");
                         continue;
                     }
-                    Err(_) => {
-                        if let Some(observer) = &this.inner.observer {
-                            observer.on_attempt(
-                                method,
-                                idx,
-                                FallbackAttemptStatus::Timeout,
-                                attempt_start.elapsed().as_millis() as u64,
-                            );
-                        }
-                        warn!(
-                            idx,
-                            timeout_ms = per_try_timeout.as_millis(),
-                            "rpc transport attempt timed out"
-                        );
-                        continue;
+                },
+                None => in_flight.next().await,
+            };
+            let Some((idx, attempt)) = next else {
+                break;
+            };
+            running.retain(|(i, _)| *i != idx);
+
+            match attempt {
+                Attempt::Done(resp) => {
+                    for (loser, started) in running {
+                        self.note_latency(loser, started);
                     }
+                    self.mark_preferred(method, idx);
+                    return Ok(resp);
                 }
+                Attempt::Retryable(resp) => last_retryable = Some(resp),
+                Attempt::RateLimited => rate_limited = true,
+                Attempt::Failed => {}
+            }
+            if in_flight.is_empty() {
+                let Some(next) = candidates.next() else {
+                    break;
+                };
+                running.push((next, Instant::now()));
+                in_flight.push(launch(next));
+            }
+        }
+
+        if let Some(observer) = &self.inner.observer {
+            observer.on_all_failed(method);
+        }
+
+        // Prefer a real JSON-RPC error over a synthetic one so callers see what went wrong.
+        if let Some(resp) = last_retryable {
+            return Ok(resp);
+        }
+        // A 429 lets retry layers above us (e.g. alloy's `RetryBackoffLayer`) back off too.
+        if rate_limited {
+            return Err(TransportErrorKind::http_error(
+                429,
+                "all rpc endpoints are rate limited".to_string(),
+            ));
+        }
+
+        // If all failed, return a synthetic error.
+        Err(TransportErrorKind::custom(std::io::Error::other(
+            "all rpc endpoints failed",
+        )))
+    }
+
+    /// Agreement a quorum read needs given `usable` endpoints, or `None` if it can't be had
+    /// and the policy doesn't allow degrading. Logs when quorum is lost or restored.
+    fn quorum_min_agree(&self, policy: &QuorumPolicy, usable: usize) -> Option<usize> {
+        let degraded = usable < policy.min_agree;
+        let was_degraded = std::mem::replace(&mut self.state().quorum_degraded, degraded);
+        if degraded && !was_degraded {
+            if policy.allow_degraded {
+                warn!(
+                    usable,
+                    min_agree = policy.min_agree,
+                    "too few usable rpc endpoints for quorum; requiring agreement of all usable ones"
+                );
+            } else {
+                warn!(
+                    usable,
+                    min_agree = policy.min_agree,
+                    "too few usable rpc endpoints for quorum; failing quorum reads"
+                );
             }
+        } else if !degraded && was_degraded {
+            info!(min_agree = policy.min_agree, "rpc quorum restored");
+        }
+        if degraded && !policy.allow_degraded {
+            return None;
+        }
+        Some(policy.min_agree.min(usable))
+    }
 
-            if let Some(observer) = &this.inner.observer {
+    async fn call_with_quorum(
+        &self,
+        req: &RequestPacket,
+        method: &str,
+        policy: &QuorumPolicy,
+    ) -> Result<ResponsePacket, TransportError> {
+        let ranked = self.ranked();
+        if ranked.is_empty() {
+            // Everything is backing off; failover reports that as a rate limit.
+            return self.call_with_failover(req, method).await;
+        }
+        let Some(min_agree) = self.quorum_min_agree(policy, ranked.len()) else {
+            if let Some(observer) = &self.inner.observer {
                 observer.on_all_failed(method);
             }
+            return Err(TransportErrorKind::custom(std::io::Error::other(format!(
+                "rpc quorum unavailable ({} usable endpoints, {} matching responses required)",
+                ranked.len(),
+                policy.min_agree
+            ))));
+        };
+        let mut untried = ranked.into_iter();
+        let launch = move |idx: usize| {
+            let req = req.clone();
+            async move { (idx, self.try_endpoint(idx, method, req).await) }
+        };
+
+        let mut in_flight = untried
+            .by_ref()
+            .take(policy.fanout)
+            .map(launch)
+            .collect::<FuturesUnordered<_>>();
+        // (response, endpoints that returned it), in arrival order.
+        let mut groups: Vec<(QuorumKey, ResponsePacket, Vec<usize>)> = Vec::new();
+
+        while let Some((idx, resp)) = in_flight.next().await {
+            // Only final responses vote; endpoint-specific errors say nothing about the answer.
+            if let Attempt::Done(resp) = resp
+                && let Some(key) = quorum_key(method, &resp)
+            {
+                match groups.iter_mut().find(|(k, ..)| *k == key) {
+                    Some((.., idxs)) => idxs.push(idx),
+                    None => groups.push((key, resp, vec![idx])),
+                }
+            }
+
+            let best = groups
+                .iter()
+                .map(|(.., idxs)| idxs.len())
+                .max()
+                .unwrap_or(0);
+            if best >= min_agree {
+                break;
+            }
+            while best + in_flight.len() < min_agree {
+                let Some(next) = untried.next() else {
+                    break;
+                };
+                in_flight.push(launch(next));
+            }
+        }
+
+        let winner = groups.iter().position(|(.., idxs)| idxs.len() >= min_agree);
+        if groups.len() > 1 {
+            let agreed = winner.map(|i| groups[i].2.clone()).unwrap_or_default();
+            let dissented = groups
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| Some(*i) != winner)
+                .flat_map(|(_, (.., idxs))| idxs.iter().copied())
+                .collect::<Vec<_>>();
+            warn!(
+                method,
+                ?agreed,
+                ?dissented,
+                "rpc endpoints disagree on quorum read"
+            );
+            if let Some(observer) = &self.inner.observer {
+                observer.on_disagreement(method, &agreed, &dissented);
+            }
+        }
+
+        match winner {
+            Some(i) => Ok(groups.swap_remove(i).1),
+            None => {
+                if let Some(observer) = &self.inner.observer {
+                    observer.on_all_failed(method);
+                }
+                Err(TransportErrorKind::custom(std::io::Error::other(format!(
+                    "rpc quorum not reached ({} matching responses required)",
+                    min_agree
+                ))))
+            }
+        }
+    }
+}
 
-            // If all failed, return a synthetic error.
-            Err(TransportErrorKind::custom(std::io::Error::other(
-                "all rpc endpoints failed",
-            )))
+impl Service<RequestPacket> for FallbackHttpTransport {
+    type Response = alloy_json_rpc::ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> task::Poll<Result<(), Self::Error>> {
+        // We'll lazily attempt requests; treat as always-ready.
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let this = self.clone();
+        Box::pin(async move {
+            let method = request_method_label(&req);
+            if let Some(quorum) = &this.inner.options.quorum
+                && quorum.applies_to(&req)
+            {
+                return this.call_with_quorum(&req, method, quorum).await;
+            }
+            this.call_with_failover(&req, method).await
         })
     }
 }
@@ -331,9 +1159,189 @@ fn request_method_label(req: &RequestPacket) -> &str {
 
 #[cfg(test)]
 mod tests {
-    use super::spacing_for_rate;
+    use super::{
+        QuorumKey, QuorumPolicy, RpcRateLimiter, RpcRateLimits, quorum_key, spacing_for_rate,
+    };
+    use alloy_json_rpc::{Id, Request, RequestPacket, ResponsePacket};
     use tokio::time::Duration;
 
+    fn single(method: &'static str, params: serde_json::Value) -> RequestPacket {
+        RequestPacket::Single(
+            Request::new(method, Id::Number(1), params)
+                .serialize()
+                .unwrap(),
+        )
+    }
+
+    fn response(body: &str) -> ResponsePacket {
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn quorum_applies_only_to_pinned_reads_of_listed_methods() {
+        let policy = QuorumPolicy {
+            methods: vec!["eth_getBlockByNumber".to_string(), "eth_call".to_string()],
+            fanout: 3,
+            min_agree: 2,
+            allow_degraded: false,
+        };
+        let pinned = serde_json::json!(["0x10", false]);
+        assert!(policy.applies_to(&single("eth_getBlockByNumber", pinned.clone())));
+        assert!(!policy.applies_to(&single("eth_getBalance", pinned.clone())));
+        assert!(!policy.applies_to(&single(
+            "eth_getBlockByNumber",
+            serde_json::json!(["latest", false])
+        )));
+        assert!(!policy.applies_to(&single(
+            "eth_call",
+            serde_json::json!([{"to": "0x00"}, "finalized"])
+        )));
+        // An omitted block parameter means `latest`.
+        assert!(!policy.applies_to(&single("eth_call", serde_json::json!([{"to": "0x00"}]))));
+        // Tag-like strings elsewhere in the params don't count, EIP-1898 selectors do.
+        assert!(policy.applies_to(&single(
+            "eth_call",
+            serde_json::json!([{"to": "0x00", "data": "latest"}, "0x10"])
+        )));
+        assert!(policy.applies_to(&single(
+            "eth_call",
+            serde_json::json!([{"to": "0x00"}, {"blockHash": "0xaa"}])
+        )));
+        assert!(!policy.applies_to(&single(
+            "eth_call",
+            serde_json::json!([{"to": "0x00"}, {"blockNumber": "safe"}])
+        )));
+
+        let logs = QuorumPolicy {
+            methods: vec!["eth_getLogs".to_string()],
+            fanout: 3,
+            min_agree: 2,
+            allow_degraded: false,
+        };
+        assert!(logs.applies_to(&single(
+            "eth_getLogs",
+            serde_json::json!([{"fromBlock": "0x10", "toBlock": "0x20"}])
+        )));
+        assert!(logs.applies_to(&single(
+            "eth_getLogs",
+            serde_json::json!([{"blockHash": "0xaa"}])
+        )));
+        assert!(!logs.applies_to(&single(
+            "eth_getLogs",
+            serde_json::json!([{"fromBlock": "0x10"}])
+        )));
+
+        let batch = match single("eth_call", pinned) {
+            RequestPacket::Single(req) => RequestPacket::Batch(vec![req]),
+            RequestPacket::Batch(_) => unreachable!(),
+        };
+        assert!(!policy.applies_to(&batch));
+    }
+
+    #[test]
+    fn quorum_key_ignores_formatting_and_ids() {
+        let a = response(r#"{"jsonrpc":"2.0","id":1,"result":{"hash":"0xaa","number":"0x10"}}"#);
+        let b =
+            response(r#"{"jsonrpc":"2.0","id":7,"result":{ "number": "0x10", "hash": "0xaa" }}"#);
+        let c = response(r#"{"jsonrpc":"2.0","id":1,"result":{"hash":"0xbb","number":"0x10"}}"#);
+        assert_eq!(quorum_key("eth_call", &a), quorum_key("eth_call", &b));
+        assert_ne!(quorum_key("eth_call", &a), quorum_key("eth_call", &c));
+
+        let reverted = response(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted"}}"#,
+        );
+        assert_eq!(quorum_key("eth_call", &reverted), Some(QuorumKey::Err(3)));
+    }
+
+    #[test]
+    fn quorum_key_compares_blocks_and_logs_on_consensus_fields() {
+        let result = |value: serde_json::Value| {
+            response(&serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": value}).to_string())
+        };
+
+        let key = |value| quorum_key("eth_getBlockByNumber", &result(value));
+        let geth = key(serde_json::json!({
+            "hash": "0xAA", "number": "0x10", "totalDifficulty": "0x1", "size": "0x220"
+        }));
+        let reth = key(serde_json::json!({
+            "hash": "0xaa", "number": "0x10", "size": "0x221", "requestsHash": "0x00"
+        }));
+        assert_eq!(geth, reth);
+        assert_ne!(
+            geth,
+            key(serde_json::json!({"hash": "0xbb", "number": "0x10"}))
+        );
+        assert_ne!(geth, key(serde_json::Value::Null));
+
+        let key = |value| quorum_key("eth_getLogs", &result(value));
+        let log = serde_json::json!({
+            "blockHash": "0xaa", "logIndex": "0x0a", "address": "0x01", "data": "0x",
+            "topics": ["0xT1"]
+        });
+        let with = |field: &str, value: serde_json::Value| {
+            let mut log = log.clone();
+            log[field] = value;
+            serde_json::json!([log])
+        };
+        let agreed = key(serde_json::json!([log]));
+        // Per-client extras and hex formatting don't count.
+        assert_eq!(agreed, key(with("removed", false.into())));
+        assert_eq!(agreed, key(with("blockTimestamp", "0x5".into())));
+        assert_eq!(agreed, key(with("logIndex", "0xa".into())));
+        assert_eq!(agreed, key(with("topics", serde_json::json!(["0xt1"]))));
+        assert_ne!(agreed, key(with("data", "0x01".into())));
+        assert_ne!(agreed, key(with("logIndex", "0xb".into())));
+        assert_ne!(agreed, key(with("blockHash", "0xbb".into())));
+    }
+
+    #[test]
+    fn batches_are_charged_the_sum_of_their_member_costs() {
+        let mut limits = RpcRateLimits::new(500);
+        limits.method_costs =
+            RpcRateLimits::method_map_from_csv("eth_getLogs=75, eth_blockNumber=10").unwrap();
+        limits.default_cost = 20;
+        let limiter = RpcRateLimiter::with_limits(limits).unwrap();
+
+        let head = single("eth_blockNumber", serde_json::json!([]));
+        assert_eq!(limiter.cost(&head), 10);
+
+        let members = ["eth_getLogs", "eth_blockNumber", "eth_call"]
+            .into_iter()
+            .map(|method| match single(method, serde_json::json!([])) {
+                RequestPacket::Single(req) => req,
+                RequestPacket::Batch(_) => unreachable!(),
+            })
+            .collect();
+        assert_eq!(limiter.cost(&RequestPacket::Batch(members)), 75 + 10 + 20);
+
+        assert!(RpcRateLimits::method_map_from_csv("eth_getLogs:75").is_err());
+    }
+
+    #[test]
+    fn capped_methods_leave_room_for_uncapped_ones() {
+        let mut limits = RpcRateLimits::new(100);
+        limits.method_costs =
+            RpcRateLimits::method_map_from_csv("eth_getLogs=75, eth_blockNumber=10").unwrap();
+        limits.method_requests_per_second =
+            RpcRateLimits::method_map_from_csv("eth_getLogs=5").unwrap();
+        let limiter = RpcRateLimiter::with_limits(limits).unwrap();
+        let budget = &limiter.inner.budget;
+        assert_eq!((budget.capacity, budget.reserve), (85, 10));
+
+        let start = tokio::time::Instant::now();
+        budget.state.lock().updated = start;
+        // A full bucket pays for one backfill read, but not a second one...
+        assert_eq!(budget.try_take(start, 75, true), None);
+        assert!(budget.try_take(start, 75, true).is_some());
+        // ...while the head poll goes right away, without waiting for it.
+        assert_eq!(budget.try_take(start, 10, false), None);
+
+        // The next backfill read needs its 75 units plus the reserve to accrue: 850ms at 100/s.
+        let wait = budget.try_take(start, 75, true).unwrap();
+        assert!(wait > Duration::from_millis(849) && wait <= Duration::from_millis(851));
+        assert_eq!(budget.try_take(start + wait, 75, true), None);
+    }
+
     #[test]
     fn rate_spacing_rounds_up_to_avoid_microbursts() {
         assert_eq!(
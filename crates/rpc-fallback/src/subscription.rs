@@ -0,0 +1,650 @@
+use crate::FallbackObserver;
+use alloy_primitives::{B256, U64, U256};
+use alloy_rpc_client::{ClientBuilder, RpcClient};
+use anyhow::{Context, Result};
+use serde_json::{Value, value::RawValue};
+use std::{sync::Arc, time::Instant};
+use tokio::{
+    sync::{broadcast::error::RecvError, mpsc, watch},
+    task::JoinHandle,
+    time::{Duration, MissedTickBehavior, interval, timeout},
+};
+use tracing::{info, warn};
+
+/// Most blocks fetched by one `eth_getLogs` when polling or closing a gap after a switch.
+const LOG_CHUNK_BLOCKS: u64 = 2_000;
+/// Logs buffered for a slow consumer before the subscription task waits on it.
+const LOG_CHANNEL_CAPACITY: usize = 1_024;
+
+#[derive(Debug, Clone)]
+pub struct SubscriptionOptions {
+    /// How often HTTP is polled while no WS/IPC endpoint holds the subscription.
+    pub poll_interval: Duration,
+    /// A head subscription that delivers nothing for this long is treated as dead and moved to
+    /// the next endpoint. Must comfortably exceed the chain's block time. Log subscriptions
+    /// are exempt, since a filter can legitimately match nothing for hours.
+    pub stall_timeout: Duration,
+    /// Bound on connecting and subscribing to one endpoint.
+    pub connect_timeout: Duration,
+    /// After every WS/IPC endpoint failed, poll HTTP this long before trying them again.
+    pub retry_interval: Duration,
+}
+
+impl Default for SubscriptionOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(2),
+            stall_timeout: Duration::from_secs(60),
+            connect_timeout: Duration::from_secs(10),
+            retry_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Latest chain head from a `newHeads` subscription, or from `eth_blockNumber` polling while
+/// no WS/IPC endpoint is usable.
+///
+/// Cheap to clone; the background task stops once every clone is dropped.
+#[derive(Clone)]
+pub struct HeadSubscription {
+    rx: watch::Receiver<Option<u64>>,
+    _task: Arc<AbortOnDrop>,
+}
+
+impl HeadSubscription {
+    pub fn latest(&self) -> Option<u64> {
+        *self.rx.borrow()
+    }
+
+    /// Waits for a head different from the last one returned. `None` once the task stopped.
+    pub async fn changed(&mut self) -> Option<u64> {
+        self.rx.changed().await.ok()?;
+        *self.rx.borrow_and_update()
+    }
+}
+
+/// Raw log objects from a `logs` subscription, or from `eth_getLogs` polling while no WS/IPC
+/// endpoint is usable.
+///
+/// Delivery is at-least-once: after every switch, and whenever the endpoint's notifications
+/// outran this task, the blocks since the last delivered log are re-read over HTTP, so logs
+/// around those points may repeat.
+pub struct LogSubscription {
+    rx: mpsc::Receiver<Box<RawValue>>,
+    _task: AbortOnDrop,
+}
+
+impl LogSubscription {
+    /// `None` once the task stopped.
+    pub async fn next(&mut self) -> Option<Box<RawValue>> {
+        self.rx.recv().await
+    }
+}
+
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Holds a `newHeads` subscription on one of `ws_urls` (`ws://`, `wss://` or an IPC path),
+/// resubscribing on the next one when it drops or stalls and polling `http` meanwhile.
+///
+/// With no `ws_urls` it only polls. Must be called from within a Tokio runtime.
+pub fn subscribe_new_heads(
+    ws_urls: Vec<String>,
+    http: RpcClient,
+    options: SubscriptionOptions,
+    observer: Option<Arc<dyn FallbackObserver>>,
+) -> HeadSubscription {
+    let (tx, rx) = watch::channel(None);
+    let worker = Worker {
+        ws_urls,
+        http,
+        options,
+        observer,
+        sink: Sink::Heads(tx),
+        next_block: None,
+    };
+    HeadSubscription {
+        rx,
+        _task: Arc::new(AbortOnDrop(tokio::spawn(worker.run()))),
+    }
+}
+
+/// Like `subscribe_new_heads`, for `logs` matching `filter` (an `eth_getLogs`-style object
+/// without a block range).
+pub fn subscribe_logs(
+    ws_urls: Vec<String>,
+    http: RpcClient,
+    filter: Value,
+    options: SubscriptionOptions,
+    observer: Option<Arc<dyn FallbackObserver>>,
+) -> LogSubscription {
+    let (tx, rx) = mpsc::channel(LOG_CHANNEL_CAPACITY);
+    let worker = Worker {
+        ws_urls,
+        http,
+        options,
+        observer,
+        sink: Sink::Logs { tx, filter },
+        next_block: None,
+    };
+    LogSubscription {
+        rx,
+        _task: AbortOnDrop(tokio::spawn(worker.run())),
+    }
+}
+
+enum Sink {
+    Heads(watch::Sender<Option<u64>>),
+    Logs {
+        tx: mpsc::Sender<Box<RawValue>>,
+        filter: Value,
+    },
+}
+
+impl Sink {
+    fn name(&self) -> &'static str {
+        match self {
+            Sink::Heads(_) => "newHeads",
+            Sink::Logs { .. } => "logs",
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        match self {
+            Sink::Heads(tx) => tx.is_closed(),
+            Sink::Logs { tx, .. } => tx.is_closed(),
+        }
+    }
+
+    fn subscribe_params(&self) -> Value {
+        match self {
+            Sink::Heads(_) => serde_json::json!(["newHeads"]),
+            Sink::Logs { filter, .. } => serde_json::json!(["logs", filter]),
+        }
+    }
+}
+
+struct Worker {
+    ws_urls: Vec<String>,
+    http: RpcClient,
+    options: SubscriptionOptions,
+    observer: Option<Arc<dyn FallbackObserver>>,
+    sink: Sink,
+    /// Logs only: first block not yet fully delivered.
+    next_block: Option<u64>,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        let kind = self.sink.name();
+        let mut start = 0;
+        loop {
+            for offset in 0..self.ws_urls.len() {
+                let idx = (start + offset) % self.ws_urls.len();
+                if let Err(err) = self.ws_session(idx).await {
+                    warn!(
+                        subscription = kind,
+                        ws_idx = idx,
+                        err = %format!("{err:#}"),
+                        "rpc subscription lost; trying next endpoint"
+                    );
+                }
+                if self.sink.is_closed() {
+                    return;
+                }
+                start = idx + 1;
+            }
+
+            self.report_source(None);
+            self.poll_http().await;
+            if self.sink.is_closed() {
+                return;
+            }
+        }
+    }
+
+    /// Subscribes on endpoint `idx` and forwards notifications until it fails or stalls.
+    /// `Ok` only when the consumer went away.
+    async fn ws_session(&mut self, idx: usize) -> Result<()> {
+        let url = self.ws_urls[idx].clone();
+        let connect_timeout = self.options.connect_timeout;
+        let client = timeout(connect_timeout, ClientBuilder::default().connect(&url))
+            .await
+            .context("connect timed out")?
+            .with_context(|| format!("connect {url}"))?;
+        let frontend = client
+            .pubsub_frontend()
+            .context("endpoint does not support subscriptions")?;
+
+        let mut call = client.request::<_, U256>("eth_subscribe", self.sink.subscribe_params());
+        call.set_is_subscription();
+        let id = timeout(connect_timeout, call)
+            .await
+            .context("eth_subscribe timed out")?
+            .context("eth_subscribe")?;
+        let mut sub = frontend
+            .get_subscription(B256::from(id))
+            .await
+            .context("attach subscription")?;
+
+        info!(
+            subscription = self.sink.name(),
+            ws_idx = idx,
+            "rpc subscription established"
+        );
+        self.report_source(Some(idx));
+        self.catch_up_logs().await?;
+
+        loop {
+            if self.sink.is_closed() {
+                return Ok(());
+            }
+            let next = match self.sink {
+                Sink::Heads(_) => timeout(self.options.stall_timeout, sub.recv()).await,
+                Sink::Logs { .. } => Ok(sub.recv().await),
+            };
+            match next {
+                Err(_) => anyhow::bail!(
+                    "no notification for {}s",
+                    self.options.stall_timeout.as_secs()
+                ),
+                Ok(Err(RecvError::Closed)) => anyhow::bail!("subscription closed"),
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    warn!(
+                        subscription = self.sink.name(),
+                        skipped, "rpc subscription lagged"
+                    );
+                    // Heads are superseded anyway; skipped logs are re-read over HTTP.
+                    self.catch_up_logs().await?;
+                }
+                Ok(Ok(item)) => self.deliver(item).await,
+            }
+        }
+    }
+
+    /// Polls HTTP every `poll_interval`, for `retry_interval` (forever without WS/IPC URLs).
+    async fn poll_http(&mut self) {
+        let until =
+            (!self.ws_urls.is_empty()).then(|| Instant::now() + self.options.retry_interval);
+        let mut ticker = interval(self.options.poll_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if self.sink.is_closed() {
+                return;
+            }
+            let polled = match self.sink {
+                Sink::Heads(_) => self.poll_head().await,
+                Sink::Logs { .. } => self.catch_up_logs().await,
+            };
+            if let Err(err) = polled {
+                warn!(
+                    subscription = self.sink.name(),
+                    err = %format!("{err:#}"),
+                    "rpc subscription poll failed"
+                );
+            }
+            if until.is_some_and(|until| Instant::now() >= until) {
+                return;
+            }
+        }
+    }
+
+    async fn deliver(&mut self, item: Box<RawValue>) {
+        match &self.sink {
+            Sink::Heads(tx) => {
+                let head = serde_json::from_str::<Value>(item.get())
+                    .ok()
+                    .and_then(|header| quantity(header.get("number")?));
+                if let Some(head) = head {
+                    tx.send_if_modified(|h| h.replace(head) != Some(head));
+                }
+            }
+            Sink::Logs { tx, .. } => {
+                let block = serde_json::from_str::<Value>(item.get())
+                    .ok()
+                    .and_then(|log| quantity(log.get("blockNumber")?));
+                if let Some(block) = block {
+                    // The rest of this block may still be on its way; re-read it after a switch.
+                    self.next_block = Some(self.next_block.unwrap_or(0).max(block));
+                }
+                let _ = tx.send(item).await;
+            }
+        }
+    }
+
+    /// Logs only: fetches `next_block..=head` over HTTP. The first call just records where
+    /// the subscription starts.
+    async fn catch_up_logs(&mut self) -> Result<()> {
+        let Sink::Logs { tx, filter } = &self.sink else {
+            return Ok(());
+        };
+        let head = self.head().await?;
+        let Some(mut from) = self.next_block else {
+            self.next_block = Some(head + 1);
+            return Ok(());
+        };
+        while from <= head {
+            let to = head.min(from + LOG_CHUNK_BLOCKS - 1);
+            let mut range = filter.clone();
+            if let Some(obj) = range.as_object_mut() {
+                obj.insert("fromBlock".to_string(), format!("{from:#x}").into());
+                obj.insert("toBlock".to_string(), format!("{to:#x}").into());
+            }
+            let logs = self
+                .http
+                .request::<_, Vec<Box<RawValue>>>("eth_getLogs", (range,))
+                .await
+                .with_context(|| format!("eth_getLogs {from}..={to}"))?;
+            for log in logs {
+                if tx.send(log).await.is_err() {
+                    return Ok(());
+                }
+            }
+            from = to + 1;
+            self.next_block = Some(from);
+        }
+        Ok(())
+    }
+
+    async fn poll_head(&self) -> Result<()> {
+        let head = self.head().await?;
+        if let Sink::Heads(tx) = &self.sink {
+            tx.send_if_modified(|h| h.replace(head) != Some(head));
+        }
+        Ok(())
+    }
+
+    async fn head(&self) -> Result<u64> {
+        let head = self
+            .http
+            .request_noparams::<U64>("eth_blockNumber")
+            .await
+            .context("eth_blockNumber")?;
+        Ok(head.to::<u64>())
+    }
+
+    fn report_source(&self, ws_idx: Option<usize>) {
+        if let Some(observer) = &self.observer {
+            observer.on_subscription_source(self.sink.name(), ws_idx);
+        }
+    }
+}
+
+/// A JSON-RPC hex quantity such as `"0x1b4"`.
+fn quantity(v: &Value) -> Option<u64> {
+    u64::from_str_radix(v.as_str()?.strip_prefix("0x")?, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SubscriptionOptions, quantity, subscribe_logs, subscribe_new_heads};
+    use crate::{FallbackAttemptStatus, FallbackObserver};
+    use alloy_json_rpc::{RequestPacket, ResponsePacket};
+    use alloy_rpc_client::RpcClient;
+    use alloy_transport::{TransportError, TransportFut};
+    use parking_lot::Mutex;
+    use serde_json::{Value, json};
+    use std::{
+        collections::VecDeque,
+        io::Write,
+        os::unix::net::{UnixListener, UnixStream},
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        task,
+        time::Duration,
+    };
+    use tokio::time::{Instant, timeout};
+    use tower::Service;
+
+    /// HTTP endpoint answering `eth_blockNumber` from `heads` (the last one repeats) and
+    /// `eth_getLogs` from `logs`, recording every requested range.
+    #[derive(Clone, Default)]
+    struct ScriptedHttp {
+        heads: Arc<Mutex<VecDeque<u64>>>,
+        logs: Arc<Vec<Value>>,
+        log_ranges: Arc<Mutex<Vec<(u64, u64)>>>,
+    }
+
+    impl ScriptedHttp {
+        fn new(heads: &[u64], log_blocks: &[u64]) -> Self {
+            let logs = log_blocks
+                .iter()
+                .map(|block| json!({"blockNumber": format!("{block:#x}"), "logIndex": "0x0"}))
+                .collect();
+            Self {
+                heads: Arc::new(Mutex::new(heads.iter().copied().collect())),
+                logs: Arc::new(logs),
+                ..Self::default()
+            }
+        }
+
+        fn client(&self) -> RpcClient {
+            RpcClient::builder().transport(self.clone(), false)
+        }
+
+        fn result(&self, method: &str, params: Value) -> Value {
+            match method {
+                "eth_blockNumber" => {
+                    let mut heads = self.heads.lock();
+                    let head = if heads.len() > 1 {
+                        heads.pop_front().unwrap()
+                    } else {
+                        heads[0]
+                    };
+                    format!("{head:#x}").into()
+                }
+                "eth_getLogs" => {
+                    let from = quantity(&params[0]["fromBlock"]).unwrap();
+                    let to = quantity(&params[0]["toBlock"]).unwrap();
+                    self.log_ranges.lock().push((from, to));
+                    self.logs
+                        .iter()
+                        .filter(|log| (from..=to).contains(&quantity(&log["blockNumber"]).unwrap()))
+                        .cloned()
+                        .collect()
+                }
+                other => panic!("unexpected {other}"),
+            }
+        }
+    }
+
+    impl Service<RequestPacket> for ScriptedHttp {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut task::Context<'_>,
+        ) -> task::Poll<Result<(), Self::Error>> {
+            task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: RequestPacket) -> Self::Future {
+            let RequestPacket::Single(req) = req else {
+                panic!("unexpected batch");
+            };
+            let params = req
+                .params()
+                .map_or(Value::Null, |p| serde_json::from_str(p.get()).unwrap());
+            let result = self.result(req.method(), params);
+            let body = json!({"jsonrpc": "2.0", "id": req.id(), "result": result});
+            let resp = serde_json::from_str(&body.to_string()).unwrap();
+            Box::pin(async move { Ok(resp) })
+        }
+    }
+
+    /// IPC endpoint that answers every `eth_subscribe` with the heads in `numbers`, then goes
+    /// silent until the client hangs up. Returns its path and a count of subscriptions.
+    fn silent_after(name: &str, numbers: &[u64]) -> (String, Arc<AtomicUsize>) {
+        let path =
+            std::env::temp_dir().join(format!("rpc-fallback-{}-{name}.ipc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let subscribes = Arc::new(AtomicUsize::new(0));
+        let heads: Vec<Value> = numbers
+            .iter()
+            .map(|n| json!({"number": format!("{n:#x}")}))
+            .collect();
+        let counter = subscribes.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (stream, heads, counter) = (stream.unwrap(), heads.clone(), counter.clone());
+                std::thread::spawn(move || serve_subscription(stream, &heads, &counter));
+            }
+        });
+        (path.to_string_lossy().into_owned(), subscribes)
+    }
+
+    fn serve_subscription(mut stream: UnixStream, heads: &[Value], subscribes: &AtomicUsize) {
+        let requests = serde_json::Deserializer::from_reader(stream.try_clone().unwrap());
+        for req in requests.into_iter::<Value>() {
+            let Ok(req) = req else { return };
+            let subscribe = req["method"] == "eth_subscribe";
+            if subscribe {
+                subscribes.fetch_add(1, Ordering::SeqCst);
+            }
+            let result = if subscribe { json!("0x1") } else { Value::Null };
+            let reply = json!({"jsonrpc": "2.0", "id": req["id"], "result": result});
+            if stream.write_all(reply.to_string().as_bytes()).is_err() {
+                return;
+            }
+            if !subscribe {
+                continue;
+            }
+            // Let the client attach to the subscription before notifying.
+            std::thread::sleep(Duration::from_millis(100));
+            for head in heads {
+                let note = json!({
+                    "jsonrpc": "2.0",
+                    "method": "eth_subscription",
+                    "params": {"subscription": "0x1", "result": head},
+                });
+                if stream.write_all(note.to_string().as_bytes()).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Sources(Mutex<Vec<Option<usize>>>);
+
+    impl FallbackObserver for Sources {
+        fn on_attempt(&self, _: &str, _: usize, _: FallbackAttemptStatus, _: u64) {}
+        fn on_switch(&self, _: &str, _: usize, _: usize) {}
+        fn on_all_failed(&self, _: &str) {}
+        fn on_subscription_source(&self, _subscription: &str, ws_idx: Option<usize>) {
+            self.0.lock().push(ws_idx);
+        }
+    }
+
+    fn options() -> SubscriptionOptions {
+        SubscriptionOptions {
+            poll_interval: Duration::from_millis(20),
+            stall_timeout: Duration::from_millis(300),
+            connect_timeout: Duration::from_secs(2),
+            retry_interval: Duration::from_millis(200),
+        }
+    }
+
+    fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(fut)
+    }
+
+    #[test]
+    fn stalled_heads_move_to_the_next_endpoint_and_resubscribe() {
+        block_on(async {
+            let (first, first_subscribes) = silent_after("stall-first", &[0x10]);
+            let (second, second_subscribes) = silent_after("stall-second", &[0x11]);
+            let http = ScriptedHttp::new(&[0x0f], &[]);
+            let sources = Arc::new(Sources::default());
+            let mut sub = subscribe_new_heads(
+                vec![first, second],
+                http.client(),
+                options(),
+                Some(sources.clone() as Arc<dyn FallbackObserver>),
+            );
+
+            let deadline = Instant::now() + Duration::from_secs(10);
+            let mut heads = Vec::new();
+            while sources.0.lock().len() < 4 {
+                assert!(Instant::now() < deadline, "sources: {:?}", sources.0.lock());
+                if let Ok(Some(head)) = timeout(Duration::from_millis(20), sub.changed()).await {
+                    heads.push(head);
+                }
+            }
+
+            // Each endpoint stalls after one head; once both did, HTTP polling covers the
+            // retry interval and the first endpoint is subscribed to again.
+            assert_eq!(sources.0.lock()[..4], [Some(0), Some(1), None, Some(0)]);
+            assert!(heads.starts_with(&[0x10, 0x11, 0x0f]), "{heads:?}");
+            assert_eq!(first_subscribes.load(Ordering::SeqCst), 2);
+            assert_eq!(second_subscribes.load(Ordering::SeqCst), 1);
+        });
+    }
+
+    #[test]
+    fn polled_heads_are_delivered_once_each() {
+        block_on(async {
+            let http = ScriptedHttp::new(&[1, 1, 2, 2, 2, 3], &[]);
+            let mut sub = subscribe_new_heads(Vec::new(), http.client(), options(), None);
+            let mut heads = Vec::new();
+            while heads.last() != Some(&3) {
+                let head = timeout(Duration::from_secs(5), sub.changed())
+                    .await
+                    .expect("head")
+                    .unwrap();
+                heads.push(head);
+            }
+            assert_eq!(heads, [1, 2, 3]);
+            assert_eq!(sub.latest(), Some(3));
+        });
+    }
+
+    #[test]
+    fn polled_logs_cover_every_block_once() {
+        block_on(async {
+            // The first poll only marks the start, so the log in block 3 is never delivered.
+            let http = ScriptedHttp::new(&[4, 6, 6, 2_010], &[3, 5, 6, 2_004, 2_010]);
+            let mut sub = subscribe_logs(
+                Vec::new(),
+                http.client(),
+                json!({"address": "0x01"}),
+                options(),
+                None,
+            );
+            let mut blocks = Vec::new();
+            while let Ok(Some(log)) = timeout(Duration::from_millis(500), sub.next()).await {
+                let log: Value = serde_json::from_str(log.get()).unwrap();
+                blocks.push(quantity(&log["blockNumber"]).unwrap());
+            }
+            assert_eq!(blocks, [5, 6, 2_004, 2_010]);
+            // Contiguous ranges, chunked at `LOG_CHUNK_BLOCKS`.
+            assert_eq!(
+                *http.log_ranges.lock(),
+                [(5, 6), (7, 2_006), (2_007, 2_010)]
+            );
+        });
+    }
+
+    #[test]
+    fn quantity_parses_hex_only() {
+        assert_eq!(quantity(&json!("0x1b4")), Some(436));
+        assert_eq!(quantity(&json!("0x0")), Some(0));
+        assert_eq!(quantity(&json!("436")), None);
+        assert_eq!(quantity(&json!(436)), None);
+    }
+}
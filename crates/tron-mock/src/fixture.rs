@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Path of a file under `crates/tron/testdata/fixtures`.
+pub fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tron/testdata/fixtures")
+        .join(name)
+}
+
+/// A block captured by the `gen_tron_blockext_raw_fixture` binary: the node's exact
+/// `GetBlockByNum2` response bytes plus the tx the fixture was generated for.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockExtRawFixture {
+    pub block_number: String,
+    pub tx_id: String,
+    pub block_extention: String,
+    pub expected_tx_index: Option<usize>,
+}
+
+impl BlockExtRawFixture {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("read fixture {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("parse fixture {}", path.display()))
+    }
+
+    pub fn block_number(&self) -> Result<i64> {
+        self.block_number
+            .parse()
+            .context("fixture blockNumber must be an integer")
+    }
+
+    pub fn txid(&self) -> Result<[u8; 32]> {
+        let bytes = decode_hex0x(&self.tx_id).context("fixture txId")?;
+        bytes
+            .try_into()
+            .map_err(|b: Vec<u8>| anyhow::anyhow!("fixture txId must be 32 bytes, got {}", b.len()))
+    }
+
+    /// Raw `BlockExtention` protobuf bytes, exactly as the node served them.
+    pub fn raw_block(&self) -> Result<Bytes> {
+        Ok(Bytes::from(
+            decode_hex0x(&self.block_extention).context("fixture blockExtention")?,
+        ))
+    }
+}
+
+fn decode_hex0x(s: &str) -> Result<Vec<u8>> {
+    let s = s.trim();
+    hex::decode(s.strip_prefix("0x").unwrap_or(s)).context("invalid hex")
+}
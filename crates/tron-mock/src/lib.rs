@@ -0,0 +1,17 @@
+//! In-process stand-in for a java-tron node, for exercising Tron code paths in `cargo test`.
+//!
+//! `MockTronNode` holds scripted chain state (blocks, accounts, TRC-20 balances) and simulates
+//! just enough of the node to drive `TronTxProofBuilder`, `TronWallet` and the apps' executors:
+//! broadcasts land in a mempool, `produce_block` includes them while charging bandwidth/energy
+//! and applying TRC-20 transfers. `MockTronServer` serves it as a `protocol.Wallet` gRPC
+//! service on localhost, so callers connect with the regular `TronGrpc`/`TronClient`;
+//! `MockTronServer::connect_in_memory` serves it over in-process pipes where sockets are off
+//! limits.
+
+pub mod fixture;
+pub mod node;
+pub mod server;
+
+pub use fixture::{BlockExtRawFixture, fixture_path};
+pub use node::{DEFAULT_CALL_ENERGY, DEFAULT_FREE_NET_LIMIT, MockAccount, MockTronNode};
+pub use server::MockTronServer;
@@ -0,0 +1,939 @@
+use super::fixture::BlockExtRawFixture;
+use alloy::primitives::{Address, U256, keccak256};
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use prost::Message;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tonic::Status;
+use tron::protocol::{
+    Account, AccountResourceMessage, BlockExtention, BlockHeader, ChainParameters,
+    EstimateEnergyMessage, ResourceReceipt, Return, Transaction, TransactionExtention,
+    TransactionInfo, TransferContract, TriggerSmartContract, block_header,
+    chain_parameters::ChainParameter,
+    r#return::ResponseCode,
+    transaction::{self, contract::ContractType, result::ContractResult},
+    transaction_info,
+};
+use tron::resources::{CHAIN_PARAM_ENERGY_FEE, CHAIN_PARAM_TX_FEE_PER_BYTE};
+use tron::{ChainFees, DecodedTrc20Call, TronAddress, decode_trc20_call_data};
+
+/// Energy charged for a contract call unless overridden with `set_call_energy`.
+pub const DEFAULT_CALL_ENERGY: i64 = 30_000;
+/// Daily free bandwidth every account gets, as on mainnet.
+pub const DEFAULT_FREE_NET_LIMIT: i64 = 600;
+
+const BLOCK_INTERVAL_MS: i64 = 3_000;
+const TX_EXPIRATION_MS: i64 = 60_000;
+// Synthetic chains start at mainnet-sized numbers and timestamps so their headers encode with
+// `TronHeaderProfile::MAINNET`.
+const FIRST_BLOCK_NUMBER: i64 = 80_000_000;
+const FIRST_BLOCK_TIMESTAMP_MS: i64 = 1_760_000_000_000;
+const HEADER_VERSION: i32 = 32;
+const TRIGGER_SMART_CONTRACT_TYPE_URL: &str = "type.googleapis.com/protocol.TriggerSmartContract";
+
+/// Resources of one simulated account. Usage never recovers on its own; reset it with
+/// `set_account` between test phases if needed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MockAccount {
+    pub balance_sun: i64,
+    pub energy_limit: i64,
+    pub energy_used: i64,
+    pub net_limit: i64,
+    pub net_used: i64,
+    pub free_net_used: i64,
+}
+
+/// Scriptable Tron chain state shared between a test and the `MockTronServer` serving it.
+///
+/// Cloning is cheap and every clone sees the same chain.
+#[derive(Clone, Default)]
+pub struct MockTronNode {
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    fees: ChainFees,
+    blocks: BTreeMap<i64, StoredBlock>,
+    txs: HashMap<[u8; 32], IncludedTx>,
+    pending: Vec<([u8; 32], Transaction)>,
+    accounts: HashMap<TronAddress, MockAccount>,
+    tokens: HashMap<TronAddress, HashMap<TronAddress, U256>>,
+    call_energy: HashMap<TronAddress, i64>,
+    reverts: HashMap<TronAddress, String>,
+    auto_produce: bool,
+    unavailable: bool,
+}
+
+struct StoredBlock {
+    /// Encoded `BlockExtention`, served verbatim so tx bytes keep their original layout.
+    raw: Bytes,
+    timestamp: i64,
+    id: [u8; 32],
+}
+
+struct IncludedTx {
+    tx: Transaction,
+    info: TransactionInfo,
+}
+
+enum Contract {
+    Trigger(TriggerSmartContract),
+    Transfer(TransferContract),
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            fees: ChainFees {
+                energy_fee_sun_per_energy: 100,
+                tx_fee_sun_per_byte: 1_000,
+            },
+            blocks: BTreeMap::new(),
+            txs: HashMap::new(),
+            pending: Vec::new(),
+            accounts: HashMap::new(),
+            tokens: HashMap::new(),
+            call_energy: HashMap::new(),
+            reverts: HashMap::new(),
+            auto_produce: false,
+            unavailable: false,
+        }
+    }
+}
+
+impl MockTronNode {
+    /// An empty chain with mainnet fees (100 sun/energy, 1000 sun/byte).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_chain_fees(&self, fees: ChainFees) {
+        self.lock().fees = fees;
+    }
+
+    pub fn set_account(&self, address: TronAddress, account: MockAccount) {
+        self.lock().accounts.insert(address, account);
+    }
+
+    pub fn account(&self, address: TronAddress) -> MockAccount {
+        self.lock()
+            .accounts
+            .get(&address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Registers `token` as a TRC-20 contract (if it isn't one yet) and sets `owner`'s balance.
+    ///
+    /// Token contracts answer `balanceOf` and apply `transfer`/`transferFrom`; allowances are
+    /// not modelled.
+    pub fn set_trc20_balance(&self, token: TronAddress, owner: TronAddress, amount: U256) {
+        self.lock()
+            .tokens
+            .entry(token)
+            .or_default()
+            .insert(owner, amount);
+    }
+
+    pub fn trc20_balance(&self, token: TronAddress, owner: TronAddress) -> U256 {
+        self.lock()
+            .tokens
+            .get(&token)
+            .and_then(|balances| balances.get(&owner))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Energy every call to `contract` costs (estimated and charged).
+    pub fn set_call_energy(&self, contract: TronAddress, energy: i64) {
+        self.lock().call_energy.insert(contract, energy);
+    }
+
+    /// Makes every call to `contract` revert with `reason`, both in simulation and on-chain.
+    pub fn set_revert(&self, contract: TronAddress, reason: impl Into<String>) {
+        self.lock().reverts.insert(contract, reason.into());
+    }
+
+    pub fn clear_revert(&self, contract: TronAddress) {
+        self.lock().reverts.remove(&contract);
+    }
+
+    /// When enabled, every accepted broadcast is included in a fresh block immediately.
+    pub fn set_auto_produce(&self, enabled: bool) {
+        self.lock().auto_produce = enabled;
+    }
+
+    /// While set, the served node answers every call with `UNAVAILABLE`, like a node that is
+    /// down or restarting. Chain state is kept, so clearing it brings the node straight back.
+    pub fn set_unavailable(&self, unavailable: bool) {
+        self.lock().unavailable = unavailable;
+    }
+
+    pub fn is_unavailable(&self) -> bool {
+        self.lock().unavailable
+    }
+
+    /// Appends an encoded `BlockExtention` (e.g. a captured mainnet block) as-is and indexes
+    /// its transactions. Returns the block number.
+    pub fn push_block(&self, raw: Bytes) -> Result<i64> {
+        let block = BlockExtention::decode(raw.clone()).context("decode BlockExtention")?;
+        let raw_header = block
+            .block_header
+            .as_ref()
+            .and_then(|h| h.raw_data.as_ref())
+            .context("missing block_header.raw_data")?;
+        let number = raw_header.number;
+        let timestamp = raw_header.timestamp;
+        let id = match <[u8; 32]>::try_from(block.blockid.as_slice()) {
+            Ok(id) => id,
+            Err(_) => block_id(raw_header),
+        };
+
+        let mut state = self.lock();
+        for txe in block.transactions {
+            let txid =
+                <[u8; 32]>::try_from(txe.txid.as_slice()).context("block tx with invalid txid")?;
+            let info = TransactionInfo {
+                id: txe.txid,
+                block_number: number,
+                block_time_stamp: timestamp,
+                ..Default::default()
+            };
+            let tx = txe.transaction.unwrap_or_default();
+            state.txs.insert(txid, IncludedTx { tx, info });
+        }
+        state
+            .blocks
+            .insert(number, StoredBlock { raw, timestamp, id });
+        Ok(number)
+    }
+
+    pub fn push_fixture(&self, fixture: &BlockExtRawFixture) -> Result<i64> {
+        let number = self.push_block(fixture.raw_block()?)?;
+        if number != fixture.block_number()? {
+            anyhow::bail!(
+                "fixture blockNumber {} does not match its header ({number})",
+                fixture.block_number
+            );
+        }
+        Ok(number)
+    }
+
+    /// Queues `tx` for the next block without broadcast validation, e.g. to script a third
+    /// party's tx (an energy provider's delegation). Returns its txid.
+    pub fn submit(&self, tx: Transaction) -> [u8; 32] {
+        let txid = tx.raw_data.as_ref().map(txid_of).unwrap_or_default();
+        self.lock().pending.push((txid, tx));
+        txid
+    }
+
+    /// Mines one block on top of the head, including (and executing) all pending txs.
+    /// Returns the new block number.
+    pub fn produce_block(&self) -> i64 {
+        self.lock().produce_block()
+    }
+
+    pub fn produce_blocks(&self, count: usize) -> i64 {
+        let mut state = self.lock();
+        let mut number = state.head().map(|(n, _)| n).unwrap_or_default();
+        for _ in 0..count {
+            number = state.produce_block();
+        }
+        number
+    }
+
+    pub fn head_block_number(&self) -> Option<i64> {
+        self.lock().head().map(|(n, _)| n)
+    }
+
+    pub fn pending_txids(&self) -> Vec<[u8; 32]> {
+        self.lock().pending.iter().map(|(id, _)| *id).collect()
+    }
+
+    pub fn transaction_info(&self, txid: [u8; 32]) -> Option<TransactionInfo> {
+        self.lock().txs.get(&txid).map(|t| t.info.clone())
+    }
+
+    // ===== RPC handlers (see `server`) =====
+
+    pub(crate) fn now_block_raw(&self) -> Bytes {
+        self.lock()
+            .head()
+            .map(|(_, b)| b.raw.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn block_raw(&self, num: i64) -> Bytes {
+        self.lock()
+            .blocks
+            .get(&num)
+            .map(|b| b.raw.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn transaction_info_by_id(&self, txid: &[u8]) -> TransactionInfo {
+        let state = self.lock();
+        <[u8; 32]>::try_from(txid)
+            .ok()
+            .and_then(|id| state.txs.get(&id))
+            .map(|t| t.info.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn transaction_by_id(&self, txid: &[u8]) -> Transaction {
+        let state = self.lock();
+        <[u8; 32]>::try_from(txid)
+            .ok()
+            .and_then(|id| state.txs.get(&id))
+            .map(|t| t.tx.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn trigger_contract(
+        &self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention, Status> {
+        parse_address(&msg.owner_address)?;
+        parse_address(&msg.contract_address)?;
+
+        let state = self.lock();
+        let (head_number, head_timestamp, head_id) = state
+            .head()
+            .map(|(n, b)| (n, b.timestamp, b.id))
+            .unwrap_or((0, FIRST_BLOCK_TIMESTAMP_MS, [0u8; 32]));
+        let raw = transaction::Raw {
+            ref_block_bytes: head_number.to_be_bytes()[6..].to_vec(),
+            ref_block_hash: head_id[8..16].to_vec(),
+            expiration: head_timestamp + TX_EXPIRATION_MS,
+            timestamp: head_timestamp,
+            contract: vec![transaction::Contract {
+                r#type: ContractType::TriggerSmartContract.into(),
+                parameter: Some(prost_types::Any {
+                    type_url: TRIGGER_SMART_CONTRACT_TYPE_URL.to_string(),
+                    value: msg.encode_to_vec(),
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        Ok(TransactionExtention {
+            txid: txid_of(&raw).to_vec(),
+            transaction: Some(Transaction {
+                raw_data: Some(raw),
+                ..Default::default()
+            }),
+            result: Some(accepted()),
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn trigger_constant_contract(
+        &self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention, Status> {
+        let owner = parse_address(&msg.owner_address)?;
+        let contract = parse_address(&msg.contract_address)?;
+
+        let mut state = self.lock();
+        let energy = state.call_energy(contract);
+        Ok(match state.call(owner, contract, &msg.data, false) {
+            Ok(output) => TransactionExtention {
+                constant_result: vec![output],
+                result: Some(accepted()),
+                energy_used: energy,
+                ..Default::default()
+            },
+            Err(reason) => TransactionExtention {
+                result: Some(rejected(ResponseCode::ContractExeError, &reason)),
+                energy_used: energy,
+                ..Default::default()
+            },
+        })
+    }
+
+    pub(crate) fn estimate_energy(
+        &self,
+        msg: TriggerSmartContract,
+    ) -> Result<EstimateEnergyMessage, Status> {
+        let owner = parse_address(&msg.owner_address)?;
+        let contract = parse_address(&msg.contract_address)?;
+
+        let mut state = self.lock();
+        Ok(match state.call(owner, contract, &msg.data, false) {
+            Ok(_) => EstimateEnergyMessage {
+                result: Some(accepted()),
+                energy_required: state.call_energy(contract),
+            },
+            Err(reason) => EstimateEnergyMessage {
+                result: Some(rejected(ResponseCode::ContractExeError, &reason)),
+                energy_required: 0,
+            },
+        })
+    }
+
+    pub(crate) fn broadcast_transaction(&self, tx: Transaction) -> Return {
+        self.lock().broadcast(tx)
+    }
+
+    pub(crate) fn get_account(&self, address: &[u8]) -> Result<Account, Status> {
+        let address = parse_address(address)?;
+        Ok(match self.lock().accounts.get(&address) {
+            Some(acct) => Account {
+                address: address.prefixed_bytes().to_vec(),
+                balance: acct.balance_sun,
+                ..Default::default()
+            },
+            None => Account::default(),
+        })
+    }
+
+    pub(crate) fn get_account_resource(
+        &self,
+        address: &[u8],
+    ) -> Result<AccountResourceMessage, Status> {
+        let address = parse_address(address)?;
+        Ok(match self.lock().accounts.get(&address) {
+            Some(acct) => AccountResourceMessage {
+                free_net_used: acct.free_net_used,
+                free_net_limit: DEFAULT_FREE_NET_LIMIT,
+                net_used: acct.net_used,
+                net_limit: acct.net_limit,
+                energy_used: acct.energy_used,
+                energy_limit: acct.energy_limit,
+                ..Default::default()
+            },
+            None => AccountResourceMessage::default(),
+        })
+    }
+
+    pub(crate) fn get_chain_parameters(&self) -> ChainParameters {
+        let fees = self.lock().fees;
+        ChainParameters {
+            chain_parameter: vec![
+                ChainParameter {
+                    key: CHAIN_PARAM_ENERGY_FEE.to_string(),
+                    value: i64::try_from(fees.energy_fee_sun_per_energy).unwrap_or(i64::MAX),
+                },
+                ChainParameter {
+                    key: CHAIN_PARAM_TX_FEE_PER_BYTE.to_string(),
+                    value: i64::try_from(fees.tx_fee_sun_per_byte).unwrap_or(i64::MAX),
+                },
+            ],
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A test that panicked mid-update only poisons its own scenario.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    fn head(&self) -> Option<(i64, &StoredBlock)> {
+        self.blocks.last_key_value().map(|(n, b)| (*n, b))
+    }
+
+    fn call_energy(&self, contract: TronAddress) -> i64 {
+        self.call_energy
+            .get(&contract)
+            .copied()
+            .unwrap_or(DEFAULT_CALL_ENERGY)
+    }
+
+    fn broadcast(&mut self, tx: Transaction) -> Return {
+        let Some(raw) = tx.raw_data.as_ref() else {
+            return rejected(ResponseCode::OtherError, "missing raw_data");
+        };
+        if tx.signature.is_empty() {
+            return rejected(ResponseCode::Sigerror, "missing signature");
+        }
+
+        let txid = txid_of(raw);
+        if self.txs.contains_key(&txid) || self.pending.iter().any(|(id, _)| *id == txid) {
+            return rejected(ResponseCode::DupTransactionError, "dup transaction");
+        }
+
+        let head_timestamp = self
+            .head()
+            .map(|(_, b)| b.timestamp)
+            .unwrap_or(FIRST_BLOCK_TIMESTAMP_MS);
+        if raw.expiration <= head_timestamp {
+            return rejected(
+                ResponseCode::TransactionExpirationError,
+                "transaction expired",
+            );
+        }
+
+        let (owner, value) = match decode_contract(raw) {
+            Some(Contract::Trigger(msg)) => (msg.owner_address, msg.call_value),
+            Some(Contract::Transfer(msg)) => (msg.owner_address, msg.amount),
+            None => (Vec::new(), 0),
+        };
+        if value > 0 {
+            let Ok(owner) = parse_address(&owner) else {
+                return rejected(ResponseCode::ContractValidateError, "invalid owner address");
+            };
+            let balance = self
+                .accounts
+                .get(&owner)
+                .map(|a| a.balance_sun)
+                .unwrap_or_default();
+            if balance < value {
+                return rejected(
+                    ResponseCode::ContractValidateError,
+                    "balance is not sufficient",
+                );
+            }
+        }
+
+        self.pending.push((txid, tx));
+        if self.auto_produce {
+            self.produce_block();
+        }
+        accepted()
+    }
+
+    fn produce_block(&mut self) -> i64 {
+        let (number, timestamp, parent) = match self.head() {
+            Some((n, b)) => (n + 1, b.timestamp + BLOCK_INTERVAL_MS, b.id),
+            None => (FIRST_BLOCK_NUMBER, FIRST_BLOCK_TIMESTAMP_MS, [0u8; 32]),
+        };
+
+        let mut transactions = Vec::new();
+        let mut leaves = Vec::new();
+        for (txid, tx) in std::mem::take(&mut self.pending) {
+            let (tx, mut info) = self.execute(txid, tx);
+            info.block_number = number;
+            info.block_time_stamp = timestamp;
+            leaves.push(sha256(&tx.encode_to_vec()));
+            transactions.push(TransactionExtention {
+                transaction: Some(tx.clone()),
+                txid: txid.to_vec(),
+                ..Default::default()
+            });
+            self.txs.insert(txid, IncludedTx { tx, info });
+        }
+
+        let raw_header = block_header::Raw {
+            timestamp,
+            tx_trie_root: tx_trie_root(leaves).to_vec(),
+            parent_hash: parent.to_vec(),
+            number,
+            witness_address: witness_address().to_vec(),
+            version: HEADER_VERSION,
+            ..Default::default()
+        };
+        let id = block_id(&raw_header);
+        let block = BlockExtention {
+            transactions,
+            block_header: Some(BlockHeader {
+                raw_data: Some(raw_header),
+                witness_signature: vec![0u8; 65],
+            }),
+            blockid: id.to_vec(),
+        };
+
+        self.blocks.insert(
+            number,
+            StoredBlock {
+                raw: Bytes::from(block.encode_to_vec()),
+                timestamp,
+                id,
+            },
+        );
+        number
+    }
+
+    /// Applies `tx` to account state, returning it with its `ret` filled in plus its receipt.
+    fn execute(&mut self, txid: [u8; 32], mut tx: Transaction) -> (Transaction, TransactionInfo) {
+        let mut info = TransactionInfo {
+            id: txid.to_vec(),
+            ..Default::default()
+        };
+        let mut receipt = ResourceReceipt::default();
+        let size = i64::try_from(tx.encoded_len()).unwrap_or(i64::MAX);
+        let raw = tx.raw_data.clone().unwrap_or_default();
+
+        let contract_ret = match decode_contract(&raw) {
+            Some(Contract::Trigger(msg)) => match (
+                parse_address(&msg.owner_address),
+                parse_address(&msg.contract_address),
+            ) {
+                (Ok(owner), Ok(contract)) => {
+                    self.charge_bandwidth(owner, size, &mut receipt);
+                    self.run_trigger(
+                        owner,
+                        contract,
+                        &msg,
+                        raw.fee_limit,
+                        &mut receipt,
+                        &mut info,
+                    )
+                }
+                _ => ContractResult::Unknown,
+            },
+            Some(Contract::Transfer(msg)) => match (
+                parse_address(&msg.owner_address),
+                parse_address(&msg.to_address),
+            ) {
+                (Ok(owner), Ok(to)) => {
+                    self.charge_bandwidth(owner, size, &mut receipt);
+                    self.move_trx(owner, to, msg.amount)
+                }
+                _ => ContractResult::Unknown,
+            },
+            // Other contract types (e.g. a provider's delegation) are included without effects.
+            None => ContractResult::Success,
+        };
+
+        receipt.result = contract_ret.into();
+        info.fee = receipt.energy_fee + receipt.net_fee;
+        if contract_ret != ContractResult::Success {
+            info.result = transaction_info::Code::Failed.into();
+        }
+        info.receipt = Some(receipt);
+        tx.ret = vec![transaction::Result {
+            contract_ret: contract_ret.into(),
+            ..Default::default()
+        }];
+        (tx, info)
+    }
+
+    fn charge_bandwidth(&mut self, owner: TronAddress, size: i64, receipt: &mut ResourceReceipt) {
+        let tx_fee = i64::try_from(self.fees.tx_fee_sun_per_byte).unwrap_or(i64::MAX);
+        let acct = self.accounts.entry(owner).or_default();
+        receipt.net_usage = size;
+        if acct.net_limit - acct.net_used >= size {
+            acct.net_used += size;
+        } else if DEFAULT_FREE_NET_LIMIT - acct.free_net_used >= size {
+            acct.free_net_used += size;
+        } else {
+            let fee = size.saturating_mul(tx_fee).min(acct.balance_sun);
+            acct.balance_sun -= fee;
+            receipt.net_usage = 0;
+            receipt.net_fee = fee;
+        }
+    }
+
+    fn run_trigger(
+        &mut self,
+        owner: TronAddress,
+        contract: TronAddress,
+        msg: &TriggerSmartContract,
+        fee_limit: i64,
+        receipt: &mut ResourceReceipt,
+        info: &mut TransactionInfo,
+    ) -> ContractResult {
+        let required = self.call_energy(contract);
+        let energy_fee = i64::try_from(self.fees.energy_fee_sun_per_energy).unwrap_or(i64::MAX);
+        let acct = self.accounts.entry(owner).or_default();
+        let staked = required.min((acct.energy_limit - acct.energy_used).max(0));
+        let burn = (required - staked).saturating_mul(energy_fee);
+
+        info.contract_address = contract.prefixed_bytes().to_vec();
+        acct.energy_used += staked;
+        receipt.energy_usage = staked;
+        if burn > fee_limit.max(0) || burn > acct.balance_sun {
+            // Like java-tron: the call runs out of energy and the whole fee_limit is burnt.
+            let charged = fee_limit.max(0).min(acct.balance_sun);
+            acct.balance_sun -= charged;
+            receipt.energy_fee = charged;
+            receipt.energy_usage_total = staked + charged / energy_fee.max(1);
+            return ContractResult::OutOfEnergy;
+        }
+        acct.balance_sun -= burn;
+        receipt.energy_fee = burn;
+        receipt.energy_usage_total = required;
+
+        match self.call(owner, contract, &msg.data, true) {
+            Ok(output) => {
+                if msg.call_value > 0 {
+                    self.move_trx(owner, contract, msg.call_value);
+                }
+                info.contract_result = vec![output];
+                ContractResult::Success
+            }
+            Err(reason) => {
+                info.res_message = reason.into_bytes();
+                ContractResult::Revert
+            }
+        }
+    }
+
+    /// Executes `data` against `contract`, returning the call's output or its revert reason.
+    /// Only TRC-20 tokens registered via `set_trc20_balance` have behaviour; any other
+    /// contract accepts every call and returns nothing.
+    fn call(
+        &mut self,
+        owner: TronAddress,
+        contract: TronAddress,
+        data: &[u8],
+        commit: bool,
+    ) -> Result<Vec<u8>, String> {
+        if let Some(reason) = self.reverts.get(&contract) {
+            return Err(reason.clone());
+        }
+        let Some(balances) = self.tokens.get_mut(&contract) else {
+            return Ok(Vec::new());
+        };
+
+        if data.len() == 4 + 32 && data[..4] == keccak256("balanceOf(address)")[..4] {
+            let holder = TronAddress::from_evm(Address::from_slice(&data[16..36]));
+            let balance = balances.get(&holder).copied().unwrap_or_default();
+            return Ok(balance.to_be_bytes::<32>().to_vec());
+        }
+
+        let (from, to, amount) = match decode_trc20_call_data(data, owner) {
+            Ok(DecodedTrc20Call::Transfer { from, to, amount })
+            | Ok(DecodedTrc20Call::TransferFrom { from, to, amount }) => (from, to, amount),
+            Err(_) => return Ok(Vec::new()),
+        };
+        let from_balance = balances.get(&from).copied().unwrap_or_default();
+        if from_balance < amount {
+            return Err("TRC20: transfer amount exceeds balance".to_string());
+        }
+        if commit {
+            balances.insert(from, from_balance - amount);
+            let to_balance = balances.entry(to).or_default();
+            *to_balance = to_balance.saturating_add(amount);
+        }
+        Ok(U256::from(1u64).to_be_bytes::<32>().to_vec())
+    }
+
+    fn move_trx(&mut self, from: TronAddress, to: TronAddress, amount: i64) -> ContractResult {
+        let from_acct = self.accounts.entry(from).or_default();
+        if from_acct.balance_sun < amount {
+            return ContractResult::TransferFailed;
+        }
+        from_acct.balance_sun -= amount;
+        let to_acct = self.accounts.entry(to).or_default();
+        to_acct.balance_sun = to_acct.balance_sun.saturating_add(amount);
+        ContractResult::Success
+    }
+}
+
+fn decode_contract(raw: &transaction::Raw) -> Option<Contract> {
+    let contract = raw.contract.first()?;
+    let value = contract.parameter.as_ref()?.value.as_slice();
+    match ContractType::try_from(contract.r#type).ok()? {
+        ContractType::TriggerSmartContract => TriggerSmartContract::decode(value)
+            .ok()
+            .map(Contract::Trigger),
+        ContractType::TransferContract => {
+            TransferContract::decode(value).ok().map(Contract::Transfer)
+        }
+        _ => None,
+    }
+}
+
+fn parse_address(bytes: &[u8]) -> Result<TronAddress, Status> {
+    if bytes.len() != 21 || bytes[0] != TronAddress::MAINNET_PREFIX {
+        return Err(Status::invalid_argument(format!(
+            "invalid Tron address 0x{}",
+            hex::encode(bytes)
+        )));
+    }
+    Ok(TronAddress::from_evm(Address::from_slice(&bytes[1..])))
+}
+
+fn accepted() -> Return {
+    Return {
+        result: true,
+        ..Default::default()
+    }
+}
+
+fn rejected(code: ResponseCode, message: &str) -> Return {
+    Return {
+        result: false,
+        code: code.into(),
+        message: message.as_bytes().to_vec(),
+    }
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+fn txid_of(raw: &transaction::Raw) -> [u8; 32] {
+    sha256(&raw.encode_to_vec())
+}
+
+/// java-tron block id: the header hash with its first 8 bytes replaced by the block number.
+fn block_id(raw: &block_header::Raw) -> [u8; 32] {
+    let mut id = sha256(&raw.encode_to_vec());
+    id[..8].copy_from_slice(&raw.number.to_be_bytes());
+    id
+}
+
+/// Tron's "carry-up" Merkle root: an odd node out is promoted unchanged, not self-paired.
+fn tx_trie_root(mut level: Vec<[u8; 32]>) -> [u8; 32] {
+    if level.is_empty() {
+        return [0u8; 32];
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => sha256(&[left.as_slice(), right.as_slice()].concat()),
+                [single] => *single,
+                _ => unreachable!("chunks(2) yields one or two nodes"),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+fn witness_address() -> [u8; 21] {
+    let mut out = [0u8; 21];
+    out[0] = TronAddress::MAINNET_PREFIX;
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(byte: u8) -> TronAddress {
+        TronAddress::from_evm(Address::repeat_byte(byte))
+    }
+
+    fn trigger(owner: TronAddress, contract: TronAddress, data: Vec<u8>) -> TriggerSmartContract {
+        TriggerSmartContract {
+            owner_address: owner.prefixed_bytes().to_vec(),
+            contract_address: contract.prefixed_bytes().to_vec(),
+            data,
+            ..Default::default()
+        }
+    }
+
+    fn signed(mut ext: TransactionExtention, fee_limit: i64) -> Transaction {
+        let mut tx = ext.transaction.take().unwrap();
+        tx.raw_data.as_mut().unwrap().fee_limit = fee_limit;
+        tx.signature = vec![vec![0u8; 65]];
+        tx
+    }
+
+    fn transfer_data(to: TronAddress, amount: u64) -> Vec<u8> {
+        let mut data = tron::SELECTOR_TRANSFER.to_vec();
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(to.evm().as_slice());
+        data.extend_from_slice(&U256::from(amount).to_be_bytes::<32>());
+        data
+    }
+
+    #[test]
+    fn produced_blocks_chain_and_fit_the_mainnet_header_profile() {
+        let node = MockTronNode::new();
+        let first = node.produce_block();
+        let second = node.produce_block();
+        assert_eq!(second, first + 1);
+
+        let parent = BlockExtention::decode(node.block_raw(first)).unwrap();
+        let child = BlockExtention::decode(node.block_raw(second)).unwrap();
+        let header = child.block_header.unwrap();
+        assert_eq!(
+            header.raw_data.as_ref().unwrap().parent_hash,
+            parent.blockid
+        );
+        assert_eq!(
+            tron::TronHeaderProfile::of_header(&header).unwrap(),
+            tron::TronHeaderProfile::MAINNET
+        );
+    }
+
+    #[test]
+    fn trc20_transfer_burns_energy_and_moves_balances() {
+        let node = MockTronNode::new();
+        let (owner, token, to) = (addr(1), addr(2), addr(3));
+        node.produce_block();
+        node.set_account(
+            owner,
+            MockAccount {
+                balance_sun: 10_000_000,
+                energy_limit: 10_000,
+                ..Default::default()
+            },
+        );
+        node.set_trc20_balance(token, owner, U256::from(500u64));
+
+        let ext = node
+            .trigger_contract(trigger(owner, token, transfer_data(to, 200)))
+            .unwrap();
+        let txid: [u8; 32] = ext.txid.clone().try_into().unwrap();
+        assert!(node.broadcast_transaction(signed(ext, 5_000_000)).result);
+        node.produce_block();
+
+        let info = node.transaction_info(txid).unwrap();
+        let receipt = info.receipt.unwrap();
+        assert_eq!(receipt.result, i32::from(ContractResult::Success));
+        assert_eq!(receipt.energy_usage, 10_000);
+        assert_eq!(receipt.energy_fee, (DEFAULT_CALL_ENERGY - 10_000) * 100);
+        assert_eq!(node.trc20_balance(token, owner), U256::from(300u64));
+        assert_eq!(node.trc20_balance(token, to), U256::from(200u64));
+
+        let acct = node.account(owner);
+        assert_eq!(acct.energy_used, 10_000);
+        assert_eq!(acct.balance_sun, 10_000_000 - receipt.energy_fee);
+        assert!(acct.free_net_used > 0);
+    }
+
+    #[test]
+    fn call_runs_out_of_energy_when_fee_limit_is_too_low() {
+        let node = MockTronNode::new();
+        let (owner, token, to) = (addr(1), addr(2), addr(3));
+        node.produce_block();
+        node.set_account(
+            owner,
+            MockAccount {
+                balance_sun: 10_000_000,
+                ..Default::default()
+            },
+        );
+        node.set_trc20_balance(token, owner, U256::from(500u64));
+
+        let ext = node
+            .trigger_contract(trigger(owner, token, transfer_data(to, 200)))
+            .unwrap();
+        let txid: [u8; 32] = ext.txid.clone().try_into().unwrap();
+        assert!(node.broadcast_transaction(signed(ext, 1_000)).result);
+        node.produce_block();
+
+        let info = node.transaction_info(txid).unwrap();
+        assert_eq!(info.result, i32::from(transaction_info::Code::Failed));
+        assert_eq!(
+            info.receipt.unwrap().result,
+            i32::from(ContractResult::OutOfEnergy)
+        );
+        assert_eq!(node.trc20_balance(token, owner), U256::from(500u64));
+    }
+
+    #[test]
+    fn broadcast_rejects_unsigned_and_duplicate_txs() {
+        let node = MockTronNode::new();
+        node.produce_block();
+        let ext = node
+            .trigger_contract(trigger(addr(1), addr(2), Vec::new()))
+            .unwrap();
+
+        let mut unsigned = signed(ext.clone(), 0);
+        unsigned.signature.clear();
+        let ret = node.broadcast_transaction(unsigned);
+        assert_eq!(ret.code, i32::from(ResponseCode::Sigerror));
+
+        assert!(node.broadcast_transaction(signed(ext.clone(), 0)).result);
+        let ret = node.broadcast_transaction(signed(ext, 0));
+        assert_eq!(ret.code, i32::from(ResponseCode::DupTransactionError));
+    }
+
+    #[test]
+    fn tx_trie_root_promotes_odd_node() {
+        let leaves = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let left = sha256(&[[1u8; 32], [2u8; 32]].concat());
+        let expected = sha256(&[left, [3u8; 32]].concat());
+        assert_eq!(tx_trie_root(leaves), expected);
+    }
+}
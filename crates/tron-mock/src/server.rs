@@ -0,0 +1,527 @@
+use super::node::MockTronNode;
+use anyhow::{Context, Result};
+use bytes::{BufMut, Bytes};
+use prost::Message;
+use std::convert::Infallible;
+use std::marker::PhantomData;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::{Body, BoxFuture, Context as TaskContext, Poll, Service, StdError, http};
+use tonic::server::{Grpc, NamedService, UnaryService};
+use tonic::{Request, Response, Status};
+use tonic_prost::ProstCodec;
+use tron::TronGrpc;
+use tron::protocol::{
+    Account, BytesMessage, EmptyMessage, NumberMessage, Transaction, TriggerSmartContract,
+};
+
+/// A `MockTronNode` served over gRPC on `127.0.0.1` until dropped.
+pub struct MockTronServer {
+    node: MockTronNode,
+    url: String,
+    task: JoinHandle<()>,
+}
+
+impl MockTronServer {
+    pub async fn start(node: MockTronNode) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("bind mock Tron node")?;
+        let addr = listener.local_addr().context("mock Tron node local_addr")?;
+
+        let wallet = WalletService { node: node.clone() };
+        let task = tokio::spawn(async move {
+            let _ = tonic::transport::Server::builder()
+                .add_service(wallet)
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await;
+        });
+
+        Ok(Self {
+            node,
+            url: format!("http://{addr}"),
+            task,
+        })
+    }
+
+    /// `http://127.0.0.1:<port>`, usable anywhere a `TRON_GRPC_URLS` entry is expected.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn node(&self) -> &MockTronNode {
+        &self.node
+    }
+
+    pub async fn connect(&self) -> Result<TronGrpc> {
+        TronGrpc::connect(&self.url, None, tron::DEFAULT_API_KEY_HEADER).await
+    }
+
+    /// Serves `node` over in-process pipes instead of a localhost socket, for tests that must
+    /// run where binding one is not allowed. Every (re)connect of the returned channel gets a
+    /// fresh pipe and server task. Must be called inside a Tokio runtime.
+    pub fn connect_in_memory(node: &MockTronNode) -> Result<TronGrpc> {
+        let wallet = WalletService { node: node.clone() };
+        let channel = tonic::transport::Endpoint::from_static("http://tron-mock.invalid")
+            .connect_with_connector_lazy(tower::service_fn(move |_: http::Uri| {
+                let wallet = wallet.clone();
+                async move {
+                    let (client, server) = tokio::io::duplex(1 << 20);
+                    tokio::spawn(async move {
+                        let _ = tonic::transport::Server::builder()
+                            .add_service(wallet)
+                            .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(
+                                server,
+                            )))
+                            .await;
+                    });
+                    Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(client))
+                }
+            }));
+        TronGrpc::from_channel(channel, None, tron::DEFAULT_API_KEY_HEADER)
+    }
+}
+
+impl Drop for MockTronServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The subset of `protocol.Wallet` the Tron crate calls. Anything else is `UNIMPLEMENTED`.
+///
+/// Hand-rolled rather than generated so block responses can be served as the exact bytes a
+/// real node produced: decoding and re-encoding them would drop unknown fields and break
+/// `txTrieRoot` for captured mainnet blocks.
+#[derive(Clone)]
+struct WalletService {
+    node: MockTronNode,
+}
+
+impl NamedService for WalletService {
+    const NAME: &'static str = "protocol.Wallet";
+}
+
+impl<B> Service<http::Request<B>> for WalletService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let node = self.node.clone();
+        let method = req
+            .uri()
+            .path()
+            .strip_prefix("/protocol.Wallet/")
+            .unwrap_or_default()
+            .to_string();
+
+        Box::pin(async move {
+            if node.is_unavailable() {
+                return Ok(Status::unavailable("mock Tron node is unavailable").into_http());
+            }
+            let resp = match method.as_str() {
+                "GetNowBlock2" => raw(node, req, |n, _: EmptyMessage| Ok(n.now_block_raw())).await,
+                "GetBlockByNum2" => {
+                    raw(node, req, |n, m: NumberMessage| Ok(n.block_raw(m.num))).await
+                }
+                "GetTransactionInfoById" => {
+                    unary(node, req, |n, m: BytesMessage| {
+                        Ok(n.transaction_info_by_id(&m.value))
+                    })
+                    .await
+                }
+                "GetTransactionById" => {
+                    unary(node, req, |n, m: BytesMessage| {
+                        Ok(n.transaction_by_id(&m.value))
+                    })
+                    .await
+                }
+                "TriggerContract" => {
+                    unary(node, req, |n, m: TriggerSmartContract| {
+                        n.trigger_contract(m)
+                    })
+                    .await
+                }
+                "TriggerConstantContract" => {
+                    unary(node, req, |n, m: TriggerSmartContract| {
+                        n.trigger_constant_contract(m)
+                    })
+                    .await
+                }
+                "EstimateEnergy" => {
+                    unary(node, req, |n, m: TriggerSmartContract| n.estimate_energy(m)).await
+                }
+                "BroadcastTransaction" => {
+                    unary(node, req, |n, tx: Transaction| {
+                        Ok(n.broadcast_transaction(tx))
+                    })
+                    .await
+                }
+                "GetAccount" => unary(node, req, |n, m: Account| n.get_account(&m.address)).await,
+                "GetAccountResource" => {
+                    unary(node, req, |n, m: Account| {
+                        n.get_account_resource(&m.address)
+                    })
+                    .await
+                }
+                "GetChainParameters" => {
+                    unary(node, req, |n, _: EmptyMessage| Ok(n.get_chain_parameters())).await
+                }
+                other => Status::unimplemented(format!("mock Tron node does not serve {other}"))
+                    .into_http(),
+            };
+            Ok(resp)
+        })
+    }
+}
+
+type HandlerFn<Req, Resp> = fn(&MockTronNode, Req) -> Result<Resp, Status>;
+
+async fn unary<Req, Resp, B>(
+    node: MockTronNode,
+    req: http::Request<B>,
+    handler: HandlerFn<Req, Resp>,
+) -> http::Response<tonic::body::Body>
+where
+    Req: Message + Default + Send + 'static,
+    Resp: Message + Send + 'static,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    let mut grpc = Grpc::new(ProstCodec::<Resp, Req>::default());
+    grpc.unary(Handler { node, handler }, req).await
+}
+
+/// Like `unary`, but the handler returns an already-encoded response message.
+async fn raw<Req, B>(
+    node: MockTronNode,
+    req: http::Request<B>,
+    handler: HandlerFn<Req, Bytes>,
+) -> http::Response<tonic::body::Body>
+where
+    Req: Message + Default + Send + 'static,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    let mut grpc = Grpc::new(RawResponseCodec::<Req>(PhantomData));
+    grpc.unary(Handler { node, handler }, req).await
+}
+
+struct Handler<Req, Resp> {
+    node: MockTronNode,
+    handler: HandlerFn<Req, Resp>,
+}
+
+impl<Req, Resp> UnaryService<Req> for Handler<Req, Resp>
+where
+    Req: Send + 'static,
+    Resp: Send + 'static,
+{
+    type Response = Resp;
+    type Future = BoxFuture<Response<Resp>, Status>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        let out = (self.handler)(&self.node, request.into_inner()).map(Response::new);
+        Box::pin(std::future::ready(out))
+    }
+}
+
+struct RawResponseCodec<Req>(PhantomData<Req>);
+
+impl<Req> Codec for RawResponseCodec<Req>
+where
+    Req: Message + Default + Send + 'static,
+{
+    type Encode = Bytes;
+    type Decode = Req;
+    type Encoder = RawBytesEncoder;
+    type Decoder = MessageDecoder<Req>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawBytesEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        MessageDecoder(PhantomData)
+    }
+}
+
+struct RawBytesEncoder;
+
+impl Encoder for RawBytesEncoder {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Bytes, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        dst.put_slice(&item);
+        Ok(())
+    }
+}
+
+struct MessageDecoder<T>(PhantomData<T>);
+
+impl<T> Decoder for MessageDecoder<T>
+where
+    T: Message + Default,
+{
+    type Item = T;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<T>, Self::Error> {
+        T::decode(src)
+            .map(Some)
+            .map_err(|e| Status::internal(format!("prost decode failed: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{BlockExtRawFixture, fixture_path};
+    use crate::node::MockAccount;
+    use alloy::primitives::{Address, U256};
+    use tron::{
+        FeeLimitPolicy, TronAddress, TronApi, TronClient, TronGrpcPool, TronGrpcPoolOptions,
+        TronSystemContract, TronTxProofBuilder, TronWallet, protocol::ResourceCode,
+        wallet::trc20_balance_of,
+    };
+
+    const BLOCKEXT_FIXTURE: &str = "tron_blockext_raw_79072107_860981c986ef3c0313916283483e6e0f8614686b2c011c31d68d42e8efff6601.json";
+
+    // The node is served in-process (`connect_in_memory`), so these run where sockets can't be
+    // bound.
+    #[tokio::test]
+    async fn proof_builder_proves_fixture_tx_once_final() {
+        let fixture = BlockExtRawFixture::load(fixture_path(BLOCKEXT_FIXTURE)).unwrap();
+        let txid = fixture.txid().unwrap();
+        let node = MockTronNode::new();
+        let tx_block = node.push_fixture(&fixture).unwrap();
+
+        let mut grpc = MockTronServer::connect_in_memory(&node).unwrap();
+        let builder = TronTxProofBuilder::new(19);
+
+        let err = builder.build(&mut grpc, txid).await.err().unwrap();
+        assert!(err.to_string().contains("not finalized"), "{err:#}");
+
+        node.produce_blocks(19);
+        let bundle = builder.build(&mut grpc, txid).await.unwrap();
+        assert_eq!(bundle.blocks.len(), 20);
+        assert!(bundle.blocks.iter().all(|b| b.len() == 174));
+        assert_eq!(node.head_block_number(), Some(tx_block + 19));
+    }
+
+    #[tokio::test]
+    async fn wallet_broadcast_settles_trc20_transfer() {
+        let node = MockTronNode::new();
+        node.produce_block();
+        node.set_auto_produce(true);
+
+        let wallet = TronWallet::new([0x11u8; 32]).unwrap();
+        let token = TronAddress::from_evm(Address::repeat_byte(0x22));
+        let to = TronAddress::from_evm(Address::repeat_byte(0x33));
+        node.set_account(
+            wallet.address(),
+            MockAccount {
+                balance_sun: 100_000_000,
+                ..Default::default()
+            },
+        );
+        node.set_trc20_balance(token, wallet.address(), U256::from(1_000u64));
+
+        let mut tron = TronClient::Grpc(MockTronServer::connect_in_memory(&node).unwrap());
+
+        let mut data = tron::SELECTOR_TRANSFER.to_vec();
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(to.evm().as_slice());
+        data.extend_from_slice(&U256::from(400u64).to_be_bytes::<32>());
+        let txid = wallet
+            .broadcast_trigger_smart_contract(&mut tron, token, data, 0, FeeLimitPolicy::FIXED)
+            .await
+            .unwrap();
+
+        let info = tron.get_transaction_info_by_id(txid).await.unwrap();
+        assert!(info.block_number > 0);
+        assert!(info.fee > 0);
+        let balance = trc20_balance_of(&mut tron, token, to, wallet.address())
+            .await
+            .unwrap();
+        assert_eq!(balance, U256::from(400u64));
+
+        node.set_revert(token, "paused");
+        let err = wallet
+            .broadcast_trigger_smart_contract(
+                &mut tron,
+                token,
+                Vec::new(),
+                0,
+                FeeLimitPolicy::FIXED,
+            )
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("would revert"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn wallet_broadcast_settles_trx_transfer_and_freeze() {
+        let node = MockTronNode::new();
+        node.produce_block();
+        node.set_auto_produce(true);
+
+        let wallet = TronWallet::new([0x11u8; 32]).unwrap();
+        let to = TronAddress::from_evm(Address::repeat_byte(0x33));
+        node.set_account(
+            wallet.address(),
+            MockAccount {
+                balance_sun: 10_000_000,
+                ..Default::default()
+            },
+        );
+
+        let mut tron = TronClient::Grpc(MockTronServer::connect_in_memory(&node).unwrap());
+
+        let txid = wallet
+            .broadcast_system_contract(
+                &mut tron,
+                TronSystemContract::Transfer {
+                    to,
+                    amount_sun: 4_000_000,
+                },
+            )
+            .await
+            .unwrap();
+        let info = tron.get_transaction_info_by_id(txid).await.unwrap();
+        assert!(info.block_number > 0);
+        assert_eq!(node.account(to).balance_sun, 4_000_000);
+
+        let txid = wallet
+            .broadcast_system_contract(
+                &mut tron,
+                TronSystemContract::FreezeBalanceV2 {
+                    amount_sun: 1_000_000,
+                    resource: ResourceCode::Energy,
+                },
+            )
+            .await
+            .unwrap();
+        let tx = tron.get_transaction_by_id(txid).await.unwrap();
+        assert_eq!(tx.signature.len(), 1);
+
+        let err = wallet
+            .broadcast_system_contract(
+                &mut tron,
+                TronSystemContract::Transfer {
+                    to,
+                    amount_sun: 1_000_000_000,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("not sufficient"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn in_memory_pool_fails_over_around_an_unavailable_node() {
+        let down = MockTronNode::new();
+        down.produce_block();
+        down.set_unavailable(true);
+        let up = MockTronNode::new();
+        up.produce_blocks(3);
+
+        let client = |node: &MockTronNode| {
+            TronClient::Grpc(MockTronServer::connect_in_memory(node).unwrap())
+        };
+        let pool = TronGrpcPool::from_clients(
+            vec![
+                ("down".to_string(), client(&down)),
+                ("up".to_string(), client(&up)),
+            ],
+            TronGrpcPoolOptions {
+                hedge_after: None,
+                head_probe_interval: std::time::Duration::ZERO,
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+
+        let head = pool
+            .with_read("get_now_block2", |tron| {
+                Box::pin(async move {
+                    tron.get_now_block2()
+                        .await
+                        .map(|b| b.block_header.and_then(|h| h.raw_data).map(|r| r.number))
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(head, up.head_block_number());
+        assert_eq!(pool.preferred_index(), 1);
+
+        // Once the first node is back, the pool can use it again without reconnecting.
+        up.set_unavailable(true);
+        down.set_unavailable(false);
+        let head = pool
+            .with_failover("get_now_block2", |tron| {
+                Box::pin(async move {
+                    tron.get_now_block2()
+                        .await
+                        .map(|b| b.block_header.and_then(|h| h.raw_data).map(|r| r.number))
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(head, down.head_block_number());
+        assert_eq!(pool.preferred_index(), 0);
+    }
+
+    #[tokio::test]
+    async fn grpc_pool_reads_around_a_lagging_endpoint() {
+        let behind = MockTronNode::new();
+        behind.produce_block();
+        let synced = MockTronNode::new();
+        synced.produce_blocks(30);
+
+        let client = |node: &MockTronNode| {
+            TronClient::Grpc(MockTronServer::connect_in_memory(node).unwrap())
+        };
+        let pool = TronGrpcPool::from_clients(
+            vec![
+                ("behind".to_string(), client(&behind)),
+                ("synced".to_string(), client(&synced)),
+            ],
+            TronGrpcPoolOptions {
+                max_head_lag_blocks: 5,
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(pool.preferred_index(), 0);
+        // Calls only start probes in the background; run one to completion first.
+        pool.refresh_heads().await;
+
+        let head = pool
+            .with_read("get_now_block2", |tron| {
+                Box::pin(async move {
+                    tron.get_now_block2()
+                        .await
+                        .map(|b| b.block_header.and_then(|h| h.raw_data).map(|r| r.number))
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(head, synced.head_block_number());
+        assert_eq!(pool.preferred_index(), 1);
+    }
+}
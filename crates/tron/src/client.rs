@@ -0,0 +1,333 @@
+use super::grpc::TronGrpc;
+use super::http::{HTTP_URL_PREFIX, TronHttp};
+use super::protocol::{
+    Account, AccountResourceMessage, BlockExtention, ChainParameters, EstimateEnergyMessage,
+    Return, Transaction, TransactionExtention, TransactionInfo, TriggerSmartContract,
+};
+use anyhow::Result;
+use std::future::Future;
+
+/// Node operations the relayer, pool and proof builder rely on, independent of transport.
+///
+/// Implemented by `TronGrpc`, `TronHttp` and `TronClient` (which picks one per URL).
+pub trait TronApi: Send {
+    fn get_now_block2(&mut self) -> impl Future<Output = Result<BlockExtention>> + Send;
+
+    fn get_block_by_num2(
+        &mut self,
+        num: i64,
+    ) -> impl Future<Output = Result<BlockExtention>> + Send;
+
+    /// The block plus the canonical encoded bytes of each transaction (for txTrieRoot). Only
+    /// gRPC can serve this; `TronHttp` always returns an error.
+    fn get_block_by_num2_raw_txs(
+        &mut self,
+        num: i64,
+    ) -> impl Future<Output = Result<(BlockExtention, Vec<Vec<u8>>)>> + Send;
+
+    fn get_transaction_info_by_id(
+        &mut self,
+        txid: [u8; 32],
+    ) -> impl Future<Output = Result<TransactionInfo>> + Send;
+
+    fn get_transaction_by_id(
+        &mut self,
+        txid: [u8; 32],
+    ) -> impl Future<Output = Result<Transaction>> + Send;
+
+    fn trigger_contract(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> impl Future<Output = Result<TransactionExtention>> + Send;
+
+    fn trigger_constant_contract(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> impl Future<Output = Result<TransactionExtention>> + Send;
+
+    fn estimate_energy(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> impl Future<Output = Result<EstimateEnergyMessage>> + Send;
+
+    fn broadcast_transaction(
+        &mut self,
+        tx: Transaction,
+    ) -> impl Future<Output = Result<Return>> + Send;
+
+    fn get_account(
+        &mut self,
+        address_prefixed: Vec<u8>,
+    ) -> impl Future<Output = Result<Account>> + Send;
+
+    fn get_account_resource(
+        &mut self,
+        address_prefixed: Vec<u8>,
+    ) -> impl Future<Output = Result<AccountResourceMessage>> + Send;
+
+    fn get_chain_parameters(&mut self) -> impl Future<Output = Result<ChainParameters>> + Send;
+}
+
+impl TronApi for TronGrpc {
+    async fn get_now_block2(&mut self) -> Result<BlockExtention> {
+        TronGrpc::get_now_block2(self).await
+    }
+
+    async fn get_block_by_num2(&mut self, num: i64) -> Result<BlockExtention> {
+        TronGrpc::get_block_by_num2(self, num).await
+    }
+
+    async fn get_block_by_num2_raw_txs(
+        &mut self,
+        num: i64,
+    ) -> Result<(BlockExtention, Vec<Vec<u8>>)> {
+        TronGrpc::get_block_by_num2_raw_txs(self, num).await
+    }
+
+    async fn get_transaction_info_by_id(&mut self, txid: [u8; 32]) -> Result<TransactionInfo> {
+        TronGrpc::get_transaction_info_by_id(self, txid).await
+    }
+
+    async fn get_transaction_by_id(&mut self, txid: [u8; 32]) -> Result<Transaction> {
+        TronGrpc::get_transaction_by_id(self, txid).await
+    }
+
+    async fn trigger_contract(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention> {
+        TronGrpc::trigger_contract(self, msg).await
+    }
+
+    async fn trigger_constant_contract(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention> {
+        TronGrpc::trigger_constant_contract(self, msg).await
+    }
+
+    async fn estimate_energy(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<EstimateEnergyMessage> {
+        TronGrpc::estimate_energy(self, msg).await
+    }
+
+    async fn broadcast_transaction(&mut self, tx: Transaction) -> Result<Return> {
+        TronGrpc::broadcast_transaction(self, tx).await
+    }
+
+    async fn get_account(&mut self, address_prefixed: Vec<u8>) -> Result<Account> {
+        TronGrpc::get_account(self, address_prefixed).await
+    }
+
+    async fn get_account_resource(
+        &mut self,
+        address_prefixed: Vec<u8>,
+    ) -> Result<AccountResourceMessage> {
+        TronGrpc::get_account_resource(self, address_prefixed).await
+    }
+
+    async fn get_chain_parameters(&mut self) -> Result<ChainParameters> {
+        TronGrpc::get_chain_parameters(self).await
+    }
+}
+
+impl TronApi for TronHttp {
+    async fn get_now_block2(&mut self) -> Result<BlockExtention> {
+        TronHttp::get_now_block2(self).await
+    }
+
+    async fn get_block_by_num2(&mut self, num: i64) -> Result<BlockExtention> {
+        TronHttp::get_block_by_num2(self, num).await
+    }
+
+    async fn get_block_by_num2_raw_txs(
+        &mut self,
+        num: i64,
+    ) -> Result<(BlockExtention, Vec<Vec<u8>>)> {
+        TronHttp::get_block_by_num2_raw_txs(self, num).await
+    }
+
+    async fn get_transaction_info_by_id(&mut self, txid: [u8; 32]) -> Result<TransactionInfo> {
+        TronHttp::get_transaction_info_by_id(self, txid).await
+    }
+
+    async fn get_transaction_by_id(&mut self, txid: [u8; 32]) -> Result<Transaction> {
+        TronHttp::get_transaction_by_id(self, txid).await
+    }
+
+    async fn trigger_contract(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention> {
+        TronHttp::trigger_contract(self, msg).await
+    }
+
+    async fn trigger_constant_contract(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention> {
+        TronHttp::trigger_constant_contract(self, msg).await
+    }
+
+    async fn estimate_energy(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<EstimateEnergyMessage> {
+        TronHttp::estimate_energy(self, msg).await
+    }
+
+    async fn broadcast_transaction(&mut self, tx: Transaction) -> Result<Return> {
+        TronHttp::broadcast_transaction(self, tx).await
+    }
+
+    async fn get_account(&mut self, address_prefixed: Vec<u8>) -> Result<Account> {
+        TronHttp::get_account(self, address_prefixed).await
+    }
+
+    async fn get_account_resource(
+        &mut self,
+        address_prefixed: Vec<u8>,
+    ) -> Result<AccountResourceMessage> {
+        TronHttp::get_account_resource(self, address_prefixed).await
+    }
+
+    async fn get_chain_parameters(&mut self) -> Result<ChainParameters> {
+        TronHttp::get_chain_parameters(self).await
+    }
+}
+
+/// A Tron node connection over whichever transport its URL selects.
+// Connections are long-lived and rarely moved; boxing the gRPC side buys nothing.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+pub enum TronClient {
+    Grpc(TronGrpc),
+    Http(TronHttp),
+}
+
+impl TronClient {
+    /// `rest+https://host` (see `HTTP_URL_PREFIX`) uses the `/wallet/*` HTTP API; any other URL
+    /// is passed to `TronGrpc::connect`.
+    pub async fn connect(url: &str, api_key: Option<&str>, api_key_header: &str) -> Result<Self> {
+        match url.trim().strip_prefix(HTTP_URL_PREFIX) {
+            Some(http_url) => Ok(Self::Http(TronHttp::new(
+                http_url,
+                api_key,
+                api_key_header,
+            )?)),
+            None => Ok(Self::Grpc(
+                TronGrpc::connect(url, api_key, api_key_header).await?,
+            )),
+        }
+    }
+
+    pub fn transport(&self) -> &'static str {
+        match self {
+            Self::Grpc(_) => "grpc",
+            Self::Http(_) => "http",
+        }
+    }
+}
+
+impl TronApi for TronClient {
+    async fn get_now_block2(&mut self) -> Result<BlockExtention> {
+        match self {
+            Self::Grpc(c) => c.get_now_block2().await,
+            Self::Http(c) => c.get_now_block2().await,
+        }
+    }
+
+    async fn get_block_by_num2(&mut self, num: i64) -> Result<BlockExtention> {
+        match self {
+            Self::Grpc(c) => c.get_block_by_num2(num).await,
+            Self::Http(c) => c.get_block_by_num2(num).await,
+        }
+    }
+
+    async fn get_block_by_num2_raw_txs(
+        &mut self,
+        num: i64,
+    ) -> Result<(BlockExtention, Vec<Vec<u8>>)> {
+        match self {
+            Self::Grpc(c) => c.get_block_by_num2_raw_txs(num).await,
+            Self::Http(c) => c.get_block_by_num2_raw_txs(num).await,
+        }
+    }
+
+    async fn get_transaction_info_by_id(&mut self, txid: [u8; 32]) -> Result<TransactionInfo> {
+        match self {
+            Self::Grpc(c) => c.get_transaction_info_by_id(txid).await,
+            Self::Http(c) => c.get_transaction_info_by_id(txid).await,
+        }
+    }
+
+    async fn get_transaction_by_id(&mut self, txid: [u8; 32]) -> Result<Transaction> {
+        match self {
+            Self::Grpc(c) => c.get_transaction_by_id(txid).await,
+            Self::Http(c) => c.get_transaction_by_id(txid).await,
+        }
+    }
+
+    async fn trigger_contract(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention> {
+        match self {
+            Self::Grpc(c) => c.trigger_contract(msg).await,
+            Self::Http(c) => c.trigger_contract(msg).await,
+        }
+    }
+
+    async fn trigger_constant_contract(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention> {
+        match self {
+            Self::Grpc(c) => c.trigger_constant_contract(msg).await,
+            Self::Http(c) => c.trigger_constant_contract(msg).await,
+        }
+    }
+
+    async fn estimate_energy(
+        &mut self,
+        msg: TriggerSmartContract,
+    ) -> Result<EstimateEnergyMessage> {
+        match self {
+            Self::Grpc(c) => c.estimate_energy(msg).await,
+            Self::Http(c) => c.estimate_energy(msg).await,
+        }
+    }
+
+    async fn broadcast_transaction(&mut self, tx: Transaction) -> Result<Return> {
+        match self {
+            Self::Grpc(c) => c.broadcast_transaction(tx).await,
+            Self::Http(c) => c.broadcast_transaction(tx).await,
+        }
+    }
+
+    async fn get_account(&mut self, address_prefixed: Vec<u8>) -> Result<Account> {
+        match self {
+            Self::Grpc(c) => c.get_account(address_prefixed).await,
+            Self::Http(c) => c.get_account(address_prefixed).await,
+        }
+    }
+
+    async fn get_account_resource(
+        &mut self,
+        address_prefixed: Vec<u8>,
+    ) -> Result<AccountResourceMessage> {
+        match self {
+            Self::Grpc(c) => c.get_account_resource(address_prefixed).await,
+            Self::Http(c) => c.get_account_resource(address_prefixed).await,
+        }
+    }
+
+    async fn get_chain_parameters(&mut self) -> Result<ChainParameters> {
+        match self {
+            Self::Grpc(c) => c.get_chain_parameters().await,
+            Self::Http(c) => c.get_chain_parameters().await,
+        }
+    }
+}
@@ -52,7 +52,15 @@ impl TronGrpc {
         };
 
         let channel = endpoint.connect().await.context("connect TRON gRPC")?;
+        Self::from_channel(channel, api_key, api_key_header)
+    }
 
+    /// Wraps an already-built channel, e.g. an in-process transport in tests.
+    pub fn from_channel(
+        channel: Channel,
+        api_key: Option<&str>,
+        api_key_header: &str,
+    ) -> Result<Self> {
         let api_key = match api_key {
             Some(k) if !k.trim().is_empty() => {
                 Some(MetadataValue::from_str(k).context("invalid TRON_API_KEY (metadata value)")?)
@@ -375,7 +383,7 @@ pub(crate) fn extract_transaction_bytes_from_transaction_extention(buf: &[u8]) -
     anyhow::bail!("missing TransactionExtention.transaction field")
 }
 
-fn decode_varint(buf: &[u8]) -> Result<(u64, &[u8])> {
+pub(crate) fn decode_varint(buf: &[u8]) -> Result<(u64, &[u8])> {
     let mut out: u64 = 0;
     let mut shift: u32 = 0;
     let mut idx: usize = 0;
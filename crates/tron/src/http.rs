@@ -0,0 +1,817 @@
+use super::protocol::{
+    Account, AccountResourceMessage, BlockExtention, BlockHeader, ChainParameters,
+    EstimateEnergyMessage, ResourceCode, ResourceReceipt, Return, Transaction,
+    TransactionExtention, TransactionInfo, TriggerSmartContract, account, block_header,
+    chain_parameters, r#return, transaction, transaction_info,
+};
+use anyhow::{Context, Result};
+use prost::Message;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// URL prefix that selects the `/wallet/*` HTTP backend in `TronClient::connect`, e.g.
+/// `rest+https://api.trongrid.io`. Bare `http(s)://` URLs keep meaning gRPC.
+pub const HTTP_URL_PREFIX: &str = "rest+";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// java-tron `/wallet/*` HTTP JSON API client.
+///
+/// Responses are mapped onto the same protobuf types `TronGrpc` returns. Transactions are
+/// rebuilt from `raw_data_hex` (not the JSON `raw_data`), so their txid matches the gRPC one.
+/// The JSON leaves out `Transaction.Result` fields it has no name for, so the full encoding
+/// can't be rebuilt: transaction proofs (`get_block_by_num2_raw_txs`) need a gRPC endpoint.
+#[derive(Clone)]
+pub struct TronHttp {
+    base_url: String,
+    client: reqwest::Client,
+    api_key: Option<(HeaderName, HeaderValue)>,
+}
+
+impl TronHttp {
+    /// `base_url` is the node root (without `/wallet`). `api_key` is sent under `api_key_header`,
+    /// same as the gRPC metadata.
+    pub fn new(base_url: &str, api_key: Option<&str>, api_key_header: &str) -> Result<Self> {
+        let base_url = base_url.trim().trim_end_matches('/').to_string();
+        if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            anyhow::bail!("invalid TRON HTTP URL (expected http:// or https://): {base_url}");
+        }
+
+        let api_key = match api_key {
+            Some(k) if !k.trim().is_empty() => Some((
+                HeaderName::from_bytes(api_key_header.as_bytes())
+                    .context("invalid TRON_API_KEY_HEADER (http header name)")?,
+                HeaderValue::from_str(k).context("invalid TRON_API_KEY (http header value)")?,
+            )),
+            _ => None,
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .context("build TRON HTTP client")?;
+
+        Ok(Self {
+            base_url,
+            client,
+            api_key,
+        })
+    }
+
+    async fn post(&self, method: &str, body: Value) -> Result<Value> {
+        let url = format!("{}/wallet/{method}", self.base_url);
+        let mut req = self.client.post(&url).json(&body);
+        if let Some((name, value)) = &self.api_key {
+            req = req.header(name.clone(), value.clone());
+        }
+
+        let resp = req.send().await.with_context(|| format!("POST {method}"))?;
+        let status = resp.status();
+        let text = resp
+            .text()
+            .await
+            .with_context(|| format!("read {method} response"))?;
+        if !status.is_success() {
+            anyhow::bail!("{method}: http {status}: {text}");
+        }
+
+        let v: Value =
+            serde_json::from_str(&text).with_context(|| format!("{method}: invalid json"))?;
+        // java-tron reports request-level failures as 200 + {"Error": "..."}.
+        if let Some(err) = v.get("Error") {
+            anyhow::bail!("{method}: {err}");
+        }
+        Ok(v)
+    }
+
+    pub async fn get_now_block2(&self) -> Result<BlockExtention> {
+        let v = self.post("getnowblock", json!({})).await?;
+        block_from_json(&v).context("GetNowBlock")
+    }
+
+    pub async fn get_block_by_num2(&self, num: i64) -> Result<BlockExtention> {
+        let v = self.post("getblockbynum", json!({ "num": num })).await?;
+        block_from_json(&v).context("GetBlockByNum")
+    }
+
+    /// Always fails: the HTTP API can't return the canonical transaction bytes a txTrieRoot
+    /// proof is built from (see the type docs).
+    pub async fn get_block_by_num2_raw_txs(
+        &self,
+        num: i64,
+    ) -> Result<(BlockExtention, Vec<Vec<u8>>)> {
+        anyhow::bail!(
+            "block {num}: the /wallet HTTP API does not return canonical transaction bytes; \
+             Tron proofs need a gRPC endpoint"
+        )
+    }
+
+    pub async fn get_transaction_info_by_id(&self, txid: [u8; 32]) -> Result<TransactionInfo> {
+        let v = self
+            .post(
+                "gettransactioninfobyid",
+                json!({ "value": hex::encode(txid) }),
+            )
+            .await?;
+        transaction_info_from_json(&v).context("GetTransactionInfoById")
+    }
+
+    pub async fn get_transaction_by_id(&self, txid: [u8; 32]) -> Result<Transaction> {
+        let v = self
+            .post("gettransactionbyid", json!({ "value": hex::encode(txid) }))
+            .await?;
+        if v.as_object().is_some_and(|o| o.is_empty()) {
+            // Unknown txid: gRPC returns an empty message too.
+            return Ok(Transaction::default());
+        }
+        let tx = transaction_from_json(&v).context("GetTransactionById")?;
+        Ok(tx)
+    }
+
+    pub async fn trigger_contract(
+        &self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention> {
+        let v = self
+            .post("triggersmartcontract", trigger_to_json(&msg))
+            .await?;
+        transaction_extention_from_json(&v).context("TriggerContract")
+    }
+
+    pub async fn trigger_constant_contract(
+        &self,
+        msg: TriggerSmartContract,
+    ) -> Result<TransactionExtention> {
+        let v = self
+            .post("triggerconstantcontract", trigger_to_json(&msg))
+            .await?;
+        transaction_extention_from_json(&v).context("TriggerConstantContract")
+    }
+
+    pub async fn estimate_energy(
+        &self,
+        msg: TriggerSmartContract,
+    ) -> Result<EstimateEnergyMessage> {
+        let v = self.post("estimateenergy", trigger_to_json(&msg)).await?;
+        Ok(EstimateEnergyMessage {
+            result: v.get("result").map(return_from_json).transpose()?,
+            energy_required: i64_field(&v, "energy_required")?,
+        })
+    }
+
+    pub async fn broadcast_transaction(&self, tx: Transaction) -> Result<Return> {
+        let v = self
+            .post(
+                "broadcasthex",
+                json!({ "transaction": hex::encode(tx.encode_to_vec()) }),
+            )
+            .await?;
+        return_from_json(&v).context("BroadcastTransaction")
+    }
+
+    pub async fn get_account(&self, address_prefixed: Vec<u8>) -> Result<Account> {
+        let v = self
+            .post(
+                "getaccount",
+                json!({ "address": hex::encode(&address_prefixed), "visible": false }),
+            )
+            .await?;
+        account_from_json(&v).context("GetAccount")
+    }
+
+    pub async fn get_account_resource(
+        &self,
+        address_prefixed: Vec<u8>,
+    ) -> Result<AccountResourceMessage> {
+        let v = self
+            .post(
+                "getaccountresource",
+                json!({ "address": hex::encode(&address_prefixed), "visible": false }),
+            )
+            .await?;
+        Ok(AccountResourceMessage {
+            free_net_used: i64_field(&v, "freeNetUsed")?,
+            free_net_limit: i64_field(&v, "freeNetLimit")?,
+            net_used: i64_field(&v, "NetUsed")?,
+            net_limit: i64_field(&v, "NetLimit")?,
+            total_net_limit: i64_field(&v, "TotalNetLimit")?,
+            total_net_weight: i64_field(&v, "TotalNetWeight")?,
+            total_tron_power_weight: i64_field(&v, "TotalTronPowerWeight")?,
+            tron_power_used: i64_field(&v, "tronPowerUsed")?,
+            tron_power_limit: i64_field(&v, "tronPowerLimit")?,
+            energy_used: i64_field(&v, "EnergyUsed")?,
+            energy_limit: i64_field(&v, "EnergyLimit")?,
+            total_energy_limit: i64_field(&v, "TotalEnergyLimit")?,
+            total_energy_weight: i64_field(&v, "TotalEnergyWeight")?,
+            storage_used: i64_field(&v, "storageUsed")?,
+            storage_limit: i64_field(&v, "storageLimit")?,
+            ..Default::default()
+        })
+    }
+
+    pub async fn get_chain_parameters(&self) -> Result<ChainParameters> {
+        let v = self.post("getchainparameters", json!({})).await?;
+        let mut chain_parameter = Vec::new();
+        for p in array_field(&v, "chainParameter") {
+            chain_parameter.push(chain_parameters::ChainParameter {
+                key: p
+                    .get("key")
+                    .and_then(Value::as_str)
+                    .context("chainParameter missing key")?
+                    .to_string(),
+                // Zero-valued parameters are omitted from the JSON.
+                value: i64_field(p, "value")?,
+            });
+        }
+        Ok(ChainParameters { chain_parameter })
+    }
+}
+
+fn account_from_json(v: &Value) -> Result<Account> {
+    let frozen_v2 = array_field(v, "frozenV2")
+        .iter()
+        .map(|f| {
+            Ok(account::FreezeV2 {
+                // BANDWIDTH is the zero value, so the node leaves `type` out for it.
+                r#type: enum_field(f, "type", ResourceCode::from_str_name)?,
+                amount: i64_field(f, "amount")?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    let account_resource = v
+        .get("account_resource")
+        .map(|r| -> Result<_> {
+            Ok(account::AccountResource {
+                energy_usage: i64_field(r, "energy_usage")?,
+                latest_consume_time_for_energy: i64_field(r, "latest_consume_time_for_energy")?,
+                energy_window_size: i64_field(r, "energy_window_size")?,
+                energy_window_optimized: r
+                    .get("energy_window_optimized")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                delegated_frozen_v2_balance_for_energy: i64_field(
+                    r,
+                    "delegated_frozenV2_balance_for_energy",
+                )?,
+                acquired_delegated_frozen_v2_balance_for_energy: i64_field(
+                    r,
+                    "acquired_delegated_frozenV2_balance_for_energy",
+                )?,
+                ..Default::default()
+            })
+        })
+        .transpose()?;
+    Ok(Account {
+        address: hex_field(v, "address")?,
+        balance: i64_field(v, "balance")?,
+        create_time: i64_field(v, "create_time")?,
+        net_usage: i64_field(v, "net_usage")?,
+        free_net_usage: i64_field(v, "free_net_usage")?,
+        latest_consume_time: i64_field(v, "latest_consume_time")?,
+        latest_consume_free_time: i64_field(v, "latest_consume_free_time")?,
+        asset: asset_map(v, "asset")?,
+        asset_v2: asset_map(v, "assetV2")?,
+        account_resource,
+        frozen_v2,
+        delegated_frozen_v2_balance_for_bandwidth: i64_field(
+            v,
+            "delegated_frozenV2_balance_for_bandwidth",
+        )?,
+        acquired_delegated_frozen_v2_balance_for_bandwidth: i64_field(
+            v,
+            "acquired_delegated_frozenV2_balance_for_bandwidth",
+        )?,
+        ..Default::default()
+    })
+}
+
+fn trigger_to_json(msg: &TriggerSmartContract) -> Value {
+    json!({
+        "owner_address": hex::encode(&msg.owner_address),
+        "contract_address": hex::encode(&msg.contract_address),
+        "data": hex::encode(&msg.data),
+        "call_value": msg.call_value,
+        "call_token_value": msg.call_token_value,
+        "token_id": msg.token_id,
+        "visible": false,
+    })
+}
+
+fn block_from_json(v: &Value) -> Result<BlockExtention> {
+    let header = v.get("block_header").context("missing block_header")?;
+    let raw = header
+        .get("raw_data")
+        .context("missing block_header.raw_data")?;
+    let block_header = BlockHeader {
+        raw_data: Some(block_header::Raw {
+            timestamp: i64_field(raw, "timestamp")?,
+            tx_trie_root: hex_field(raw, "txTrieRoot")?,
+            parent_hash: hex_field(raw, "parentHash")?,
+            number: i64_field(raw, "number")?,
+            witness_id: i64_field(raw, "witness_id")?,
+            witness_address: hex_field(raw, "witness_address")?,
+            version: i32::try_from(i64_field(raw, "version")?).context("version out of range")?,
+            account_state_root: hex_field(raw, "accountStateRoot")?,
+        }),
+        witness_signature: hex_field(header, "witness_signature")?,
+    };
+
+    let mut transactions = Vec::new();
+    for (idx, t) in array_field(v, "transactions").iter().enumerate() {
+        let tx = transaction_from_json(t).with_context(|| format!("transaction {idx}"))?;
+        let txid = match hex_field(t, "txID")? {
+            id if id.is_empty() => txid_of(&tx)?,
+            id => id,
+        };
+        transactions.push(TransactionExtention {
+            transaction: Some(tx),
+            txid,
+            ..Default::default()
+        });
+    }
+
+    Ok(BlockExtention {
+        transactions,
+        block_header: Some(block_header),
+        blockid: hex_field(v, "blockID")?,
+    })
+}
+
+/// Rebuilds a `Transaction` from the node's JSON.
+///
+/// `raw_data_hex` is embedded byte-for-byte, so `raw_data` (and the txid) are exact. `ret`
+/// entries carry only the `Transaction.Result` fields the JSON names.
+fn transaction_from_json(v: &Value) -> Result<Transaction> {
+    let raw = hex_field(v, "raw_data_hex")?;
+    if raw.is_empty() {
+        anyhow::bail!("missing raw_data_hex");
+    }
+
+    let mut encoded = Vec::new();
+    put_len_delimited(&mut encoded, 1, &raw);
+    for sig in array_field(v, "signature") {
+        let sig = sig.as_str().context("signature is not a string")?;
+        put_len_delimited(
+            &mut encoded,
+            2,
+            &hex::decode(sig).context("decode signature")?,
+        );
+    }
+    for ret in array_field(v, "ret") {
+        put_len_delimited(&mut encoded, 5, &result_bytes_from_json(ret)?);
+    }
+
+    Transaction::decode(encoded.as_slice()).context("decode rebuilt transaction")
+}
+
+fn put_len_delimited(out: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    prost::encoding::encode_key(field, prost::encoding::WireType::LengthDelimited, out);
+    prost::encoding::encode_varint(bytes.len() as u64, out);
+    out.extend_from_slice(bytes);
+}
+
+/// Encodes one `Transaction.Result` from its JSON form, field by field in field-number order.
+///
+/// Covers every field the node prints by name. `cancel_unfreezeV2_amount` entries come out in
+/// `serde_json`'s key order (sorted), which need not be the order the node stored them in.
+fn result_bytes_from_json(v: &Value) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    put_int64(&mut out, 1, i64_field(v, "fee")?);
+    put_int64(
+        &mut out,
+        2,
+        enum_field(v, "ret", transaction::result::Code::from_str_name)?.into(),
+    );
+    put_int64(
+        &mut out,
+        3,
+        enum_field(
+            v,
+            "contractRet",
+            transaction::result::ContractResult::from_str_name,
+        )?
+        .into(),
+    );
+    let asset_issue_id = match v.get("assetIssueID") {
+        None | Some(Value::Null) => "",
+        Some(id) => id.as_str().context("assetIssueID is not a string")?,
+    };
+    put_bytes(&mut out, 14, asset_issue_id.as_bytes());
+    for (field, key) in [
+        (15, "withdraw_amount"),
+        (16, "unfreeze_amount"),
+        (18, "exchange_received_amount"),
+        (19, "exchange_inject_another_amount"),
+        (20, "exchange_withdraw_another_amount"),
+        (21, "exchange_id"),
+        (22, "shielded_transaction_fee"),
+    ] {
+        put_int64(&mut out, field, i64_field(v, key)?);
+    }
+    put_bytes(&mut out, 25, &hex_field(v, "orderId")?);
+    for detail in array_field(v, "orderDetails") {
+        let mut d = Vec::new();
+        put_bytes(&mut d, 1, &hex_field(detail, "makerOrderId")?);
+        put_bytes(&mut d, 2, &hex_field(detail, "takerOrderId")?);
+        put_int64(&mut d, 3, i64_field(detail, "fillSellQuantity")?);
+        put_int64(&mut d, 4, i64_field(detail, "fillBuyQuantity")?);
+        put_len_delimited(&mut out, 26, &d);
+    }
+    put_int64(&mut out, 27, i64_field(v, "withdraw_expire_amount")?);
+    for (key, amount) in map_entries(v, "cancel_unfreezeV2_amount")? {
+        // protobuf-java writes both halves of a map entry, even when they are defaults.
+        let mut entry = Vec::new();
+        put_len_delimited(&mut entry, 1, key.as_bytes());
+        prost::encoding::encode_key(2, prost::encoding::WireType::Varint, &mut entry);
+        prost::encoding::encode_varint(amount as u64, &mut entry);
+        put_len_delimited(&mut out, 28, &entry);
+    }
+    Ok(out)
+}
+
+/// Skips proto3 defaults, like every non-repeated scalar field java-tron writes.
+fn put_int64(out: &mut Vec<u8>, field: u32, value: i64) {
+    if value != 0 {
+        prost::encoding::encode_key(field, prost::encoding::WireType::Varint, out);
+        prost::encoding::encode_varint(value as u64, out);
+    }
+}
+
+fn put_bytes(out: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    if !bytes.is_empty() {
+        put_len_delimited(out, field, bytes);
+    }
+}
+
+/// A `map<string, int64>` field, printed either as a JSON object or as `[{key, value}]`.
+fn map_entries<'a>(v: &'a Value, key: &str) -> Result<Vec<(&'a str, i64)>> {
+    match v.get(key) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(obj @ Value::Object(map)) => map
+            .keys()
+            .map(|k| Ok((k.as_str(), i64_field(obj, k)?)))
+            .collect(),
+        Some(Value::Array(entries)) => entries
+            .iter()
+            .map(|e| {
+                let k = e
+                    .get("key")
+                    .and_then(Value::as_str)
+                    .with_context(|| format!("{key}: entry without a string key"))?;
+                Ok((k, i64_field(e, "value")?))
+            })
+            .collect(),
+        Some(other) => anyhow::bail!("field {key}: expected map, got {other}"),
+    }
+}
+
+fn txid_of(tx: &Transaction) -> Result<Vec<u8>> {
+    let raw = tx.raw_data.as_ref().context("missing raw_data")?;
+    Ok(Sha256::digest(raw.encode_to_vec()).to_vec())
+}
+
+fn transaction_extention_from_json(v: &Value) -> Result<TransactionExtention> {
+    let transaction = match v.get("transaction") {
+        Some(t) if t.get("raw_data_hex").is_some() => Some(transaction_from_json(t)?),
+        _ => None,
+    };
+    let txid = match &transaction {
+        Some(tx) => txid_of(tx)?,
+        None => Vec::new(),
+    };
+    Ok(TransactionExtention {
+        transaction,
+        txid,
+        constant_result: hex_array(v, "constant_result")?,
+        result: v.get("result").map(return_from_json).transpose()?,
+        energy_used: i64_field(v, "energy_used")?,
+        energy_penalty: i64_field(v, "energy_penalty")?,
+        ..Default::default()
+    })
+}
+
+fn return_from_json(v: &Value) -> Result<Return> {
+    // `message` is hex-encoded bytes on most endpoints, but a few send plain text.
+    let message = match v.get("message").and_then(Value::as_str) {
+        Some(s) => hex::decode(s).unwrap_or_else(|_| s.as_bytes().to_vec()),
+        None => Vec::new(),
+    };
+    Ok(Return {
+        result: v.get("result").and_then(Value::as_bool).unwrap_or(false),
+        code: enum_field(v, "code", r#return::ResponseCode::from_str_name)?,
+        message,
+    })
+}
+
+fn transaction_info_from_json(v: &Value) -> Result<TransactionInfo> {
+    let receipt = match v.get("receipt") {
+        Some(r) => Some(ResourceReceipt {
+            energy_usage: i64_field(r, "energy_usage")?,
+            energy_fee: i64_field(r, "energy_fee")?,
+            origin_energy_usage: i64_field(r, "origin_energy_usage")?,
+            energy_usage_total: i64_field(r, "energy_usage_total")?,
+            net_usage: i64_field(r, "net_usage")?,
+            net_fee: i64_field(r, "net_fee")?,
+            result: enum_field(
+                r,
+                "result",
+                transaction::result::ContractResult::from_str_name,
+            )?,
+            energy_penalty_total: i64_field(r, "energy_penalty_total")?,
+        }),
+        None => None,
+    };
+
+    let mut log = Vec::new();
+    for l in array_field(v, "log") {
+        log.push(transaction_info::Log {
+            address: hex_field(l, "address")?,
+            topics: hex_array(l, "topics")?,
+            data: hex_field(l, "data")?,
+        });
+    }
+
+    Ok(TransactionInfo {
+        id: hex_field(v, "id")?,
+        fee: i64_field(v, "fee")?,
+        block_number: i64_field(v, "blockNumber")?,
+        block_time_stamp: i64_field(v, "blockTimeStamp")?,
+        contract_result: hex_array(v, "contractResult")?,
+        contract_address: hex_field(v, "contract_address")?,
+        receipt,
+        log,
+        result: enum_field(v, "result", transaction_info::Code::from_str_name)?,
+        res_message: hex_field(v, "resMessage")?,
+        ..Default::default()
+    })
+}
+
+fn array_field<'a>(v: &'a Value, key: &str) -> &'a [Value] {
+    v.get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// TRC-10 balances, which the node renders as `[{"key": <token>, "value": <amount>}]`.
+fn asset_map(v: &Value, key: &str) -> Result<HashMap<String, i64>> {
+    array_field(v, key)
+        .iter()
+        .map(|entry| {
+            let token = entry
+                .get("key")
+                .and_then(Value::as_str)
+                .with_context(|| format!("{key}: expected string key"))?;
+            Ok((token.to_owned(), i64_field(entry, "value")?))
+        })
+        .collect()
+}
+
+/// Missing / null fields decode as empty, matching proto3 defaults.
+fn hex_field(v: &Value, key: &str) -> Result<Vec<u8>> {
+    match v.get(key) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(s)) => {
+            let s = s.strip_prefix("0x").unwrap_or(s);
+            hex::decode(s).with_context(|| format!("decode hex field {key}"))
+        }
+        Some(other) => anyhow::bail!("field {key}: expected hex string, got {other}"),
+    }
+}
+
+fn hex_array(v: &Value, key: &str) -> Result<Vec<Vec<u8>>> {
+    array_field(v, key)
+        .iter()
+        .map(|x| {
+            let s = x
+                .as_str()
+                .with_context(|| format!("{key}: expected hex string"))?;
+            hex::decode(s).with_context(|| format!("decode hex in {key}"))
+        })
+        .collect()
+}
+
+fn i64_field(v: &Value, key: &str) -> Result<i64> {
+    match v.get(key) {
+        None | Some(Value::Null) => Ok(0),
+        Some(Value::Number(n)) => n
+            .as_i64()
+            .with_context(|| format!("field {key} out of i64 range: {n}")),
+        Some(Value::String(s)) => s
+            .parse()
+            .with_context(|| format!("field {key}: invalid integer {s:?}")),
+        Some(other) => anyhow::bail!("field {key}: expected integer, got {other}"),
+    }
+}
+
+fn enum_field<E: Into<i32>>(
+    v: &Value,
+    key: &str,
+    parse: impl Fn(&str) -> Option<E>,
+) -> Result<i32> {
+    match v.get(key) {
+        None | Some(Value::Null) => Ok(0),
+        Some(Value::String(s)) => parse(s)
+            .map(Into::into)
+            .with_context(|| format!("field {key}: unknown value {s:?}")),
+        Some(Value::Number(n)) => n
+            .as_i64()
+            .and_then(|n| i32::try_from(n).ok())
+            .with_context(|| format!("field {key}: invalid enum value {n}")),
+        Some(other) => anyhow::bail!("field {key}: expected enum, got {other}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transaction_from_json_keeps_raw_data_bytes() {
+        let raw = transaction::Raw {
+            ref_block_bytes: vec![0x01, 0x02],
+            expiration: 42,
+            fee_limit: 7,
+            ..Default::default()
+        };
+        let raw_bytes = raw.encode_to_vec();
+        let v = json!({
+            "raw_data_hex": hex::encode(&raw_bytes),
+            "signature": [hex::encode([0x11u8; 65])],
+            "ret": [{ "contractRet": "SUCCESS" }],
+        });
+
+        let tx = transaction_from_json(&v).unwrap();
+        let expected = Transaction {
+            raw_data: Some(raw),
+            signature: vec![vec![0x11u8; 65]],
+            ret: vec![transaction::Result {
+                contract_ret: transaction::result::ContractResult::Success.into(),
+                ..Default::default()
+            }],
+        };
+        assert_eq!(tx, expected);
+    }
+
+    #[test]
+    fn transaction_from_json_keeps_every_result_field() {
+        let raw = transaction::Raw {
+            expiration: 42,
+            ..Default::default()
+        };
+        let v = json!({
+            "raw_data_hex": hex::encode(raw.encode_to_vec()),
+            "ret": [{
+                "fee": 1100000,
+                "contractRet": "SUCCESS",
+                "assetIssueID": "1002000",
+                "withdraw_amount": 5,
+                "unfreeze_amount": 6,
+                "exchange_received_amount": 7,
+                "exchange_inject_another_amount": 8,
+                "exchange_withdraw_another_amount": 9,
+                "exchange_id": 10,
+                "shielded_transaction_fee": 11,
+                "orderId": "ab".repeat(32),
+                "orderDetails": [{
+                    "makerOrderId": "cd".repeat(32),
+                    "takerOrderId": "ef".repeat(32),
+                    "fillSellQuantity": 12,
+                    "fillBuyQuantity": 13,
+                }],
+                "withdraw_expire_amount": 14,
+                "cancel_unfreezeV2_amount": [{ "key": "ENERGY", "value": 15 }],
+            }],
+        });
+
+        let tx = transaction_from_json(&v).unwrap();
+        let expected = Transaction {
+            raw_data: Some(raw),
+            signature: Vec::new(),
+            ret: vec![transaction::Result {
+                fee: 1_100_000,
+                contract_ret: transaction::result::ContractResult::Success.into(),
+                asset_issue_id: "1002000".into(),
+                withdraw_amount: 5,
+                unfreeze_amount: 6,
+                exchange_received_amount: 7,
+                exchange_inject_another_amount: 8,
+                exchange_withdraw_another_amount: 9,
+                exchange_id: 10,
+                shielded_transaction_fee: 11,
+                order_id: vec![0xab; 32],
+                order_details: vec![crate::protocol::MarketOrderDetail {
+                    maker_order_id: vec![0xcd; 32],
+                    taker_order_id: vec![0xef; 32],
+                    fill_sell_quantity: 12,
+                    fill_buy_quantity: 13,
+                }],
+                withdraw_expire_amount: 14,
+                cancel_unfreeze_v2_amount: [("ENERGY".to_string(), 15)].into(),
+                ..Default::default()
+            }],
+        };
+        assert_eq!(tx, expected);
+    }
+
+    #[test]
+    fn block_from_json_maps_header_and_txids() {
+        let raw = transaction::Raw {
+            timestamp: 1,
+            ..Default::default()
+        };
+        let v = json!({
+            "blockID": "00".repeat(32),
+            "block_header": {
+                "raw_data": {
+                    "number": 78812179,
+                    "txTrieRoot": "11".repeat(32),
+                    "witness_address": format!("41{}", "22".repeat(20)),
+                    "parentHash": "33".repeat(32),
+                    "version": 32,
+                    "timestamp": 1_767_000_000_000i64,
+                },
+                "witness_signature": "44".repeat(65),
+            },
+            "transactions": [{ "raw_data_hex": hex::encode(raw.encode_to_vec()) }],
+        });
+
+        let block = block_from_json(&v).unwrap();
+        let header = block.block_header.unwrap();
+        let header_raw = header.raw_data.unwrap();
+        assert_eq!(header_raw.number, 78_812_179);
+        assert_eq!(header_raw.version, 32);
+        assert_eq!(header_raw.tx_trie_root, vec![0x11u8; 32]);
+        assert_eq!(header.witness_signature, vec![0x44u8; 65]);
+        assert_eq!(block.transactions.len(), 1);
+        assert_eq!(
+            block.transactions[0].txid,
+            Sha256::digest(raw.encode_to_vec()).to_vec()
+        );
+    }
+
+    #[test]
+    fn return_from_json_decodes_hex_message_and_code() {
+        let v = json!({ "code": "SIGERROR", "message": hex::encode("bad sig") });
+        let ret = return_from_json(&v).unwrap();
+        assert!(!ret.result);
+        assert_eq!(ret.code, i32::from(r#return::ResponseCode::Sigerror));
+        assert_eq!(ret.message, b"bad sig");
+    }
+
+    #[test]
+    fn account_from_json_reads_stake_and_energy_usage() {
+        let v = json!({
+            "address": format!("41{}", "11".repeat(20)),
+            "balance": 5_000_000,
+            "assetV2": [{ "key": "1002000", "value": 42 }],
+            "frozenV2": [
+                { "amount": 3_000_000 },
+                { "type": "ENERGY", "amount": 7_000_000 },
+                { "type": "TRON_POWER" },
+            ],
+            "account_resource": {
+                "energy_usage": 1_234,
+                "latest_consume_time_for_energy": 1_700_000_000_000i64,
+                "energy_window_size": 28_800,
+                "delegated_frozenV2_balance_for_energy": 2_000_000,
+            },
+        });
+        let acct = account_from_json(&v).unwrap();
+        assert_eq!(acct.balance, 5_000_000);
+        assert_eq!(acct.asset_v2, HashMap::from([("1002000".to_owned(), 42)]));
+        assert!(acct.asset.is_empty());
+        assert_eq!(
+            acct.frozen_v2,
+            vec![
+                account::FreezeV2 {
+                    r#type: ResourceCode::Bandwidth.into(),
+                    amount: 3_000_000,
+                },
+                account::FreezeV2 {
+                    r#type: ResourceCode::Energy.into(),
+                    amount: 7_000_000,
+                },
+                account::FreezeV2 {
+                    r#type: ResourceCode::TronPower.into(),
+                    amount: 0,
+                },
+            ]
+        );
+        let resource = acct.account_resource.unwrap();
+        assert_eq!(resource.energy_usage, 1_234);
+        assert_eq!(resource.energy_window_size, 28_800);
+        assert_eq!(resource.delegated_frozen_v2_balance_for_energy, 2_000_000);
+    }
+
+    #[test]
+    fn i64_field_defaults_missing_and_accepts_strings() {
+        let v = json!({ "a": 5, "b": "6" });
+        assert_eq!(i64_field(&v, "a").unwrap(), 5);
+        assert_eq!(i64_field(&v, "b").unwrap(), 6);
+        assert_eq!(i64_field(&v, "missing").unwrap(), 0);
+    }
+}
@@ -1,25 +1,36 @@
 pub mod address;
+pub mod client;
 pub mod grpc;
+pub mod http;
+pub mod pool;
 pub mod proof;
 pub mod rental;
 pub mod resources;
 pub mod sender;
 pub mod tx;
+pub mod verify;
 pub mod wallet;
 
 pub use address::TronAddress;
+pub use client::{TronApi, TronClient};
 pub use grpc::{DEFAULT_API_KEY_HEADER, TronGrpc};
-pub use proof::{TronTxProofBuilder, TronTxProofBundle};
+pub use http::{HTTP_URL_PREFIX, TronHttp};
+pub use pool::{TronAttemptStatus, TronGrpcPool, TronGrpcPoolOptions, TronPoolObserver};
+pub use proof::{TronHeaderProfile, TronTxProofBuilder, TronTxProofBundle};
 pub use rental::{
-    JsonApiRentalProvider, JsonApiRentalProviderConfig, RentalAttempt, RentalContext,
-    RentalResourceKind,
+    EnergyLease, EnergyQuote, EnergySource, JsonApiQuoteConfig, JsonApiRentalProvider,
+    JsonApiRentalProviderConfig, JsonApiStatusConfig, RentalAttempt, RentalContext,
+    RentalOrderStatus, RentalPriceUnit, RentalResourceKind, StakedEnergyDelegator,
+    quote_energy_sources, rank_energy_sources,
 };
 pub use resources::{AccountResources, ChainFees, TxCostQuote};
-pub use sender::{FIXED_FEE_LIMIT_SUN, FeeLimitPolicy, SignedTronTx};
+pub use sender::{FIXED_FEE_LIMIT_SUN, FeeLimitPolicy, SignedTronTx, TronSystemContract};
 pub use tx::{
-    DecodedTrc20Call, DecodedTriggerSmartContract, SELECTOR_TRANSFER, SELECTOR_TRANSFER_FROM,
-    TRIGGER_SMART_CONTRACT_TYPE, decode_trc20_call_data, decode_trigger_smart_contract,
+    DecodedNativeTransfer, DecodedTrc20Call, DecodedTriggerSmartContract, SELECTOR_TRANSFER,
+    SELECTOR_TRANSFER_FROM, TRIGGER_SMART_CONTRACT_TYPE, decode_native_transfer,
+    decode_trc20_call_data, decode_trigger_smart_contract,
 };
+pub use verify::{TronSrSet, VerifiedTronTx, verify_bundle};
 pub use wallet::{BroadcastedTronTx, TronWallet};
 
 pub mod protocol {
@@ -0,0 +1,862 @@
+use super::client::{TronApi, TronClient};
+use anyhow::{Context, Result};
+use std::future::Future;
+use std::pin::{Pin, pin};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::task::JoinSet;
+
+/// Weight of the newest sample in an endpoint's latency EWMA.
+const LATENCY_EWMA_ALPHA: f64 = 0.3;
+/// Every failure adds 1 to an endpoint's error score; every success multiplies it by this.
+const ERROR_SCORE_DECAY: f64 = 0.5;
+/// Latency-equivalent cost of one unit of error score when ranking endpoints.
+const ERROR_PENALTY_MS: f64 = 2_000.0;
+/// Assumed latency of an endpoint that has not answered yet, so untried endpoints keep their
+/// configured order.
+const UNPROBED_LATENCY_MS: f64 = 250.0;
+/// The current endpoint keeps priority until another one scores this many times better, so
+/// near-equal nodes don't flap on every latency sample.
+const SWITCH_HYSTERESIS: f64 = 1.5;
+
+type OpFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TronAttemptStatus {
+    Ok,
+    Err,
+    Timeout,
+}
+
+/// Metrics hooks for `TronGrpcPool`, mirroring `untron_rpc_fallback::FallbackObserver`.
+pub trait TronPoolObserver: Send + Sync {
+    fn on_attempt(&self, op: &str, endpoint_idx: usize, status: TronAttemptStatus, ms: u64);
+    fn on_switch(&self, op: &str, from_idx: usize, to_idx: usize);
+    fn on_all_failed(&self, op: &str);
+    /// A read was duplicated onto `hedge_idx` because `primary_idx` had not answered in time.
+    fn on_hedge(&self, _op: &str, _primary_idx: usize, _hedge_idx: usize) {}
+    /// Blocks between this endpoint's head and the best head seen by the same probe round.
+    fn on_head_lag(&self, _endpoint_idx: usize, _lag_blocks: u64) {}
+}
+
+#[derive(Debug, Clone)]
+pub struct TronGrpcPoolOptions {
+    /// Upper bound on one read attempt against one endpoint. Writes (`with_failover`) are not
+    /// bounded, since they may include rental settlement waits.
+    pub per_try_timeout: Duration,
+    /// Start the same read on the next-best endpoint if the first has not answered by then.
+    /// `None` disables hedging.
+    pub hedge_after: Option<Duration>,
+    /// Endpoints more than this many blocks behind the best probed head are only tried after
+    /// every in-sync endpoint failed. 0 disables lag detection.
+    pub max_head_lag_blocks: u64,
+    /// How often every endpoint's head is re-probed. Probes run in the background, started
+    /// by the first call after the interval elapses. Zero disables probing.
+    pub head_probe_interval: Duration,
+}
+
+impl Default for TronGrpcPoolOptions {
+    fn default() -> Self {
+        Self {
+            per_try_timeout: Duration::from_secs(30),
+            hedge_after: Some(Duration::from_secs(2)),
+            // ~1 minute of Tron blocks.
+            max_head_lag_blocks: 20,
+            head_probe_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A set of Tron node endpoints (`TRON_GRPC_URLS`) used as one client.
+///
+/// Each call goes to the best-ranked endpoint and fails over down the ranking. Ranking uses
+/// a per-endpoint latency EWMA plus a decaying error score, keeps the last good endpoint
+/// sticky, and demotes endpoints whose head lags the best one (probed in the background every
+/// `head_probe_interval`). Reads are optionally hedged onto the runner-up endpoint.
+///
+/// Cheap to clone; clones share connections and health state.
+#[derive(Clone)]
+pub struct TronGrpcPool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    endpoints: Vec<Endpoint>,
+    api_key: Option<String>,
+    api_key_header: String,
+    options: TronGrpcPoolOptions,
+    observer: Option<Arc<dyn TronPoolObserver>>,
+    state: Mutex<PoolState>,
+    /// Held by the running background head probe, if any.
+    heads_probed_at: Arc<AsyncMutex<Option<Instant>>>,
+}
+
+struct Endpoint {
+    url: String,
+    /// Connected lazily; calls run on clones so concurrent reads don't serialize.
+    client: AsyncMutex<Option<TronClient>>,
+    /// Handed in already connected (`from_clients`); reused instead of re-dialling `url`.
+    pinned: bool,
+    /// Served over the `/wallet/*` HTTP API rather than gRPC.
+    http: bool,
+}
+
+struct PoolState {
+    health: Vec<EndpointHealth>,
+    /// Endpoint that served the last successful call.
+    preferred: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct EndpointHealth {
+    latency_ms: Option<f64>,
+    error_score: f64,
+    head: Option<i64>,
+    lagging: bool,
+    /// Set after a timeout so the next call starts from a fresh connection.
+    reconnect: bool,
+}
+
+impl EndpointHealth {
+    /// Lower is better.
+    fn score(&self) -> f64 {
+        self.latency_ms.unwrap_or(UNPROBED_LATENCY_MS) + self.error_score * ERROR_PENALTY_MS
+    }
+
+    fn record_latency(&mut self, ms: f64) {
+        self.latency_ms = Some(match self.latency_ms {
+            Some(prev) => prev + LATENCY_EWMA_ALPHA * (ms - prev),
+            None => ms,
+        });
+    }
+
+    fn record(&mut self, status: TronAttemptStatus, ms: f64) {
+        match status {
+            TronAttemptStatus::Ok => {
+                self.record_latency(ms);
+                self.error_score *= ERROR_SCORE_DECAY;
+            }
+            TronAttemptStatus::Err => self.error_score += 1.0,
+            TronAttemptStatus::Timeout => {
+                self.record_latency(ms);
+                self.error_score += 1.0;
+                self.reconnect = true;
+            }
+        }
+    }
+}
+
+impl TronGrpcPool {
+    /// Connects to the first reachable endpoint (failing if none is) and leaves the rest to
+    /// connect on first use.
+    pub async fn connect(
+        urls: Vec<String>,
+        api_key: Option<String>,
+        api_key_header: String,
+        options: TronGrpcPoolOptions,
+        observer: Option<Arc<dyn TronPoolObserver>>,
+    ) -> Result<Self> {
+        anyhow::ensure!(!urls.is_empty(), "no TRON_GRPC_URLS configured");
+
+        let endpoints = urls
+            .into_iter()
+            .map(|url| Endpoint {
+                http: url.trim().starts_with(crate::HTTP_URL_PREFIX),
+                url,
+                client: AsyncMutex::new(None),
+                pinned: false,
+            })
+            .collect();
+        let pool = Self::with_endpoints(endpoints, api_key, api_key_header, options, observer);
+
+        let mut last_err: Option<anyhow::Error> = None;
+        for idx in 0..pool.len() {
+            match pool.connected(idx, false).await {
+                Ok(_) => {
+                    pool.state().preferred = idx;
+                    if let Some(err) = last_err {
+                        tracing::info!(err = %err, "using fallback Tron gRPC endpoint");
+                    }
+                    return Ok(pool);
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        tron_grpc = %pool.url(idx),
+                        err = %err,
+                        "failed to connect Tron gRPC endpoint"
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        Err(last_err
+            .expect("urls is non-empty")
+            .context("connect TRON gRPC"))
+    }
+
+    /// A pool over clients that are already connected, labelled for logs and metrics. They
+    /// are never re-dialled, so this suits transports that reconnect on their own, such as the
+    /// in-process channels `tron-mock` hands out.
+    pub fn from_clients(
+        clients: Vec<(String, TronClient)>,
+        options: TronGrpcPoolOptions,
+        observer: Option<Arc<dyn TronPoolObserver>>,
+    ) -> Result<Self> {
+        anyhow::ensure!(!clients.is_empty(), "no Tron clients given");
+        let endpoints = clients
+            .into_iter()
+            .map(|(url, client)| Endpoint {
+                url,
+                http: matches!(client, TronClient::Http(_)),
+                client: AsyncMutex::new(Some(client)),
+                pinned: true,
+            })
+            .collect();
+        Ok(Self::with_endpoints(
+            endpoints,
+            None,
+            crate::DEFAULT_API_KEY_HEADER.to_string(),
+            options,
+            observer,
+        ))
+    }
+
+    fn with_endpoints(
+        endpoints: Vec<Endpoint>,
+        api_key: Option<String>,
+        api_key_header: String,
+        options: TronGrpcPoolOptions,
+        observer: Option<Arc<dyn TronPoolObserver>>,
+    ) -> Self {
+        let health = vec![EndpointHealth::default(); endpoints.len()];
+        Self {
+            inner: Arc::new(Inner {
+                endpoints,
+                api_key,
+                api_key_header,
+                options,
+                observer,
+                state: Mutex::new(PoolState {
+                    health,
+                    preferred: 0,
+                }),
+                heads_probed_at: Arc::new(AsyncMutex::new(None)),
+            }),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.endpoints.is_empty()
+    }
+
+    pub fn url(&self, idx: usize) -> &str {
+        &self.inner.endpoints[idx].url
+    }
+
+    /// Endpoint that served the last successful call.
+    pub fn preferred_index(&self) -> usize {
+        self.state().preferred
+    }
+
+    /// A connection to the preferred endpoint, for code that still drives a single client.
+    pub async fn client(&self) -> Result<TronClient> {
+        self.connected(self.preferred_index(), false).await
+    }
+
+    /// Runs a read on the best endpoint, hedged onto the runner-up after `hedge_after`, then
+    /// fails over through the remaining endpoints. Each attempt is bounded by
+    /// `per_try_timeout`, and `op` may run more than once (including concurrently), so it
+    /// must be side-effect free.
+    pub async fn with_read<T, F>(&self, op_name: &'static str, mut op: F) -> Result<T>
+    where
+        F: for<'a> FnMut(&'a mut TronClient) -> OpFuture<'a, T>,
+    {
+        self.refresh_heads_if_due();
+        let order = self.ranked();
+        let per_try_timeout = Some(self.inner.options.per_try_timeout);
+
+        let mut tried = 0;
+        let mut last_err = None;
+        if let (Some(hedge_after), [primary, hedge, ..]) =
+            (self.inner.options.hedge_after, order.as_slice())
+        {
+            // A lagging runner-up could win the race with stale data; only hedge in-sync.
+            if !self.is_lagging(*hedge) {
+                match self
+                    .hedged(op_name, *primary, *hedge, hedge_after, &mut op)
+                    .await
+                {
+                    Ok((v, idx)) => {
+                        self.mark_preferred(op_name, idx);
+                        return Ok(v);
+                    }
+                    Err((err, n)) => {
+                        tried = n;
+                        last_err = Some(err);
+                    }
+                }
+            }
+        }
+
+        self.failover(op_name, &order[tried..], per_try_timeout, &mut op, last_err)
+            .await
+    }
+
+    /// Runs `op` on one endpoint at a time in ranked order until it succeeds, without hedging
+    /// or a per-try timeout. For writes, where a duplicate in flight is not acceptable.
+    pub async fn with_failover<T, F>(&self, op_name: &'static str, mut op: F) -> Result<T>
+    where
+        F: for<'a> FnMut(&'a mut TronClient) -> OpFuture<'a, T>,
+    {
+        self.refresh_heads_if_due();
+        let order = self.ranked();
+        self.failover(op_name, &order, None, &mut op, None).await
+    }
+
+    /// Like `with_failover`, but only over gRPC endpoints: for ops that need
+    /// `get_block_by_num2_raw_txs`, such as building transaction proofs.
+    pub async fn with_grpc_failover<T, F>(&self, op_name: &'static str, mut op: F) -> Result<T>
+    where
+        F: for<'a> FnMut(&'a mut TronClient) -> OpFuture<'a, T>,
+    {
+        self.refresh_heads_if_due();
+        let order = self
+            .ranked()
+            .into_iter()
+            .filter(|&idx| !self.inner.endpoints[idx].http)
+            .collect::<Vec<_>>();
+        anyhow::ensure!(
+            !order.is_empty(),
+            "{op_name} needs a gRPC endpoint; TRON_GRPC_URLS only has HTTP ones"
+        );
+        self.failover(op_name, &order, None, &mut op, None).await
+    }
+
+    /// Like `with_failover`, but `op` gets its own clone of the endpoint's client, so the
+    /// future it returns may borrow from the caller (e.g. state it updates between steps).
+    pub async fn with_failover_scoped<'s, T, F>(
+        &self,
+        op_name: &'static str,
+        mut op: F,
+    ) -> Result<T>
+    where
+        F: FnMut(TronClient) -> OpFuture<'s, T>,
+    {
+        self.refresh_heads_if_due();
+        let order = self.ranked();
+
+        let mut last_err = None;
+        for (idx, force_reconnect) in self.attempt_plan(&order) {
+            let started = Instant::now();
+            let client = match self.connected(idx, force_reconnect).await {
+                Ok(client) => client,
+                Err(err) => {
+                    last_err = Some(self.fail(op_name, idx, TronAttemptStatus::Err, started, err));
+                    continue;
+                }
+            };
+            match self.observe(op_name, idx, started, None, op(client)).await {
+                Ok(v) => {
+                    self.mark_preferred(op_name, idx);
+                    return Ok(v);
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(self.all_failed(op_name, last_err))
+    }
+
+    /// Probes every endpoint's head block and demotes those more than `max_head_lag_blocks`
+    /// behind the best. Successful probes also decay error scores, which is how an endpoint
+    /// that failed earlier earns its way back up the ranking.
+    pub async fn refresh_heads(&self) {
+        let mut probes = JoinSet::new();
+        for idx in 0..self.len() {
+            let pool = self.clone();
+            probes.spawn(async move {
+                let timeout = Some(pool.inner.options.per_try_timeout);
+                let head = pool
+                    .attempt("get_now_block2", idx, false, timeout, &mut head_number)
+                    .await
+                    .ok();
+                (idx, head)
+            });
+        }
+
+        let mut heads = vec![None; self.len()];
+        while let Some(res) = probes.join_next().await {
+            if let Ok((idx, head)) = res {
+                heads[idx] = head;
+            }
+        }
+
+        let max_lag = self.inner.options.max_head_lag_blocks;
+        let lags = {
+            let mut state = self.state();
+            for (health, head) in state.health.iter_mut().zip(heads) {
+                health.head = head;
+            }
+            apply_heads(&mut state.health, max_lag)
+        };
+        for (idx, lag) in lags.into_iter().enumerate() {
+            let Some(lag) = lag else {
+                continue;
+            };
+            if let Some(observer) = &self.inner.observer {
+                observer.on_head_lag(idx, lag);
+            }
+            if max_lag > 0 && lag > max_lag {
+                tracing::warn!(
+                    tron_grpc = %self.url(idx),
+                    lag_blocks = lag,
+                    max_head_lag_blocks = max_lag,
+                    "Tron endpoint is behind the best head; deprioritizing"
+                );
+            }
+        }
+    }
+
+    /// Starts a background head probe if one is due. Calls never wait for it; they rank with
+    /// whatever the last finished probe found.
+    fn refresh_heads_if_due(&self) {
+        let interval = self.inner.options.head_probe_interval;
+        if self.len() < 2 || interval.is_zero() {
+            return;
+        }
+        // A probe is already running.
+        let Ok(mut probed_at) = self.inner.heads_probed_at.clone().try_lock_owned() else {
+            return;
+        };
+        if probed_at.is_some_and(|at| at.elapsed() < interval) {
+            return;
+        }
+        let pool = self.clone();
+        tokio::spawn(async move {
+            pool.refresh_heads().await;
+            *probed_at = Some(Instant::now());
+        });
+    }
+
+    async fn failover<T, F>(
+        &self,
+        op_name: &'static str,
+        order: &[usize],
+        per_try_timeout: Option<Duration>,
+        op: &mut F,
+        mut last_err: Option<anyhow::Error>,
+    ) -> Result<T>
+    where
+        F: for<'a> FnMut(&'a mut TronClient) -> OpFuture<'a, T>,
+    {
+        for (idx, force_reconnect) in self.attempt_plan(order) {
+            match self
+                .attempt(op_name, idx, force_reconnect, per_try_timeout, &mut *op)
+                .await
+            {
+                Ok(v) => {
+                    self.mark_preferred(op_name, idx);
+                    return Ok(v);
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(self.all_failed(op_name, last_err))
+    }
+
+    /// `(endpoint, force_reconnect)` pairs to try in turn. A lone endpoint gets a second try
+    /// on a fresh connection.
+    fn attempt_plan(&self, order: &[usize]) -> Vec<(usize, bool)> {
+        if self.len() == 1 {
+            vec![(0, false), (0, true)]
+        } else {
+            order.iter().map(|&idx| (idx, false)).collect()
+        }
+    }
+
+    fn all_failed(&self, op_name: &'static str, last_err: Option<anyhow::Error>) -> anyhow::Error {
+        if let Some(observer) = &self.inner.observer {
+            observer.on_all_failed(op_name);
+        }
+        last_err.unwrap_or_else(|| anyhow::anyhow!("all TRON_GRPC_URLS endpoints failed"))
+    }
+
+    /// Races `op` on `primary` against a copy started on `hedge` once `hedge_after` elapses.
+    /// On failure, also returns how many endpoints of the ranking were used up.
+    async fn hedged<T, F>(
+        &self,
+        op_name: &'static str,
+        primary: usize,
+        hedge: usize,
+        hedge_after: Duration,
+        op: &mut F,
+    ) -> std::result::Result<(T, usize), (anyhow::Error, usize)>
+    where
+        F: for<'a> FnMut(&'a mut TronClient) -> OpFuture<'a, T>,
+    {
+        let per_try_timeout = Some(self.inner.options.per_try_timeout);
+
+        let started = Instant::now();
+        let mut primary_client = match self.connected(primary, false).await {
+            Ok(client) => client,
+            Err(err) => {
+                let err = self.fail(op_name, primary, TronAttemptStatus::Err, started, err);
+                return Err((err, 1));
+            }
+        };
+        let mut primary_fut = pin!(self.observe(
+            op_name,
+            primary,
+            started,
+            per_try_timeout,
+            op(&mut primary_client)
+        ));
+
+        tokio::select! {
+            res = &mut primary_fut => return res.map(|v| (v, primary)).map_err(|e| (e, 1)),
+            () = tokio::time::sleep(hedge_after) => {}
+        }
+
+        if let Some(observer) = &self.inner.observer {
+            observer.on_hedge(op_name, primary, hedge);
+        }
+        tracing::debug!(
+            tron_grpc = %self.url(primary),
+            hedge_tron_grpc = %self.url(hedge),
+            op = op_name,
+            "hedging slow Tron read"
+        );
+
+        let hedge_started = Instant::now();
+        let mut hedge_client = match self.connected(hedge, false).await {
+            Ok(client) => client,
+            Err(err) => {
+                self.fail(op_name, hedge, TronAttemptStatus::Err, hedge_started, err);
+                return primary_fut.await.map(|v| (v, primary)).map_err(|e| (e, 2));
+            }
+        };
+        let mut hedge_fut = pin!(self.observe(
+            op_name,
+            hedge,
+            hedge_started,
+            per_try_timeout,
+            op(&mut hedge_client)
+        ));
+
+        tokio::select! {
+            res = &mut primary_fut => match res {
+                Ok(v) => {
+                    self.note_latency(hedge, hedge_started);
+                    Ok((v, primary))
+                }
+                Err(_) => hedge_fut.await.map(|v| (v, hedge)).map_err(|e| (e, 2)),
+            },
+            res = &mut hedge_fut => match res {
+                Ok(v) => {
+                    self.note_latency(primary, started);
+                    Ok((v, hedge))
+                }
+                Err(_) => primary_fut.await.map(|v| (v, primary)).map_err(|e| (e, 2)),
+            },
+        }
+    }
+
+    async fn attempt<T, F>(
+        &self,
+        op_name: &'static str,
+        idx: usize,
+        force_reconnect: bool,
+        per_try_timeout: Option<Duration>,
+        op: &mut F,
+    ) -> Result<T>
+    where
+        F: for<'a> FnMut(&'a mut TronClient) -> OpFuture<'a, T>,
+    {
+        let started = Instant::now();
+        let mut client = match self.connected(idx, force_reconnect).await {
+            Ok(client) => client,
+            Err(err) => return Err(self.fail(op_name, idx, TronAttemptStatus::Err, started, err)),
+        };
+        self.observe(op_name, idx, started, per_try_timeout, op(&mut client))
+            .await
+    }
+
+    /// Awaits one attempt's future and records its outcome.
+    async fn observe<T>(
+        &self,
+        op_name: &'static str,
+        idx: usize,
+        started: Instant,
+        per_try_timeout: Option<Duration>,
+        fut: OpFuture<'_, T>,
+    ) -> Result<T> {
+        let res = match per_try_timeout {
+            Some(limit) => match tokio::time::timeout(limit, fut).await {
+                Ok(res) => res,
+                Err(_) => {
+                    let err = anyhow::anyhow!("timed out after {}ms", limit.as_millis());
+                    return Err(self.fail(op_name, idx, TronAttemptStatus::Timeout, started, err));
+                }
+            },
+            None => fut.await,
+        };
+        match res {
+            Ok(v) => {
+                self.record(op_name, idx, TronAttemptStatus::Ok, started);
+                Ok(v)
+            }
+            Err(err) => Err(self.fail(op_name, idx, TronAttemptStatus::Err, started, err)),
+        }
+    }
+
+    /// A clone of endpoint `idx`'s connection, connecting first if needed.
+    async fn connected(&self, idx: usize, force_reconnect: bool) -> Result<TronClient> {
+        let endpoint = &self.inner.endpoints[idx];
+        let mut client = endpoint.client.lock().await;
+        let stale = std::mem::take(&mut self.state().health[idx].reconnect);
+        if let Some(c) = client
+            .as_ref()
+            .filter(|_| endpoint.pinned || (!force_reconnect && !stale))
+        {
+            return Ok(c.clone());
+        }
+
+        let limit = self.inner.options.per_try_timeout;
+        let connect = TronClient::connect(
+            &endpoint.url,
+            self.inner.api_key.as_deref(),
+            &self.inner.api_key_header,
+        );
+        let fresh = tokio::time::timeout(limit, connect)
+            .await
+            .map_err(|_| anyhow::anyhow!("timed out after {}ms", limit.as_millis()))
+            .and_then(|res| res)
+            .with_context(|| format!("connect TRON gRPC: {}", endpoint.url))?;
+        *client = Some(fresh.clone());
+        if force_reconnect || stale {
+            tracing::info!(tron_grpc = %endpoint.url, "reconnected Tron gRPC endpoint");
+        }
+        Ok(fresh)
+    }
+
+    fn fail(
+        &self,
+        op_name: &'static str,
+        idx: usize,
+        status: TronAttemptStatus,
+        started: Instant,
+        err: anyhow::Error,
+    ) -> anyhow::Error {
+        self.record(op_name, idx, status, started);
+        tracing::warn!(
+            tron_grpc = %self.url(idx),
+            op = op_name,
+            err = %err,
+            "tron operation failed; trying next endpoint"
+        );
+        err
+    }
+
+    fn record(&self, op_name: &str, idx: usize, status: TronAttemptStatus, started: Instant) {
+        let ms = started.elapsed().as_millis() as u64;
+        self.state().health[idx].record(status, ms as f64);
+        if let Some(observer) = &self.inner.observer {
+            observer.on_attempt(op_name, idx, status, ms);
+        }
+    }
+
+    /// Counts the elapsed time of an attempt that lost a hedge race: it was at least that slow.
+    fn note_latency(&self, idx: usize, started: Instant) {
+        let ms = started.elapsed().as_millis() as f64;
+        self.state().health[idx].record_latency(ms);
+    }
+
+    fn mark_preferred(&self, op_name: &str, idx: usize) {
+        let prev = std::mem::replace(&mut self.state().preferred, idx);
+        if prev == idx {
+            return;
+        }
+        if let Some(observer) = &self.inner.observer {
+            observer.on_switch(op_name, prev, idx);
+        }
+        tracing::info!(
+            from = %self.url(prev),
+            tron_grpc = %self.url(idx),
+            op = op_name,
+            "switched Tron gRPC endpoint"
+        );
+    }
+
+    fn is_lagging(&self, idx: usize) -> bool {
+        self.state().health[idx].lagging
+    }
+
+    fn ranked(&self) -> Vec<usize> {
+        let state = self.state();
+        rank(&state.health, state.preferred)
+    }
+
+    fn state(&self) -> MutexGuard<'_, PoolState> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn head_number(tron: &mut TronClient) -> OpFuture<'_, i64> {
+    Box::pin(async move {
+        let block = tron.get_now_block2().await?;
+        let raw = block
+            .block_header
+            .and_then(|h| h.raw_data)
+            .context("missing block_header.raw_data")?;
+        Ok(raw.number)
+    })
+}
+
+/// Endpoint indices, best first: in-sync before lagging, then by score, then by configured
+/// order. `preferred` stays first unless it lags or another endpoint beats it by more than
+/// `SWITCH_HYSTERESIS`.
+fn rank(health: &[EndpointHealth], preferred: usize) -> Vec<usize> {
+    let mut order = (0..health.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        health[a]
+            .lagging
+            .cmp(&health[b].lagging)
+            .then(health[a].score().total_cmp(&health[b].score()))
+            .then(a.cmp(&b))
+    });
+
+    if let Some(pos) = order.iter().position(|&idx| idx == preferred) {
+        let current = &health[preferred];
+        let best = &health[order[0]];
+        if pos > 0 && !current.lagging && current.score() <= best.score() * SWITCH_HYSTERESIS {
+            order.remove(pos);
+            order.insert(0, preferred);
+        }
+    }
+    order
+}
+
+/// Marks endpoints more than `max_lag` blocks behind the best known head as lagging (never
+/// when `max_lag` is 0) and returns each endpoint's lag, if its head is known.
+fn apply_heads(health: &mut [EndpointHealth], max_lag: u64) -> Vec<Option<u64>> {
+    let best = health.iter().filter_map(|h| h.head).max();
+    health
+        .iter_mut()
+        .map(|h| {
+            let lag = best
+                .zip(h.head)
+                .map(|(best, head)| u64::try_from(best - head).unwrap_or(0));
+            h.lagging = max_lag > 0 && lag.is_some_and(|lag| lag > max_lag);
+            lag
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(latency_ms: f64, error_score: f64) -> EndpointHealth {
+        EndpointHealth {
+            latency_ms: Some(latency_ms),
+            error_score,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn untried_endpoints_keep_configured_order() {
+        let h = vec![EndpointHealth::default(); 3];
+        assert_eq!(rank(&h, 0), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn rank_prefers_fast_healthy_endpoints() {
+        let h = vec![health(400.0, 0.0), health(50.0, 1.0), health(80.0, 0.0)];
+        // Endpoint 1 is fastest but its error score costs ERROR_PENALTY_MS.
+        assert_eq!(rank(&h, 2), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn preferred_endpoint_is_sticky_within_hysteresis() {
+        let h = vec![health(100.0, 0.0), health(120.0, 0.0)];
+        assert_eq!(rank(&h, 1), vec![1, 0]);
+
+        let h = vec![health(100.0, 0.0), health(200.0, 0.0)];
+        assert_eq!(rank(&h, 1), vec![0, 1]);
+    }
+
+    #[test]
+    fn lagging_endpoints_rank_last_even_if_preferred() {
+        let mut h = vec![health(10.0, 0.0), health(300.0, 0.0), health(200.0, 0.0)];
+        h[0].head = Some(1_000);
+        h[1].head = Some(1_030);
+        h[2].head = Some(1_029);
+
+        let lags = apply_heads(&mut h, 20);
+        assert_eq!(lags, vec![Some(30), Some(0), Some(1)]);
+        assert!(h[0].lagging && !h[1].lagging && !h[2].lagging);
+        assert_eq!(rank(&h, 0), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn lag_detection_can_be_disabled() {
+        let mut h = vec![EndpointHealth::default(); 2];
+        h[0].head = Some(1);
+        h[1].head = Some(1_000);
+        apply_heads(&mut h, 0);
+        assert!(h.iter().all(|h| !h.lagging));
+    }
+
+    #[test]
+    fn failures_decay_on_success_and_timeouts_force_reconnect() {
+        let mut h = EndpointHealth::default();
+        h.record(TronAttemptStatus::Timeout, 1_000.0);
+        assert!(h.reconnect);
+        assert_eq!(h.error_score, 1.0);
+        assert_eq!(h.latency_ms, Some(1_000.0));
+
+        h.record(TronAttemptStatus::Ok, 100.0);
+        assert_eq!(h.error_score, 0.5);
+        assert_eq!(
+            h.latency_ms,
+            Some(1_000.0 + LATENCY_EWMA_ALPHA * (100.0 - 1_000.0))
+        );
+
+        h.record(TronAttemptStatus::Err, 5.0);
+        assert_eq!(h.error_score, 1.5);
+    }
+
+    #[tokio::test]
+    async fn grpc_failover_skips_http_endpoints() {
+        let http =
+            crate::TronHttp::new("https://api.trongrid.io", None, "tron-pro-api-key").unwrap();
+        let pool = TronGrpcPool::from_clients(
+            vec![(
+                "rest+https://api.trongrid.io".into(),
+                TronClient::Http(http),
+            )],
+            TronGrpcPoolOptions::default(),
+            None,
+        )
+        .unwrap();
+
+        let mut calls = 0;
+        let err = pool
+            .with_grpc_failover("build_proof", |_| {
+                calls += 1;
+                Box::pin(async { Ok(()) })
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("needs a gRPC endpoint"), "{err}");
+        assert_eq!(calls, 0);
+    }
+}
@@ -1,21 +1,156 @@
-use super::grpc::TronGrpc;
+use super::client::TronApi;
 use super::protocol::{BlockExtention, BlockHeader};
 use alloy::primitives::{FixedBytes, U256};
 use anyhow::{Context, Result};
 use prost::Message;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
 
 pub struct TronTxProofBundle {
-    pub blocks: [Vec<u8>; 20],
+    /// Encoded headers: the tx block followed by `finality_blocks` blocks on top of it.
+    pub blocks: Vec<Vec<u8>>,
     pub encoded_tx: Vec<u8>,
     pub proof: Vec<FixedBytes<32>>,
     pub index: U256,
 }
 
+/// Varint widths a `StatefulTronTxReader` deployment hardcodes when it reads `BlockHeader`
+/// fields at fixed offsets.
+///
+/// Mainnet headers currently encode a 6-byte timestamp (ms), a 4-byte block number and a 1-byte
+/// version. Testnets and private java-tron networks can differ (e.g. a young chain's block
+/// numbers fit in fewer bytes) and need a reader deployment built for that layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TronHeaderProfile {
+    pub timestamp_varint_len: usize,
+    pub number_varint_len: usize,
+    pub version_varint_len: usize,
+}
+
+impl TronHeaderProfile {
+    pub const MAINNET: TronHeaderProfile = TronHeaderProfile {
+        timestamp_varint_len: 6,
+        number_varint_len: 4,
+        version_varint_len: 1,
+    };
+
+    /// Length of the encoded `BlockHeader.raw_data` message (105 on mainnet).
+    pub fn raw_data_len(&self) -> usize {
+        // timestamp tag, txTrieRoot and parentHash (tag + len + 32 each), number tag,
+        // witness_address (tag + len + 21), version tag.
+        1 + self.timestamp_varint_len
+            + 34
+            + 34
+            + 1
+            + self.number_varint_len
+            + 23
+            + 1
+            + self.version_varint_len
+    }
+
+    /// Length of the full encoded header, including signature framing (174 on mainnet).
+    pub fn encoded_len(&self) -> usize {
+        2 + self.raw_data_len() + 2 + 65
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for (name, len) in [
+            ("timestamp", self.timestamp_varint_len),
+            ("number", self.number_varint_len),
+            ("version", self.version_varint_len),
+        ] {
+            if !(1..=10).contains(&len) {
+                anyhow::bail!("invalid header profile: {name} varint length {len} not in 1..=10");
+            }
+        }
+        Ok(())
+    }
+
+    /// The profile `h` encodes to as-is. Useful to check a network's current headers against
+    /// the profile a reader was deployed with.
+    pub fn of_header(h: &BlockHeader) -> Result<Self> {
+        let raw = h
+            .raw_data
+            .as_ref()
+            .context("missing block_header.raw_data")?;
+        Ok(Self {
+            timestamp_varint_len: varint_len(
+                u64::try_from(raw.timestamp).context("header timestamp out of range")?,
+            ),
+            number_varint_len: varint_len(
+                u64::try_from(raw.number).context("header number out of range")?,
+            ),
+            version_varint_len: varint_len(
+                u64::try_from(raw.version).context("header version out of range")?,
+            ),
+        })
+    }
+}
+
+impl Default for TronHeaderProfile {
+    fn default() -> Self {
+        Self::MAINNET
+    }
+}
+
+impl fmt::Display for TronHeaderProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timestamp={},number={},version={}",
+            self.timestamp_varint_len, self.number_varint_len, self.version_varint_len
+        )
+    }
+}
+
+impl FromStr for TronHeaderProfile {
+    type Err = anyhow::Error;
+
+    /// Accepts `mainnet` or `timestamp=N,number=N,version=N`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if trimmed.eq_ignore_ascii_case("mainnet") {
+            return Ok(Self::MAINNET);
+        }
+
+        let (mut ts, mut number, mut version) = (None, None, None);
+        for part in trimmed.split(',') {
+            let (k, v) = part
+                .split_once('=')
+                .with_context(|| format!("invalid header profile entry: {part}"))?;
+            let v: usize = v
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid header profile value: {part}"))?;
+            match k.trim() {
+                "timestamp" => ts = Some(v),
+                "number" => number = Some(v),
+                "version" => version = Some(v),
+                other => anyhow::bail!("unknown header profile key: {other}"),
+            }
+        }
+
+        let profile = Self {
+            timestamp_varint_len: ts.context("header profile missing timestamp")?,
+            number_varint_len: number.context("header profile missing number")?,
+            version_varint_len: version.context("header profile missing version")?,
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+}
+
 pub struct TronTxProofBuilder {
-    /// Must be 19 for the hub's `bytes[20]` proof format.
+    /// Number of blocks on top of the tx block the target reader requires. The hub's mainnet
+    /// `bytes[20]` proof format needs 19.
     pub finality_blocks: u64,
+    /// Header layout the target reader was built for.
+    pub header_profile: TronHeaderProfile,
+    header_cache: Mutex<HeaderCache>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -48,18 +183,106 @@ struct TxTrieRootMismatchDump {
 }
 
 impl TronTxProofBuilder {
+    /// Headers kept across `build`/`build_many` calls by default: enough for a dozen
+    /// non-overlapping 20-block windows.
+    pub const DEFAULT_HEADER_CACHE_CAPACITY: usize = 256;
+
     pub fn new(finality_blocks: u64) -> Self {
-        Self { finality_blocks }
+        Self {
+            finality_blocks,
+            header_profile: TronHeaderProfile::MAINNET,
+            header_cache: Mutex::new(HeaderCache::new(Self::DEFAULT_HEADER_CACHE_CAPACITY)),
+        }
+    }
+
+    /// Caps the cross-call header cache at `capacity` headers; 0 disables it.
+    pub fn with_header_cache_capacity(self, capacity: usize) -> Self {
+        Self {
+            header_cache: Mutex::new(HeaderCache::new(capacity)),
+            ..self
+        }
+    }
+
+    pub fn with_header_profile(mut self, header_profile: TronHeaderProfile) -> Self {
+        self.header_profile = header_profile;
+        self
+    }
+
+    /// Number of encoded headers in every bundle (`finality_blocks + 1`).
+    pub fn block_count(&self) -> Result<usize> {
+        self.finality_blocks
+            .checked_add(1)
+            .and_then(|n| usize::try_from(n).ok())
+            .context("Tron finality_blocks out of range")
+    }
+
+    /// Encodes `header` the way this builder's bundles carry it, e.g. to probe a deployed
+    /// reader with a real header before relying on the configured profile.
+    pub fn encode_header(&self, header: &BlockHeader) -> Result<Vec<u8>> {
+        encode_block_header_with_profile(header, self.header_profile)
     }
 
-    pub async fn build(&self, grpc: &mut TronGrpc, txid: [u8; 32]) -> Result<TronTxProofBundle> {
-        if self.finality_blocks != 19 {
+    /// Fails unless this builder's bundles fill a reader's `bytes[reader_block_count] blocks`.
+    ///
+    /// The header length can't be checked this way: readers don't expose it, so callers probe
+    /// the reader with an encoded header instead (see [`Self::encode_header`]).
+    pub fn ensure_reader_block_count(&self, reader_block_count: usize) -> Result<()> {
+        let block_count = self.block_count()?;
+        if block_count != reader_block_count {
             anyhow::bail!(
-                "unsupported Tron finality_blocks: expected 19, got {}",
-                self.finality_blocks
+                "Tron finality_blocks={} yields {} headers, but the reader expects {}",
+                self.finality_blocks,
+                block_count,
+                reader_block_count
             );
         }
+        Ok(())
+    }
+
+    pub async fn build(
+        &self,
+        grpc: &mut impl TronApi,
+        txid: [u8; 32],
+    ) -> Result<TronTxProofBundle> {
+        self.build_many(grpc, &[txid])
+            .await?
+            .pop()
+            .context("build_many returned no result")?
+    }
+
+    /// Builds proofs for `txids` in one pass, returning one result per txid in input order.
+    ///
+    /// Tx blocks and headers fetched for one txid are reused by the others, so a burst of txs
+    /// from the same or adjacent blocks costs one fetch per distinct block. Headers already
+    /// `finality_blocks` deep are also kept in the builder's bounded cache across calls.
+    pub async fn build_many(
+        &self,
+        grpc: &mut impl TronApi,
+        txids: &[[u8; 32]],
+    ) -> Result<Vec<Result<TronTxProofBundle>>> {
+        self.header_profile.validate()?;
+        let block_count = self.block_count()?;
+        let head = tron_head_block(grpc).await?;
+
+        let mut batch = BatchBlocks::default();
+        let mut out = Vec::with_capacity(txids.len());
+        for &txid in txids {
+            out.push(
+                self.build_in_batch(grpc, txid, head, block_count, &mut batch)
+                    .await,
+            );
+        }
+        Ok(out)
+    }
 
+    async fn build_in_batch(
+        &self,
+        grpc: &mut impl TronApi,
+        txid: [u8; 32],
+        head: u64,
+        block_count: usize,
+        batch: &mut BatchBlocks,
+    ) -> Result<TronTxProofBundle> {
         let tx_info = grpc
             .get_transaction_info_by_id(txid)
             .await
@@ -67,7 +290,6 @@ impl TronTxProofBuilder {
         let tron_block_number =
             u64::try_from(tx_info.block_number).context("Tron tx blockNumber out of range")?;
 
-        let head = tron_head_block(grpc).await?;
         if head < tron_block_number + self.finality_blocks {
             anyhow::bail!(
                 "tx not finalized: head={}, tx_block={}, need >= {}",
@@ -78,10 +300,19 @@ impl TronTxProofBuilder {
         }
 
         // Fetch the tx block (with tx list) first, preserving canonical tx bytes for txTrieRoot.
-        let (tx_block, tx_bytes) = grpc
-            .get_block_by_num2_raw_txs(i64::try_from(tron_block_number)?)
-            .await
-            .context("get tx block (raw tx bytes)")?;
+        if !batch.tx_blocks.contains_key(&tron_block_number) {
+            let (tx_block, tx_bytes) = grpc
+                .get_block_by_num2_raw_txs(i64::try_from(tron_block_number)?)
+                .await
+                .context("get tx block (raw tx bytes)")?;
+            if let Some(h) = &tx_block.block_header {
+                self.remember_header(batch, tron_block_number, h, head);
+            }
+            batch
+                .tx_blocks
+                .insert(tron_block_number, (tx_block, tx_bytes));
+        }
+        let (tx_block, tx_bytes) = &batch.tx_blocks[&tron_block_number];
 
         let header_raw = tx_block
             .block_header
@@ -90,7 +321,7 @@ impl TronTxProofBuilder {
             .context("missing block_header.raw_data")?;
         let header_tx_trie_root = header_raw.tx_trie_root.clone();
 
-        let details = compute_proof_from_block_ext(&tx_block, &tx_bytes, tron_block_number, txid)
+        let details = compute_proof_from_block_ext(tx_block, tx_bytes, tron_block_number, txid)
             .context("tx proof")?;
 
         if details.computed_root.as_slice() != header_tx_trie_root.as_slice() {
@@ -101,16 +332,19 @@ impl TronTxProofBuilder {
             );
         }
 
-        // Fetch 19 blocks after, for the hub's stateful Tron reader.
-        let mut blocks: [Vec<u8>; 20] = std::array::from_fn(|_| Vec::new());
-        blocks[0] = encode_block_header(&tx_block.block_header)?;
-        for (i, block) in blocks.iter_mut().enumerate().skip(1) {
+        // Fetch `finality_blocks` blocks after, for the hub's stateful Tron reader.
+        let mut blocks = Vec::with_capacity(block_count);
+        blocks.push(encode_block_header(
+            &tx_block.block_header,
+            self.header_profile,
+        )?);
+        for i in 1..block_count {
             let num = tron_block_number + (i as u64);
-            let b = grpc
-                .get_block_by_num2(i64::try_from(num)?)
-                .await
-                .with_context(|| format!("get block {num}"))?;
-            *block = encode_block_header(&b.block_header)?;
+            let header = self.header(grpc, batch, num, head).await?;
+            blocks.push(
+                encode_block_header_with_profile(&header, self.header_profile)
+                    .with_context(|| format!("encode block {num}"))?,
+            );
         }
 
         Ok(TronTxProofBundle {
@@ -120,6 +354,85 @@ impl TronTxProofBuilder {
             index: details.index_bits,
         })
     }
+
+    async fn header(
+        &self,
+        grpc: &mut impl TronApi,
+        batch: &mut BatchBlocks,
+        num: u64,
+        head: u64,
+    ) -> Result<BlockHeader> {
+        if let Some(h) = batch.headers.get(&num) {
+            return Ok(h.clone());
+        }
+        if let Some(h) = self.lock_header_cache().get(num) {
+            batch.headers.insert(num, h.clone());
+            return Ok(h);
+        }
+
+        let b = grpc
+            .get_block_by_num2(i64::try_from(num)?)
+            .await
+            .with_context(|| format!("get block {num}"))?;
+        let h = b
+            .block_header
+            .with_context(|| format!("missing block_header for block {num}"))?;
+        self.remember_header(batch, num, &h, head);
+        Ok(h)
+    }
+
+    fn remember_header(&self, batch: &mut BatchBlocks, num: u64, h: &BlockHeader, head: u64) {
+        batch.headers.insert(num, h.clone());
+        // Only headers that were already final when fetched outlive the batch; anything
+        // shallower could still be reorged away.
+        if num.saturating_add(self.finality_blocks) <= head {
+            self.lock_header_cache().insert(num, h.clone());
+        }
+    }
+
+    fn lock_header_cache(&self) -> std::sync::MutexGuard<'_, HeaderCache> {
+        // The cache holds plain data; a panic mid-insert can't leave it inconsistent.
+        self.header_cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Blocks fetched during one `build_many` pass.
+#[derive(Default)]
+struct BatchBlocks {
+    tx_blocks: HashMap<u64, (BlockExtention, Vec<Vec<u8>>)>,
+    headers: HashMap<u64, BlockHeader>,
+}
+
+/// Finalized block headers keyed by block number, evicting the lowest numbers first once
+/// `capacity` is exceeded (the relayer proves txs in roughly increasing block order).
+struct HeaderCache {
+    capacity: usize,
+    headers: BTreeMap<u64, BlockHeader>,
+}
+
+impl HeaderCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            headers: BTreeMap::new(),
+        }
+    }
+
+    fn get(&self, num: u64) -> Option<BlockHeader> {
+        self.headers.get(&num).cloned()
+    }
+
+    fn insert(&mut self, num: u64, h: BlockHeader) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.headers.insert(num, h);
+        while self.headers.len() > self.capacity {
+            self.headers.pop_first();
+        }
+    }
 }
 
 struct ProofDetails {
@@ -245,7 +558,7 @@ fn compute_proof_from_block_ext(
     })
 }
 
-async fn tron_head_block(grpc: &mut TronGrpc) -> Result<u64> {
+async fn tron_head_block(grpc: &mut impl TronApi) -> Result<u64> {
     let b = grpc.get_now_block2().await.context("get now block")?;
     let raw = b
         .block_header
@@ -255,19 +568,19 @@ async fn tron_head_block(grpc: &mut TronGrpc) -> Result<u64> {
     u64::try_from(raw.number).context("now block number out of range")
 }
 
-fn encode_block_header(h: &Option<BlockHeader>) -> Result<Vec<u8>> {
+fn encode_block_header(h: &Option<BlockHeader>, profile: TronHeaderProfile) -> Result<Vec<u8>> {
     let h = h.as_ref().context("missing block_header")?;
-    encode_block_header_stateful(h)
+    encode_block_header_with_profile(h, profile)
 }
 
-fn sha256_bytes32(bytes: &[u8]) -> FixedBytes<32> {
+pub(crate) fn sha256_bytes32(bytes: &[u8]) -> FixedBytes<32> {
     let digest = Sha256::digest(bytes);
     FixedBytes::from_slice(&digest)
 }
 
 /// Encode a Tron `BlockHeader` in the exact fixed format expected by `StatefulTronTxReader`.
 ///
-/// The onchain verifier expects:
+/// The mainnet verifier expects:
 /// - total length 174 bytes,
 /// - `raw_data` length 105 bytes (0x69),
 /// - `witness_signature` length 65 bytes (0x41),
@@ -275,9 +588,13 @@ fn sha256_bytes32(bytes: &[u8]) -> FixedBytes<32> {
 ///   - timestamp (ms): 6 bytes varint
 ///   - block number: 4 bytes varint
 ///
-/// If any of these assumptions are violated (future-proofing / different Tron network),
-/// we fail fast so the relayer doesn't submit transactions that are guaranteed to revert.
-fn encode_block_header_stateful(h: &BlockHeader) -> Result<Vec<u8>> {
+/// Other reader deployments may hardcode different varint lengths; `profile` describes them.
+/// If a header doesn't fit the profile (future-proofing / different Tron network), we fail fast
+/// so the relayer doesn't submit transactions that are guaranteed to revert.
+pub(crate) fn encode_block_header_with_profile(
+    h: &BlockHeader,
+    profile: TronHeaderProfile,
+) -> Result<Vec<u8>> {
     let raw = h
         .raw_data
         .as_ref()
@@ -306,12 +623,16 @@ fn encode_block_header_stateful(h: &BlockHeader) -> Result<Vec<u8>> {
         anyhow::bail!("unexpected witness_signature length: {}", sig.len());
     }
 
-    // Build the raw_data message (105 bytes).
-    let ts_var = encode_varint_fixed(ts_ms, 6)?;
-    let num_var = encode_varint_fixed(number, 4)?;
-    let ver_var = encode_varint_fixed(version, 1)?;
+    // Build the raw_data message (105 bytes on mainnet).
+    let ts_var = encode_varint_fixed(ts_ms, profile.timestamp_varint_len)
+        .context("header timestamp does not fit profile")?;
+    let num_var = encode_varint_fixed(number, profile.number_varint_len)
+        .context("header number does not fit profile")?;
+    let ver_var = encode_varint_fixed(version, profile.version_varint_len)
+        .context("header version does not fit profile")?;
 
-    let mut raw_data = Vec::with_capacity(105);
+    let raw_data_len = profile.raw_data_len();
+    let mut raw_data = Vec::with_capacity(raw_data_len);
     raw_data.push(0x08); // field 1 (timestamp), wire 0
     raw_data.extend_from_slice(&ts_var);
     raw_data.push(0x12); // field 2 (txTrieRoot), wire 2
@@ -328,25 +649,35 @@ fn encode_block_header_stateful(h: &BlockHeader) -> Result<Vec<u8>> {
     raw_data.push(0x50); // field 10 (version), wire 0
     raw_data.extend_from_slice(&ver_var);
 
-    if raw_data.len() != 105 {
+    if raw_data.len() != raw_data_len {
         anyhow::bail!("unexpected raw_data length: {}", raw_data.len());
     }
 
-    // Wrap in BlockHeader framing.
-    let mut out = Vec::with_capacity(174);
+    // Wrap in BlockHeader framing. Every valid profile keeps raw_data under 128 bytes, so its
+    // length prefix is a single byte.
+    let mut out = Vec::with_capacity(profile.encoded_len());
     out.push(0x0a); // field 1 (raw_data), wire 2
-    out.push(0x69); // length 105
+    out.push(u8::try_from(raw_data_len).context("raw_data too long for profile")?);
     out.extend_from_slice(&raw_data);
     out.push(0x12); // field 2 (witness_signature), wire 2
     out.push(0x41); // length 65
     out.extend_from_slice(sig);
 
-    if out.len() != 174 {
+    if out.len() != profile.encoded_len() {
         anyhow::bail!("unexpected encoded header length: {}", out.len());
     }
     Ok(out)
 }
 
+fn varint_len(mut v: u64) -> usize {
+    let mut len = 1;
+    while v >= 0x80 {
+        v >>= 7;
+        len += 1;
+    }
+    len
+}
+
 fn encode_varint_fixed(mut v: u64, expected_len: usize) -> Result<Vec<u8>> {
     let mut out = Vec::new();
     while v >= 0x80 {
@@ -421,7 +752,7 @@ fn merkle_proof_sha256(
     Ok((proof, index_bits, level[0]))
 }
 
-fn sha256_concat(a: FixedBytes<32>, b: FixedBytes<32>) -> FixedBytes<32> {
+pub(crate) fn sha256_concat(a: FixedBytes<32>, b: FixedBytes<32>) -> FixedBytes<32> {
     let mut hasher = Sha256::new();
     hasher.update(a.as_slice());
     hasher.update(b.as_slice());
@@ -459,6 +790,10 @@ mod tests {
     use serde::Deserialize;
     use std::path::PathBuf;
 
+    fn encode_block_header_stateful(h: &BlockHeader) -> Result<Vec<u8>> {
+        encode_block_header_with_profile(h, TronHeaderProfile::MAINNET)
+    }
+
     fn workspace_path(rel: &str) -> PathBuf {
         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(rel)
     }
@@ -633,6 +968,101 @@ mod tests {
         assert_eq!(&out[109..], vec![0x44u8; 65].as_slice());
     }
 
+    #[test]
+    fn encode_block_header_with_profile_follows_varint_widths() {
+        let mut witness_address = vec![0x33u8; 21];
+        witness_address[0] = TronAddress::MAINNET_PREFIX;
+        let header = BlockHeader {
+            raw_data: Some(crate::protocol::block_header::Raw {
+                timestamp: 1_700_000_000_000,
+                tx_trie_root: vec![1u8; 32],
+                parent_hash: vec![2u8; 32],
+                // A young private net: block numbers still fit in 2 varint bytes.
+                number: 5_000,
+                witness_address,
+                version: 30,
+                ..Default::default()
+            }),
+            witness_signature: vec![0x44u8; 65],
+        };
+
+        let err = encode_block_header_with_profile(&header, TronHeaderProfile::MAINNET)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("number does not fit profile"), "{err}");
+
+        let profile = TronHeaderProfile::of_header(&header).unwrap();
+        assert_eq!(profile.number_varint_len, 2);
+        let out = encode_block_header_with_profile(&header, profile).unwrap();
+        assert_eq!(out.len(), profile.encoded_len());
+        assert_eq!(out.len(), 172);
+        assert_eq!(usize::from(out[1]), profile.raw_data_len());
+
+        let raw = crate::protocol::block_header::Raw::decode(&out[2..2 + profile.raw_data_len()])
+            .unwrap();
+        assert_eq!(Some(raw), header.raw_data);
+    }
+
+    #[test]
+    fn header_cache_evicts_lowest_block_numbers() {
+        let header = |n: i64| BlockHeader {
+            raw_data: Some(crate::protocol::block_header::Raw {
+                number: n,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let mut cache = HeaderCache::new(2);
+        cache.insert(11, header(11));
+        cache.insert(10, header(10));
+        cache.insert(12, header(12));
+        assert!(cache.get(10).is_none());
+        assert_eq!(cache.get(11).unwrap().raw_data.unwrap().number, 11);
+        assert_eq!(cache.get(12).unwrap().raw_data.unwrap().number, 12);
+
+        let mut disabled = HeaderCache::new(0);
+        disabled.insert(1, header(1));
+        assert!(disabled.get(1).is_none());
+    }
+
+    #[test]
+    fn tron_header_profile_parses_mainnet_and_explicit_widths() {
+        let mainnet: TronHeaderProfile = "mainnet".parse().unwrap();
+        assert_eq!(mainnet, TronHeaderProfile::MAINNET);
+        assert_eq!(mainnet.raw_data_len(), 105);
+        assert_eq!(mainnet.encoded_len(), 174);
+
+        let p: TronHeaderProfile = " timestamp=6, number=3, version=1 ".parse().unwrap();
+        assert_eq!(p.number_varint_len, 3);
+        assert_eq!(p.to_string().parse::<TronHeaderProfile>().unwrap(), p);
+
+        assert!("timestamp=6,number=3".parse::<TronHeaderProfile>().is_err());
+        assert!(
+            "timestamp=0,number=3,version=1"
+                .parse::<TronHeaderProfile>()
+                .is_err()
+        );
+        assert!(
+            "timestamp=6,number=3,version=1,extra=1"
+                .parse::<TronHeaderProfile>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn ensure_reader_block_count_checks_finality_blocks() {
+        TronTxProofBuilder::new(19)
+            .ensure_reader_block_count(20)
+            .unwrap();
+
+        let err = TronTxProofBuilder::new(26)
+            .ensure_reader_block_count(20)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("reader expects 20"), "{err}");
+    }
+
     #[test]
     fn encode_block_header_stateful_matches_real_mainnet_fixture_bytes() {
         let fixture = load_tron_headers_sample_fixture();
@@ -1173,4 +1603,232 @@ mod tests {
             "expected proof encoded_tx to contain field 1002 tag"
         );
     }
+
+    /// In-memory chain serving synthetic blocks, counting block fetches.
+    #[derive(Default)]
+    struct FakeChain {
+        head: u64,
+        blocks: HashMap<u64, (BlockExtention, Vec<Vec<u8>>)>,
+        tx_blocks: HashMap<[u8; 32], u64>,
+        raw_tx_fetches: BTreeMap<u64, usize>,
+        header_fetches: BTreeMap<u64, usize>,
+    }
+
+    impl FakeChain {
+        /// Blocks `first..=head`; block `first + i` carries `txs_per_block[i]` transactions.
+        fn new(first: u64, head: u64, txs_per_block: &[usize]) -> Self {
+            let mut chain = Self {
+                head,
+                ..Default::default()
+            };
+            for num in first..=head {
+                let tx_count = txs_per_block
+                    .get(usize::try_from(num - first).unwrap())
+                    .copied()
+                    .unwrap_or(0);
+                let mut txs = Vec::with_capacity(tx_count);
+                let mut tx_bytes = Vec::with_capacity(tx_count);
+                for i in 0..tx_count {
+                    let raw = crate::protocol::transaction::Raw {
+                        ref_block_num: i64::try_from(num).unwrap(),
+                        expiration: i64::try_from(i).unwrap() + 1,
+                        ..Default::default()
+                    };
+                    let txid = sha256_bytes32(&raw.encode_to_vec());
+                    let tx = crate::protocol::Transaction {
+                        raw_data: Some(raw),
+                        ..Default::default()
+                    };
+                    tx_bytes.push(tx.encode_to_vec());
+                    txs.push(crate::protocol::TransactionExtention {
+                        txid: txid.to_vec(),
+                        ..Default::default()
+                    });
+                    chain.tx_blocks.insert(txid.0, num);
+                }
+                let leaves: Vec<_> = tx_bytes.iter().map(|b| sha256_bytes32(b)).collect();
+                let tx_trie_root = if leaves.is_empty() {
+                    vec![0u8; 32]
+                } else {
+                    merkle_proof_sha256(&leaves, 0).unwrap().2.to_vec()
+                };
+                let mut witness_address = vec![0x33u8; 21];
+                witness_address[0] = TronAddress::MAINNET_PREFIX;
+                let header = BlockHeader {
+                    raw_data: Some(crate::protocol::block_header::Raw {
+                        timestamp: 1_700_000_000_000 + i64::try_from(num).unwrap() * 3_000,
+                        tx_trie_root,
+                        parent_hash: vec![2u8; 32],
+                        number: i64::try_from(num).unwrap(),
+                        witness_address,
+                        version: 30,
+                        ..Default::default()
+                    }),
+                    witness_signature: vec![0x44u8; 65],
+                };
+                let block = BlockExtention {
+                    transactions: txs,
+                    block_header: Some(header),
+                    ..Default::default()
+                };
+                chain.blocks.insert(num, (block, tx_bytes));
+            }
+            chain
+        }
+
+        fn txids_in(&self, num: u64) -> Vec<[u8; 32]> {
+            self.blocks[&num]
+                .0
+                .transactions
+                .iter()
+                .map(|t| t.txid.as_slice().try_into().unwrap())
+                .collect()
+        }
+
+        fn block(&self, num: i64) -> Result<&(BlockExtention, Vec<Vec<u8>>)> {
+            let num = u64::try_from(num)?;
+            self.blocks.get(&num).context("unknown block")
+        }
+    }
+
+    impl TronApi for FakeChain {
+        async fn get_now_block2(&mut self) -> Result<BlockExtention> {
+            Ok(self.block(i64::try_from(self.head)?)?.0.clone())
+        }
+
+        async fn get_block_by_num2(&mut self, num: i64) -> Result<BlockExtention> {
+            *self.header_fetches.entry(u64::try_from(num)?).or_default() += 1;
+            Ok(self.block(num)?.0.clone())
+        }
+
+        async fn get_block_by_num2_raw_txs(
+            &mut self,
+            num: i64,
+        ) -> Result<(BlockExtention, Vec<Vec<u8>>)> {
+            *self.raw_tx_fetches.entry(u64::try_from(num)?).or_default() += 1;
+            Ok(self.block(num)?.clone())
+        }
+
+        async fn get_transaction_info_by_id(
+            &mut self,
+            txid: [u8; 32],
+        ) -> Result<crate::protocol::TransactionInfo> {
+            let num = self.tx_blocks.get(&txid).context("transaction not found")?;
+            Ok(crate::protocol::TransactionInfo {
+                id: txid.to_vec(),
+                block_number: i64::try_from(*num)?,
+                ..Default::default()
+            })
+        }
+
+        async fn get_transaction_by_id(
+            &mut self,
+            _txid: [u8; 32],
+        ) -> Result<crate::protocol::Transaction> {
+            anyhow::bail!("not served by FakeChain")
+        }
+
+        async fn trigger_contract(
+            &mut self,
+            _msg: crate::protocol::TriggerSmartContract,
+        ) -> Result<crate::protocol::TransactionExtention> {
+            anyhow::bail!("not served by FakeChain")
+        }
+
+        async fn trigger_constant_contract(
+            &mut self,
+            _msg: crate::protocol::TriggerSmartContract,
+        ) -> Result<crate::protocol::TransactionExtention> {
+            anyhow::bail!("not served by FakeChain")
+        }
+
+        async fn estimate_energy(
+            &mut self,
+            _msg: crate::protocol::TriggerSmartContract,
+        ) -> Result<crate::protocol::EstimateEnergyMessage> {
+            anyhow::bail!("not served by FakeChain")
+        }
+
+        async fn broadcast_transaction(
+            &mut self,
+            _tx: crate::protocol::Transaction,
+        ) -> Result<crate::protocol::Return> {
+            anyhow::bail!("not served by FakeChain")
+        }
+
+        async fn get_account(
+            &mut self,
+            _address_prefixed: Vec<u8>,
+        ) -> Result<crate::protocol::Account> {
+            anyhow::bail!("not served by FakeChain")
+        }
+
+        async fn get_account_resource(
+            &mut self,
+            _address_prefixed: Vec<u8>,
+        ) -> Result<crate::protocol::AccountResourceMessage> {
+            anyhow::bail!("not served by FakeChain")
+        }
+
+        async fn get_chain_parameters(&mut self) -> Result<crate::protocol::ChainParameters> {
+            anyhow::bail!("not served by FakeChain")
+        }
+    }
+
+    #[tokio::test]
+    async fn build_many_shares_blocks_and_keeps_input_order() {
+        const FIRST: u64 = 60_000_000;
+        // Two txs in FIRST, one in FIRST + 1, one in FIRST + 3 (not final at head FIRST + 4).
+        let mut chain = FakeChain::new(FIRST, FIRST + 4, &[2, 1, 0, 1]);
+        let first_txids = chain.txids_in(FIRST);
+        let (a, b) = (first_txids[0], first_txids[1]);
+        let c = chain.txids_in(FIRST + 1)[0];
+        let shallow = chain.txids_in(FIRST + 3)[0];
+        let unknown = [0xeeu8; 32];
+
+        let builder = TronTxProofBuilder::new(2);
+        let out = builder
+            .build_many(&mut chain, &[c, a, unknown, b, shallow])
+            .await
+            .unwrap();
+        assert_eq!(out.len(), 5);
+
+        let tx_bytes = |num: u64, idx: usize| chain.blocks[&num].1[idx].clone();
+        let header = |num: u64| {
+            encode_block_header_stateful(chain.blocks[&num].0.block_header.as_ref().unwrap())
+                .unwrap()
+        };
+        let expected = [(c, FIRST + 1, 0), (a, FIRST, 0), (b, FIRST, 1)];
+        for (bundle, (txid, num, idx)) in [&out[0], &out[1], &out[3]].into_iter().zip(expected) {
+            let bundle = bundle.as_ref().unwrap();
+            assert_eq!(
+                bundle.encoded_tx,
+                tx_bytes(num, idx),
+                "{}",
+                hex::encode(txid)
+            );
+            assert_eq!(bundle.index, U256::from(idx));
+            assert_eq!(
+                bundle.blocks,
+                (num..=num + 2).map(&header).collect::<Vec<_>>(),
+                "{}",
+                hex::encode(txid)
+            );
+        }
+        let err = format!("{:#}", out[2].as_ref().unwrap_err());
+        assert!(err.contains("transaction not found"), "{err}");
+        let err = format!("{:#}", out[4].as_ref().unwrap_err());
+        assert!(err.contains("tx not finalized"), "{err}");
+
+        // Each tx block is fetched once for the whole batch, and headers come from blocks
+        // already fetched in the batch whenever possible.
+        assert_eq!(
+            chain.raw_tx_fetches,
+            BTreeMap::from([(FIRST, 1), (FIRST + 1, 1)])
+        );
+        assert_eq!(
+            chain.header_fetches,
+            BTreeMap::from([(FIRST + 2, 1), (FIRST + 3, 1)])
+        );
+    }
 }
@@ -1,7 +1,23 @@
+use super::client::TronApi;
+use super::protocol::{Account, AccountResourceMessage, ResourceCode};
+use super::resources::{ChainFees, quote_fee_limit_sun};
+use super::sender::TronSystemContract;
+use super::{TronAddress, TronWallet};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// Budgeted size of one Stake 2.0 delegation tx; real ones are ~270 bytes.
+const STAKED_DELEGATION_TX_BYTES: u64 = 300;
+const SUN_PER_TRX: u64 = 1_000_000;
+/// How long a staked lease waits for its tx to confirm before reclaiming anyway. Tron txs
+/// expire 60s after their reference block, so anything unconfirmed by then never lands.
+const ENERGY_RELEASE_MAX_WAIT: Duration = Duration::from_secs(90);
+const ENERGY_RELEASE_POLL_INTERVAL: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -10,7 +26,7 @@ pub enum RentalResourceKind {
     Bandwidth,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RentalContext {
     pub resource: RentalResourceKind,
     pub amount: u64,
@@ -24,6 +40,8 @@ pub struct RentalContext {
 
     /// Optional txid for correlation (0x-prefixed 32-byte hex).
     pub txid: Option<String>,
+    /// Provider order id; only set when polling an existing order's status.
+    pub order_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -39,12 +57,183 @@ pub struct JsonApiRentalProviderConfig {
     pub body: Value,
 
     pub response: JsonApiResponseMapping,
+
+    /// Optional flat price (sun per energy unit), used to rank this provider against other
+    /// energy sources and the TRX burn alternative. Unpriced providers are tried last.
+    #[serde(default)]
+    pub price_sun_per_energy: Option<u64>,
+
+    /// Optional live price-quote endpoint. Takes precedence over `price_sun_per_energy`.
+    #[serde(default)]
+    pub quote: Option<JsonApiQuoteConfig>,
+
+    /// Optional order-status endpoint. Without it, an accepted order is assumed delegated.
+    #[serde(default)]
+    pub status: Option<JsonApiStatusConfig>,
 }
 
 fn default_method() -> String {
     "POST".to_string()
 }
 
+fn default_quote_method() -> String {
+    "GET".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonApiQuoteConfig {
+    /// Same `{{placeholders}}` as the order request.
+    pub url: String,
+    #[serde(default = "default_quote_method")]
+    pub method: String, // "GET" or "POST"
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// JSON body template for POST quotes.
+    #[serde(default)]
+    pub body: Value,
+
+    /// JSON pointer to the quoted price (number or numeric string).
+    pub price_pointer: String,
+    #[serde(default)]
+    pub price_unit: RentalPriceUnit,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonApiStatusConfig {
+    /// Same `{{placeholders}}` as the order request, plus `{{order_id}}`.
+    pub url: String,
+    #[serde(default = "default_quote_method")]
+    pub method: String, // "GET" or "POST"
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// JSON body template for POST status requests.
+    #[serde(default)]
+    pub body: Value,
+
+    /// JSON pointer to the order's state. Values are matched like `success_equals`; anything
+    /// not listed below is treated as still pending.
+    pub status_pointer: String,
+    #[serde(default)]
+    pub delegated_values: Vec<Value>,
+    #[serde(default)]
+    pub failed_values: Vec<Value>,
+    #[serde(default)]
+    pub refunded_values: Vec<Value>,
+    /// States meaning the provider stopped after delegating only part of the order.
+    #[serde(default)]
+    pub partially_filled_values: Vec<Value>,
+    /// Optional JSON pointer to the energy delegated so far. A delegated order that reports
+    /// less than it was placed for counts as partially filled.
+    #[serde(default)]
+    pub filled_energy_pointer: Option<String>,
+}
+
+/// Where a provider order is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RentalOrderStatus {
+    /// Accepted, but the energy has not been delegated yet.
+    Pending,
+    Delegated,
+    /// Rejected or abandoned by the provider without charging us.
+    Failed,
+    /// Charged, then paid back by the provider.
+    Refunded,
+    /// The provider stopped after delegating part of the order (`filled_energy`, if it says).
+    PartiallyFilled {
+        filled_energy: Option<u64>,
+    },
+}
+
+impl RentalOrderStatus {
+    pub fn is_terminal(self) -> bool {
+        !matches!(self, Self::Pending)
+    }
+
+    /// Whether the order cost (or may still cost) us money.
+    pub fn is_spend(self) -> bool {
+        matches!(
+            self,
+            Self::Pending | Self::Delegated | Self::PartiallyFilled { .. }
+        )
+    }
+}
+
+/// How a provider denominates a price: a quote, or the realised cost of an order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RentalPriceUnit {
+    /// Total for the whole order, in sun.
+    #[default]
+    TotalSun,
+    /// Total for the whole order, in TRX (may be fractional).
+    TotalTrx,
+    /// Per energy unit, in sun (may be fractional).
+    SunPerEnergy,
+}
+
+impl RentalPriceUnit {
+    /// Total sun for an order of `amount` energy, rounded up. Saturates; a quote that large is
+    /// never chosen anyway.
+    fn total_sun(self, price: Decimal, amount: u64) -> u64 {
+        let total = match self {
+            Self::TotalSun => price.mul_ceil(1),
+            Self::TotalTrx => price.mul_ceil(u128::from(SUN_PER_TRX)),
+            Self::SunPerEnergy => price.mul_ceil(u128::from(amount)),
+        };
+        u64::try_from(total).unwrap_or(u64::MAX)
+    }
+}
+
+/// A non-negative decimal read exactly from a provider's JSON: `digits / 10^scale`. Prices
+/// like "0.000035" TRX don't survive a round trip through `f64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Decimal {
+    digits: u128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Parses plain (`"38.5"`) and exponent (`"3.5e-5"`) notation; signs are rejected.
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (mantissa, exp) = match s.split_once(['e', 'E']) {
+            Some((m, e)) => (m, e.parse::<i64>().ok()?),
+            None => (s, 0),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if (int.is_empty() && frac.is_empty())
+            || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        let digits: u128 = format!("{int}{frac}").parse().ok()?;
+        let scale = i64::try_from(frac.len()).ok()?.checked_sub(exp)?;
+        match u32::try_from(scale) {
+            Ok(scale) => Some(Self { digits, scale }),
+            Err(_) => {
+                let shift = 10u128.checked_pow(u32::try_from(-scale).ok()?)?;
+                Some(Self {
+                    digits: digits.checked_mul(shift)?,
+                    scale: 0,
+                })
+            }
+        }
+    }
+
+    /// `self * factor`, rounded up and saturating.
+    fn mul_ceil(self, factor: u128) -> u128 {
+        let Some(product) = self.digits.checked_mul(factor) else {
+            return u128::MAX;
+        };
+        match 10u128.checked_pow(self.scale) {
+            Some(denominator) => product.div_ceil(denominator),
+            // The denominator exceeds any u128 product: a nonzero value rounds up to 1.
+            None => u128::from(product > 0),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct JsonApiResponseMapping {
     /// JSON pointer to a truthy success flag (bool/number/string).
@@ -60,6 +249,11 @@ pub struct JsonApiResponseMapping {
     /// Optional JSON pointer to an error message.
     #[serde(default)]
     pub error_pointer: Option<String>,
+    /// Optional JSON pointer to what the order actually cost (number or numeric string).
+    #[serde(default)]
+    pub cost_pointer: Option<String>,
+    #[serde(default)]
+    pub cost_unit: RentalPriceUnit,
 }
 
 #[derive(Debug, Clone)]
@@ -69,6 +263,8 @@ pub struct RentalAttempt {
     pub order_id: Option<String>,
     pub response_json: Option<Value>,
     pub error: Option<String>,
+    /// Realised cost in sun, when the provider reports it.
+    pub cost_sun: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -90,32 +286,460 @@ impl JsonApiRentalProvider {
     }
 
     pub async fn rent(&self, ctx: &RentalContext) -> Result<RentalAttempt> {
-        let mut body = self.cfg.body.clone();
-        render_in_place(&mut body, ctx);
+        let req = self.request(
+            &self.cfg.method,
+            &self.cfg.url,
+            &self.cfg.headers,
+            &self.cfg.body,
+            ctx,
+        )?;
 
-        let url = render_str(&self.cfg.url, ctx);
-        let mut req = match self.cfg.method.to_uppercase().as_str() {
+        let resp = req.send().await.context("rental provider http")?;
+        let status = resp.status();
+        let text = resp.text().await.context("read rental response body")?;
+        let mut attempt = interpret_json_response(&self.cfg, status.as_u16(), &text);
+        if attempt.ok
+            && let Some(json) = &attempt.response_json
+        {
+            attempt.cost_sun = realised_cost_sun(&self.cfg.response, json, ctx.amount);
+        }
+        Ok(attempt)
+    }
+
+    /// Total sun this provider would charge for `ctx.amount` energy: live from the quote
+    /// endpoint if configured, else from the flat `price_sun_per_energy`, else `None`.
+    pub async fn quote(&self, ctx: &RentalContext) -> Result<Option<u64>> {
+        let Some(quote) = &self.cfg.quote else {
+            return Ok(self
+                .cfg
+                .price_sun_per_energy
+                .map(|price| price.saturating_mul(ctx.amount)));
+        };
+
+        let req = self.request(&quote.method, &quote.url, &quote.headers, &quote.body, ctx)?;
+        let resp = req.send().await.context("rental quote http")?;
+        let status = resp.status();
+        let text = resp.text().await.context("read rental quote body")?;
+        interpret_quote_response(quote, status.as_u16(), &text, ctx.amount).map(Some)
+    }
+
+    /// Current state of `order_id`, or `None` when the provider has no status endpoint.
+    pub async fn order_status(
+        &self,
+        ctx: &RentalContext,
+        order_id: &str,
+    ) -> Result<Option<RentalOrderStatus>> {
+        let Some(status) = &self.cfg.status else {
+            return Ok(None);
+        };
+
+        let ctx = RentalContext {
+            order_id: Some(order_id.to_string()),
+            ..ctx.clone()
+        };
+        let req = self.request(
+            &status.method,
+            &status.url,
+            &status.headers,
+            &status.body,
+            &ctx,
+        )?;
+        let resp = req.send().await.context("rental status http")?;
+        let code = resp.status();
+        let text = resp.text().await.context("read rental status body")?;
+        interpret_status_response(status, code.as_u16(), &text, ctx.amount).map(Some)
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &BTreeMap<String, String>,
+        body: &Value,
+        ctx: &RentalContext,
+    ) -> Result<reqwest::RequestBuilder> {
+        let url = render_str(url, ctx);
+        let mut req = match method.to_uppercase().as_str() {
             "POST" => self.client.post(url),
             "GET" => self.client.get(url),
             other => anyhow::bail!("unsupported rental provider method: {other}"),
         };
 
-        for (k, v) in &self.cfg.headers {
+        for (k, v) in headers {
             req = req.header(k, render_str(v, ctx));
         }
 
         // Keep it simple: JSON body for POST. GET bodies are ignored.
-        if self.cfg.method.to_uppercase() == "POST" {
+        if method.to_uppercase() == "POST" {
+            let mut body = body.clone();
+            render_in_place(&mut body, ctx);
             req = req.json(&body);
         }
 
-        let resp = req.send().await.context("rental provider http")?;
-        let status = resp.status();
-        let text = resp.text().await.context("read rental response body")?;
-        Ok(interpret_json_response(&self.cfg, status.as_u16(), &text))
+        Ok(req)
     }
 }
 
+/// Where a tx's energy comes from: an external rental API or a staking account we own.
+#[derive(Clone)]
+pub enum EnergySource {
+    JsonApi(JsonApiRentalProvider),
+    Staked(StakedEnergyDelegator),
+}
+
+/// What an `EnergySource` would charge for a given amount of energy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyQuote {
+    /// The source cannot cover the amount right now (e.g. not enough staked TRX).
+    Unavailable,
+    /// The source has no known price; it is tried after every priced source.
+    Unpriced,
+    Sun(u64),
+}
+
+/// Energy obtained from an `EnergySource`.
+///
+/// Staked leases must be handed back via `EnergySource::spawn_release` once the tx they paid
+/// for has confirmed (or failed to broadcast); rental leases expire on the provider's side.
+#[derive(Debug, Clone)]
+pub struct EnergyLease {
+    pub attempt: RentalAttempt,
+    reclaim: Option<StakedReclaim>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StakedReclaim {
+    receiver: TronAddress,
+    balance_sun: i64,
+}
+
+impl EnergyLease {
+    pub fn needs_release(&self) -> bool {
+        self.reclaim.is_some()
+    }
+}
+
+impl EnergySource {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::JsonApi(p) => p.name(),
+            Self::Staked(s) => s.name(),
+        }
+    }
+
+    pub async fn quote(
+        &self,
+        grpc: &mut impl TronApi,
+        ctx: &RentalContext,
+        fees: ChainFees,
+    ) -> Result<EnergyQuote> {
+        match self {
+            Self::JsonApi(p) => Ok(match p.quote(ctx).await? {
+                Some(sun) => EnergyQuote::Sun(sun),
+                None => EnergyQuote::Unpriced,
+            }),
+            Self::Staked(s) => s.quote(grpc, ctx.amount, fees).await,
+        }
+    }
+
+    pub async fn acquire(
+        &self,
+        grpc: &mut impl TronApi,
+        ctx: &RentalContext,
+    ) -> Result<EnergyLease> {
+        match self {
+            Self::JsonApi(p) => Ok(EnergyLease {
+                attempt: p.rent(ctx).await?,
+                reclaim: None,
+            }),
+            Self::Staked(s) => s.delegate(grpc, ctx).await,
+        }
+    }
+
+    /// Current state of provider order `order_id`, or `None` when it can't be tracked (no
+    /// status endpoint, or a staked delegation).
+    pub async fn order_status(
+        &self,
+        ctx: &RentalContext,
+        order_id: &str,
+    ) -> Result<Option<RentalOrderStatus>> {
+        match self {
+            Self::JsonApi(p) => p.order_status(ctx, order_id).await,
+            Self::Staked(_) => Ok(None),
+        }
+    }
+
+    /// Reclaims a staked lease right away. No-op for rental leases.
+    pub async fn release(&self, grpc: &mut impl TronApi, lease: &EnergyLease) -> Result<()> {
+        match (self, lease.reclaim) {
+            (Self::Staked(s), Some(reclaim)) => s.undelegate(grpc, reclaim).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Releases `lease` in the background: after `txid` confirms (or is given up on), or
+    /// immediately when `txid` is `None` because the tx never made it out.
+    pub fn spawn_release<C>(&self, mut grpc: C, lease: EnergyLease, txid: Option<[u8; 32]>)
+    where
+        C: TronApi + 'static,
+    {
+        if !lease.needs_release() {
+            return;
+        }
+        let source = self.clone();
+        tokio::spawn(async move {
+            if let Some(txid) = txid {
+                wait_for_tx_included(&mut grpc, txid, ENERGY_RELEASE_MAX_WAIT).await;
+            }
+            if let Err(err) = source.release(&mut grpc, &lease).await {
+                tracing::warn!(
+                    source = source.name(),
+                    err = %format!("{err:#}"),
+                    "failed to reclaim delegated energy"
+                );
+            }
+        });
+    }
+}
+
+/// Quotes every source for `ctx.amount` at once, in `sources` order. A source whose quote
+/// fails comes back `Unpriced`, so it is still tried after every priced source.
+pub async fn quote_energy_sources<C>(
+    sources: &[EnergySource],
+    grpc: &C,
+    ctx: &RentalContext,
+    fees: ChainFees,
+) -> Vec<EnergyQuote>
+where
+    C: TronApi + Clone + Send + 'static,
+{
+    let mut pending = JoinSet::new();
+    for (idx, source) in sources.iter().enumerate() {
+        let (source, mut grpc, ctx) = (source.clone(), grpc.clone(), ctx.clone());
+        pending.spawn(async move { (idx, source.quote(&mut grpc, &ctx, fees).await) });
+    }
+
+    let mut quotes = vec![EnergyQuote::Unpriced; sources.len()];
+    while let Some(res) = pending.join_next().await {
+        let Ok((idx, quote)) = res else {
+            continue;
+        };
+        quotes[idx] = quote.unwrap_or_else(|err| {
+            tracing::warn!(
+                source = %sources[idx].name(),
+                err = %format!("{err:#}"),
+                "energy source quote failed; trying it after the priced sources"
+            );
+            EnergyQuote::Unpriced
+        });
+    }
+    quotes
+}
+
+/// Orders `candidates` (indices into `quotes`) cheapest-first, dropping sources that are
+/// unavailable or no cheaper than burning `burn_sun` of TRX. Unpriced sources keep their
+/// relative order after every priced one.
+pub fn rank_energy_sources(
+    candidates: &[usize],
+    quotes: &[EnergyQuote],
+    burn_sun: u64,
+) -> Vec<usize> {
+    let mut out = candidates
+        .iter()
+        .copied()
+        .filter(|&idx| match quotes.get(idx) {
+            Some(EnergyQuote::Sun(cost)) => *cost < burn_sun,
+            Some(EnergyQuote::Unpriced) => true,
+            Some(EnergyQuote::Unavailable) | None => false,
+        })
+        .collect::<Vec<_>>();
+    out.sort_by_key(|&idx| match quotes[idx] {
+        EnergyQuote::Sun(cost) => (0, cost),
+        _ => (1, 0),
+    });
+    out
+}
+
+/// Delegates energy from a Stake 2.0 account we control, unlocked so it can be reclaimed as
+/// soon as the tx it paid for confirms.
+#[derive(Clone)]
+pub struct StakedEnergyDelegator {
+    name: String,
+    staker: Arc<TronWallet>,
+}
+
+impl StakedEnergyDelegator {
+    pub fn new(name: impl Into<String>, staker: Arc<TronWallet>) -> Self {
+        Self {
+            name: name.into(),
+            staker,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn staker(&self) -> TronAddress {
+        self.staker.address()
+    }
+
+    /// Staked sun needed to delegate `energy`, or `None` if the staker doesn't have it.
+    async fn stake_needed_sun(&self, grpc: &mut impl TronApi, energy: u64) -> Result<Option<i64>> {
+        let staker = self.staker.address().prefixed_bytes().to_vec();
+        let res = grpc
+            .get_account_resource(staker.clone())
+            .await
+            .context("GetAccountResource (staker)")?;
+        let acct = grpc
+            .get_account(staker)
+            .await
+            .context("GetAccount (staker)")?;
+
+        let needed = stake_sun_for_energy(energy, res.total_energy_limit, res.total_energy_weight)?;
+        Ok((needed <= delegatable_energy_stake_sun(&acct, &res)?).then_some(needed))
+    }
+
+    async fn quote(
+        &self,
+        grpc: &mut impl TronApi,
+        energy: u64,
+        fees: ChainFees,
+    ) -> Result<EnergyQuote> {
+        Ok(match self.stake_needed_sun(grpc, energy).await? {
+            // Delegate + reclaim only cost bandwidth, burned if the staker has none left.
+            Some(_) => {
+                EnergyQuote::Sun(quote_fee_limit_sun(0, 2 * STAKED_DELEGATION_TX_BYTES, fees))
+            }
+            None => EnergyQuote::Unavailable,
+        })
+    }
+
+    async fn delegate(&self, grpc: &mut impl TronApi, ctx: &RentalContext) -> Result<EnergyLease> {
+        let receiver = TronAddress::parse_text(&ctx.address_base58check)
+            .context("parse delegation receiver")?;
+        let Some(balance_sun) = self.stake_needed_sun(grpc, ctx.amount).await? else {
+            return Ok(EnergyLease {
+                attempt: RentalAttempt {
+                    provider: self.name.clone(),
+                    ok: false,
+                    order_id: None,
+                    response_json: None,
+                    error: Some("insufficient staked TRX for energy delegation".to_string()),
+                    cost_sun: None,
+                },
+                reclaim: None,
+            });
+        };
+
+        let res = self
+            .staker
+            .broadcast_system_contract_result(
+                grpc,
+                TronSystemContract::DelegateResource {
+                    receiver,
+                    amount_sun: balance_sun,
+                    resource: ResourceCode::Energy,
+                    lock_period_blocks: None,
+                },
+            )
+            .await?;
+        let ok = res.ok();
+
+        Ok(EnergyLease {
+            attempt: RentalAttempt {
+                provider: self.name.clone(),
+                ok,
+                // The delegation txid, so callers can wait for inclusion like a rental order.
+                order_id: ok.then(|| hex::encode(res.txid)),
+                response_json: None,
+                error: (!ok).then(|| res.message_lossy()),
+                cost_sun: None,
+            },
+            reclaim: ok.then_some(StakedReclaim {
+                receiver,
+                balance_sun,
+            }),
+        })
+    }
+
+    async fn undelegate(&self, grpc: &mut impl TronApi, reclaim: StakedReclaim) -> Result<()> {
+        let txid = self
+            .staker
+            .broadcast_system_contract(
+                grpc,
+                TronSystemContract::UnDelegateResource {
+                    receiver: reclaim.receiver,
+                    amount_sun: reclaim.balance_sun,
+                    resource: ResourceCode::Energy,
+                },
+            )
+            .await
+            .context("UnDelegateResource")?;
+        tracing::info!(
+            source = %self.name,
+            receiver = %reclaim.receiver,
+            balance_sun = reclaim.balance_sun,
+            txid = %hex::encode(txid),
+            "reclaimed delegated energy"
+        );
+        Ok(())
+    }
+}
+
+/// Whole TRX of stake (in sun) that yields at least `energy` at the network's current
+/// `TotalEnergyLimit / TotalEnergyWeight` ratio (weight is in TRX).
+fn stake_sun_for_energy(
+    energy: u64,
+    total_energy_limit: i64,
+    total_energy_weight: i64,
+) -> Result<i64> {
+    let limit = u128::try_from(total_energy_limit).unwrap_or(0);
+    let weight = u128::try_from(total_energy_weight).unwrap_or(0);
+    if limit == 0 || weight == 0 {
+        anyhow::bail!(
+            "node reported no network energy totals (limit={total_energy_limit}, weight={total_energy_weight})"
+        );
+    }
+    let trx = (u128::from(energy) * weight).div_ceil(limit).max(1);
+    i64::try_from(trx * u128::from(SUN_PER_TRX)).context("delegation stake out of range")
+}
+
+/// Sun staked for energy that is neither delegated elsewhere nor backing energy the staker
+/// used itself; Tron refuses to delegate the stake behind `EnergyUsed`.
+fn delegatable_energy_stake_sun(acct: &Account, res: &AccountResourceMessage) -> Result<i64> {
+    let staked: i64 = acct
+        .frozen_v2
+        .iter()
+        .filter(|f| f.r#type == ResourceCode::Energy as i32)
+        .map(|f| f.amount)
+        .sum();
+    let in_use = match u64::try_from(res.energy_used) {
+        Ok(used) if used > 0 => {
+            stake_sun_for_energy(used, res.total_energy_limit, res.total_energy_weight)?
+        }
+        _ => 0,
+    };
+    Ok((staked - in_use).max(0))
+}
+
+async fn wait_for_tx_included(grpc: &mut impl TronApi, txid: [u8; 32], max_wait: Duration) {
+    let start = tokio::time::Instant::now();
+    while start.elapsed() < max_wait {
+        tokio::time::sleep(ENERGY_RELEASE_POLL_INTERVAL).await;
+        if let Ok(info) = grpc.get_transaction_info_by_id(txid).await
+            && info.block_number > 0
+        {
+            return;
+        }
+    }
+    tracing::warn!(
+        txid = %hex::encode(txid),
+        max_wait_secs = max_wait.as_secs(),
+        "tx not confirmed in time; reclaiming delegated energy anyway"
+    );
+}
+
 fn interpret_json_response(
     cfg: &JsonApiRentalProviderConfig,
     status_code: u16,
@@ -130,6 +754,7 @@ fn interpret_json_response(
             order_id: None,
             response_json: parsed,
             error: Some(format!("http status {status_code}: {body}")),
+            cost_sun: None,
         };
     }
 
@@ -140,6 +765,7 @@ fn interpret_json_response(
             order_id: None,
             response_json: None,
             error: Some("response was not valid JSON".to_string()),
+            cost_sun: None,
         };
     };
 
@@ -176,9 +802,75 @@ fn interpret_json_response(
         order_id,
         response_json: Some(json),
         error,
+        cost_sun: None,
     }
 }
 
+fn realised_cost_sun(mapping: &JsonApiResponseMapping, json: &Value, amount: u64) -> Option<u64> {
+    let cost = json.pointer(mapping.cost_pointer.as_ref()?)?;
+    Some(mapping.cost_unit.total_sun(value_to_decimal(cost)?, amount))
+}
+
+fn interpret_quote_response(
+    cfg: &JsonApiQuoteConfig,
+    status_code: u16,
+    body: &str,
+    amount: u64,
+) -> Result<u64> {
+    if !(200..=299).contains(&status_code) {
+        anyhow::bail!("quote http status {status_code}: {body}");
+    }
+    let json: Value = serde_json::from_str(body).context("quote response was not valid JSON")?;
+    let price = json
+        .pointer(&cfg.price_pointer)
+        .and_then(value_to_decimal)
+        .with_context(|| {
+            format!(
+                "quote response has no non-negative number at {}",
+                cfg.price_pointer
+            )
+        })?;
+    Ok(cfg.price_unit.total_sun(price, amount))
+}
+
+fn interpret_status_response(
+    cfg: &JsonApiStatusConfig,
+    status_code: u16,
+    body: &str,
+    ordered_energy: u64,
+) -> Result<RentalOrderStatus> {
+    if !(200..=299).contains(&status_code) {
+        anyhow::bail!("status http status {status_code}: {body}");
+    }
+    let json: Value = serde_json::from_str(body).context("status response was not valid JSON")?;
+    let state = json
+        .pointer(&cfg.status_pointer)
+        .with_context(|| format!("status response has no {}", cfg.status_pointer))?;
+    let filled_energy = cfg
+        .filled_energy_pointer
+        .as_ref()
+        .and_then(|p| json.pointer(p))
+        .and_then(value_to_string)
+        .and_then(|s| s.trim().parse::<u64>().ok());
+    let matches = |values: &[Value]| values.iter().any(|v| is_equalish(state, v));
+    Ok(if matches(&cfg.refunded_values) {
+        RentalOrderStatus::Refunded
+    } else if matches(&cfg.failed_values) {
+        RentalOrderStatus::Failed
+    } else if matches(&cfg.partially_filled_values) {
+        RentalOrderStatus::PartiallyFilled { filled_energy }
+    } else if matches(&cfg.delegated_values) {
+        match filled_energy {
+            Some(filled) if filled < ordered_energy => RentalOrderStatus::PartiallyFilled {
+                filled_energy: Some(filled),
+            },
+            _ => RentalOrderStatus::Delegated,
+        }
+    } else {
+        RentalOrderStatus::Pending
+    })
+}
+
 fn is_truthy(v: &Value) -> bool {
     match v {
         Value::Bool(b) => *b,
@@ -214,6 +906,14 @@ fn value_to_string(v: &Value) -> Option<String> {
     }
 }
 
+fn value_to_decimal(v: &Value) -> Option<Decimal> {
+    match v {
+        Value::Number(n) => Decimal::parse(&n.to_string()),
+        Value::String(s) => Decimal::parse(s),
+        _ => None,
+    }
+}
+
 fn render_in_place(v: &mut Value, ctx: &RentalContext) {
     match v {
         Value::String(s) => {
@@ -247,6 +947,7 @@ fn render_str(s: &str, ctx: &RentalContext) -> String {
     out = out.replace("{{address_hex41}}", &ctx.address_hex41);
     out = out.replace("{{address_evm_hex}}", &ctx.address_evm_hex);
     out = out.replace("{{txid}}", ctx.txid.as_deref().unwrap_or(""));
+    out = out.replace("{{order_id}}", ctx.order_id.as_deref().unwrap_or(""));
     out
 }
 
@@ -263,6 +964,7 @@ mod tests {
             address_hex41: "0x41abcd".to_string(),
             address_evm_hex: "0xabcd".to_string(),
             txid: Some("0x11".to_string()),
+            order_id: None,
         };
 
         let mut v = serde_json::json!({
@@ -291,7 +993,12 @@ mod tests {
                 success_equals: None,
                 order_id_pointer: Some("/data/orderId".to_string()),
                 error_pointer: Some("/error".to_string()),
+                cost_pointer: None,
+                cost_unit: RentalPriceUnit::TotalSun,
             },
+            price_sun_per_energy: None,
+            quote: None,
+            status: None,
         };
 
         let res =
@@ -313,7 +1020,12 @@ mod tests {
                 success_equals: Some(serde_json::json!(200)),
                 order_id_pointer: None,
                 error_pointer: Some("/message".to_string()),
+                cost_pointer: None,
+                cost_unit: RentalPriceUnit::TotalSun,
             },
+            price_sun_per_energy: None,
+            quote: None,
+            status: None,
         };
 
         let res = interpret_json_response(&cfg, 200, r#"{"code":200,"message":"ok"}"#);
@@ -337,7 +1049,12 @@ mod tests {
                 success_equals: None,
                 order_id_pointer: None,
                 error_pointer: Some("/error/message".to_string()),
+                cost_pointer: None,
+                cost_unit: RentalPriceUnit::TotalSun,
             },
+            price_sun_per_energy: None,
+            quote: None,
+            status: None,
         };
 
         let res =
@@ -359,7 +1076,12 @@ mod tests {
                 success_equals: None,
                 order_id_pointer: None,
                 error_pointer: None,
+                cost_pointer: None,
+                cost_unit: RentalPriceUnit::TotalSun,
             },
+            price_sun_per_energy: None,
+            quote: None,
+            status: None,
         };
 
         let res = interpret_json_response(&cfg, 200, "not json");
@@ -380,7 +1102,12 @@ mod tests {
                 success_equals: None,
                 order_id_pointer: None,
                 error_pointer: None,
+                cost_pointer: None,
+                cost_unit: RentalPriceUnit::TotalSun,
             },
+            price_sun_per_energy: None,
+            quote: None,
+            status: None,
         };
 
         let res = interpret_json_response(&cfg, 503, r#"{"success":true}"#);
@@ -391,4 +1118,242 @@ mod tests {
         );
         assert!(res.response_json.is_some());
     }
+
+    #[test]
+    fn rank_energy_sources_prefers_cheapest_below_burn() {
+        let quotes = [
+            EnergyQuote::Unpriced,
+            EnergyQuote::Sun(900),
+            EnergyQuote::Unavailable,
+            EnergyQuote::Sun(300),
+            EnergyQuote::Sun(5_000),
+            EnergyQuote::Unpriced,
+        ];
+
+        assert_eq!(
+            rank_energy_sources(&[5, 0, 1, 2, 3, 4], &quotes, 1_000),
+            vec![3, 1, 5, 0]
+        );
+        assert_eq!(
+            rank_energy_sources(&[0, 1, 2, 3, 4, 5], &quotes, u64::MAX),
+            vec![3, 1, 4, 0, 5]
+        );
+        assert_eq!(
+            rank_energy_sources(&[1, 3], &quotes, 300),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn stake_sun_for_energy_rounds_up_to_whole_trx() {
+        // 10 energy per staked TRX.
+        assert_eq!(
+            stake_sun_for_energy(65_000, 1_000, 100).unwrap(),
+            6_500_000_000
+        );
+        assert_eq!(
+            stake_sun_for_energy(65_001, 1_000, 100).unwrap(),
+            6_501_000_000
+        );
+        assert_eq!(stake_sun_for_energy(1, 1_000, 100).unwrap(), 1_000_000);
+        assert!(stake_sun_for_energy(1, 0, 100).is_err());
+    }
+
+    #[test]
+    fn delegatable_energy_stake_ignores_bandwidth_and_own_usage() {
+        use crate::protocol::account::FreezeV2;
+
+        let acct = Account {
+            frozen_v2: vec![
+                FreezeV2 {
+                    r#type: ResourceCode::Energy.into(),
+                    amount: 7_000_000,
+                },
+                FreezeV2 {
+                    r#type: ResourceCode::Bandwidth.into(),
+                    amount: 3_000_000,
+                },
+            ],
+            ..Default::default()
+        };
+        let mut res = AccountResourceMessage {
+            total_energy_limit: 1_000,
+            total_energy_weight: 100,
+            ..Default::default()
+        };
+        assert_eq!(
+            delegatable_energy_stake_sun(&acct, &res).unwrap(),
+            7_000_000
+        );
+
+        // 25 energy used is backed by 3 TRX (rounded up) of the stake.
+        res.energy_used = 25;
+        assert_eq!(
+            delegatable_energy_stake_sun(&acct, &res).unwrap(),
+            4_000_000
+        );
+
+        res.energy_used = 1_000;
+        assert_eq!(delegatable_energy_stake_sun(&acct, &res).unwrap(), 0);
+    }
+
+    #[test]
+    fn json_api_provider_price_is_optional() {
+        let cfg: JsonApiRentalProviderConfig = serde_json::from_value(serde_json::json!({
+            "name": "p1",
+            "url": "http://example",
+            "body": {},
+            "response": {"success_pointer": "/ok"},
+        }))
+        .unwrap();
+        assert_eq!(cfg.price_sun_per_energy, None);
+
+        let cfg: JsonApiRentalProviderConfig = serde_json::from_value(serde_json::json!({
+            "name": "p1",
+            "url": "http://example",
+            "body": {},
+            "response": {"success_pointer": "/ok"},
+            "price_sun_per_energy": 60,
+        }))
+        .unwrap();
+        assert_eq!(cfg.price_sun_per_energy, Some(60));
+    }
+
+    #[test]
+    fn interpret_quote_response_converts_units() {
+        let mut cfg: JsonApiQuoteConfig = serde_json::from_value(serde_json::json!({
+            "url": "http://example/price?energy={{amount}}",
+            "price_pointer": "/data/price",
+        }))
+        .unwrap();
+        assert_eq!(cfg.method, "GET");
+        assert_eq!(cfg.price_unit, RentalPriceUnit::TotalSun);
+
+        let total = interpret_quote_response(&cfg, 200, r#"{"data":{"price":2500000}}"#, 65_000);
+        assert_eq!(total.unwrap(), 2_500_000);
+
+        cfg.price_unit = RentalPriceUnit::TotalTrx;
+        let total = interpret_quote_response(&cfg, 200, r#"{"data":{"price":"2.5"}}"#, 65_000);
+        assert_eq!(total.unwrap(), 2_500_000);
+
+        cfg.price_unit = RentalPriceUnit::SunPerEnergy;
+        let total = interpret_quote_response(&cfg, 200, r#"{"data":{"price":38.5}}"#, 65_000);
+        assert_eq!(total.unwrap(), 2_502_500);
+    }
+
+    #[test]
+    fn decimal_prices_convert_exactly() {
+        let sun = |unit: RentalPriceUnit, price: &str, amount| {
+            unit.total_sun(Decimal::parse(price).unwrap(), amount)
+        };
+        // 0.000035 * 1e6 is 34.99999999999999 in f64.
+        assert_eq!(sun(RentalPriceUnit::TotalTrx, "0.000035", 0), 35);
+        assert_eq!(sun(RentalPriceUnit::TotalTrx, "3.5e-5", 0), 35);
+        assert_eq!(sun(RentalPriceUnit::SunPerEnergy, "0.1", 65_001), 6_501);
+        assert_eq!(sun(RentalPriceUnit::SunPerEnergy, "0.1", 65_005), 6_501);
+        assert_eq!(sun(RentalPriceUnit::TotalSun, "12", 0), 12);
+        assert_eq!(sun(RentalPriceUnit::TotalSun, "1.5E2", 0), 150);
+        assert_eq!(sun(RentalPriceUnit::TotalSun, "1e-60", 0), 1);
+        assert_eq!(sun(RentalPriceUnit::TotalTrx, "1e30", 0), u64::MAX);
+
+        for bad in ["", ".", "-1", "+1", "1e", "n/a", "1.2.3"] {
+            assert_eq!(Decimal::parse(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn interpret_quote_response_rejects_bad_responses() {
+        let cfg: JsonApiQuoteConfig = serde_json::from_value(serde_json::json!({
+            "url": "http://example",
+            "price_pointer": "/price",
+        }))
+        .unwrap();
+
+        assert!(interpret_quote_response(&cfg, 503, r#"{"price":1}"#, 1).is_err());
+        assert!(interpret_quote_response(&cfg, 200, "not json", 1).is_err());
+        assert!(interpret_quote_response(&cfg, 200, r#"{"price":"n/a"}"#, 1).is_err());
+        assert!(interpret_quote_response(&cfg, 200, r#"{"price":-1}"#, 1).is_err());
+    }
+
+    #[test]
+    fn realised_cost_sun_reads_cost_pointer() {
+        let mut mapping = JsonApiResponseMapping {
+            success_pointer: "/ok".to_string(),
+            success_equals: None,
+            order_id_pointer: None,
+            error_pointer: None,
+            cost_pointer: Some("/order/paid".to_string()),
+            cost_unit: RentalPriceUnit::TotalTrx,
+        };
+        let json = serde_json::json!({"ok": true, "order": {"paid": "3.2"}});
+        assert_eq!(realised_cost_sun(&mapping, &json, 65_000), Some(3_200_000));
+
+        mapping.cost_pointer = Some("/order/missing".to_string());
+        assert_eq!(realised_cost_sun(&mapping, &json, 65_000), None);
+        mapping.cost_pointer = None;
+        assert_eq!(realised_cost_sun(&mapping, &json, 65_000), None);
+    }
+
+    #[test]
+    fn interpret_status_response_maps_lifecycle() {
+        let cfg: JsonApiStatusConfig = serde_json::from_value(serde_json::json!({
+            "url": "http://example/orders/{{order_id}}",
+            "status_pointer": "/data/state",
+            "delegated_values": ["filled", 2],
+            "failed_values": ["failed"],
+            "refunded_values": ["refunded"],
+            "partially_filled_values": ["partial"],
+            "filled_energy_pointer": "/data/filled",
+        }))
+        .unwrap();
+
+        let status = |body: &str| interpret_status_response(&cfg, 200, body, 65_000).unwrap();
+        assert_eq!(
+            status(r#"{"data":{"state":"filled"}}"#),
+            RentalOrderStatus::Delegated
+        );
+        assert_eq!(
+            status(r#"{"data":{"state":"2"}}"#),
+            RentalOrderStatus::Delegated
+        );
+        assert_eq!(
+            status(r#"{"data":{"state":"failed"}}"#),
+            RentalOrderStatus::Failed
+        );
+        assert_eq!(
+            status(r#"{"data":{"state":"refunded"}}"#),
+            RentalOrderStatus::Refunded
+        );
+        assert_eq!(
+            status(r#"{"data":{"state":"queued"}}"#),
+            RentalOrderStatus::Pending
+        );
+        assert_eq!(
+            status(r#"{"data":{"state":"partial"}}"#),
+            RentalOrderStatus::PartiallyFilled {
+                filled_energy: None
+            }
+        );
+        assert_eq!(
+            status(r#"{"data":{"state":"filled","filled":"40000"}}"#),
+            RentalOrderStatus::PartiallyFilled {
+                filled_energy: Some(40_000)
+            }
+        );
+        assert_eq!(
+            status(r#"{"data":{"state":"filled","filled":65000}}"#),
+            RentalOrderStatus::Delegated
+        );
+
+        assert!(interpret_status_response(&cfg, 404, "{}", 1).is_err());
+        assert!(interpret_status_response(&cfg, 200, r#"{"data":{}}"#, 1).is_err());
+        assert!(RentalOrderStatus::Pending.is_spend());
+        assert!(!RentalOrderStatus::Refunded.is_spend());
+        assert!(
+            RentalOrderStatus::PartiallyFilled {
+                filled_energy: None
+            }
+            .is_spend()
+        );
+    }
 }
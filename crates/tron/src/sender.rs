@@ -1,5 +1,9 @@
-use super::grpc::TronGrpc;
-use super::protocol::{Transaction, TriggerSmartContract};
+use super::client::TronApi;
+use super::protocol::transaction::{self, contract::ContractType};
+use super::protocol::{
+    BlockExtention, DelegateResourceContract, FreezeBalanceV2Contract, ResourceCode, Transaction,
+    TransferContract, TriggerSmartContract, UnDelegateResourceContract, UnfreezeBalanceV2Contract,
+};
 use super::resources::{ChainFees, quote_fee_limit_sun};
 use super::{TronAddress, TronWallet};
 use anyhow::{Context, Result};
@@ -10,6 +14,8 @@ use sha2::{Digest, Sha256};
 /// and as a sane upper bound in dynamic policies.
 pub const FIXED_FEE_LIMIT_SUN: u64 = 100_000_000;
 const MAX_VARINT64_BYTES: u64 = 10;
+/// Matches java-tron's default: a locally built tx expires 60s after its reference block.
+const SYSTEM_TX_EXPIRATION_MS: i64 = 60_000;
 
 /// Policy for computing a tx's `fee_limit` — the max TRX burn the node is allowed to charge.
 ///
@@ -55,14 +61,178 @@ pub struct SignedTronTx {
     pub tx_size_bytes: u64,
 }
 
+/// A non-VM contract the wallet can sign: TRX transfers and Stake 2.0 resource management.
+///
+/// Amounts are in sun. None of these run in the TVM, so they consume no energy and the node
+/// never consults `fee_limit`; the only cost is bandwidth (burned as TRX if the owner has no
+/// free or staked bandwidth left).
+#[derive(Debug, Clone, Copy)]
+pub enum TronSystemContract {
+    /// Native TRX transfer (`TransferContract`).
+    Transfer { to: TronAddress, amount_sun: i64 },
+    /// Stakes TRX for `resource` (`FreezeBalanceV2Contract`).
+    FreezeBalanceV2 {
+        amount_sun: i64,
+        resource: ResourceCode,
+    },
+    /// Starts unstaking; the TRX becomes withdrawable after the chain's unfreeze delay.
+    UnfreezeBalanceV2 {
+        amount_sun: i64,
+        resource: ResourceCode,
+    },
+    /// Delegates staked `resource` to `receiver`. `lock_period_blocks: None` leaves it
+    /// reclaimable immediately.
+    DelegateResource {
+        receiver: TronAddress,
+        amount_sun: i64,
+        resource: ResourceCode,
+        lock_period_blocks: Option<i64>,
+    },
+    /// Reclaims a delegation previously made to `receiver`.
+    UnDelegateResource {
+        receiver: TronAddress,
+        amount_sun: i64,
+        resource: ResourceCode,
+    },
+}
+
+impl TronSystemContract {
+    pub fn contract_type(&self) -> ContractType {
+        match self {
+            Self::Transfer { .. } => ContractType::TransferContract,
+            Self::FreezeBalanceV2 { .. } => ContractType::FreezeBalanceV2Contract,
+            Self::UnfreezeBalanceV2 { .. } => ContractType::UnfreezeBalanceV2Contract,
+            Self::DelegateResource { .. } => ContractType::DelegateResourceContract,
+            Self::UnDelegateResource { .. } => ContractType::UnDelegateResourceContract,
+        }
+    }
+
+    /// The `raw_data.contract[0]` entry for this contract, owned by `owner`.
+    pub fn to_contract(&self, owner: TronAddress) -> transaction::Contract {
+        let owner_address = owner.prefixed_bytes().to_vec();
+        let value = match *self {
+            Self::Transfer { to, amount_sun } => TransferContract {
+                owner_address,
+                to_address: to.prefixed_bytes().to_vec(),
+                amount: amount_sun,
+            }
+            .encode_to_vec(),
+            Self::FreezeBalanceV2 {
+                amount_sun,
+                resource,
+            } => FreezeBalanceV2Contract {
+                owner_address,
+                frozen_balance: amount_sun,
+                resource: resource.into(),
+            }
+            .encode_to_vec(),
+            Self::UnfreezeBalanceV2 {
+                amount_sun,
+                resource,
+            } => UnfreezeBalanceV2Contract {
+                owner_address,
+                unfreeze_balance: amount_sun,
+                resource: resource.into(),
+            }
+            .encode_to_vec(),
+            Self::DelegateResource {
+                receiver,
+                amount_sun,
+                resource,
+                lock_period_blocks,
+            } => DelegateResourceContract {
+                owner_address,
+                resource: resource.into(),
+                balance: amount_sun,
+                receiver_address: receiver.prefixed_bytes().to_vec(),
+                lock: lock_period_blocks.is_some(),
+                lock_period: lock_period_blocks.unwrap_or(0),
+            }
+            .encode_to_vec(),
+            Self::UnDelegateResource {
+                receiver,
+                amount_sun,
+                resource,
+            } => UnDelegateResourceContract {
+                owner_address,
+                resource: resource.into(),
+                balance: amount_sun,
+                receiver_address: receiver.prefixed_bytes().to_vec(),
+            }
+            .encode_to_vec(),
+        };
+
+        let contract_type = self.contract_type();
+        transaction::Contract {
+            r#type: contract_type.into(),
+            parameter: Some(prost_types::Any {
+                type_url: format!(
+                    "type.googleapis.com/protocol.{}",
+                    contract_type.as_str_name()
+                ),
+                value,
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Builds the unsigned `raw_data` for `contract`, referencing `head` (TAPoS) the same way the
+/// node does when it builds a tx skeleton.
+fn system_contract_raw(
+    head: &BlockExtention,
+    contract: transaction::Contract,
+) -> Result<transaction::Raw> {
+    let header = head
+        .block_header
+        .as_ref()
+        .and_then(|h| h.raw_data.as_ref())
+        .context("head block has no header")?;
+    if head.blockid.len() != 32 {
+        anyhow::bail!("unexpected head blockid length: {}", head.blockid.len());
+    }
+
+    Ok(transaction::Raw {
+        ref_block_bytes: header.number.to_be_bytes()[6..].to_vec(),
+        ref_block_hash: head.blockid[8..16].to_vec(),
+        expiration: header.timestamp.saturating_add(SYSTEM_TX_EXPIRATION_MS),
+        timestamp: header.timestamp,
+        contract: vec![contract],
+        ..Default::default()
+    })
+}
+
 impl TronWallet {
+    /// Builds and signs a non-VM contract tx on top of the node's current head block.
+    ///
+    /// Built locally rather than via the per-contract wallet RPCs so it works on every
+    /// `TronApi` backend. `fee_limit` is left at zero since no energy can be consumed.
+    pub async fn build_and_sign_system_contract(
+        &self,
+        grpc: &mut impl TronApi,
+        contract: TronSystemContract,
+    ) -> Result<SignedTronTx> {
+        let head = grpc.get_now_block2().await.context("GetNowBlock2")?;
+        let raw = system_contract_raw(&head, contract.to_contract(self.address))?;
+
+        let (tx, txid, tx_size_bytes) = self.sign_raw_with_fee_limit(raw, Vec::new(), 0)?;
+
+        Ok(SignedTronTx {
+            tx,
+            txid,
+            fee_limit_sun: 0,
+            energy_required: 0,
+            tx_size_bytes,
+        })
+    }
+
     /// Builds and signs a `TriggerSmartContract` tx with a fee_limit derived from `policy`.
     ///
     /// Fails fast if `estimate_energy` reports the call would revert — the signed tx would be
     /// dead-on-arrival, and broadcasting it would still burn energy consumed before the revert.
     pub async fn build_and_sign_trigger_smart_contract(
         &self,
-        grpc: &mut TronGrpc,
+        grpc: &mut impl TronApi,
         contract: TronAddress,
         data: Vec<u8>,
         call_value_sun: i64,
@@ -245,4 +415,86 @@ mod tests {
         // Must not panic or wrap.
         let _ = p.compute(u64::MAX, 0);
     }
+
+    #[test]
+    fn system_contract_raw_references_head_block() {
+        use crate::protocol::{BlockHeader, block_header};
+
+        let mut blockid = [0u8; 32];
+        for (i, b) in blockid.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let head = BlockExtention {
+            block_header: Some(BlockHeader {
+                raw_data: Some(block_header::Raw {
+                    number: 0x0102_0304,
+                    timestamp: 1_700_000_000_000,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            blockid: blockid.to_vec(),
+            ..Default::default()
+        };
+        let to = TronAddress::from_evm(alloy::primitives::Address::repeat_byte(0x33));
+        let contract = TronSystemContract::Transfer {
+            to,
+            amount_sun: 5_000_000,
+        };
+        let owner = TronAddress::from_evm(alloy::primitives::Address::repeat_byte(0x11));
+
+        let raw = system_contract_raw(&head, contract.to_contract(owner)).unwrap();
+        assert_eq!(raw.ref_block_bytes, vec![0x03, 0x04]);
+        assert_eq!(raw.ref_block_hash, blockid[8..16].to_vec());
+        assert_eq!(raw.expiration, 1_700_000_060_000);
+        assert_eq!(raw.fee_limit, 0);
+
+        let c = &raw.contract[0];
+        assert_eq!(c.r#type, ContractType::TransferContract as i32);
+        let any = c.parameter.as_ref().unwrap();
+        assert_eq!(
+            any.type_url,
+            "type.googleapis.com/protocol.TransferContract"
+        );
+        let msg = TransferContract::decode(any.value.as_slice()).unwrap();
+        assert_eq!(msg.owner_address, owner.prefixed_bytes().to_vec());
+        assert_eq!(msg.to_address, to.prefixed_bytes().to_vec());
+        assert_eq!(msg.amount, 5_000_000);
+
+        let headless = BlockExtention::default();
+        assert!(system_contract_raw(&headless, contract.to_contract(owner)).is_err());
+    }
+
+    #[test]
+    fn delegate_resource_lock_follows_lock_period() {
+        let owner = TronAddress::from_evm(alloy::primitives::Address::repeat_byte(0x11));
+        let receiver = TronAddress::from_evm(alloy::primitives::Address::repeat_byte(0x44));
+        let decode = |lock_period_blocks| {
+            let c = TronSystemContract::DelegateResource {
+                receiver,
+                amount_sun: 1_000_000,
+                resource: ResourceCode::Energy,
+                lock_period_blocks,
+            }
+            .to_contract(owner);
+            assert_eq!(
+                c.parameter.as_ref().unwrap().type_url,
+                "type.googleapis.com/protocol.DelegateResourceContract"
+            );
+            DelegateResourceContract::decode(c.parameter.unwrap().value.as_slice()).unwrap()
+        };
+
+        let unlocked = decode(None);
+        assert!(!unlocked.lock);
+        assert_eq!(unlocked.lock_period, 0);
+        assert_eq!(unlocked.resource, ResourceCode::Energy as i32);
+        assert_eq!(
+            unlocked.receiver_address,
+            receiver.prefixed_bytes().to_vec()
+        );
+
+        let locked = decode(Some(28_800));
+        assert!(locked.lock);
+        assert_eq!(locked.lock_period, 28_800);
+    }
 }
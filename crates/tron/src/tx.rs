@@ -1,5 +1,6 @@
 use crate::address::TronAddress;
-use crate::protocol::{Transaction, TriggerSmartContract};
+use crate::protocol::transaction::contract::ContractType;
+use crate::protocol::{Transaction, TransferAssetContract, TransferContract, TriggerSmartContract};
 use alloy::primitives::{Address, U256};
 use anyhow::{Context, Result};
 use prost::Message;
@@ -15,6 +16,17 @@ pub struct DecodedTriggerSmartContract {
     pub data: Vec<u8>,
 }
 
+/// A native value transfer: TRX (`TransferContract`) or a TRC-10 token (`TransferAssetContract`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedNativeTransfer {
+    pub from: TronAddress,
+    pub to: TronAddress,
+    /// TRC-10 token id (`asset_name` after `ALLOW_SAME_TOKEN_NAME`); `None` for TRX.
+    pub asset_id: Option<String>,
+    /// Sun for TRX, token base units for TRC-10.
+    pub amount: u64,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum DecodedTrc20Call {
     Transfer {
@@ -80,6 +92,50 @@ pub fn decode_trigger_smart_contract(tx: &Transaction) -> Result<DecodedTriggerS
     })
 }
 
+/// Decodes `raw_data.contract[0]` if it is a TRX or TRC-10 transfer; `Ok(None)` for any other
+/// contract type.
+pub fn decode_native_transfer(tx: &Transaction) -> Result<Option<DecodedNativeTransfer>> {
+    let raw = tx
+        .raw_data
+        .as_ref()
+        .context("missing Transaction.raw_data")?;
+    let Some(contract0) = raw.contract.first() else {
+        return Ok(None);
+    };
+
+    let (owner_address, to_address, asset_id, amount) = match contract0.r#type {
+        t if t == ContractType::TransferContract as i32 => {
+            let any = contract0
+                .parameter
+                .as_ref()
+                .context("missing Transaction.raw_data.contract[0].parameter")?;
+            let c = TransferContract::decode(any.value.as_slice())
+                .context("decode TransferContract")?;
+            (c.owner_address, c.to_address, None, c.amount)
+        }
+        t if t == ContractType::TransferAssetContract as i32 => {
+            let any = contract0
+                .parameter
+                .as_ref()
+                .context("missing Transaction.raw_data.contract[0].parameter")?;
+            let c = TransferAssetContract::decode(any.value.as_slice())
+                .context("decode TransferAssetContract")?;
+            let asset_id = String::from_utf8(c.asset_name)
+                .context("decode TransferAssetContract.asset_name")?;
+            (c.owner_address, c.to_address, Some(asset_id), c.amount)
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(DecodedNativeTransfer {
+        from: tron_address_from_prefixed_bytes(&owner_address)
+            .context("decode transfer owner_address")?,
+        to: tron_address_from_prefixed_bytes(&to_address).context("decode transfer to_address")?,
+        asset_id,
+        amount: u64::try_from(amount).context("negative transfer amount")?,
+    }))
+}
+
 pub fn decode_trc20_call_data(data: &[u8], sender: TronAddress) -> Result<DecodedTrc20Call> {
     if data.len() < 4 {
         anyhow::bail!(
@@ -197,4 +253,61 @@ mod tests {
             DecodedTrc20Call::TransferFrom { .. } => panic!("fixture expected transfer"),
         }
     }
+
+    #[test]
+    fn decode_native_transfer_handles_trx_trc10_and_other_contracts() {
+        use crate::protocol::transaction::{Contract, Raw};
+
+        let from = TronAddress::from_evm(Address::repeat_byte(0x11));
+        let to = TronAddress::from_evm(Address::repeat_byte(0x22));
+        let tx_with = |contract: Contract| Transaction {
+            raw_data: Some(Raw {
+                contract: vec![contract],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let trx = tx_with(
+            crate::TronSystemContract::Transfer {
+                to,
+                amount_sun: 1_500_000,
+            }
+            .to_contract(from),
+        );
+        assert_eq!(
+            decode_native_transfer(&trx).unwrap(),
+            Some(DecodedNativeTransfer {
+                from,
+                to,
+                asset_id: None,
+                amount: 1_500_000,
+            })
+        );
+
+        let trc10 = tx_with(Contract {
+            r#type: ContractType::TransferAssetContract as i32,
+            parameter: Some(prost_types::Any {
+                type_url: "type.googleapis.com/protocol.TransferAssetContract".to_string(),
+                value: TransferAssetContract {
+                    asset_name: b"1002000".to_vec(),
+                    owner_address: from.prefixed_bytes().to_vec(),
+                    to_address: to.prefixed_bytes().to_vec(),
+                    amount: 42,
+                }
+                .encode_to_vec(),
+            }),
+            ..Default::default()
+        });
+        let decoded = decode_native_transfer(&trc10).unwrap().unwrap();
+        assert_eq!(decoded.asset_id.as_deref(), Some("1002000"));
+        assert_eq!(decoded.to, to);
+        assert_eq!(decoded.amount, 42);
+
+        let trigger = tx_with(Contract {
+            r#type: TRIGGER_SMART_CONTRACT_TYPE,
+            ..Default::default()
+        });
+        assert_eq!(decode_native_transfer(&trigger).unwrap(), None);
+    }
 }
@@ -0,0 +1,482 @@
+use super::TronAddress;
+use super::grpc::decode_varint;
+use super::proof::{
+    TronHeaderProfile, TronTxProofBundle, encode_block_header_with_profile, sha256_bytes32,
+    sha256_concat,
+};
+use super::protocol::{BlockHeader, Transaction, block_header};
+use super::tx::{DecodedTriggerSmartContract, decode_trigger_smart_contract};
+use alloy::primitives::{Address, FixedBytes, U256, keccak256};
+use anyhow::{Context, Result};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Super-representative set a `StatefulTronTxReader` deployment was constructed with.
+///
+/// `srs[i]` is the SR owner account that appears in `BlockHeader.raw_data.witness_address`;
+/// `witness_delegatees[i]` is the key that actually signs that SR's blocks (often the same
+/// account, but SRs may delegate witness permission to a separate key).
+#[derive(Debug, Clone)]
+pub struct TronSrSet {
+    index: HashMap<Address, usize>,
+    witness_delegatees: Vec<Address>,
+}
+
+impl TronSrSet {
+    pub fn new(srs: &[Address], witness_delegatees: &[Address]) -> Result<Self> {
+        if srs.is_empty() {
+            anyhow::bail!("empty SR set");
+        }
+        if srs.len() != witness_delegatees.len() {
+            anyhow::bail!(
+                "SR set length mismatch: srs={} witness_delegatees={}",
+                srs.len(),
+                witness_delegatees.len()
+            );
+        }
+
+        let mut index = HashMap::with_capacity(srs.len());
+        for (i, sr) in srs.iter().enumerate() {
+            if index.insert(*sr, i).is_some() {
+                anyhow::bail!("duplicate SR in set: {}", TronAddress::from_evm(*sr));
+            }
+        }
+
+        Ok(Self {
+            index,
+            witness_delegatees: witness_delegatees.to_vec(),
+        })
+    }
+
+    fn signer_for(&self, witness: Address) -> Option<(usize, Address)> {
+        self.index
+            .get(&witness)
+            .map(|&i| (i, self.witness_delegatees[i]))
+    }
+}
+
+/// What `StatefulTronTxReader.readTriggerSmartContract` would return for a bundle.
+#[derive(Debug, Clone)]
+pub struct VerifiedTronTx {
+    /// `sha256(raw_data_bytes)` of `encoded_tx`.
+    pub txid: [u8; 32],
+    pub tron_block_number: u64,
+    /// Block timestamp in seconds (the reader truncates the header's milliseconds).
+    pub tron_block_timestamp: u32,
+    pub tx_trie_root: FixedBytes<32>,
+    pub call: DecodedTriggerSmartContract,
+}
+
+/// Offline equivalent of `StatefulTronTxReader.readTriggerSmartContract`.
+///
+/// Performs the same checks as the onchain reader, in the same order:
+/// - there are exactly `block_count` headers (the length of the reader's `bytes[N] blocks`),
+/// - every header has the fixed layout described by `profile` and chains to the previous one via `parentHash`,
+/// - every header is signed by the delegatee of a distinct SR in `srs`,
+/// - `sha256(encoded_tx)` is included under the first header's txTrieRoot (carry-up Merkle tree),
+/// - `encoded_tx` is a single successful `TriggerSmartContract`.
+///
+/// A bundle that passes here should not revert onchain against a reader built with the same SRs,
+/// header profile and block count.
+pub fn verify_bundle(
+    bundle: &TronTxProofBundle,
+    srs: &TronSrSet,
+    profile: TronHeaderProfile,
+    block_count: usize,
+) -> Result<VerifiedTronTx> {
+    profile.validate()?;
+    if bundle.blocks.len() != block_count {
+        anyhow::bail!(
+            "bundle has {} headers, reader expects {block_count}",
+            bundle.blocks.len()
+        );
+    }
+
+    let mut prev_block_id: Option<FixedBytes<32>> = None;
+    let mut seen = vec![false; srs.witness_delegatees.len()];
+    let mut first = None;
+
+    for (i, encoded) in bundle.blocks.iter().enumerate() {
+        let header =
+            parse_stateful_header(encoded, profile).with_context(|| format!("block header {i}"))?;
+
+        if let Some(prev) = prev_block_id
+            && prev != header.parent_hash
+        {
+            anyhow::bail!(
+                "invalid block sequence at {i}: parentHash=0x{} expected=0x{}",
+                hex::encode(header.parent_hash),
+                hex::encode(prev)
+            );
+        }
+
+        let (sr_idx, delegatee) = srs.signer_for(header.witness).with_context(|| {
+            format!(
+                "unknown SR at block {}: {}",
+                header.number,
+                TronAddress::from_evm(header.witness)
+            )
+        })?;
+        if seen[sr_idx] {
+            anyhow::bail!(
+                "duplicate SR at block {}: {}",
+                header.number,
+                TronAddress::from_evm(header.witness)
+            );
+        }
+        seen[sr_idx] = true;
+
+        if header.signer != delegatee {
+            anyhow::bail!(
+                "invalid witness signature at block {}: signer={} expected={}",
+                header.number,
+                TronAddress::from_evm(header.signer),
+                TronAddress::from_evm(delegatee)
+            );
+        }
+
+        prev_block_id = Some(make_block_id(header.number, header.raw_hash));
+        if first.is_none() {
+            first = Some(header);
+        }
+    }
+
+    let first = first.context("empty block list")?;
+    let tron_block_timestamp = u32::try_from(first.timestamp_ms / 1000)
+        .context("block timestamp overflows uint32 seconds")?;
+
+    let leaf = sha256_bytes32(&bundle.encoded_tx);
+    let root = merkle_root_from_proof(leaf, &bundle.proof, bundle.index);
+    if root != first.tx_trie_root {
+        anyhow::bail!(
+            "invalid tx merkle proof: computed root=0x{} header txTrieRoot=0x{}",
+            hex::encode(root),
+            hex::encode(first.tx_trie_root)
+        );
+    }
+
+    let txid = txid_from_encoded_tx(&bundle.encoded_tx)?;
+
+    let tx = Transaction::decode(bundle.encoded_tx.as_slice()).context("decode Transaction")?;
+    if tx.raw_data.as_ref().map_or(0, |raw| raw.contract.len()) != 1 {
+        anyhow::bail!("not a TriggerSmartContract: expected exactly 1 contract");
+    }
+    let call = decode_trigger_smart_contract(&tx).context("not a TriggerSmartContract")?;
+    // Mirrors `_parseTxSuccess`: at least one ret entry, and every entry must be SUCCESS.
+    let success = !tx.ret.is_empty() && tx.ret.iter().all(|r| r.ret == 0 && r.contract_ret == 1);
+    if !success {
+        anyhow::bail!("Tron tx not successful: txid=0x{}", hex::encode(txid));
+    }
+
+    Ok(VerifiedTronTx {
+        txid,
+        tron_block_number: first.number,
+        tron_block_timestamp,
+        tx_trie_root: first.tx_trie_root,
+        call,
+    })
+}
+
+struct ParsedHeader {
+    number: u64,
+    timestamp_ms: u64,
+    tx_trie_root: FixedBytes<32>,
+    parent_hash: FixedBytes<32>,
+    witness: Address,
+    /// `sha256(raw_data_bytes)`, the digest witnesses sign.
+    raw_hash: FixedBytes<32>,
+    signer: Address,
+}
+
+fn parse_stateful_header(encoded: &[u8], profile: TronHeaderProfile) -> Result<ParsedHeader> {
+    if encoded.len() != profile.encoded_len() {
+        anyhow::bail!("invalid encoded block length: {}", encoded.len());
+    }
+    let raw_end = 2 + profile.raw_data_len();
+    if encoded[0] != 0x0a
+        || usize::from(encoded[1]) != profile.raw_data_len()
+        || encoded[raw_end..raw_end + 2] != [0x12, 0x41]
+    {
+        anyhow::bail!("invalid header framing");
+    }
+
+    let raw_bytes = &encoded[2..raw_end];
+    let sig_bytes = &encoded[raw_end + 2..];
+    let raw = block_header::Raw::decode(raw_bytes).context("decode BlockHeader.raw_data")?;
+
+    // The reader reads fields at fixed offsets, so a header prost accepts but lays out
+    // differently (e.g. reordered fields, extra fields) would be read differently onchain.
+    let canonical = encode_block_header_with_profile(
+        &BlockHeader {
+            raw_data: Some(raw.clone()),
+            witness_signature: sig_bytes.to_vec(),
+        },
+        profile,
+    )?;
+    if canonical != encoded {
+        anyhow::bail!("header is not in the fixed layout of profile {profile}");
+    }
+
+    if raw.witness_address[0] != TronAddress::MAINNET_PREFIX {
+        anyhow::bail!(
+            "invalid witness address prefix: 0x{:02x}",
+            raw.witness_address[0]
+        );
+    }
+
+    let raw_hash = sha256_bytes32(raw_bytes);
+    let signer = recover_signer(raw_bytes, sig_bytes).context("recover witness signer")?;
+
+    Ok(ParsedHeader {
+        number: u64::try_from(raw.number).context("header number out of range")?,
+        timestamp_ms: u64::try_from(raw.timestamp).context("header timestamp out of range")?,
+        tx_trie_root: FixedBytes::from_slice(&raw.tx_trie_root),
+        parent_hash: FixedBytes::from_slice(&raw.parent_hash),
+        witness: Address::from_slice(&raw.witness_address[1..]),
+        raw_hash,
+        signer,
+    })
+}
+
+fn recover_signer(message: &[u8], sig65: &[u8]) -> Result<Address> {
+    let sig = Signature::try_from(&sig65[..64]).context("parse r||s signature")?;
+    // Same normalization as the reader: 0/1 -> 27/28, anything else is rejected.
+    let v = match sig65[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        v => anyhow::bail!("invalid signature v: {v}"),
+    };
+    let recid = RecoveryId::try_from(v).context("invalid recovery id")?;
+
+    // `ecrecover` accepts high-s signatures; k256 only recovers from normalized ones.
+    let (sig, recid) = match sig.normalize_s() {
+        Some(normalized) => (
+            normalized,
+            RecoveryId::new(!recid.is_y_odd(), recid.is_x_reduced()),
+        ),
+        None => (sig, recid),
+    };
+
+    let vk = VerifyingKey::recover_from_digest(Sha256::new_with_prefix(message), &sig, recid)
+        .context("ecrecover")?;
+    Ok(evm_address_from_verifying_key(&vk))
+}
+
+fn evm_address_from_verifying_key(vk: &VerifyingKey) -> Address {
+    let public_key = vk.to_encoded_point(false);
+    let hash = keccak256(&public_key.as_bytes()[1..]);
+    Address::from_slice(&hash[12..])
+}
+
+/// Tron block id: block number in the top 8 bytes, low 24 bytes of the raw_data hash below.
+fn make_block_id(number: u64, raw_hash: FixedBytes<32>) -> FixedBytes<32> {
+    let mut out = raw_hash;
+    out[..8].copy_from_slice(&number.to_be_bytes());
+    out
+}
+
+/// Mirrors `TronSha256MerkleVerifier.verify`: bit i of `index` set means the path node was a
+/// right child at level i.
+fn merkle_root_from_proof(
+    leaf: FixedBytes<32>,
+    proof: &[FixedBytes<32>],
+    index: U256,
+) -> FixedBytes<32> {
+    let mut cur = leaf;
+    for (bit, sibling) in proof.iter().enumerate() {
+        cur = if index.bit(bit) {
+            sha256_concat(*sibling, cur)
+        } else {
+            sha256_concat(cur, *sibling)
+        };
+    }
+    cur
+}
+
+/// Computes `sha256(raw_data)` over the canonical bytes, without decoding and re-encoding.
+fn txid_from_encoded_tx(encoded_tx: &[u8]) -> Result<[u8; 32]> {
+    let rest = match encoded_tx.split_first() {
+        Some((0x0a, rest)) => rest,
+        _ => anyhow::bail!("encoded tx does not start with raw_data"),
+    };
+    let (len, rest) = decode_varint(rest).context("decode raw_data length")?;
+    let len = usize::try_from(len).context("raw_data length overflow")?;
+    let raw = rest.get(..len).context("raw_data length out of bounds")?;
+
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&Sha256::digest(raw));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::path::PathBuf;
+
+    fn workspace_path(rel: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(rel)
+    }
+
+    fn decode_hex0x(s: &str) -> Vec<u8> {
+        let s = s.strip_prefix("0x").unwrap_or(s);
+        hex::decode(s).expect("valid hex")
+    }
+
+    #[derive(Deserialize)]
+    struct TronTxProofFixture {
+        #[serde(rename = "blockNumber")]
+        block_number: String,
+        #[serde(rename = "txId")]
+        tx_id: String,
+        #[serde(rename = "encodedTx")]
+        encoded_tx: String,
+        #[serde(rename = "proof")]
+        proof: Vec<String>,
+        #[serde(rename = "indexBits")]
+        index_bits: String,
+        #[serde(rename = "blocks")]
+        blocks: Vec<String>,
+    }
+
+    /// `StatefulTronTxReader.readTriggerSmartContract` takes `bytes[20] blocks`.
+    const READER_BLOCKS: usize = 20;
+
+    fn load_fixture() -> (TronTxProofFixture, TronTxProofBundle) {
+        let path = workspace_path(
+            "testdata/fixtures/tron_tx_proof_78812179_1d649769f0ecf78bd6812226d067144bca18b4d01fb34cfdb260fd51cc3072db.json",
+        );
+        let json = std::fs::read_to_string(path).expect("read tx proof fixture json");
+        let fixture: TronTxProofFixture =
+            serde_json::from_str(&json).expect("parse tx proof fixture json");
+
+        let blocks: Vec<Vec<u8>> = fixture.blocks.iter().map(|b| decode_hex0x(b)).collect();
+        let bundle = TronTxProofBundle {
+            blocks,
+            encoded_tx: decode_hex0x(&fixture.encoded_tx),
+            proof: fixture
+                .proof
+                .iter()
+                .map(|h| FixedBytes::<32>::from_slice(&decode_hex0x(h)))
+                .collect(),
+            index: U256::from_str_radix(&fixture.index_bits, 10).expect("indexBits decimal"),
+        };
+        (fixture, bundle)
+    }
+
+    /// SR set made of exactly the witnesses (and their recovered signers) of `bundle.blocks`.
+    fn sr_set_from_bundle(bundle: &TronTxProofBundle) -> (Vec<Address>, Vec<Address>) {
+        bundle
+            .blocks
+            .iter()
+            .map(|b| {
+                let h = parse_stateful_header(b, TronHeaderProfile::MAINNET)
+                    .expect("parse fixture header");
+                (h.witness, h.signer)
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn verify_bundle_accepts_mainnet_fixture() {
+        let (fixture, bundle) = load_fixture();
+        let (srs, delegatees) = sr_set_from_bundle(&bundle);
+        let set = TronSrSet::new(&srs, &delegatees).unwrap();
+
+        let verified = verify_bundle(&bundle, &set, TronHeaderProfile::MAINNET, READER_BLOCKS)
+            .expect("fixture bundle verifies");
+        assert_eq!(verified.txid.as_slice(), decode_hex0x(&fixture.tx_id));
+        assert_eq!(
+            verified.tron_block_number,
+            fixture.block_number.parse::<u64>().unwrap()
+        );
+    }
+
+    #[test]
+    fn verify_bundle_rejects_wrong_block_count() {
+        let (_, mut bundle) = load_fixture();
+        let (srs, delegatees) = sr_set_from_bundle(&bundle);
+        let set = TronSrSet::new(&srs, &delegatees).unwrap();
+
+        // A valid chain, just not as long as the reader's array.
+        let err = verify_bundle(&bundle, &set, TronHeaderProfile::MAINNET, READER_BLOCKS + 1)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("reader expects 21"), "{err}");
+
+        bundle.blocks.pop();
+        let err = verify_bundle(&bundle, &set, TronHeaderProfile::MAINNET, READER_BLOCKS)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("bundle has 19 headers"), "{err}");
+    }
+
+    #[test]
+    fn verify_bundle_rejects_bad_merkle_proof() {
+        let (_, mut bundle) = load_fixture();
+        let (srs, delegatees) = sr_set_from_bundle(&bundle);
+        let set = TronSrSet::new(&srs, &delegatees).unwrap();
+
+        bundle.index ^= U256::from(1u64);
+        let err = verify_bundle(&bundle, &set, TronHeaderProfile::MAINNET, READER_BLOCKS)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid tx merkle proof"), "{err}");
+    }
+
+    #[test]
+    fn verify_bundle_rejects_broken_header_chain() {
+        let (_, mut bundle) = load_fixture();
+        let (srs, delegatees) = sr_set_from_bundle(&bundle);
+        let set = TronSrSet::new(&srs, &delegatees).unwrap();
+
+        bundle.blocks.swap(3, 4);
+        let err = format!(
+            "{:#}",
+            verify_bundle(&bundle, &set, TronHeaderProfile::MAINNET, READER_BLOCKS).unwrap_err()
+        );
+        assert!(err.contains("invalid block sequence"), "{err}");
+    }
+
+    #[test]
+    fn verify_bundle_rejects_unknown_sr_and_wrong_delegatee() {
+        let (_, bundle) = load_fixture();
+        let (srs, mut delegatees) = sr_set_from_bundle(&bundle);
+
+        let set = TronSrSet::new(&srs[1..], &delegatees[1..]).unwrap();
+        let err = format!(
+            "{:#}",
+            verify_bundle(&bundle, &set, TronHeaderProfile::MAINNET, READER_BLOCKS).unwrap_err()
+        );
+        assert!(err.contains("unknown SR"), "{err}");
+
+        delegatees[5] = Address::repeat_byte(0x55);
+        let set = TronSrSet::new(&srs, &delegatees).unwrap();
+        let err = format!(
+            "{:#}",
+            verify_bundle(&bundle, &set, TronHeaderProfile::MAINNET, READER_BLOCKS).unwrap_err()
+        );
+        assert!(err.contains("invalid witness signature"), "{err}");
+    }
+
+    #[test]
+    fn verify_bundle_rejects_tampered_header_signature() {
+        let (_, mut bundle) = load_fixture();
+        let (srs, delegatees) = sr_set_from_bundle(&bundle);
+        let set = TronSrSet::new(&srs, &delegatees).unwrap();
+
+        // Flip a byte inside raw_data's timestamp: layout stays valid, but the signer changes.
+        bundle.blocks[7][4] ^= 0x01;
+        assert!(verify_bundle(&bundle, &set, TronHeaderProfile::MAINNET, READER_BLOCKS).is_err());
+    }
+
+    #[test]
+    fn make_block_id_packs_number_into_top_bytes() {
+        let hash = FixedBytes::<32>::from([0xffu8; 32]);
+        let id = make_block_id(0x0102_0304, hash);
+        assert_eq!(&id[..8], &[0, 0, 0, 0, 1, 2, 3, 4]);
+        assert_eq!(&id[8..], &[0xffu8; 24]);
+    }
+}
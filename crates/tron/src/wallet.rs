@@ -1,6 +1,6 @@
 use super::protocol::TriggerSmartContract;
-use super::sender::FeeLimitPolicy;
-use super::{address::TronAddress, grpc::TronGrpc};
+use super::sender::{FeeLimitPolicy, TronSystemContract};
+use super::{address::TronAddress, client::TronApi};
 use alloy::primitives::{Address, FixedBytes, U256, keccak256};
 use anyhow::{Context, Result};
 use k256::ecdsa::SigningKey;
@@ -43,7 +43,7 @@ impl TronWallet {
 
     pub async fn broadcast_trigger_smart_contract(
         &self,
-        grpc: &mut TronGrpc,
+        grpc: &mut impl TronApi,
         contract: TronAddress,
         data: Vec<u8>,
         call_value_sun: i64,
@@ -72,7 +72,7 @@ impl TronWallet {
 
     pub async fn broadcast_trigger_smart_contract_result(
         &self,
-        grpc: &mut TronGrpc,
+        grpc: &mut impl TronApi,
         contract: TronAddress,
         data: Vec<u8>,
         call_value_sun: i64,
@@ -99,6 +99,47 @@ impl TronWallet {
             result: ret,
         })
     }
+
+    pub async fn broadcast_system_contract(
+        &self,
+        grpc: &mut impl TronApi,
+        contract: TronSystemContract,
+    ) -> Result<[u8; 32]> {
+        let res = self
+            .broadcast_system_contract_result(grpc, contract)
+            .await?;
+
+        if !res.ok() {
+            anyhow::bail!(
+                "broadcast failed: msg_hex=0x{}, msg_utf8={}",
+                res.message_hex(),
+                res.message_lossy()
+            );
+        }
+
+        Ok(res.txid)
+    }
+
+    pub async fn broadcast_system_contract_result(
+        &self,
+        grpc: &mut impl TronApi,
+        contract: TronSystemContract,
+    ) -> Result<BroadcastedTronTx> {
+        let signed = self
+            .build_and_sign_system_contract(grpc, contract)
+            .await
+            .context("build_and_sign_system_contract")?;
+
+        let ret = grpc
+            .broadcast_transaction(signed.tx)
+            .await
+            .context("broadcast_transaction")?;
+
+        Ok(BroadcastedTronTx {
+            txid: signed.txid,
+            result: ret,
+        })
+    }
 }
 
 fn tron_address_from_signing_key(key: &SigningKey) -> TronAddress {
@@ -112,7 +153,7 @@ fn tron_address_from_signing_key(key: &SigningKey) -> TronAddress {
 // ===== ABI helpers (EVM ABI, used by Tron TriggerSmartContract) =====
 
 pub async fn trc20_balance_of(
-    grpc: &mut TronGrpc,
+    grpc: &mut impl TronApi,
     token_contract: TronAddress,
     owner: TronAddress,
     caller: TronAddress,
@@ -171,6 +212,15 @@ pub fn encode_pull_from_receivers(token: Address, receiver_salts: &[FixedBytes<3
     out
 }
 
+pub fn encode_trc20_approve(spender: Address, amount: U256) -> Vec<u8> {
+    let selector = keccak256("approve(address,uint256)".as_bytes());
+    let mut out = Vec::with_capacity(4 + 32 + 32);
+    out.extend_from_slice(&selector[..4]);
+    out.extend_from_slice(&encode_address(spender));
+    out.extend_from_slice(&encode_u256(amount));
+    out
+}
+
 pub fn encode_rebalance_usdt(rebalancer: Address, in_amount: U256) -> Vec<u8> {
     let selector = keccak256("rebalanceUsdt(address,uint256)".as_bytes());
     let mut out = Vec::with_capacity(4 + 32 + 32);
@@ -224,6 +274,19 @@ mod tests {
         assert_eq!(&out[36..68], &amt_word);
     }
 
+    #[test]
+    fn encode_trc20_approve_layout() {
+        let spender = Address::from_slice(&[0x55u8; 20]);
+        let out = encode_trc20_approve(spender, U256::MAX);
+
+        assert_eq!(&out[..4], &[0x09, 0x5e, 0xa7, 0xb3]);
+        let mut spender_word = [0u8; 32];
+        spender_word[12..].copy_from_slice(spender.as_slice());
+        assert_eq!(&out[4..36], &spender_word);
+        assert_eq!(&out[36..68], &[0xffu8; 32]);
+        assert_eq!(out.len(), 68);
+    }
+
     #[test]
     fn encode_pull_from_receivers_layout() {
         let token = Address::from_slice(&[0x44u8; 20]);